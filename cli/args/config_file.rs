@@ -345,6 +345,7 @@ struct SerializedLintConfig {
   pub rules: LintRulesConfig,
   pub files: SerializedFilesConfig,
   pub report: Option<String>,
+  pub plugins: Vec<String>,
 }
 
 impl SerializedLintConfig {
@@ -352,10 +353,16 @@ impl SerializedLintConfig {
     self,
     config_file_specifier: &ModuleSpecifier,
   ) -> Result<LintConfig, AnyError> {
+    let config_dir = specifier_parent(config_file_specifier);
     Ok(LintConfig {
       rules: self.rules,
       files: self.files.into_resolved(config_file_specifier)?,
       report: self.report,
+      plugins: self
+        .plugins
+        .into_iter()
+        .map(|p| config_dir.join(&p))
+        .collect::<Result<Vec<_>, _>>()?,
     })
   }
 }
@@ -365,6 +372,8 @@ pub struct LintConfig {
   pub rules: LintRulesConfig,
   pub files: FilesConfig,
   pub report: Option<String>,
+  /// Modules exporting custom lint rules.
+  pub plugins: Vec<ModuleSpecifier>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -384,6 +393,10 @@ pub struct FmtOptionsConfig {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<ProseWrap>,
   pub semi_colons: Option<bool>,
+  /// Whether CSS, YAML and HTML files are formatted, which is set by the
+  /// `--unstable` flag rather than the config file.
+  #[serde(skip_deserializing)]
+  pub unstable: bool,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -415,6 +428,7 @@ pub struct FmtConfig {
 #[serde(default, deny_unknown_fields)]
 struct SerializedTestConfig {
   pub files: SerializedFilesConfig,
+  pub timeout: Option<u64>,
 }
 
 impl SerializedTestConfig {
@@ -424,6 +438,7 @@ impl SerializedTestConfig {
   ) -> Result<TestConfig, AnyError> {
     Ok(TestConfig {
       files: self.files.into_resolved(config_file_specifier)?,
+      timeout: self.timeout,
     })
   }
 }
@@ -431,6 +446,8 @@ impl SerializedTestConfig {
 #[derive(Clone, Debug, Default)]
 pub struct TestConfig {
   pub files: FilesConfig,
+  /// Default timeout of each test in milliseconds.
+  pub timeout: Option<u64>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -455,6 +472,60 @@ pub struct BenchConfig {
   pub files: FilesConfig,
 }
 
+/// An npm package whose lifecycle scripts are allowed to run, along with the
+/// commands besides `node` that its scripts are allowed to run.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AllowScriptsPackage {
+  pub name: String,
+  #[serde(default)]
+  pub commands: Vec<String>,
+}
+
+impl AllowScriptsPackage {
+  /// Parses a package name, optionally followed by a command its scripts are
+  /// allowed to run, like `sharp:node-gyp`.
+  pub fn parse(text: &str) -> Self {
+    match text.split_once(':') {
+      Some((name, command)) => AllowScriptsPackage {
+        name: name.to_string(),
+        commands: vec![command.to_string()],
+      },
+      None => AllowScriptsPackage {
+        name: text.to_string(),
+        commands: Vec::new(),
+      },
+    }
+  }
+
+  /// Merges the entries of the same package.
+  pub fn merge(
+    packages: impl IntoIterator<Item = AllowScriptsPackage>,
+  ) -> Vec<AllowScriptsPackage> {
+    let mut merged: Vec<AllowScriptsPackage> = Vec::new();
+    for package in packages {
+      match merged.iter_mut().find(|p| p.name == package.name) {
+        Some(existing) => {
+          for command in package.commands {
+            if !existing.commands.contains(&command) {
+              existing.commands.push(command);
+            }
+          }
+        }
+        None => merged.push(package),
+      }
+    }
+    merged
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+enum SerializedAllowScriptsPackage {
+  Text(String),
+  Package(AllowScriptsPackage),
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged)]
 pub enum LockConfig {
@@ -475,6 +546,7 @@ pub struct ConfigFileJson {
   pub test: Option<Value>,
   pub bench: Option<Value>,
   pub lock: Option<Value>,
+  pub allow_scripts: Option<Value>,
 }
 
 #[derive(Clone, Debug)]
@@ -821,6 +893,25 @@ impl ConfigFile {
     Ok(tasks_config)
   }
 
+  pub fn to_allow_scripts_config(
+    &self,
+  ) -> Result<Option<Vec<AllowScriptsPackage>>, AnyError> {
+    if let Some(config) = self.json.allow_scripts.clone() {
+      let serialized: Vec<SerializedAllowScriptsPackage> =
+        serde_json::from_value(config)
+          .context("Failed to parse \"allowScripts\" configuration")?;
+      let packages = serialized.into_iter().map(|entry| match entry {
+        SerializedAllowScriptsPackage::Text(text) => {
+          AllowScriptsPackage::parse(&text)
+        }
+        SerializedAllowScriptsPackage::Package(package) => package,
+      });
+      Ok(Some(AllowScriptsPackage::merge(packages)))
+    } else {
+      Ok(None)
+    }
+  }
+
   pub fn to_lock_config(&self) -> Result<Option<LockConfig>, AnyError> {
     if let Some(config) = self.json.lock.clone() {
       let lock_config: LockConfig = serde_json::from_value(config)
@@ -1040,7 +1131,8 @@ mod tests {
         "rules": {
           "tags": ["recommended"],
           "include": ["ban-untagged-todo"]
-        }
+        },
+        "plugins": ["./lint/logger.ts"]
       },
       "fmt": {
         "files": {
@@ -1095,6 +1187,10 @@ mod tests {
       Some(vec!["recommended".to_string()])
     );
     assert!(lint_config.rules.exclude.is_none());
+    assert_eq!(
+      lint_config.plugins,
+      vec![ModuleSpecifier::parse("file:///deno/lint/logger.ts").unwrap()]
+    );
 
     let fmt_config = config_file
       .to_fmt_config()
@@ -1152,6 +1248,37 @@ mod tests {
     assert!(ConfigFile::new(config_text, &config_specifier).is_err());
   }
 
+  #[test]
+  fn test_parse_allow_scripts_config() {
+    let config_text = r#"{
+      "allowScripts": [
+        "esbuild",
+        "sharp:node-gyp",
+        { "name": "sharp", "commands": ["make", "node-gyp"] }
+      ]
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_allow_scripts_config().unwrap(),
+      Some(vec![
+        AllowScriptsPackage {
+          name: "esbuild".to_string(),
+          commands: Vec::new(),
+        },
+        AllowScriptsPackage {
+          name: "sharp".to_string(),
+          commands: vec!["node-gyp".to_string(), "make".to_string()],
+        },
+      ])
+    );
+
+    let config_text = r#"{ "allowScripts": [{ "name": "sharp", "run": [] }] }"#;
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert!(config_file.to_allow_scripts_config().is_err());
+  }
+
   #[test]
   fn test_parse_config_with_not_object_file() {
     let config_text = "[]";
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use clap::Arg;
+use clap::ArgGroup;
 use clap::ArgMatches;
 use clap::ColorChoice;
 use clap::Command;
@@ -57,6 +58,10 @@ pub struct BenchFlags {
   pub files: FileFlags,
   pub filter: Option<String>,
   pub json: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  pub regression_threshold: Option<u32>,
+  pub metrics: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -68,6 +73,10 @@ pub struct BundleFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CacheFlags {
   pub files: Vec<String>,
+  /// The archive to write the cached dependencies of the files to.
+  pub export: Option<PathBuf>,
+  /// The archive of a `--export` to populate the cache from.
+  pub import: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -94,7 +103,40 @@ pub struct CoverageFlags {
   pub output: Option<PathBuf>,
   pub include: Vec<String>,
   pub exclude: Vec<String>,
-  pub lcov: bool,
+  pub format: CoverageFormat,
+  pub html: Option<PathBuf>,
+  pub thresholds: CoverageThresholdsFlags,
+  pub tests_covering: Option<CoverageLineFlag>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoverageLineFlag {
+  pub file: String,
+  pub line: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CoverageFormat {
+  #[default]
+  Pretty,
+  Lcov,
+  Cobertura,
+  Json,
+}
+
+/// A minimum coverage percentage, either for every file or only for the files
+/// matching `glob`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoverageThresholdFlag {
+  pub glob: Option<String>,
+  pub percent: u8,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageThresholdsFlags {
+  pub lines: Vec<CoverageThresholdFlag>,
+  pub branches: Vec<CoverageThresholdFlag>,
+  pub functions: Vec<CoverageThresholdFlag>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -135,6 +177,9 @@ pub struct FmtFlags {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<String>,
   pub no_semicolons: Option<bool>,
+  pub staged: bool,
+  pub changed_since: Option<String>,
+  pub changed_lines: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -172,6 +217,9 @@ pub struct LintFlags {
   pub maybe_rules_exclude: Option<Vec<String>>,
   pub json: bool,
   pub compact: bool,
+  pub reporter: Option<String>,
+  pub fix: bool,
+  pub fix_dry_run: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -198,6 +246,16 @@ pub struct TaskFlags {
   pub task: Option<String>,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TestReporterConfig {
+  #[default]
+  Pretty,
+  Dot,
+  Junit,
+  Tap,
+  Json,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct TestFlags {
   pub doc: bool,
@@ -209,6 +267,37 @@ pub struct TestFlags {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_ops: bool,
+  pub coverage_per_test: bool,
+  pub changed: Option<String>,
+  pub shard: Option<TestShardFlags>,
+  pub retries: usize,
+  pub timeout: Option<u64>,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TestShardFlags {
+  /// One based index of the shard to run.
+  pub index: usize,
+  pub total: usize,
+  /// Output of a previous `deno test --json` run, used to balance the
+  /// shards by duration.
+  pub timings_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutdatedFlags {
+  pub files: FileFlags,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpdateFlags {
+  pub files: FileFlags,
+  /// Update to the latest versions instead of the latest semver compatible
+  /// ones.
+  pub latest: bool,
+  pub dry_run: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -245,11 +334,13 @@ pub enum DenoSubcommand {
   Uninstall(UninstallFlags),
   Lsp,
   Lint(LintFlags),
+  Outdated(OutdatedFlags),
   Repl(ReplFlags),
   Run(RunFlags),
   Task(TaskFlags),
   Test(TestFlags),
   Types,
+  Update(UpdateFlags),
   Upgrade(UpgradeFlags),
   Vendor(VendorFlags),
 }
@@ -330,6 +421,9 @@ pub struct Flags {
   pub type_check_mode: TypeCheckMode,
   pub config_flag: ConfigFlag,
   pub node_modules_dir: Option<bool>,
+  /// The npm packages whose lifecycle scripts are run when populating the
+  /// local node_modules folder.
+  pub allow_scripts: Option<Vec<String>>,
   pub coverage_dir: Option<String>,
   pub enable_testing_features: bool,
   pub ignore: Vec<PathBuf>,
@@ -352,6 +446,7 @@ pub struct Flags {
   pub v8_flags: Vec<String>,
   pub version: bool,
   pub watch: Option<Vec<PathBuf>>,
+  pub hmr: bool,
   pub no_clear_screen: bool,
 }
 
@@ -482,6 +577,8 @@ impl Flags {
     match &self.subcommand {
       Fmt(FmtFlags { files, .. }) => Some(files.include.clone()),
       Lint(LintFlags { files, .. }) => Some(files.include.clone()),
+      Outdated(OutdatedFlags { files }) => Some(files.include.clone()),
+      Update(UpdateFlags { files, .. }) => Some(files.include.clone()),
       Run(RunFlags { script }) => {
         if let Ok(module_specifier) = deno_core::resolve_url_or_path(script) {
           if module_specifier.scheme() == "file"
@@ -546,7 +643,9 @@ impl Flags {
           .ok()
       }
       Task(_) | Check(_) | Coverage(_) | Cache(_) | Info(_) | Eval(_)
-      | Test(_) | Bench(_) => std::env::current_dir().ok(),
+      | Test(_) | Bench(_) | Outdated(_) | Update(_) => {
+        std::env::current_dir().ok()
+      }
       _ => None,
     }
   }
@@ -604,7 +703,8 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
                          (module downloads, fetch)
     HTTPS_PROXY          Proxy address for HTTPS requests
                          (module downloads, fetch)
-    NPM_CONFIG_REGISTRY  URL to use for the npm registry.
+    NPM_CONFIG_REGISTRY  URL to use for the npm registry. Takes precedence
+                         over the registry of the .npmrc files.
     NO_COLOR             Set to disable color
     NO_PROXY             Comma-separated list of hosts which do not use a proxy
                          (module downloads, fetch)"#;
@@ -671,12 +771,14 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::Result<Flags> {
     Some(("install", m)) => install_parse(&mut flags, m),
     Some(("lint", m)) => lint_parse(&mut flags, m),
     Some(("lsp", m)) => lsp_parse(&mut flags, m),
+    Some(("outdated", m)) => outdated_parse(&mut flags, m),
     Some(("repl", m)) => repl_parse(&mut flags, m),
     Some(("run", m)) => run_parse(&mut flags, m),
     Some(("task", m)) => task_parse(&mut flags, m, &args),
     Some(("test", m)) => test_parse(&mut flags, m),
     Some(("types", m)) => types_parse(&mut flags, m),
     Some(("uninstall", m)) => uninstall_parse(&mut flags, m),
+    Some(("update", m)) => update_parse(&mut flags, m),
     Some(("upgrade", m)) => upgrade_parse(&mut flags, m),
     Some(("vendor", m)) => vendor_parse(&mut flags, m),
     _ => handle_repl_flags(
@@ -753,11 +855,13 @@ fn clap_root(version: &str) -> Command {
     .subcommand(uninstall_subcommand())
     .subcommand(lsp_subcommand())
     .subcommand(lint_subcommand())
+    .subcommand(outdated_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
     .subcommand(task_subcommand())
     .subcommand(test_subcommand())
     .subcommand(types_subcommand())
+    .subcommand(update_subcommand())
     .subcommand(upgrade_subcommand())
     .subcommand(vendor_subcommand())
     .long_about(DENO_HELP.as_str())
@@ -788,6 +892,59 @@ fn bench_subcommand<'a>() -> Command<'a> {
         .takes_value(true)
         .help("Run benchmarks with this string or pattern in the bench name"),
     )
+    .arg(
+      Arg::new("metrics")
+        .long("metrics")
+        .help("Also record heap allocations, garbage collections and op calls")
+        .long_help(
+          "Also record the bytes allocated on the V8 heap, the garbage \
+    collections and the op calls of each bench. These are measured in extra \
+    iterations run after the timed ones, so they don't skew the timings.",
+        )
+        .takes_value(false),
+    )
+    .arg(
+      Arg::new("save-baseline")
+        .long("save-baseline")
+        .value_name("NAME")
+        .help("Save the results as a baseline with the given name")
+        .long_help(
+          "Save the results as a baseline with the given name, so that later \
+    runs can be compared to it with --baseline. Results of benches that are \
+    already in the baseline are replaced.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .validator(baseline_name_validate),
+    )
+    .arg(
+      Arg::new("baseline")
+        .long("baseline")
+        .value_name("NAME")
+        .help("Compare the results to a baseline saved with --save-baseline")
+        .takes_value(true)
+        .require_equals(true)
+        .validator(baseline_name_validate),
+    )
+    .arg(
+      Arg::new("regression-threshold")
+        .long("regression-threshold")
+        .value_name("PERCENT")
+        .help(
+          "Fail when a bench is slower than the baseline by more than PERCENT",
+        )
+        .long_help(
+          "Fail when a bench is slower than the baseline by more than the \
+    given percentage and the difference is statistically significant.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .requires("baseline")
+        .validator(|val: &str| match val.parse::<u32>() {
+          Ok(_) => Ok(()),
+          Err(_) => Err("Regression threshold should be a number".to_string()),
+        }),
+    )
     .arg(
       Arg::new("files")
         .help("List of file names to run")
@@ -810,7 +967,12 @@ and report results to standard output:
 Directory arguments are expanded to all contained files matching the \
 glob {*_,*.,}bench.{js,mjs,ts,mts,jsx,tsx}:
 
-  deno bench src/",
+  deno bench src/
+
+Save the results as a baseline and compare later runs to it:
+
+  deno bench --save-baseline=main
+  deno bench --baseline=main --regression-threshold=10",
     )
 }
 
@@ -848,10 +1010,29 @@ fn cache_subcommand<'a>() -> Command<'a> {
     .arg(
       Arg::new("file")
         .takes_value(true)
-        .required(true)
+        .required_unless_present("import")
         .min_values(1)
         .value_hint(ValueHint::FilePath),
     )
+    .arg(
+      Arg::new("export")
+        .long("export")
+        .help("Write the remote modules and npm packages of the files to an archive")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("ARCHIVE")
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("import")
+        .long("import")
+        .help("Populate the cache from an archive of --export, verifying it against the lockfile")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("ARCHIVE")
+        .conflicts_with_all(&["file", "export"])
+        .value_hint(ValueHint::FilePath),
+    )
     .about("Cache the dependencies")
     .long_about(
       "Cache and compile remote dependencies recursively.
@@ -862,7 +1043,14 @@ them in the local cache, without running any code:
   deno cache https://deno.land/std/http/file_server.ts
 
 Future runs of this module will trigger no downloads or compilation unless \
---reload is specified.",
+--reload is specified.
+
+Pack the cached dependencies into an archive, for a machine without internet \
+access:
+
+  deno cache --lock=deno.lock --export=deps.tar.gz main.ts
+
+  deno cache --lock=deno.lock --import=deps.tar.gz",
     )
 }
 
@@ -889,12 +1077,18 @@ fn check_subcommand<'a>() -> Command<'a> {
         .min_values(1)
         .value_hint(ValueHint::FilePath),
     )
+    .arg(watch_arg(false))
+    .arg(no_clear_screen_arg())
     .about("Type-check the dependencies")
     .long_about(
       "Download and type-check without execution.
 
   deno check https://deno.land/std/http/file_server.ts
 
+Type-check again whenever a file of the module graph changes:
+
+  deno check --watch main.ts
+
 Unless --reload is specified, this command will not re-download already cached dependencies.",
     )
 }
@@ -997,9 +1191,31 @@ Write a report using the lcov format:
 
   deno coverage --lcov --output=cov.lcov cov_profile/
 
+Write a report using the Cobertura XML format:
+
+  deno coverage --format=cobertura --output=cobertura.xml cov_profile/
+
+Write a JSON summary of the line, branch and function coverage of each file:
+
+  deno coverage --format=json cov_profile/
+
+Write a report as a browsable static site:
+
+  deno coverage --html=html_cov cov_profile/
+
 Generate html reports from lcov:
 
   genhtml -o html_cov cov.lcov
+
+List the tests that exercise line 12 of a file, which requires collecting \
+the profile with 'deno test --coverage=cov_profile --coverage-per-test':
+
+  deno coverage --tests-covering=src/mod.ts:12 cov_profile
+
+Fail when line coverage is below 80% in any file, or below 95% in the files \
+matching the given glob:
+
+  deno coverage --threshold-lines=80 --threshold-lines=\"src/core/**:95\" cov_profile
 ",
     )
     .arg(
@@ -1036,18 +1252,104 @@ Generate html reports from lcov:
     .arg(
       Arg::new("lcov")
         .long("lcov")
+        .conflicts_with("format")
         .help("Output coverage report in lcov format")
+        .long_help("Output coverage report in lcov format. Same as '--format=lcov'.")
         .takes_value(false),
     )
+    .arg(
+      Arg::new("format")
+        .long("format")
+        .takes_value(true)
+        .require_equals(true)
+        .possible_values(["pretty", "lcov", "cobertura", "json"])
+        .help("Select the format of the coverage report. Defaults to 'pretty'."),
+    )
+    .arg(
+      Arg::new("html")
+        .long("html")
+        .conflicts_with_all(&["lcov", "format", "output"])
+        .value_name("DIR")
+        .help("Output coverage report as html files in the given directory")
+        .takes_value(true)
+        .require_equals(true)
+        .value_hint(ValueHint::DirPath),
+    )
+    .arg(
+      Arg::new("threshold-lines")
+        .long("threshold-lines")
+        .takes_value(true)
+        .value_name("[GLOB:]PERCENT")
+        .multiple_occurrences(true)
+        .require_equals(true)
+        .validator(coverage_threshold_validate)
+        .help("Minimum line coverage percentage for each file")
+        .long_help(
+          "Exit with an error if the line coverage of a file is below the \
+    given percentage. Prefix the percentage with a glob and a colon to only \
+    apply it to the files matching the glob, for example \
+    '--threshold-lines=src/**/*.ts:90'. When several thresholds match a file \
+    the last one wins.",
+        ),
+    )
+    .arg(
+      Arg::new("threshold-branches")
+        .long("threshold-branches")
+        .takes_value(true)
+        .value_name("[GLOB:]PERCENT")
+        .multiple_occurrences(true)
+        .require_equals(true)
+        .validator(coverage_threshold_validate)
+        .help("Minimum branch coverage percentage for each file")
+        .long_help(
+          "Exit with an error if the branch coverage of a file is below the \
+    given percentage. Prefix the percentage with a glob and a colon to only \
+    apply it to the files matching the glob, for example \
+    '--threshold-branches=src/**/*.ts:90'. When several thresholds match a file \
+    the last one wins.",
+        ),
+    )
+    .arg(
+      Arg::new("threshold-functions")
+        .long("threshold-functions")
+        .takes_value(true)
+        .value_name("[GLOB:]PERCENT")
+        .multiple_occurrences(true)
+        .require_equals(true)
+        .validator(coverage_threshold_validate)
+        .help("Minimum function coverage percentage for each file")
+        .long_help(
+          "Exit with an error if the function coverage of a file is below the \
+    given percentage. Prefix the percentage with a glob and a colon to only \
+    apply it to the files matching the glob, for example \
+    '--threshold-functions=src/**/*.ts:90'. When several thresholds match a file \
+    the last one wins.",
+        ),
+    )
+    .arg(
+      Arg::new("tests-covering")
+        .long("tests-covering")
+        .takes_value(true)
+        .require_equals(true)
+        .value_name("FILE:LINE")
+        .conflicts_with_all(&["lcov", "format", "html", "output"])
+        .validator(coverage_line_validate)
+        .help("List the tests that exercise the given line of a file")
+        .long_help(
+          "List the tests that exercise the given line of a file instead of \
+    printing a report. Requires a profile collected with \
+    'deno test --coverage-per-test'.",
+        ),
+    )
     .arg(
       Arg::new("output")
-        .requires("lcov")
         .long("output")
-        .help("Output file (defaults to stdout) for lcov")
+        .help("Output file (defaults to stdout) for lcov, cobertura and json")
         .long_help(
-          "Exports the coverage report in lcov format to the given file. \
-    Filename should be passed along with '=' For example '--output=foo.lcov' \
-    If no --output arg is specified then the report is written to stdout.",
+          "Exports the coverage report in the lcov, cobertura or json format to \
+    the given file. Filename should be passed along with '=' For example \
+    '--output=foo.lcov' If no --output arg is specified then the report is \
+    written to stdout.",
         )
         .takes_value(true)
         .require_equals(true)
@@ -1185,6 +1487,10 @@ fn fmt_subcommand<'a>() -> Command<'a> {
   deno fmt myfile1.ts myfile2.ts
   deno fmt --check
 
+Also format CSS, SCSS, Sass, Less, YAML, and HTML files (unstable):
+
+  deno fmt --unstable
+
 Format stdin and write to stdout:
 
   cat file.ts | deno fmt -
@@ -1195,7 +1501,16 @@ Ignore formatting code by preceding it with an ignore comment:
 
 Ignore formatting a file by adding an ignore comment at the top of the file:
 
-  // deno-fmt-ignore-file",
+  // deno-fmt-ignore-file
+
+Only format the files staged in git, or the files changed since a git ref:
+
+  deno fmt --staged
+  deno fmt --changed-since=main
+
+Only format the lines that changed in those files:
+
+  deno fmt --staged --changed-lines",
     )
     .arg(config_arg())
     .arg(no_config_arg())
@@ -1211,7 +1526,10 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
         .help("Set standard input (stdin) content type")
         .takes_value(true)
         .default_value("ts")
-        .possible_values(["ts", "tsx", "js", "jsx", "md", "json", "jsonc"]),
+        .possible_values([
+          "ts", "tsx", "js", "jsx", "md", "json", "jsonc", "css", "scss",
+          "sass", "less", "yml", "yaml", "html",
+        ]),
     )
     .arg(
       Arg::new("ignore")
@@ -1232,6 +1550,38 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
     )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
+    .arg(
+      Arg::new("staged")
+        .long("staged")
+        .help("Only format the files staged in git")
+        .takes_value(false)
+        .conflicts_with("watch"),
+    )
+    .arg(
+      Arg::new("changed-since")
+        .long("changed-since")
+        .value_name("GIT_REF")
+        .help("Only format the files changed since the given git ref")
+        .takes_value(true)
+        .require_equals(true)
+        .conflicts_with("watch"),
+    )
+    .arg(
+      Arg::new("changed-lines")
+        .long("changed-lines")
+        .help("Only format the lines changed in git")
+        .long_help(
+          "Only format the lines changed in git. Requires --staged or \
+--changed-since. Files that are new to git are formatted entirely.",
+        )
+        .takes_value(false)
+        .requires("git-changes"),
+    )
+    .group(
+      ArgGroup::new("git-changes")
+        .args(&["staged", "changed-since"])
+        .multiple(false),
+    )
     .arg(
       Arg::new("use-tabs")
         .long("use-tabs")
@@ -1345,6 +1695,7 @@ TypeScript compiler cache: Subdirectory containing TS compiler output.",
     .arg(config_arg())
     .arg(import_map_arg())
     .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
     .arg(
       Arg::new("json")
         .long("json")
@@ -1471,6 +1822,11 @@ Print result as JSON:
 
   deno lint --json
 
+Print result as SARIF for code scanning, or as GitHub Actions annotations:
+
+  deno lint --reporter=sarif > deno-lint.sarif
+  deno lint --reporter=github
+
 Read from stdin:
 
   cat file.ts | deno lint -
@@ -1480,6 +1836,11 @@ List available rules:
 
   deno lint --rules
 
+Fix the problems that have a safe fix, or show the fixes as a diff:
+
+  deno lint --fix
+  deno lint --fix-dry-run
+
 Ignore diagnostics on the next line by preceding it with an ignore comment and
 rule name:
 
@@ -1532,6 +1893,17 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .help("Ignore linting particular source files")
         .value_hint(ValueHint::AnyPath),
     )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .help(
+          "Select reporter to use. Default to 'pretty'. Takes precedence over \
+          --json and --compact.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .possible_values(["pretty", "json", "compact", "sarif", "github"]),
+    )
     .arg(
       Arg::new("json")
         .long("json")
@@ -1545,6 +1917,20 @@ Ignore linting a file by adding an ignore comment at the top of the file:
         .takes_value(false)
         .conflicts_with("json"),
     )
+    .arg(
+      Arg::new("fix")
+        .long("fix")
+        .help("Fix the problems that have a safe fix")
+        .takes_value(false)
+        .conflicts_with("rules"),
+    )
+    .arg(
+      Arg::new("fix-dry-run")
+        .long("fix-dry-run")
+        .help("Show the fixes as a diff without writing them")
+        .takes_value(false)
+        .conflicts_with_all(&["fix", "rules"]),
+    )
     .arg(
       Arg::new("files")
         .takes_value(true)
@@ -1557,6 +1943,23 @@ Ignore linting a file by adding an ignore comment at the top of the file:
     .arg(no_clear_screen_arg())
 }
 
+fn outdated_subcommand<'a>() -> Command<'a> {
+  dependency_files_args(Command::new("outdated"))
+    .about("Show dependencies with newer versions")
+    .long_about(
+      "Show the npm and remote module dependencies that have newer versions.
+
+  deno outdated
+  deno outdated src/
+
+The versioned \"npm:\" specifiers and module registry urls, like
+\"https://deno.land/x/oak@v12.0.0/mod.ts\", of the source files, the
+configuration file and the import map are checked, as well as the dependencies
+of the package.json. For each dependency, the newest semver compatible version
+and the latest version are shown.",
+    )
+}
+
 fn repl_subcommand<'a>() -> Command<'a> {
   runtime_args(Command::new("repl"), true, true)
     .about("Read Eval Print Loop")
@@ -1587,7 +1990,21 @@ fn run_subcommand<'a>() -> Command<'a> {
         .conflicts_with("inspect-wait")
         .conflicts_with("inspect-brk"),
     )
-    .arg(no_clear_screen_arg())
+    .arg(
+      hmr_arg()
+        .conflicts_with("watch")
+        .conflicts_with("inspect")
+        .conflicts_with("inspect-wait")
+        .conflicts_with("inspect-brk"),
+    )
+    .arg(
+      // can't use `no_clear_screen_arg()`, it's also allowed with `--watch-hmr`
+      Arg::new("no-clear-screen")
+        .requires("watch-mode")
+        .long("no-clear-screen")
+        .help("Do not clear terminal screen when under watch mode"),
+    )
+    .group(ArgGroup::new("watch-mode").args(&["watch", "hmr"]))
     .trailing_var_arg(true)
     .arg(script_arg().required(true))
     .about("Run a JavaScript or TypeScript program")
@@ -1633,6 +2050,8 @@ fn task_subcommand<'a>() -> Command<'a> {
     // arguments, but there is a bug in clap that's preventing us from doing
     // this (https://github.com/clap-rs/clap/issues/1538). Once that's fixed,
     // then we can revert this back to what it used to be.
+    .arg(watch_arg(true))
+    .arg(no_clear_screen_arg())
     .arg(Arg::new("task_name_and_args")
         .multiple_values(true)
         .multiple_occurrences(true)
@@ -1642,7 +2061,13 @@ fn task_subcommand<'a>() -> Command<'a> {
     .long_about(
       "Run a task defined in the configuration file
 
-  deno task build",
+  deno task build
+
+Run the task again whenever one of the given paths changes. Only the inputs of
+the task should be watched, or a task that writes files restarts itself. Changes
+in node_modules and .git directories are ignored:
+
+  deno task --watch=src,static build",
     )
 }
 
@@ -1718,6 +2143,23 @@ fn test_subcommand<'a>() -> Command<'a> {
           Err(_) => Err("Shuffle seed should be a number".to_string()),
         }),
     )
+    .arg(
+      Arg::new("changed")
+        .long("changed")
+        .value_name("GIT_REF")
+        .help("Only run test modules affected by changed files")
+        .long_help(
+          "Only run the test modules that import, directly or transitively, a \
+    file that changed since the given git ref or that has uncommitted changes \
+    in the working tree. Defaults to HEAD, which only looks at the working \
+    tree.",
+        )
+        .min_values(0)
+        .max_values(1)
+        .require_equals(true)
+        .takes_value(true)
+        .conflicts_with("watch"),
+    )
     .arg(
       Arg::new("coverage")
         .long("coverage")
@@ -1729,6 +2171,13 @@ fn test_subcommand<'a>() -> Command<'a> {
         .conflicts_with("inspect-brk")
         .help("Collect coverage profile data into DIR"),
     )
+    .arg(
+      Arg::new("coverage-per-test")
+        .long("coverage-per-test")
+        .requires("coverage")
+        .help("Also record the coverage of each test, so 'deno coverage --tests-covering' can tell which tests exercise a line. Slows down test execution.")
+        .takes_value(false),
+    )
     .arg(
       Arg::new("parallel")
         .long("parallel")
@@ -1750,6 +2199,98 @@ fn test_subcommand<'a>() -> Command<'a> {
           Err(_) => Err("jobs should be a non zero unsigned integer".to_string()),
         }),
     )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .help("Select reporter to use. Default to 'pretty'.")
+        .takes_value(true)
+        .possible_values(["pretty", "dot", "junit", "tap"]),
+    )
+    .arg(
+      Arg::new("json")
+        .long("json")
+        .help("UNSTABLE: Output every test event as newline delimited JSON")
+        .conflicts_with("reporter")
+        .takes_value(false),
+    )
+    .arg(
+      Arg::new("retries")
+        .long("retries")
+        .value_name("N")
+        .help("Retry failed tests up to N times")
+        .long_help(
+          "Retry failed tests up to N times. Tests that pass after being \
+    retried are reported as flaky and don't fail the run. The 'retries' option \
+    of a test takes precedence. Steps are only retried with their own \
+    'retries' option.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .validator(|val: &str| match val.parse::<usize>() {
+          Ok(_) => Ok(()),
+          Err(_) => Err("Retries should be a number".to_string()),
+        }),
+    )
+    .arg(
+      Arg::new("timeout")
+        .long("timeout")
+        .value_name("MS")
+        .help("Default timeout of each test in milliseconds")
+        .long_help(
+          "Fail tests that don't finish within the given number of \
+    milliseconds, showing the async ops they are still waiting for. A test \
+    that times out is stopped even if it blocks the event loop, and the \
+    remaining tests of its module are cancelled. The 'timeout' option of a \
+    test or step takes precedence.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .validator(|val: &str| match val.parse::<u64>() {
+          Ok(timeout) if timeout > 0 => Ok(()),
+          _ => Err("Timeout should be a positive number".to_string()),
+        }),
+    )
+    .arg(
+      Arg::new("shard")
+        .long("shard")
+        .value_name("INDEX/TOTAL")
+        .help("Only run one of TOTAL shards of the test modules")
+        .long_help(
+          "Split the test modules into TOTAL shards and only run the shard at \
+    the one based INDEX, for example '--shard=2/8'. The same modules always \
+    end up in the same shard, so running every shard on a different machine \
+    runs every test module once.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .validator(test_shard_validate)
+        .conflicts_with("watch"),
+    )
+    .arg(
+      Arg::new("shard-timings")
+        .long("shard-timings")
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .help("Balance the shards using the durations of a previous run")
+        .long_help(
+          "Balance the shards using the test durations in FILE, which is the \
+    output of a previous 'deno test --json' run. Test modules are matched by \
+    their path relative to the current directory, so FILE can be recorded on \
+    another machine. Test modules that are missing from FILE are assumed to \
+    take the average duration.",
+        )
+        .takes_value(true)
+        .require_equals(true)
+        .requires("shard"),
+    )
+    .arg(
+      Arg::new("junit-path")
+        .long("junit-path")
+        .value_name("PATH")
+        .value_hint(ValueHint::FilePath)
+        .help("Write a JUnit XML test report to PATH. Use '-' to write to stdout which is the default when '--reporter=junit' is set.")
+        .takes_value(true),
+    )
     .arg(
       Arg::new("files")
         .help("List of file names to run")
@@ -1793,6 +2334,57 @@ The declaration file could be saved and used for typing information.",
     )
 }
 
+fn update_subcommand<'a>() -> Command<'a> {
+  dependency_files_args(Command::new("update"))
+    .about("Update dependencies to newer versions")
+    .long_about(
+      "Update the versions of the dependencies shown by `deno outdated`.
+
+  deno update
+  deno update --latest src/
+
+By default, dependencies are only updated to the newest semver compatible
+version. With --latest, they are updated to the latest version, which might
+contain breaking changes. The entries of outdated dependencies in the lockfile
+are replaced as well.",
+    )
+    .arg(
+      Arg::new("latest")
+        .long("latest")
+        .help("Update to the latest versions, including major versions"),
+    )
+    .arg(
+      Arg::new("dry-run")
+        .long("dry-run")
+        .help("Only show the updates without changing any files"),
+    )
+}
+
+fn dependency_files_args(app: Command) -> Command {
+  app
+    .arg(config_arg())
+    .arg(no_config_arg())
+    .arg(import_map_arg())
+    .arg(lock_arg())
+    .arg(ca_file_arg())
+    .arg(
+      Arg::new("ignore")
+        .long("ignore")
+        .takes_value(true)
+        .use_value_delimiter(true)
+        .require_equals(true)
+        .help("Ignore particular source files")
+        .value_hint(ValueHint::AnyPath),
+    )
+    .arg(
+      Arg::new("files")
+        .takes_value(true)
+        .multiple_values(true)
+        .multiple_occurrences(true)
+        .value_hint(ValueHint::AnyPath),
+    )
+}
+
 fn upgrade_subcommand<'a>() -> Command<'a> {
   Command::new("upgrade")
     .about("Upgrade deno executable to given version")
@@ -1895,6 +2487,7 @@ fn compile_args(app: Command) -> Command {
     .arg(no_remote_arg())
     .arg(no_npm_arg())
     .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
     .arg(no_config_arg())
     .arg(config_arg())
     .arg(no_check_arg())
@@ -1912,6 +2505,7 @@ fn compile_args_without_check_args(app: Command) -> Command {
     .arg(no_remote_arg())
     .arg(no_npm_arg())
     .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
     .arg(config_arg())
     .arg(no_config_arg())
     .arg(reload_arg())
@@ -2232,8 +2826,28 @@ Additional paths might be watched by passing them as arguments to this flag.",
   }
 }
 
-fn no_clear_screen_arg<'a>() -> Arg<'a> {
-  Arg::new("no-clear-screen")
+fn hmr_arg<'a>() -> Arg<'a> {
+  Arg::new("hmr")
+    .long("watch-hmr")
+    .value_name("FILES")
+    .min_values(0)
+    .takes_value(true)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .help("Watch for file changes and hot replace the changed modules")
+    .long_help(
+      "Watch for file changes and replace the functions of the changed modules \
+in the running process, dispatching an \"hmr\" event for each of them. The top \
+level code of the modules isn't run again, so the process is restarted instead \
+when it changed or when a change can't be applied in place.
+Local files from entry point module graph are watched by default.
+Additional paths might be watched by passing them as arguments to this flag.",
+    )
+    .value_hint(ValueHint::AnyPath)
+}
+
+fn no_clear_screen_arg<'a>() -> Arg<'a> {
+  Arg::new("no-clear-screen")
     .requires("watch")
     .long("no-clear-screen")
     .help("Do not clear terminal screen when under watch mode")
@@ -2366,6 +2980,32 @@ fn local_npm_arg<'a>() -> Arg<'a> {
     .help("Creates a local node_modules folder. This option is implicitly true when a package.json is auto-discovered.")
 }
 
+fn allow_scripts_arg<'a>() -> Arg<'a> {
+  Arg::new("allow-scripts")
+    .long("allow-scripts")
+    .min_values(1)
+    .takes_value(true)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .value_name("PACKAGES")
+    .help("Run the preinstall, install and postinstall scripts of the given npm packages when populating the local node_modules folder")
+    .long_help(
+      "Run the preinstall, install and postinstall scripts of the given npm \
+packages when populating the local node_modules folder.
+
+`node` in the scripts runs with Deno, only allowed to read the package and \
+node_modules folders, to write to node_modules, to run the executables of the \
+package and its dependencies, to connect to the registry and to read the \
+environment variables of the script. Besides it, the scripts may only use the \
+built-in commands of the shell that don't access the file system.
+
+Other commands run unsandboxed, with the full permissions of the user, so \
+they have to be allowed for the package:
+
+  --allow-scripts=esbuild,sharp:node-gyp",
+    )
+}
+
 fn unsafely_ignore_certificate_errors_arg<'a>() -> Arg<'a> {
   Arg::new("unsafely-ignore-certificate-errors")
     .long("unsafely-ignore-certificate-errors")
@@ -2395,6 +3035,12 @@ fn bench_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   };
 
   let filter = matches.value_of("filter").map(String::from);
+  let save_baseline = matches.value_of("save-baseline").map(String::from);
+  let baseline = matches.value_of("baseline").map(String::from);
+  let regression_threshold = matches
+    .value_of("regression-threshold")
+    .map(|val| val.parse().unwrap());
+  let metrics = matches.is_present("metrics");
 
   if matches.is_present("script_arg") {
     let script_arg: Vec<String> = matches
@@ -2424,6 +3070,10 @@ fn bench_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     files: FileFlags { include, ignore },
     filter,
     json,
+    save_baseline,
+    baseline,
+    regression_threshold,
+    metrics,
   });
 }
 
@@ -2453,10 +3103,13 @@ fn cache_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   compile_args_parse(flags, matches);
   let files = matches
     .values_of("file")
-    .unwrap()
-    .map(String::from)
-    .collect();
-  flags.subcommand = DenoSubcommand::Cache(CacheFlags { files });
+    .map(|f| f.map(String::from).collect())
+    .unwrap_or_default();
+  flags.subcommand = DenoSubcommand::Cache(CacheFlags {
+    files,
+    export: matches.value_of("export").map(PathBuf::from),
+    import: matches.value_of("import").map(PathBuf::from),
+  });
 }
 
 fn check_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -2470,6 +3123,7 @@ fn check_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("all") || matches.is_present("remote") {
     flags.type_check_mode = TypeCheckMode::All;
   }
+  watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Check(CheckFlags { files });
 }
 
@@ -2542,8 +3196,31 @@ fn coverage_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     Some(f) => f.map(String::from).collect(),
     None => vec![],
   };
-  let lcov = matches.is_present("lcov");
+  let format = match matches.value_of("format") {
+    _ if matches.is_present("lcov") => CoverageFormat::Lcov,
+    Some("lcov") => CoverageFormat::Lcov,
+    Some("cobertura") => CoverageFormat::Cobertura,
+    Some("json") => CoverageFormat::Json,
+    _ => CoverageFormat::Pretty,
+  };
+  let html = matches.value_of("html").map(PathBuf::from);
   let output = matches.value_of("output").map(PathBuf::from);
+  let parse_thresholds = |name: &str| -> Vec<CoverageThresholdFlag> {
+    match matches.values_of(name) {
+      Some(f) => f
+        .map(|value| parse_coverage_threshold(value).unwrap())
+        .collect(),
+      None => vec![],
+    }
+  };
+  let tests_covering = matches
+    .value_of("tests-covering")
+    .map(|value| parse_coverage_line(value).unwrap());
+  let thresholds = CoverageThresholdsFlags {
+    lines: parse_thresholds("threshold-lines"),
+    branches: parse_thresholds("threshold-branches"),
+    functions: parse_thresholds("threshold-functions"),
+  };
   flags.subcommand = DenoSubcommand::Coverage(CoverageFlags {
     files: FileFlags {
       include: files,
@@ -2552,7 +3229,10 @@ fn coverage_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     output,
     include,
     exclude,
-    lcov,
+    format,
+    html,
+    thresholds,
+    tests_covering,
   });
 }
 
@@ -2659,6 +3339,9 @@ fn fmt_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     single_quote,
     prose_wrap,
     no_semicolons,
+    staged: matches.is_present("staged"),
+    changed_since: matches.value_of("changed-since").map(ToString::to_string),
+    changed_lines: matches.is_present("changed-lines"),
   });
 }
 
@@ -2683,6 +3366,7 @@ fn info_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   location_arg_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   local_npm_args_parse(flags, matches);
+  allow_scripts_arg_parse(flags, matches);
   let json = matches.is_present("json");
   flags.subcommand = DenoSubcommand::Info(InfoFlags {
     file: matches.value_of("file").map(|f| f.to_string()),
@@ -2752,6 +3436,9 @@ fn lint_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
   let json = matches.is_present("json");
   let compact = matches.is_present("compact");
+  let reporter = matches.value_of("reporter").map(String::from);
+  let fix = matches.is_present("fix");
+  let fix_dry_run = matches.is_present("fix-dry-run");
   flags.subcommand = DenoSubcommand::Lint(LintFlags {
     files: FileFlags {
       include: files,
@@ -2764,9 +3451,17 @@ fn lint_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
     json,
     compact,
+    reporter,
+    fix,
+    fix_dry_run,
   });
 }
 
+fn outdated_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let files = dependency_files_args_parse(flags, matches);
+  flags.subcommand = DenoSubcommand::Outdated(OutdatedFlags { files });
+}
+
 fn repl_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   runtime_args_parse(flags, matches, true, true);
   unsafely_ignore_certificate_errors_parse(flags, matches);
@@ -2801,6 +3496,10 @@ fn run_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   }
 
   watch_arg_parse(flags, matches, true);
+  if let Some(f) = matches.values_of("hmr") {
+    flags.watch = Some(f.map(PathBuf::from).collect());
+    flags.hmr = true;
+  }
   flags.subcommand = DenoSubcommand::Run(RunFlags { script });
 }
 
@@ -2823,6 +3522,7 @@ fn task_parse(
   if let Some(cwd) = matches.value_of("cwd") {
     task_flags.cwd = Some(cwd.to_string());
   }
+  watch_arg_parse(flags, matches, true);
 
   if let Some(mut index) = matches.index_of("task_name_and_args") {
     let task_word_index = raw_args.iter().position(|el| el == "task").unwrap();
@@ -2847,6 +3547,25 @@ fn task_parse(
           flags.log_level = Some(Level::Error);
           index += 1;
         }
+        "--no-clear-screen" => {
+          flags.no_clear_screen = true;
+          index += 1;
+        }
+        arg if arg == "--watch" || arg.starts_with("--watch=") => {
+          flags.watch = Some(
+            arg
+              .strip_prefix("--watch=")
+              .map(|paths| {
+                paths
+                  .split(',')
+                  .filter(|path| !path.is_empty())
+                  .map(PathBuf::from)
+                  .collect()
+              })
+              .unwrap_or_default(),
+          );
+          index += 1;
+        }
         _ => break,
       }
     }
@@ -2880,9 +3599,30 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 
   let no_run = matches.is_present("no-run");
   let trace_ops = matches.is_present("trace-ops");
+  let coverage_per_test = matches.is_present("coverage-per-test");
   let doc = matches.is_present("doc");
   let allow_none = matches.is_present("allow-none");
   let filter = matches.value_of("filter").map(String::from);
+  let retries = matches
+    .value_of("retries")
+    .map(|value| value.parse().unwrap())
+    .unwrap_or(0);
+  let timeout = matches
+    .value_of("timeout")
+    .map(|value| value.parse().unwrap());
+  let shard = matches.value_of("shard").map(|value| {
+    let (index, total) = parse_test_shard(value).unwrap();
+    TestShardFlags {
+      index,
+      total,
+      timings_path: matches.value_of("shard-timings").map(String::from),
+    }
+  });
+  let changed = if matches.is_present("changed") {
+    Some(matches.value_of("changed").unwrap_or("HEAD").to_string())
+  } else {
+    None
+  };
 
   let fail_fast = if matches.is_present("fail-fast") {
     if let Some(value) = matches.value_of("fail-fast") {
@@ -2952,6 +3692,15 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     Vec::new()
   };
 
+  let reporter = match matches.value_of("reporter") {
+    _ if matches.is_present("json") => TestReporterConfig::Json,
+    Some("dot") => TestReporterConfig::Dot,
+    Some("junit") => TestReporterConfig::Junit,
+    Some("tap") => TestReporterConfig::Tap,
+    _ => TestReporterConfig::Pretty,
+  };
+  let junit_path = matches.value_of("junit-path").map(String::from);
+
   flags.coverage_dir = matches.value_of("coverage").map(String::from);
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Test(TestFlags {
@@ -2964,6 +3713,13 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     allow_none,
     concurrent_jobs,
     trace_ops,
+    coverage_per_test,
+    changed,
+    shard,
+    retries,
+    timeout,
+    reporter,
+    junit_path,
   });
 }
 
@@ -2971,6 +3727,35 @@ fn types_parse(flags: &mut Flags, _matches: &clap::ArgMatches) {
   flags.subcommand = DenoSubcommand::Types;
 }
 
+fn update_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let files = dependency_files_args_parse(flags, matches);
+  flags.subcommand = DenoSubcommand::Update(UpdateFlags {
+    files,
+    latest: matches.is_present("latest"),
+    dry_run: matches.is_present("dry-run"),
+  });
+}
+
+fn dependency_files_args_parse(
+  flags: &mut Flags,
+  matches: &clap::ArgMatches,
+) -> FileFlags {
+  config_args_parse(flags, matches);
+  import_map_arg_parse(flags, matches);
+  lock_arg_parse(flags, matches);
+  ca_file_arg_parse(flags, matches);
+  FileFlags {
+    include: match matches.values_of("files") {
+      Some(f) => f.map(PathBuf::from).collect(),
+      None => vec![],
+    },
+    ignore: match matches.values_of("ignore") {
+      Some(f) => f.map(PathBuf::from).collect(),
+      None => vec![],
+    },
+  }
+}
+
 fn upgrade_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   ca_file_arg_parse(flags, matches);
 
@@ -3015,6 +3800,7 @@ fn compile_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   no_remote_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
   local_npm_args_parse(flags, matches);
+  allow_scripts_arg_parse(flags, matches);
   config_args_parse(flags, matches);
   no_check_arg_parse(flags, matches);
   check_arg_parse(flags, matches);
@@ -3031,6 +3817,7 @@ fn compile_args_without_no_check_parse(
   no_remote_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
   local_npm_args_parse(flags, matches);
+  allow_scripts_arg_parse(flags, matches);
   config_args_parse(flags, matches);
   reload_arg_parse(flags, matches);
   lock_args_parse(flags, matches);
@@ -3304,6 +4091,86 @@ fn local_npm_args_parse(flags: &mut Flags, matches: &ArgMatches) {
   flags.node_modules_dir = optional_bool_parse(matches, "node-modules-dir");
 }
 
+fn allow_scripts_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
+  if let Some(packages) = matches.values_of("allow-scripts") {
+    flags.allow_scripts = Some(packages.map(ToString::to_string).collect());
+  }
+}
+
+fn parse_coverage_threshold(
+  value: &str,
+) -> Result<CoverageThresholdFlag, String> {
+  // split on the last colon so that globs containing a drive letter work
+  let (glob, percent) = match value.rsplit_once(':') {
+    Some((glob, percent)) => (Some(glob.to_string()), percent),
+    None => (None, value),
+  };
+  if matches!(&glob, Some(glob) if glob.is_empty()) {
+    return Err("glob should not be empty".to_string());
+  }
+  match percent.parse::<u8>() {
+    Ok(percent) if percent <= 100 => {
+      Ok(CoverageThresholdFlag { glob, percent })
+    }
+    _ => Err("threshold should be an integer between 0 and 100".to_string()),
+  }
+}
+
+fn coverage_threshold_validate(val: &str) -> Result<(), String> {
+  parse_coverage_threshold(val).map(|_| ())
+}
+
+fn parse_test_shard(value: &str) -> Result<(usize, usize), String> {
+  let error = || {
+    "shard should be INDEX/TOTAL, where INDEX is between 1 and TOTAL"
+      .to_string()
+  };
+  let (index, total) = value.split_once('/').ok_or_else(error)?;
+  match (index.parse::<usize>(), total.parse::<usize>()) {
+    (Ok(index), Ok(total)) if index >= 1 && index <= total => {
+      Ok((index, total))
+    }
+    _ => Err(error()),
+  }
+}
+
+fn test_shard_validate(val: &str) -> Result<(), String> {
+  parse_test_shard(val).map(|_| ())
+}
+
+fn baseline_name_validate(val: &str) -> Result<(), String> {
+  if !val.is_empty()
+    && val
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    && !val.starts_with('.')
+  {
+    Ok(())
+  } else {
+    Err(
+      "baseline name should only contain letters, digits, '-', '_' and '.'"
+        .to_string(),
+    )
+  }
+}
+
+fn parse_coverage_line(value: &str) -> Result<CoverageLineFlag, String> {
+  match value.rsplit_once(':') {
+    Some((file, line)) if !file.is_empty() => match line.parse::<usize>() {
+      Ok(line) if line > 0 => Ok(CoverageLineFlag {
+        file: file.to_string(),
+        line,
+      }),
+      _ => Err("line should be a positive integer".to_string()),
+    },
+    _ => Err("expected a file and a line, for example 'mod.ts:12'".to_string()),
+  }
+}
+
+fn coverage_line_validate(val: &str) -> Result<(), String> {
+  parse_coverage_line(val).map(|_| ())
+}
+
 fn inspect_arg_validate(val: &str) -> Result<(), String> {
   match val.parse::<SocketAddr>() {
     Ok(_) => Ok(()),
@@ -3410,6 +4277,49 @@ mod tests {
     );
   }
 
+  #[test]
+  fn outdated() {
+    let r = flags_from_vec(svec!["deno", "outdated", "--ignore=vendor", "src"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Outdated(OutdatedFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("src")],
+            ignore: vec![PathBuf::from("vendor")],
+          },
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn update() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "update",
+      "--latest",
+      "--dry-run",
+      "--lock=deno.lock"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Update(UpdateFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          latest: true,
+          dry_run: true,
+        }),
+        lock: Some(PathBuf::from("deno.lock")),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn version() {
     let r = flags_from_vec(svec!["deno", "--version"]);
@@ -3467,6 +4377,39 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_watch_hmr() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch-hmr=file1",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        watch: Some(vec![PathBuf::from("file1")]),
+        hmr: true,
+        no_clear_screen: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch",
+      "--watch-hmr",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_watch_with_no_clear_screen() {
     let r = flags_from_vec(svec![
@@ -3683,6 +4626,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         ..Flags::default()
       }
@@ -3705,6 +4651,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         ..Flags::default()
       }
@@ -3727,6 +4676,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         ..Flags::default()
       }
@@ -3749,6 +4701,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         watch: Some(vec![]),
         ..Flags::default()
@@ -3773,6 +4728,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         watch: Some(vec![]),
         no_clear_screen: true,
@@ -3804,6 +4762,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         watch: Some(vec![]),
         ..Flags::default()
@@ -3827,6 +4788,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
@@ -3857,6 +4821,9 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         watch: Some(vec![]),
@@ -3893,6 +4860,9 @@ mod tests {
           single_quote: Some(true),
           prose_wrap: Some("never".to_string()),
           no_semicolons: Some(true),
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
         }),
         ..Flags::default()
       }
@@ -3922,10 +4892,75 @@ mod tests {
           single_quote: Some(false),
           prose_wrap: None,
           no_semicolons: Some(false),
+          staged: false,
+          changed_since: None,
+          changed_lines: false,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn fmt_git_changes() {
+    let r = flags_from_vec(svec!["deno", "fmt", "--staged", "--changed-lines"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: false,
+          ext: "ts".to_string(),
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          staged: true,
+          changed_since: None,
+          changed_lines: true,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "fmt", "--check", "--changed-since=main"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Fmt(FmtFlags {
+          check: true,
+          ext: "ts".to_string(),
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          use_tabs: None,
+          line_width: None,
+          indent_width: None,
+          single_quote: None,
+          prose_wrap: None,
+          no_semicolons: None,
+          staged: false,
+          changed_since: Some("main".to_string()),
+          changed_lines: false,
         }),
         ..Flags::default()
       }
     );
+
+    let r =
+      flags_from_vec(svec!["deno", "fmt", "--staged", "--changed-since=main"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "fmt", "--changed-lines"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "fmt", "--staged", "--watch"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -3948,6 +4983,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
@@ -3977,6 +5015,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         watch: Some(vec![]),
         ..Flags::default()
@@ -4008,6 +5049,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         watch: Some(vec![]),
         no_clear_screen: true,
@@ -4034,6 +5078,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
@@ -4054,6 +5101,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
@@ -4080,6 +5130,9 @@ mod tests {
           maybe_rules_exclude: Some(svec!["no-const-assign"]),
           json: false,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
@@ -4100,6 +5153,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
@@ -4127,6 +5183,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: true,
           compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
@@ -4155,6 +5214,9 @@ mod tests {
           maybe_rules_exclude: None,
           json: false,
           compact: true,
+          reporter: None,
+          fix: false,
+          fix_dry_run: false,
         }),
         config_flag: ConfigFlag::Path("Deno.jsonc".to_string()),
         ..Flags::default()
@@ -4163,51 +5225,208 @@ mod tests {
   }
 
   #[test]
-  fn types() {
-    let r = flags_from_vec(svec!["deno", "types"]);
+  fn lint_reporter() {
+    let r = flags_from_vec(svec!["deno", "lint", "--reporter=sarif"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Types,
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          reporter: Some("sarif".to_string()),
+          fix: false,
+          fix_dry_run: false,
+        }),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn cache() {
-    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
+    let r =
+      flags_from_vec(svec!["deno", "lint", "--reporter=github", "--json"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: true,
+          compact: false,
+          reporter: Some("github".to_string()),
+          fix: false,
+          fix_dry_run: false,
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--reporter=xml"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn check() {
-    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
+  fn lint_fix() {
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "script_1.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Check(CheckFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("script_1.ts")],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          reporter: None,
+          fix: true,
+          fix_dry_run: false,
         }),
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    for all_flag in ["--remote", "--all"] {
-      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
-      assert_eq!(
-        r.unwrap(),
-        Flags {
-          subcommand: DenoSubcommand::Check(CheckFlags {
+    let r = flags_from_vec(svec!["deno", "lint", "--fix-dry-run"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+          reporter: None,
+          fix: false,
+          fix_dry_run: true,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "--fix-dry-run"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn types() {
+    let r = flags_from_vec(svec!["deno", "types"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Types,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cache() {
+    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          export: None,
+          import: None,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cache_export_import() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--lock=deno.lock",
+      "--export=deps.tar.gz",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          export: Some(PathBuf::from("deps.tar.gz")),
+          import: None,
+        }),
+        lock: Some(PathBuf::from("deno.lock")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--lock=deno.lock",
+      "--import=deps.tar.gz"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: vec![],
+          export: None,
+          import: Some(PathBuf::from("deps.tar.gz")),
+        }),
+        lock: Some(PathBuf::from("deno.lock")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--import=deps.tar.gz",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn check() {
+    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    for all_flag in ["--remote", "--all"] {
+      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
+      assert_eq!(
+        r.unwrap(),
+        Flags {
+          subcommand: DenoSubcommand::Check(CheckFlags {
             files: svec!["script.ts"],
           }),
           type_check_mode: TypeCheckMode::All,
@@ -4226,6 +5445,29 @@ mod tests {
     }
   }
 
+  #[test]
+  fn check_watch() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "check",
+      "--watch",
+      "--no-clear-screen",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
+        no_clear_screen: true,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn info() {
     let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
@@ -5044,6 +6286,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts"],
+          export: None,
+          import: None,
         }),
         import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
@@ -5083,6 +6327,8 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts", "script_two.ts"],
+          export: None,
+          import: None,
         }),
         ..Flags::default()
       }
@@ -5523,6 +6769,34 @@ mod tests {
     );
   }
 
+  #[test]
+  fn allow_scripts() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-scripts=esbuild,@prisma/client,sharp:node-gyp",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_scripts: Some(svec![
+          "esbuild",
+          "@prisma/client",
+          "sharp:node-gyp"
+        ]),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-scripts", "script.ts"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn cached_only() {
     let r = flags_from_vec(svec!["deno", "run", "--cached-only", "script.ts"]);
@@ -5732,6 +7006,13 @@ mod tests {
           shuffle: None,
           concurrent_jobs: None,
           trace_ops: true,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         unstable: true,
         no_prompt: true,
@@ -5805,6 +7086,13 @@ mod tests {
           },
           concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5835,6 +7123,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5869,6 +7164,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5897,6 +7199,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         no_prompt: true,
         watch: None,
@@ -5925,6 +7234,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5952,6 +7268,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5981,6 +7304,13 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          coverage_per_test: false,
+          changed: None,
+          shard: None,
+          retries: 0,
+          timeout: None,
+          reporter: Default::default(),
+          junit_path: None,
         }),
         watch: Some(vec![]),
         type_check_mode: TypeCheckMode::Local,
@@ -5992,141 +7322,377 @@ mod tests {
   }
 
   #[test]
-  fn bundle_with_cafile() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "bundle",
-      "--cert",
-      "example.crt",
-      "source.ts"
-    ]);
+  fn test_reporter() {
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=pretty"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Pretty,
+          ..TestFlags::default()
         }),
+        no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
-        ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn upgrade_with_ca_file() {
-    let r = flags_from_vec(svec!["deno", "upgrade", "--cert", "example.crt"]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=dot"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
-          force: false,
-          dry_run: false,
-          canary: false,
-          version: None,
-          output: None,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Dot,
+          ..TestFlags::default()
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn cache_with_cafile() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "cache",
-      "--cert",
-      "example.crt",
-      "script.ts",
-      "script_two.ts"
-    ]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=junit"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts", "script_two.ts"],
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Junit,
+          ..TestFlags::default()
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn info_with_cafile() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "info",
-      "--cert",
-      "example.crt",
-      "https://example.com"
-    ]);
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=tap"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("https://example.com".to_string()),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Tap,
+          ..TestFlags::default()
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn doc() {
-    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=dot",
+      "--junit-path=report.xml"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: true,
-          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
-          filter: None,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Dot,
+          junit_path: Some("report.xml".to_string()),
+          ..TestFlags::default()
         }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=foo"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_coverage_per_test() {
     let r = flags_from_vec(svec![
       "deno",
-      "doc",
-      "path/to/module.ts",
-      "SomeClass.someField"
+      "test",
+      "--coverage=cov",
+      "--coverage-per-test"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
-          filter: Some("SomeClass.someField".to_string()),
+        subcommand: DenoSubcommand::Test(TestFlags {
+          coverage_per_test: true,
+          ..TestFlags::default()
         }),
+        coverage_dir: Some("cov".to_string()),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "doc"]);
+    let r = flags_from_vec(svec!["deno", "test", "--coverage-per-test"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_changed() {
+    let r = flags_from_vec(svec!["deno", "test", "--changed"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          source_file: Default::default(),
-          filter: None,
+        subcommand: DenoSubcommand::Test(TestFlags {
+          changed: Some("HEAD".to_string()),
+          ..TestFlags::default()
         }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "doc", "--builtin", "Deno.Listener"]);
+    let r = flags_from_vec(svec!["deno", "test", "--changed=origin/main"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          changed: Some("origin/main".to_string()),
+          ..TestFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_shard() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--shard=2/8",
+      "--shard-timings=timings.jsonl"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          shard: Some(TestShardFlags {
+            index: 2,
+            total: 8,
+            timings_path: Some("timings.jsonl".to_string()),
+          }),
+          ..TestFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    for value in ["0/8", "9/8", "1", "a/b"] {
+      let r = flags_from_vec(svec!["deno", "test", format!("--shard={value}")]);
+      assert!(r.is_err());
+    }
+
+    let r =
+      flags_from_vec(svec!["deno", "test", "--shard-timings=timings.jsonl"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_retries() {
+    let r = flags_from_vec(svec!["deno", "test", "--retries=3"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          retries: 3,
+          ..TestFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--retries=-1"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_timeout() {
+    let r = flags_from_vec(svec!["deno", "test", "--timeout=5000"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          timeout: Some(5000),
+          ..TestFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--timeout=0"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_json() {
+    let r = flags_from_vec(svec!["deno", "test", "--json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          reporter: TestReporterConfig::Json,
+          ..TestFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--json", "--reporter=dot"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn bundle_with_cafile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--cert",
+      "example.crt",
+      "source.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ca_data: Some(CaData::File("example.crt".to_owned())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn upgrade_with_ca_file() {
+    let r = flags_from_vec(svec!["deno", "upgrade", "--cert", "example.crt"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: false,
+          dry_run: false,
+          canary: false,
+          version: None,
+          output: None,
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cache_with_cafile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--cert",
+      "example.crt",
+      "script.ts",
+      "script_two.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts", "script_two.ts"],
+          export: None,
+          import: None,
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn info_with_cafile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "info",
+      "--cert",
+      "example.crt",
+      "https://example.com"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("https://example.com".to_string()),
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn doc() {
+    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: true,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "path/to/module.ts",
+      "SomeClass.someField"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: Some("SomeClass.someField".to_string()),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: Default::default(),
+          filter: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc", "--builtin", "Deno.Listener"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -6272,7 +7838,10 @@ mod tests {
           output: None,
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          lcov: false,
+          format: CoverageFormat::Pretty,
+          html: None,
+          thresholds: Default::default(),
+          tests_covering: None,
         }),
         ..Flags::default()
       }
@@ -6298,12 +7867,219 @@ mod tests {
           },
           include: vec![r"^file:".to_string()],
           exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
-          lcov: true,
+          format: CoverageFormat::Lcov,
           output: Some(PathBuf::from("foo.lcov")),
+          html: None,
+          thresholds: Default::default(),
+          tests_covering: None,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn coverage_tests_covering() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--tests-covering=src/mod.ts:12",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          format: CoverageFormat::Pretty,
+          output: None,
+          html: None,
+          thresholds: Default::default(),
+          tests_covering: Some(CoverageLineFlag {
+            file: "src/mod.ts".to_string(),
+            line: 12,
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    for value in ["src/mod.ts", "src/mod.ts:0", ":12"] {
+      let r = flags_from_vec(svec![
+        "deno",
+        "coverage",
+        format!("--tests-covering={value}"),
+        "foo.json"
+      ]);
+      assert!(r.is_err());
+    }
+  }
+
+  #[test]
+  fn coverage_with_format() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--format=cobertura",
+      "--output=cobertura.xml",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          format: CoverageFormat::Cobertura,
+          output: Some(PathBuf::from("cobertura.xml")),
+          html: None,
+          thresholds: Default::default(),
+          tests_covering: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--format=json", "foo.json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          format: CoverageFormat::Json,
+          output: None,
+          html: None,
+          thresholds: Default::default(),
+          tests_covering: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--lcov",
+      "--format=json",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn coverage_with_html() {
+    let r =
+      flags_from_vec(svec!["deno", "coverage", "--html=html_cov", "foo.json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          format: CoverageFormat::Pretty,
+          output: None,
+          html: Some(PathBuf::from("html_cov")),
+          thresholds: Default::default(),
+          tests_covering: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--lcov",
+      "--html=html_cov",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn coverage_with_thresholds() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=80",
+      "--threshold-lines=src/**/*.ts:95",
+      "--threshold-branches=50",
+      "--threshold-functions=C:\\src\\**:100",
+      "foo.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Coverage(CoverageFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("foo.json")],
+            ignore: vec![],
+          },
+          output: None,
+          include: vec![r"^file:".to_string()],
+          exclude: vec![r"test\.(js|mjs|ts|jsx|tsx)$".to_string()],
+          format: CoverageFormat::Pretty,
+          html: None,
+          thresholds: CoverageThresholdsFlags {
+            lines: vec![
+              CoverageThresholdFlag {
+                glob: None,
+                percent: 80,
+              },
+              CoverageThresholdFlag {
+                glob: Some("src/**/*.ts".to_string()),
+                percent: 95,
+              },
+            ],
+            branches: vec![CoverageThresholdFlag {
+              glob: None,
+              percent: 50,
+            }],
+            functions: vec![CoverageThresholdFlag {
+              glob: Some("C:\\src\\**".to_string()),
+              percent: 100,
+            }],
+          },
+          tests_covering: None,
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=101",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "coverage",
+      "--threshold-lines=:50",
+      "foo.json"
+    ]);
+    assert!(r.is_err());
   }
   #[test]
   fn location_with_bad_scheme() {
@@ -6408,6 +8184,44 @@ mod tests {
     );
   }
 
+  #[test]
+  fn task_watch() {
+    let r =
+      flags_from_vec(svec!["deno", "task", "--watch", "build", "--watch"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+        }),
+        argv: svec!["--watch"],
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "task",
+      "--watch=src,static",
+      "--no-clear-screen",
+      "build"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+        }),
+        watch: Some(vec![PathBuf::from("src"), PathBuf::from("static")]),
+        no_clear_screen: true,
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn task_subcommand() {
     let r = flags_from_vec(svec!["deno", "task", "build", "hello", "world",]);
@@ -6634,6 +8448,10 @@ mod tests {
         subcommand: DenoSubcommand::Bench(BenchFlags {
           filter: Some("- foo".to_string()),
           json: true,
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
+          metrics: false,
           files: FileFlags {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
@@ -6659,6 +8477,10 @@ mod tests {
         subcommand: DenoSubcommand::Bench(BenchFlags {
           filter: None,
           json: false,
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
+          metrics: false,
           files: FileFlags {
             include: vec![],
             ignore: vec![],
@@ -6672,6 +8494,54 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bench_metrics() {
+    let r = flags_from_vec(svec!["deno", "bench", "--metrics"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          metrics: true,
+          ..BenchFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bench_baseline() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--save-baseline=next",
+      "--baseline=main",
+      "--regression-threshold=10"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          save_baseline: Some("next".to_string()),
+          baseline: Some("main".to_string()),
+          regression_threshold: Some(10),
+          ..BenchFlags::default()
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "bench", "--regression-threshold=10"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "bench", "--baseline=../main"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_with_check() {
     let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
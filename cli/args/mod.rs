@@ -8,11 +8,15 @@ mod lockfile;
 pub mod package_json;
 
 pub use self::import_map::resolve_import_map_from_specifier;
+use self::package_json::LocalPackages;
 use self::package_json::PackageJsonDeps;
+use self::package_json::Workspace;
 use ::import_map::ImportMap;
 use indexmap::IndexMap;
 
+use crate::npm::NpmRc;
 use crate::npm::NpmResolutionSnapshot;
+pub use config_file::AllowScriptsPackage;
 pub use config_file::BenchConfig;
 pub use config_file::CompilerOptions;
 pub use config_file::ConfigFile;
@@ -115,6 +119,9 @@ pub struct BenchOptions {
   pub files: FilesConfig,
   pub filter: Option<String>,
   pub json: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  pub regression_threshold: Option<u32>,
 }
 
 impl BenchOptions {
@@ -130,6 +137,9 @@ impl BenchOptions {
       ),
       filter: bench_flags.filter,
       json: bench_flags.json,
+      save_baseline: bench_flags.save_baseline,
+      baseline: bench_flags.baseline,
+      regression_threshold: bench_flags.regression_threshold,
     })
   }
 }
@@ -141,6 +151,9 @@ pub struct FmtOptions {
   pub ext: String,
   pub options: FmtOptionsConfig,
   pub files: FilesConfig,
+  pub staged: bool,
+  pub changed_since: Option<String>,
+  pub changed_lines: bool,
 }
 
 impl FmtOptions {
@@ -173,6 +186,14 @@ impl FmtOptions {
         maybe_fmt_flags.as_ref(),
         maybe_config_options,
       ),
+      staged: maybe_fmt_flags.as_ref().map(|f| f.staged).unwrap_or(false),
+      changed_since: maybe_fmt_flags
+        .as_ref()
+        .and_then(|f| f.changed_since.clone()),
+      changed_lines: maybe_fmt_flags
+        .as_ref()
+        .map(|f| f.changed_lines)
+        .unwrap_or(false),
       files: resolve_files(
         maybe_config_files,
         maybe_fmt_flags.map(|f| f.files),
@@ -233,6 +254,11 @@ pub struct TestOptions {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_ops: bool,
+  pub changed: Option<String>,
+  pub shard: Option<TestShardFlags>,
+  pub timeout: Option<u64>,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<String>,
 }
 
 impl TestOptions {
@@ -241,6 +267,9 @@ impl TestOptions {
     maybe_test_flags: Option<TestFlags>,
   ) -> Result<Self, AnyError> {
     let test_flags = maybe_test_flags.unwrap_or_default();
+    let timeout = test_flags
+      .timeout
+      .or_else(|| maybe_test_config.as_ref().and_then(|c| c.timeout));
 
     Ok(Self {
       files: resolve_files(
@@ -257,6 +286,11 @@ impl TestOptions {
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
       trace_ops: test_flags.trace_ops,
+      changed: test_flags.changed,
+      shard: test_flags.shard,
+      timeout,
+      reporter: test_flags.reporter,
+      junit_path: test_flags.junit_path,
     })
   }
 }
@@ -267,6 +301,21 @@ pub enum LintReporterKind {
   Pretty,
   Json,
   Compact,
+  Sarif,
+  Github,
+}
+
+impl LintReporterKind {
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "pretty" => Some(Self::Pretty),
+      "json" => Some(Self::Json),
+      "compact" => Some(Self::Compact),
+      "sarif" => Some(Self::Sarif),
+      "github" => Some(Self::Github),
+      _ => None,
+    }
+  }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -275,6 +324,9 @@ pub struct LintOptions {
   pub files: FilesConfig,
   pub is_stdin: bool,
   pub reporter_kind: LintReporterKind,
+  pub fix: bool,
+  pub fix_dry_run: bool,
+  pub plugins: Vec<ModuleSpecifier>,
 }
 
 impl LintOptions {
@@ -296,7 +348,9 @@ impl LintOptions {
 
     let mut maybe_reporter_kind =
       maybe_lint_flags.as_ref().and_then(|lint_flags| {
-        if lint_flags.json {
+        if let Some(reporter) = &lint_flags.reporter {
+          LintReporterKind::from_name(reporter)
+        } else if lint_flags.json {
           Some(LintReporterKind::Json)
         } else if lint_flags.compact {
           Some(LintReporterKind::Compact)
@@ -309,17 +363,20 @@ impl LintOptions {
       // Flag not set, so try to get lint reporter from the config file.
       if let Some(lint_config) = &maybe_lint_config {
         maybe_reporter_kind = match lint_config.report.as_deref() {
-          Some("json") => Some(LintReporterKind::Json),
-          Some("compact") => Some(LintReporterKind::Compact),
-          Some("pretty") => Some(LintReporterKind::Pretty),
-          Some(_) => {
-            bail!("Invalid lint report type in config file")
-          }
+          Some(report) => match LintReporterKind::from_name(report) {
+            Some(reporter_kind) => Some(reporter_kind),
+            None => bail!("Invalid lint report type in config file"),
+          },
           None => None,
         }
       }
     }
 
+    let (fix, fix_dry_run) = maybe_lint_flags
+      .as_ref()
+      .map(|f| (f.fix, f.fix_dry_run))
+      .unwrap_or_default();
+
     let (
       maybe_file_flags,
       maybe_rules_tags,
@@ -336,11 +393,15 @@ impl LintOptions {
       })
       .unwrap_or_default();
 
-    let (maybe_config_files, maybe_config_rules) =
-      maybe_lint_config.map(|c| (c.files, c.rules)).unzip();
+    let (maybe_config_files, maybe_config_rules, plugins) = maybe_lint_config
+      .map(|c| (Some(c.files), Some(c.rules), c.plugins))
+      .unwrap_or_default();
     Ok(Self {
       reporter_kind: maybe_reporter_kind.unwrap_or_default(),
       is_stdin,
+      fix,
+      fix_dry_run,
+      plugins,
       files: resolve_files(maybe_config_files, Some(maybe_file_flags)),
       rules: resolve_lint_rules_options(
         maybe_config_rules,
@@ -482,7 +543,7 @@ pub fn get_root_cert_store(
   Ok(root_cert_store)
 }
 
-const RESOLUTION_STATE_ENV_VAR_NAME: &str =
+pub const RESOLUTION_STATE_ENV_VAR_NAME: &str =
   "DENO_DONT_USE_INTERNAL_NODE_COMPAT_STATE";
 
 static NPM_PROCESS_STATE: Lazy<Option<NpmProcessState>> = Lazy::new(|| {
@@ -511,6 +572,7 @@ pub struct CliOptions {
   maybe_node_modules_folder: Option<PathBuf>,
   maybe_config_file: Option<ConfigFile>,
   maybe_package_json: Option<PackageJson>,
+  maybe_workspace: Option<Workspace>,
   maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
   overrides: CliOptionOverrides,
 }
@@ -545,12 +607,18 @@ impl CliOptions {
       maybe_package_json.as_ref(),
     )
     .with_context(|| "Resolving node_modules folder.")?;
+    let maybe_workspace = match &maybe_package_json {
+      Some(package_json) => package_json::discover_workspace(package_json)
+        .with_context(|| "Resolving npm workspace.")?,
+      None => None,
+    };
 
     Ok(Self {
       flags,
       maybe_config_file,
       maybe_lockfile,
       maybe_package_json,
+      maybe_workspace,
       maybe_node_modules_folder,
       overrides: Default::default(),
     })
@@ -769,6 +837,28 @@ impl CliOptions {
     }
   }
 
+  /// Resolves the npm packages whose lifecycle scripts are allowed to run.
+  /// The `--allow-scripts` flag takes precedence over the configuration file.
+  pub fn resolve_allow_scripts(
+    &self,
+  ) -> Result<Vec<AllowScriptsPackage>, AnyError> {
+    // sub processes of lifecycle scripts never run the scripts again
+    if NPM_PROCESS_STATE.is_some() {
+      return Ok(Vec::new());
+    }
+    if let Some(packages) = &self.flags.allow_scripts {
+      return Ok(AllowScriptsPackage::merge(
+        packages.iter().map(|p| AllowScriptsPackage::parse(p)),
+      ));
+    }
+    match &self.maybe_config_file {
+      Some(config_file) => {
+        Ok(config_file.to_allow_scripts_config()?.unwrap_or_default())
+      }
+      None => Ok(Vec::new()),
+    }
+  }
+
   /// Return the JSX import source configuration.
   pub fn to_maybe_jsx_import_source_config(
     &self,
@@ -797,6 +887,25 @@ impl CliOptions {
     &self.maybe_package_json
   }
 
+  /// Loads the npm registry config. The `.npmrc` of the project is the one
+  /// next to the package.json or the config file, or in the current
+  /// directory.
+  pub fn resolve_npmrc(&self) -> Arc<NpmRc> {
+    let maybe_project_dir = self
+      .maybe_package_json
+      .as_ref()
+      .map(|package_json| package_json.path.clone())
+      .or_else(|| {
+        self
+          .maybe_config_file
+          .as_ref()
+          .and_then(|config_file| config_file.specifier.to_file_path().ok())
+      })
+      .and_then(|path| path.parent().map(ToOwned::to_owned))
+      .or_else(|| std::env::current_dir().ok());
+    Arc::new(NpmRc::load(maybe_project_dir.as_deref()))
+  }
+
   pub fn maybe_package_json_deps(&self) -> Option<PackageJsonDeps> {
     if matches!(
       self.flags.subcommand,
@@ -805,13 +914,31 @@ impl CliOptions {
       // don't have any package json dependencies for deno task with no args
       None
     } else {
-      self
-        .maybe_package_json()
-        .as_ref()
-        .map(package_json::get_local_package_json_version_reqs)
+      self.maybe_package_json().as_ref().map(|package_json| {
+        package_json::get_workspace_package_json_version_reqs(
+          package_json,
+          self.maybe_workspace.as_ref(),
+        )
+      })
     }
   }
 
+  /// Gets the packages of the npm workspace and the `workspace:` and `file:`
+  /// dependencies of the package.json, which are resolved to their folder.
+  pub fn resolve_local_packages(&self) -> Result<LocalPackages, AnyError> {
+    let package_json = match &self.maybe_package_json {
+      Some(package_json) => package_json,
+      None => return Ok(LocalPackages::new()),
+    };
+    let tarballs_folder =
+      self.resolve_deno_dir()?.npm_local_tarballs_folder_path();
+    package_json::get_local_packages(
+      package_json,
+      self.maybe_workspace.as_ref(),
+      Some(&tarballs_folder),
+    )
+  }
+
   pub fn resolve_fmt_options(
     &self,
     fmt_flags: FmtFlags,
@@ -821,7 +948,10 @@ impl CliOptions {
     } else {
       None
     };
-    FmtOptions::resolve(maybe_fmt_config, Some(fmt_flags))
+    let mut fmt_options =
+      FmtOptions::resolve(maybe_fmt_config, Some(fmt_flags))?;
+    fmt_options.options.unstable = self.unstable();
+    Ok(fmt_options)
   }
 
   pub fn resolve_lint_options(
@@ -994,6 +1124,27 @@ impl CliOptions {
     }
   }
 
+  pub fn coverage_per_test(&self) -> bool {
+    match self.sub_command() {
+      DenoSubcommand::Test(flags) => flags.coverage_per_test,
+      _ => false,
+    }
+  }
+
+  pub fn test_retries(&self) -> usize {
+    match self.sub_command() {
+      DenoSubcommand::Test(flags) => flags.retries,
+      _ => 0,
+    }
+  }
+
+  pub fn bench_metrics(&self) -> bool {
+    match self.sub_command() {
+      DenoSubcommand::Bench(flags) => flags.metrics,
+      _ => false,
+    }
+  }
+
   pub fn shuffle_tests(&self) -> Option<u64> {
     match self.sub_command() {
       DenoSubcommand::Test(flags) => flags.shuffle,
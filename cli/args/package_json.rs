@@ -6,12 +6,20 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_graph::npm::NpmPackageReq;
 use deno_graph::semver::NpmVersionReqSpecifierParseError;
 use deno_graph::semver::VersionReq;
 use deno_runtime::deno_node::PackageJson;
 use thiserror::Error;
+use walkdir::WalkDir;
+
+use crate::npm::extract_local_tarball;
+use crate::util::checksum;
+use crate::util::path::glob_to_regex;
+
+const PACKAGE_JSON_NAME: &str = "package.json";
 
 #[derive(Debug, Clone, Error, PartialEq, Eq, Hash)]
 #[error("Could not find @ symbol in npm url '{value}'")]
@@ -51,7 +59,34 @@ pub enum PackageJsonDepValueParseError {
 pub type PackageJsonDeps =
   BTreeMap<String, Result<NpmPackageReq, PackageJsonDepValueParseError>>;
 
-/// Gets an application level package.json's npm package requirements.
+/// A package that is linked from a directory instead of installed from the
+/// npm registry.
+#[derive(Debug, Clone, Hash)]
+pub struct LocalPackage {
+  pub dir: PathBuf,
+  /// The npm package requirements of its package.json, which the bare
+  /// specifiers of its modules are resolved with.
+  pub deps: PackageJsonDeps,
+}
+
+/// The local packages by their bare specifier.
+pub type LocalPackages = BTreeMap<String, LocalPackage>;
+
+/// An npm workspace, which are the packages matching the globs of the
+/// `workspaces` field of its root package.json.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+  pub root: PackageJson,
+  pub members: Vec<PackageJson>,
+}
+
+fn is_local_dep(value: &str) -> bool {
+  value.starts_with("workspace:") || value.starts_with("file:")
+}
+
+/// Gets an application level package.json's npm package requirements. The
+/// `workspace:` and `file:` dependencies are excluded, they are
+/// `LocalPackages`.
 ///
 /// Note that this function is not general purpose. It is specifically for
 /// parsing the application level package.json that the user has control
@@ -64,14 +99,12 @@ pub fn get_local_package_json_version_reqs(
     key: &str,
     value: &str,
   ) -> Result<NpmPackageReq, PackageJsonDepValueParseError> {
-    if value.starts_with("workspace:")
-      || value.starts_with("file:")
-      || value.starts_with("git:")
+    if value.starts_with("git:")
       || value.starts_with("http:")
       || value.starts_with("https:")
     {
       return Err(PackageJsonDepValueParseError::Unsupported {
-        scheme: key.split(':').next().unwrap().to_string(),
+        scheme: value.split(':').next().unwrap().to_string(),
       });
     }
     let (name, version_req) = parse_dep_entry_name_and_raw_version(key, value)
@@ -93,7 +126,9 @@ pub fn get_local_package_json_version_reqs(
   ) {
     if let Some(deps) = deps {
       for (key, value) in deps {
-        result.insert(key.to_string(), parse_entry(key, value));
+        if !is_local_dep(value) {
+          result.insert(key.to_string(), parse_entry(key, value));
+        }
       }
     }
   }
@@ -110,14 +145,241 @@ pub fn get_local_package_json_version_reqs(
   result
 }
 
+/// Gets the npm package requirements of a package.json along with the ones
+/// of the root of its workspace, where the package.json takes precedence.
+/// This mirrors the node_modules folder of the root being looked up after
+/// the one of the package. The dependencies on packages of the workspace are
+/// excluded.
+pub fn get_workspace_package_json_version_reqs(
+  package_json: &PackageJson,
+  maybe_workspace: Option<&Workspace>,
+) -> PackageJsonDeps {
+  let mut result = get_local_package_json_version_reqs(package_json);
+  if let Some(workspace) = maybe_workspace {
+    if workspace.root.path != package_json.path {
+      for (key, value) in get_local_package_json_version_reqs(&workspace.root) {
+        result.entry(key).or_insert(value);
+      }
+    }
+    for member in &workspace.members {
+      if let Some(name) = &member.name {
+        result.remove(name);
+      }
+    }
+  }
+  result
+}
+
+/// Gets the local packages of a package.json and of its workspace, which are
+/// the packages of the workspace by their name and the `workspace:` and
+/// `file:` dependencies. The tarballs of `file:` dependencies are extracted
+/// to the `maybe_tarballs_folder`, or skipped when there's none.
+pub fn get_local_packages(
+  package_json: &PackageJson,
+  maybe_workspace: Option<&Workspace>,
+  maybe_tarballs_folder: Option<&Path>,
+) -> Result<LocalPackages, AnyError> {
+  let mut result = LocalPackages::new();
+  let members = maybe_workspace
+    .map(|workspace| workspace.members.as_slice())
+    .unwrap_or_default();
+  for member in members {
+    if let Some(name) = &member.name {
+      result.insert(
+        name.clone(),
+        workspace_member_package(member, maybe_workspace),
+      );
+    }
+  }
+
+  let package_jsons = std::iter::once(package_json)
+    .chain(maybe_workspace.map(|workspace| &workspace.root))
+    .chain(members.iter());
+  for package_json in package_jsons {
+    let dir = package_json.path.parent().unwrap();
+    let deps = package_json
+      .dependencies
+      .iter()
+      .chain(package_json.dev_dependencies.iter())
+      .flatten();
+    for (key, value) in deps {
+      if result.contains_key(key) {
+        continue;
+      }
+      if let Some(workspace_req) = value.strip_prefix("workspace:") {
+        // the package might be aliased like `workspace:name@*`
+        let name = match workspace_req.rsplit_once('@') {
+          Some((name, _)) if !name.is_empty() => name,
+          _ => key.as_str(),
+        };
+        let maybe_member = members
+          .iter()
+          .find(|member| member.name.as_deref() == Some(name));
+        match maybe_member {
+          Some(member) => {
+            let package = workspace_member_package(member, maybe_workspace);
+            result.insert(key.clone(), package);
+          }
+          None => log::warn!(
+            "Dependency \"{}\" in {} is not a package of the workspace.",
+            key,
+            package_json.path.display()
+          ),
+        }
+      } else if let Some(file_path) = value.strip_prefix("file:") {
+        let path = dir.join(file_path);
+        if path.is_dir() {
+          result.insert(key.clone(), load_local_package(path)?);
+        } else if let Some(tarballs_folder) = maybe_tarballs_folder {
+          let package_dir =
+            extract_local_tarball_to_folder(&path, tarballs_folder)
+              .with_context(|| {
+                format!(
+                  "Failed extracting \"{}\" for dependency \"{}\".",
+                  path.display(),
+                  key
+                )
+              })?;
+          result.insert(key.clone(), load_local_package(package_dir)?);
+        }
+      }
+    }
+  }
+  Ok(result)
+}
+
+fn workspace_member_package(
+  member: &PackageJson,
+  maybe_workspace: Option<&Workspace>,
+) -> LocalPackage {
+  LocalPackage {
+    dir: member.path.parent().unwrap().to_path_buf(),
+    deps: get_workspace_package_json_version_reqs(member, maybe_workspace),
+  }
+}
+
+/// Loads a package linked with a `file:` dependency, which might not have a
+/// package.json.
+fn load_local_package(dir: PathBuf) -> Result<LocalPackage, AnyError> {
+  let path = dir.join(PACKAGE_JSON_NAME);
+  let deps = match std::fs::read_to_string(&path) {
+    Ok(source) => get_local_package_json_version_reqs(
+      &PackageJson::load_from_string(path, source)?,
+    ),
+    Err(_) => PackageJsonDeps::new(),
+  };
+  Ok(LocalPackage { dir, deps })
+}
+
+/// Extracts a tarball to a folder named by the checksum of its content, so
+/// it's only extracted again when it changed.
+fn extract_local_tarball_to_folder(
+  tarball_path: &Path,
+  tarballs_folder: &Path,
+) -> Result<PathBuf, AnyError> {
+  let data = std::fs::read(tarball_path)?;
+  let output_folder = tarballs_folder.join(checksum::gen(&[&data]));
+  if !output_folder.exists() {
+    extract_local_tarball(&data, &output_folder)?;
+  }
+  Ok(output_folder)
+}
+
+/// Discovers the workspace of a package.json, which is the one of the
+/// closest package.json with a `workspaces` field that includes it.
+pub fn discover_workspace(
+  package_json: &PackageJson,
+) -> Result<Option<Workspace>, AnyError> {
+  let package_dir = package_json.path.parent().unwrap();
+  for ancestor in package_dir.ancestors() {
+    let root = if ancestor == package_dir {
+      package_json.clone()
+    } else {
+      let path = ancestor.join(PACKAGE_JSON_NAME);
+      match std::fs::read_to_string(&path) {
+        Ok(source) => PackageJson::load_from_string(path, source)?,
+        Err(_) => continue,
+      }
+    };
+    if root.workspaces.is_none() {
+      continue;
+    }
+    let members = discover_workspace_members(&root)?;
+    if ancestor == package_dir
+      || members
+        .iter()
+        .any(|member| member.path == package_json.path)
+    {
+      log::debug!("npm workspace found at '{}'", root.path.display());
+      return Ok(Some(Workspace { root, members }));
+    }
+  }
+  Ok(None)
+}
+
+/// Finds the packages matching the globs of the `workspaces` field of a
+/// package.json. Globs starting with `!` exclude packages.
+fn discover_workspace_members(
+  root: &PackageJson,
+) -> Result<Vec<PackageJson>, AnyError> {
+  let root_dir = root.path.parent().unwrap();
+  let mut include = Vec::new();
+  let mut exclude = Vec::new();
+  let mut max_depth = 0;
+  for glob in root.workspaces.iter().flatten() {
+    let (globs, glob) = match glob.strip_prefix('!') {
+      Some(glob) => (&mut exclude, glob),
+      None => (&mut include, glob.as_str()),
+    };
+    let glob = glob.trim_start_matches("./").trim_end_matches('/');
+    max_depth = if glob.contains("**") {
+      usize::MAX
+    } else {
+      max_depth.max(glob.split('/').count())
+    };
+    globs.push(glob_to_regex(glob)?);
+  }
+
+  let mut members = Vec::new();
+  let mut walker = WalkDir::new(root_dir)
+    .min_depth(1)
+    .max_depth(max_depth)
+    .sort_by_file_name()
+    .into_iter();
+  while let Some(entry) = walker.next() {
+    let entry = entry?;
+    if !entry.file_type().is_dir() {
+      continue;
+    }
+    let file_name = entry.file_name().to_string_lossy();
+    if file_name == "node_modules" || file_name.starts_with('.') {
+      walker.skip_current_dir();
+      continue;
+    }
+    let relative_path = entry
+      .path()
+      .strip_prefix(root_dir)
+      .unwrap()
+      .to_string_lossy()
+      .replace('\\', "/");
+    if include.iter().any(|regex| regex.is_match(&relative_path))
+      && !exclude.iter().any(|regex| regex.is_match(&relative_path))
+    {
+      let path = entry.path().join(PACKAGE_JSON_NAME);
+      if let Ok(source) = std::fs::read_to_string(&path) {
+        members.push(PackageJson::load_from_string(path, source)?);
+      }
+    }
+  }
+  Ok(members)
+}
+
 /// Attempts to discover the package.json file, maybe stopping when it
 /// reaches the specified `maybe_stop_at` directory.
 pub fn discover_from(
   start: &Path,
   maybe_stop_at: Option<PathBuf>,
 ) -> Result<Option<PackageJson>, AnyError> {
-  const PACKAGE_JSON_NAME: &str = "package.json";
-
   // note: ancestors() includes the `start` path
   for ancestor in start.ancestors() {
     let path = ancestor.join(PACKAGE_JSON_NAME);
@@ -179,7 +441,13 @@ mod test {
   fn get_local_package_json_version_reqs_for_tests(
     package_json: &PackageJson,
   ) -> BTreeMap<String, Result<NpmPackageReq, String>> {
-    get_local_package_json_version_reqs(package_json)
+    deps_for_tests(get_local_package_json_version_reqs(package_json))
+  }
+
+  fn deps_for_tests(
+    deps: PackageJsonDeps,
+  ) -> BTreeMap<String, Result<NpmPackageReq, String>> {
+    deps
       .into_iter()
       .map(|(k, v)| {
         (
@@ -264,10 +532,6 @@ mod test {
     assert_eq!(
       result,
       BTreeMap::from([
-        (
-          "file".to_string(),
-          Err("Not implemented scheme: file".to_string()),
-        ),
         (
           "git".to_string(),
           Err("Not implemented scheme: git".to_string()),
@@ -284,11 +548,98 @@ mod test {
           "test".to_string(),
           Ok(NpmPackageReq::from_str("test@1").unwrap())
         ),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_workspace() {
+    let temp_dir = test_util::TempDir::new();
+    for dir in ["packages/a", "packages/b", "packages/ignored", "local"] {
+      temp_dir.create_dir_all(dir);
+    }
+    temp_dir.write(
+      "package.json",
+      r#"{
+        "workspaces": ["packages/*", "!packages/ignored"],
+        "dependencies": { "chalk": "^5.0.0", "local": "file:./local" }
+      }"#,
+    );
+    temp_dir.write(
+      "packages/a/package.json",
+      r#"{
+        "name": "a",
+        "dependencies": { "b": "workspace:*", "chalk": "^4.0.0" }
+      }"#,
+    );
+    temp_dir.write(
+      "packages/b/package.json",
+      r#"{ "name": "b", "dependencies": { "a": "^1.0.0", "ms": "2" } }"#,
+    );
+    temp_dir.write("packages/ignored/package.json", r#"{ "name": "ignored" }"#);
+
+    let load = |path: &str| {
+      let path = temp_dir.path().join(path);
+      let source = std::fs::read_to_string(&path).unwrap();
+      PackageJson::load_from_string(path, source).unwrap()
+    };
+    let package_json = load("packages/a/package.json");
+    let workspace = discover_workspace(&package_json).unwrap().unwrap();
+    assert_eq!(workspace.root.path, temp_dir.path().join("package.json"));
+    assert_eq!(
+      workspace
+        .members
+        .iter()
+        .map(|member| member.name.clone().unwrap())
+        .collect::<Vec<_>>(),
+      vec!["a", "b"]
+    );
+
+    // the dependencies of the other packages don't leak into the ones of the
+    // package, only the root's are used when the package doesn't have them
+    let deps = deps_for_tests(get_workspace_package_json_version_reqs(
+      &package_json,
+      Some(&workspace),
+    ));
+    assert_eq!(
+      deps,
+      BTreeMap::from([(
+        "chalk".to_string(),
+        Ok(NpmPackageReq::from_str("chalk@^4.0.0").unwrap())
+      )])
+    );
+
+    let local_packages =
+      get_local_packages(&package_json, Some(&workspace), None).unwrap();
+    assert_eq!(
+      local_packages
+        .iter()
+        .map(|(name, package)| (name.as_str(), package.dir.clone()))
+        .collect::<Vec<_>>(),
+      vec![
+        ("a", temp_dir.path().join("packages/a")),
+        ("b", temp_dir.path().join("packages/b")),
+        ("local", temp_dir.path().join("local")),
+      ]
+    );
+    assert_eq!(deps_for_tests(local_packages["a"].deps.clone()), deps);
+    assert_eq!(
+      deps_for_tests(local_packages["b"].deps.clone()),
+      BTreeMap::from([
+        (
+          "chalk".to_string(),
+          Ok(NpmPackageReq::from_str("chalk@^5.0.0").unwrap())
+        ),
         (
-          "work".to_string(),
-          Err("Not implemented scheme: work".to_string()),
-        )
+          "ms".to_string(),
+          Ok(NpmPackageReq::from_str("ms@2").unwrap())
+        ),
       ])
     );
+    assert!(local_packages["local"].deps.is_empty());
+
+    // not in the workspace
+    let package_json = load("packages/ignored/package.json");
+    assert!(discover_workspace(&package_json).unwrap().is_none());
   }
 }
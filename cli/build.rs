@@ -144,6 +144,10 @@ mod ts {
       "cargo:rerun-if-changed={}",
       cwd.join("js").join("40_testing.js").display()
     );
+    println!(
+      "cargo:rerun-if-changed={}",
+      cwd.join("js").join("40_lint.js").display()
+    );
 
     // create a copy of the vector that includes any op crate libs to be passed
     // to the JavaScript compiler to build into the snapshot
@@ -360,6 +364,7 @@ fn create_cli_snapshot(snapshot_path: PathBuf) {
 
   let mut esm_files = include_js_files!(
     dir "js",
+    "40_lint.js",
     "40_testing.js",
   );
   esm_files.push(ExtensionFileSource {
@@ -1,7 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use super::DiskCache;
+use crate::util::checksum;
 
+use std::path::Path;
 use std::path::PathBuf;
 
 /// `DenoDir` serves as coordinator for multiple `DiskCache`s containing them
@@ -108,17 +110,39 @@ impl DenoDir {
     self.root.join("npm")
   }
 
+  /// Folder used for the extracted tarballs of `file:` dependencies.
+  pub fn npm_local_tarballs_folder_path(&self) -> PathBuf {
+    self.root.join("npm_local_tarballs")
+  }
+
   /// Path used for the REPL history file.
   pub fn repl_history_file_path(&self) -> PathBuf {
     self.root.join("deno_history.txt")
   }
 
+  /// File used for the history of the REPL when it's run in a project, keyed
+  /// by the directory of the project.
+  pub fn repl_project_history_file_path(&self, project_dir: &Path) -> PathBuf {
+    let hash = checksum::gen(&[project_dir.to_string_lossy().as_bytes()]);
+    self.root.join("repl_history").join(format!("{hash}.txt"))
+  }
+
+  /// Folder used for the baselines saved by `deno bench --save-baseline`.
+  pub fn bench_baselines_folder_path(&self) -> PathBuf {
+    self.root.join("bench_baselines")
+  }
+
   /// Folder path used for downloading new versions of deno.
   pub fn dl_folder_path(&self) -> PathBuf {
     self.root.join("dl")
   }
 }
 
+/// Gets the home directory of the current user.
+pub fn home_dir() -> Option<PathBuf> {
+  dirs::home_dir()
+}
+
 /// To avoid the poorly managed dirs crate
 #[cfg(not(windows))]
 mod dirs {
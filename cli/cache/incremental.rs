@@ -41,6 +41,11 @@ impl IncrementalCache {
     })
   }
 
+  /// A cache that never has any files stored.
+  pub fn disabled() -> Self {
+    IncrementalCache(None)
+  }
+
   pub fn is_file_same(&self, file_path: &Path, file_text: &str) -> bool {
     if let Some(inner) = &self.0 {
       inner.is_file_same(file_path, file_text)
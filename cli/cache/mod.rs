@@ -25,9 +25,11 @@ mod parsed_source;
 
 pub use check::TypeCheckCache;
 pub use common::FastInsecureHasher;
+pub use deno_dir::home_dir;
 pub use deno_dir::DenoDir;
 pub use disk_cache::DiskCache;
 pub use emit::EmitCache;
+pub use http_cache::url_to_filename;
 pub use http_cache::CachedUrlMetadata;
 pub use http_cache::HttpCache;
 pub use incremental::IncrementalCache;
@@ -13,6 +13,7 @@ use deno_core::futures::StreamExt;
 use deno_core::url::Url;
 use deno_runtime::deno_fetch::create_http_client;
 use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
 use deno_runtime::deno_fetch::reqwest::header::LOCATION;
 use deno_runtime::deno_fetch::reqwest::Response;
 use deno_runtime::deno_tls::rustls::RootCertStore;
@@ -258,7 +259,7 @@ impl HttpClient {
     &self,
     url: U,
   ) -> Result<Vec<u8>, AnyError> {
-    let maybe_bytes = self.inner_download(url, None).await?;
+    let maybe_bytes = self.inner_download(url, None, None).await?;
     match maybe_bytes {
       Some(bytes) => Ok(bytes),
       None => Err(custom_error("Http", "Not found.")),
@@ -270,15 +271,30 @@ impl HttpClient {
     url: U,
     progress_guard: &UpdateGuard,
   ) -> Result<Option<Vec<u8>>, AnyError> {
-    self.inner_download(url, Some(progress_guard)).await
+    self.inner_download(url, None, Some(progress_guard)).await
+  }
+
+  /// Same as `download_with_progress`, sending an `Authorization` header.
+  pub async fn download_with_progress_and_auth<U: reqwest::IntoUrl>(
+    &self,
+    url: U,
+    maybe_auth_header: Option<&str>,
+    progress_guard: &UpdateGuard,
+  ) -> Result<Option<Vec<u8>>, AnyError> {
+    self
+      .inner_download(url, maybe_auth_header, Some(progress_guard))
+      .await
   }
 
   async fn inner_download<U: reqwest::IntoUrl>(
     &self,
     url: U,
+    maybe_auth_header: Option<&str>,
     progress_guard: Option<&UpdateGuard>,
   ) -> Result<Option<Vec<u8>>, AnyError> {
-    let response = self.get_redirected_response(url).await?;
+    let response = self
+      .get_redirected_response_with_auth(url, maybe_auth_header)
+      .await?;
 
     if response.status() == 404 {
       return Ok(None);
@@ -303,14 +319,35 @@ impl HttpClient {
   pub async fn get_redirected_response<U: reqwest::IntoUrl>(
     &self,
     url: U,
+  ) -> Result<Response, AnyError> {
+    self.get_redirected_response_with_auth(url, None).await
+  }
+
+  /// Same as `get_redirected_response`, sending an `Authorization` header.
+  /// The header is only sent to the origin of the url and not to the other
+  /// origins it redirects to.
+  async fn get_redirected_response_with_auth<U: reqwest::IntoUrl>(
+    &self,
+    url: U,
+    maybe_auth_header: Option<&str>,
   ) -> Result<Response, AnyError> {
     let mut url = url.into_url()?;
-    let mut response = self.get_no_redirect(url.clone()).send().await?;
+    let origin = url.origin();
+    let get = |url: &Url| {
+      let request = self.get_no_redirect(url.clone());
+      match maybe_auth_header {
+        Some(auth_header) if url.origin() == origin => {
+          request.header(AUTHORIZATION, auth_header)
+        }
+        _ => request,
+      }
+    };
+    let mut response = get(&url).send().await?;
     let status = response.status();
     if status.is_redirection() {
       for _ in 0..5 {
         let new_url = resolve_redirect_from_response(&url, &response)?;
-        let new_response = self.get_no_redirect(new_url.clone()).send().await?;
+        let new_response = get(&new_url).send().await?;
         let status = new_response.status();
         if status.is_redirection() {
           response = new_response;
@@ -0,0 +1,173 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const internals = globalThis.__bootstrap.internals;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayIsArray,
+  ArrayPrototypeEvery,
+  ArrayPrototypeIncludes,
+  ArrayPrototypePush,
+  ObjectEntries,
+  ObjectKeys,
+  SafeArrayIterator,
+  String,
+  TypeError,
+} = primordials;
+
+/**
+ * @typedef LintPluginDiagnostic
+ * @property {string} code
+ * @property {string} message
+ * @property {string | null} hint
+ * @property {number} start
+ * @property {number} end
+ */
+
+/**
+ * @typedef LintPluginRule
+ * @property {string} code
+ * @property {string[]} tags
+ * @property {(context: object) => Record<string, (node: object) => void>} create
+ */
+
+/**
+ * @typedef LintPluginRuleInfo
+ * @property {string} code
+ * @property {string[]} tags
+ */
+
+/** @type {LintPluginRule[]} */
+const rules = [];
+
+/**
+ * Registers the rules of a plugin module. Rules without `tags` are
+ * recommended, like most of the built-in rules.
+ * @param {string} specifier
+ * @param {Record<string, any>} namespace
+ * @returns {LintPluginRuleInfo[]} the registered rules
+ */
+function installPlugin(specifier, namespace) {
+  const plugin = namespace.default;
+  if (typeof plugin !== "object" || plugin === null) {
+    throw new TypeError(
+      `Lint plugin "${specifier}" must default export a plugin object.`,
+    );
+  }
+  if (typeof plugin.name !== "string" || plugin.name === "") {
+    throw new TypeError(`Lint plugin "${specifier}" must have a "name".`);
+  }
+  if (typeof plugin.rules !== "object" || plugin.rules === null) {
+    throw new TypeError(`Lint plugin "${plugin.name}" must have "rules".`);
+  }
+
+  /** @type {LintPluginRuleInfo[]} */
+  const infos = [];
+  for (
+    const { 0: name, 1: rule } of new SafeArrayIterator(
+      ObjectEntries(plugin.rules),
+    )
+  ) {
+    const code = `${plugin.name}/${name}`;
+    if (typeof rule?.create !== "function") {
+      throw new TypeError(`Lint rule "${code}" must have a "create" function.`);
+    }
+    const tags = rule.tags ?? ["recommended"];
+    if (
+      !ArrayIsArray(tags) ||
+      !ArrayPrototypeEvery(tags, (tag) => typeof tag === "string")
+    ) {
+      throw new TypeError(`Lint rule "${code}" must have string "tags".`);
+    }
+    ArrayPrototypePush(rules, { code, tags, create: rule.create });
+    ArrayPrototypePush(infos, { code, tags });
+  }
+  return infos;
+}
+
+/**
+ * Runs the enabled rules of the plugins on a program.
+ * @param {string} fileName
+ * @param {string} source
+ * @param {object} program the swc AST of the file
+ * @param {number} startPos the position of the start of the file in the AST
+ * @param {string[]} codes the codes of the enabled rules
+ * @returns {LintPluginDiagnostic[]}
+ */
+function runPlugins(fileName, source, program, startPos, codes) {
+  /** @type {LintPluginDiagnostic[]} */
+  const diagnostics = [];
+  const visitors = [];
+  for (const rule of new SafeArrayIterator(rules)) {
+    if (!ArrayPrototypeIncludes(codes, rule.code)) {
+      continue;
+    }
+    const context = {
+      id: rule.code,
+      fileName,
+      source,
+      report({ node, message, hint }) {
+        if (typeof node?.span?.start !== "number") {
+          throw new TypeError(
+            `Lint rule "${rule.code}" reported a diagnostic without a node.`,
+          );
+        }
+        ArrayPrototypePush(diagnostics, {
+          code: rule.code,
+          message: String(message),
+          hint: hint === undefined ? null : String(hint),
+          start: node.span.start - startPos,
+          end: node.span.end - startPos,
+        });
+      },
+    };
+    const visitor = rule.create(context);
+    if (typeof visitor === "object" && visitor !== null) {
+      ArrayPrototypePush(visitors, visitor);
+    }
+  }
+  if (visitors.length > 0) {
+    visit(program, visitors);
+  }
+  return diagnostics;
+}
+
+/**
+ * Visits a node and its children. Visitors are keyed by the node type, and
+ * `<type>:exit` visitors are called after the children were visited.
+ * @param {unknown} node
+ * @param {Record<string, (node: object) => void>[]} visitors
+ */
+function visit(node, visitors) {
+  if (ArrayIsArray(node)) {
+    for (const child of new SafeArrayIterator(node)) {
+      visit(child, visitors);
+    }
+    return;
+  }
+  if (typeof node !== "object" || node === null) {
+    return;
+  }
+
+  const type = typeof node.type === "string" ? node.type : null;
+  if (type !== null) {
+    for (const visitor of new SafeArrayIterator(visitors)) {
+      visitor[type]?.(node);
+    }
+  }
+  for (const key of new SafeArrayIterator(ObjectKeys(node))) {
+    if (key !== "span") {
+      visit(node[key], visitors);
+    }
+  }
+  if (type !== null) {
+    const exitType = `${type}:exit`;
+    for (const visitor of new SafeArrayIterator(visitors)) {
+      visitor[exitType]?.(node);
+    }
+  }
+}
+
+internals.lint = {
+  installPlugin,
+  runPlugins,
+};
@@ -15,20 +15,26 @@ const {
   ArrayPrototypeMap,
   ArrayPrototypePush,
   ArrayPrototypeShift,
+  ArrayPrototypeSlice,
   ArrayPrototypeSort,
   BigInt,
   DateNow,
   Error,
+  Float64Array,
   FunctionPrototype,
   Map,
+  MapPrototypeDelete,
   MapPrototypeGet,
   MapPrototypeHas,
   MapPrototypeSet,
   MathCeil,
+  MathMin,
+  MathSqrt,
   ObjectKeys,
   ObjectPrototypeHasOwnProperty,
   ObjectPrototypeIsPrototypeOf,
   Promise,
+  PromisePrototypeThen,
   SafeArrayIterator,
   Set,
   SymbolToStringTag,
@@ -134,6 +140,71 @@ const OP_DETAILS = {
   "op_ws_send": ["send a message on a WebSocket", "closing a `WebSocket` or `WebSocketStream`"],
 };
 
+// Describes the async ops that were started between the `pre` and `post`
+// metrics but never completed and, unless `pendingOnly` is set, the ones that
+// were started before but completed in between.
+function getOpLeakDetails(
+  pre,
+  post,
+  preTraces,
+  postTraces,
+  pendingOnly = false,
+) {
+  const details = [];
+  for (const key in post.ops) {
+    if (!ObjectPrototypeHasOwnProperty(post.ops, key)) {
+      continue;
+    }
+    const preOp = pre.ops[key] ??
+      { opsDispatchedAsync: 0, opsCompletedAsync: 0 };
+    const postOp = post.ops[key];
+    const dispatchedDiff = postOp.opsDispatchedAsync -
+      preOp.opsDispatchedAsync;
+    const completedDiff = postOp.opsCompletedAsync -
+      preOp.opsCompletedAsync;
+
+    if (dispatchedDiff > completedDiff) {
+      const [name, hint] = OP_DETAILS[key] || [key, null];
+      const count = dispatchedDiff - completedDiff;
+      let message = `${count} async operation${
+        count === 1 ? "" : "s"
+      } to ${name} ${
+        count === 1 ? "was" : "were"
+      } started in this test, but never completed.`;
+      if (hint) {
+        message += ` This is often caused by not ${hint}.`;
+      }
+      const traces = [];
+      for (const [id, { opName, stack }] of postTraces) {
+        if (opName !== key) continue;
+        if (MapPrototypeHas(preTraces, id)) continue;
+        ArrayPrototypePush(traces, stack);
+      }
+      if (traces.length === 1) {
+        message += " The operation was started here:\n";
+        message += traces[0];
+      } else if (traces.length > 1) {
+        message += " The operations were started here:\n";
+        message += ArrayPrototypeJoin(traces, "\n\n");
+      }
+      ArrayPrototypePush(details, message);
+    } else if (!pendingOnly && dispatchedDiff < completedDiff) {
+      const [name, hint] = OP_DETAILS[key] || [key, null];
+      const count = completedDiff - dispatchedDiff;
+      ArrayPrototypePush(
+        details,
+        `${count} async operation${count === 1 ? "" : "s"} to ${name} ${
+          count === 1 ? "was" : "were"
+        } started before this test, but ${
+          count === 1 ? "was" : "were"
+        } completed during the test. Async operations should not complete in a test if they were not started in that test.
+            ${hint ? `This is often caused by not ${hint}.` : ""}`,
+      );
+    }
+  }
+  return details;
+}
+
 // Wrap test function in additional assertion that makes sure
 // the test case does not leak async "ops" - ie. number of async
 // completed ops after the test is the same as number of dispatched
@@ -166,58 +237,7 @@ function assertOps(fn) {
 
     if (dispatchedDiff === completedDiff) return;
 
-    const details = [];
-    for (const key in post.ops) {
-      if (!ObjectPrototypeHasOwnProperty(post.ops, key)) {
-        continue;
-      }
-      const preOp = pre.ops[key] ??
-        { opsDispatchedAsync: 0, opsCompletedAsync: 0 };
-      const postOp = post.ops[key];
-      const dispatchedDiff = postOp.opsDispatchedAsync -
-        preOp.opsDispatchedAsync;
-      const completedDiff = postOp.opsCompletedAsync -
-        preOp.opsCompletedAsync;
-
-      if (dispatchedDiff > completedDiff) {
-        const [name, hint] = OP_DETAILS[key] || [key, null];
-        const count = dispatchedDiff - completedDiff;
-        let message = `${count} async operation${
-          count === 1 ? "" : "s"
-        } to ${name} ${
-          count === 1 ? "was" : "were"
-        } started in this test, but never completed.`;
-        if (hint) {
-          message += ` This is often caused by not ${hint}.`;
-        }
-        const traces = [];
-        for (const [id, { opName, stack }] of postTraces) {
-          if (opName !== key) continue;
-          if (MapPrototypeHas(preTraces, id)) continue;
-          ArrayPrototypePush(traces, stack);
-        }
-        if (traces.length === 1) {
-          message += " The operation was started here:\n";
-          message += traces[0];
-        } else if (traces.length > 1) {
-          message += " The operations were started here:\n";
-          message += ArrayPrototypeJoin(traces, "\n\n");
-        }
-        ArrayPrototypePush(details, message);
-      } else if (dispatchedDiff < completedDiff) {
-        const [name, hint] = OP_DETAILS[key] || [key, null];
-        const count = completedDiff - dispatchedDiff;
-        ArrayPrototypePush(
-          details,
-          `${count} async operation${count === 1 ? "" : "s"} to ${name} ${
-            count === 1 ? "was" : "were"
-          } started before this test, but ${
-            count === 1 ? "was" : "were"
-          } completed during the test. Async operations should not complete in a test if they were not started in that test.
-            ${hint ? `This is often caused by not ${hint}.` : ""}`,
-        );
-      }
-    }
+    const details = getOpLeakDetails(pre, post, preTraces, postTraces);
 
     let msg = `Test case is leaking async ops.
 
@@ -234,6 +254,73 @@ function assertOps(fn) {
   };
 }
 
+/** The tests and steps that are running with a timeout, by id. */
+const timeoutStates = new Map();
+
+// Wrap test function in additional assertion that makes sure the test case
+// finishes within `desc.timeout` milliseconds. The timeout is read when the
+// test runs since the default is only known then. It is enforced by a
+// watchdog of the worker, which terminates the execution once the timeout
+// passes, so that a test blocking the event loop is stopped as well. The
+// worker then calls `reportTimedOut()`. The pending op keeps the event loop
+// alive while a test waits for a promise that never resolves.
+function assertTimeout(fn) {
+  /** @param desc {TestDescription | TestStepDescription} */
+  return async function timeoutSanitizer(desc) {
+    if (!desc.timeout) {
+      return await fn(desc);
+    }
+
+    const timeoutPromise = core.opAsync(
+      "op_test_timeout",
+      desc.id,
+      desc.timeout,
+    );
+    MapPrototypeSet(timeoutStates, desc.id, {
+      desc,
+      start: DateNow(),
+      pre: core.metrics(),
+      preTraces: new Map(core.opCallTraces),
+    });
+    try {
+      return await fn(desc);
+    } finally {
+      // The state of a test that timed out in the meantime is kept for the
+      // report, since the execution is about to be terminated.
+      if (ops.op_test_timeout_end(desc.id)) {
+        MapPrototypeDelete(timeoutStates, desc.id);
+      }
+      await timeoutPromise;
+    }
+  };
+}
+
+function formatTimeoutMessage({ desc, pre, preTraces }) {
+  const post = core.metrics();
+  // The timeouts of the steps that are still running aren't leaked by them.
+  delete post.ops.op_test_timeout;
+  const postTraces = new Map(core.opCallTraces);
+  const details = getOpLeakDetails(pre, post, preTraces, postTraces, true);
+  let msg = `${
+    "parent" in desc ? "Test step" : "Test case"
+  } timed out after ${desc.timeout}ms.`;
+  if (details.length > 0) {
+    msg += `\n\nThese async ops were started in this test and are still pending:
+
+ - ${ArrayPrototypeJoin(details, "\n - ")}`;
+    if (!core.isOpCallTracingEnabled()) {
+      msg +=
+        `\n\nTo get more details where ops were started, run again with --trace-ops flag.`;
+    } else {
+      msg += "\n";
+    }
+  } else {
+    msg +=
+      " No async ops are pending, so it is likely blocking the event loop or waiting for a promise that never resolves.";
+  }
+  return msg;
+}
+
 function prettyResourceNames(name) {
   switch (name) {
     case "fsFile":
@@ -555,6 +642,8 @@ function withPermissions(fn, permissions) {
  *   sanitizeResources: boolean,
  *   sanitizeExit: boolean,
  *   permissions: PermissionOptions,
+ *   retries: number,
+ *   timeout: number | null,
  * }} TestDescription
  *
  * @typedef {{
@@ -571,12 +660,16 @@ function withPermissions(fn, permissions) {
  *   sanitizeOps: boolean,
  *   sanitizeResources: boolean,
  *   sanitizeExit: boolean,
+ *   retries: number,
+ *   timeout: number | null,
  * }} TestStepDescription
  *
  * @typedef {{
  *   context: TestContext,
  *   children: TestStepDescription[],
  *   finalized: boolean,
+ *   flaky: boolean,
+ *   retrying: boolean,
  * }} TestState
  *
  * @typedef {{
@@ -588,6 +681,7 @@ function withPermissions(fn, permissions) {
  *   elapsed: number | null,
  *   reportedWait: boolean,
  *   reportedResult: boolean,
+ *   retrying: boolean,
  * }} TestStepState
  *
  * @typedef {{
@@ -607,6 +701,11 @@ function withPermissions(fn, permissions) {
 const testDescs = [];
 /** @type {Map<number, TestState | TestStepState>} */
 const testStates = new Map();
+/** The tests that `runTests()` didn't finish yet, starting with the running
+ * one.
+ * @type {TestDescription[]} */
+let pendingTests = [];
+let currentTestStart = 0;
 /** @type {BenchDescription[]} */
 const benchDescs = [];
 let isTestSubcommand = false;
@@ -728,6 +827,8 @@ function test(
     context: createTestContext(testDesc),
     children: [],
     finalized: false,
+    flaky: false,
+    retrying: false,
   });
 }
 
@@ -836,6 +937,29 @@ async function runTest(desc) {
     return "ignored";
   }
 
+  const state = MapPrototypeGet(testStates, desc.id);
+  for (let attempt = 0;; attempt++) {
+    state.retrying = attempt < desc.retries;
+    const result = await runTestAttempt(desc);
+    if (result === "ok" || attempt >= desc.retries) {
+      state.retrying = false;
+      // ensure the children report their result
+      for (const childDesc of state.children) {
+        stepReportResult(childDesc);
+      }
+      if (result === "ok") {
+        return attempt > 0 || state.flaky ? "flaky" : "ok";
+      }
+      return result;
+    }
+    // The steps of the failed attempt were held back, discard them and start
+    // over.
+    state.children = [];
+    state.finalized = false;
+  }
+}
+
+async function runTestAttempt(desc) {
   try {
     await desc.fn(desc);
     const failCount = failedChildStepsCount(desc);
@@ -851,12 +975,7 @@ async function runTest(desc) {
       "failed": core.destructureError(error),
     };
   } finally {
-    const state = MapPrototypeGet(testStates, desc.id);
-    state.finalized = true;
-    // ensure the children report their result
-    for (const childDesc of state.children) {
-      stepReportResult(childDesc);
-    }
+    MapPrototypeGet(testStates, desc.id).finalized = true;
   }
 }
 
@@ -868,6 +987,13 @@ function compareMeasurements(a, b) {
 }
 
 function benchStats(n, highPrecision, avg, min, max, all) {
+  const mean = avg / n;
+  let variance = 0;
+  for (let i = 0; i < n; i++) {
+    variance += (all[i] - mean) * (all[i] - mean);
+  }
+  if (n > 1) variance /= n - 1;
+
   return {
     n,
     min,
@@ -876,7 +1002,8 @@ function benchStats(n, highPrecision, avg, min, max, all) {
     p99: all[MathCeil(n * (99 / 100)) - 1],
     p995: all[MathCeil(n * (99.5 / 100)) - 1],
     p999: all[MathCeil(n * (99.9 / 100)) - 1],
-    avg: !highPrecision ? (avg / n) : MathCeil(avg / n),
+    avg: !highPrecision ? mean : MathCeil(mean),
+    stdDev: MathSqrt(variance),
   };
 }
 
@@ -992,7 +1119,55 @@ async function benchMeasure(timeBudget, desc) {
   return benchStats(n, wavg > lowPrecisionThresholdInNs, avg, min, max, all);
 }
 
-async function runBench(desc) {
+const benchMetricsBuffer = new Float64Array(3);
+
+// Records the heap allocations, garbage collections and op calls of a bench
+// in iterations that aren't timed. Nothing may be allocated in the loop
+// besides what the bench itself allocates.
+async function benchMeasureMetrics(iterations, desc) {
+  const fn = desc.fn;
+  let allocatedBytes = 0;
+  let allocatedIterations = 0;
+  let gcCount = 0;
+  let opCalls = 0;
+
+  for (let i = 0; i < iterations; i++) {
+    ops.op_bench_metrics(benchMetricsBuffer);
+    const heapUsed = benchMetricsBuffer[0];
+    const opsDispatched = benchMetricsBuffer[1];
+    const gcs = benchMetricsBuffer[2];
+
+    if (!desc.async) {
+      fn();
+    } else {
+      await fn();
+    }
+
+    ops.op_bench_metrics(benchMetricsBuffer);
+    // the first `op_bench_metrics` call is counted after it returned
+    opCalls += benchMetricsBuffer[1] - opsDispatched - 1;
+    const iterationGcs = benchMetricsBuffer[2] - gcs;
+    // when a garbage collection ran, the allocations of the iteration are
+    // unknown
+    if (iterationGcs > 0) {
+      gcCount += iterationGcs;
+    } else {
+      allocatedBytes += benchMetricsBuffer[0] - heapUsed;
+      allocatedIterations++;
+    }
+  }
+
+  return {
+    iterations,
+    allocatedBytes: allocatedIterations > 0
+      ? allocatedBytes / allocatedIterations
+      : 0,
+    gcCount,
+    opCalls: opCalls / iterations,
+  };
+}
+
+async function runBench(desc, metrics) {
   let token = null;
 
   try {
@@ -1011,6 +1186,9 @@ async function runBench(desc) {
 
     const benchTimeInMs = 500;
     const stats = await benchMeasure(benchTimeInMs, desc);
+    if (metrics) {
+      stats.metrics = await benchMeasureMetrics(MathMin(stats.n, 1000), desc);
+    }
 
     return { ok: stats };
   } catch (error) {
@@ -1051,6 +1229,9 @@ function enableBench() {
 
 async function runTests({
   shuffle = null,
+  coveragePerTest = false,
+  retries = 0,
+  timeout = null,
 } = {}) {
   core.setMacrotaskCallback(handleOpSanitizerDelayMacrotask);
 
@@ -1089,21 +1270,92 @@ async function runTests({
     }
   }
 
+  if (coveragePerTest) {
+    // Keep the coverage of loading the module apart from the first test.
+    await core.opAsync("op_test_coverage_snapshot", null);
+  }
+
+  pendingTests = ArrayPrototypeSlice(filtered);
   for (const desc of filtered) {
     if (ops.op_tests_should_stop()) {
       break;
     }
+    desc.retries ??= retries;
+    desc.timeout ??= timeout;
     ops.op_dispatch_test_event({ wait: desc.id });
     const earlier = DateNow();
+    currentTestStart = earlier;
     const result = await runTest(desc);
     const elapsed = DateNow() - earlier;
+    ArrayPrototypeShift(pendingTests);
     ops.op_dispatch_test_event({
       result: [desc.id, result, elapsed],
     });
+    if (coveragePerTest) {
+      await core.opAsync("op_test_coverage_snapshot", {
+        id: desc.id,
+        name: desc.name,
+        origin: desc.origin,
+        location: desc.location,
+      });
+    }
   }
 }
 
-async function runBenchmarks() {
+/**
+ * Called by the worker after it terminated the execution because the test or
+ * step with the given id ran past its timeout. The test can't be resumed, so
+ * it fails along with the steps the timed out one belongs to, and the tests
+ * of the module that didn't run yet are cancelled.
+ * @param id {number}
+ */
+function reportTimedOut(id) {
+  const timeoutState = MapPrototypeGet(timeoutStates, id);
+  const now = DateNow();
+  let error = new Error(formatTimeoutMessage(timeoutState));
+  let desc = timeoutState.desc;
+  while ("parent" in desc) {
+    const state = MapPrototypeGet(testStates, desc.id);
+    const start = MapPrototypeGet(timeoutStates, desc.id)?.start;
+    state.status = "failed";
+    state.error = error;
+    state.elapsed = start === undefined ? null : now - start;
+    state.finalized = true;
+    // The steps the timed out one belongs to fail due to it.
+    error = null;
+    desc = desc.parent;
+  }
+
+  const state = MapPrototypeGet(testStates, desc.id);
+  state.finalized = true;
+  for (const childDesc of state.children) {
+    stepReportResult(childDesc);
+  }
+  if (error === null) {
+    const failCount = failedChildStepsCount(desc);
+    error = new Error(
+      `${failCount} test step${failCount === 1 ? "" : "s"} failed.`,
+    );
+  }
+  ArrayPrototypeShift(pendingTests);
+  ops.op_dispatch_test_event({
+    result: [
+      desc.id,
+      { failed: core.destructureError(error) },
+      now - currentTestStart,
+    ],
+  });
+
+  for (const pendingDesc of new SafeArrayIterator(pendingTests)) {
+    const result = pendingDesc.ignore ? "ignored" : "cancelled";
+    ops.op_dispatch_test_event({ result: [pendingDesc.id, result, 0] });
+  }
+  pendingTests = [];
+}
+
+async function runBenchmarks({
+  metrics = false,
+} = {}) {
   core.setMacrotaskCallback(handleOpSanitizerDelayMacrotask);
 
   const origin = getBenchOrigin();
@@ -1147,7 +1399,7 @@ async function runBenchmarks() {
     desc.baseline = !!desc.baseline;
     ops.op_dispatch_bench_event({ wait: desc.id });
     ops.op_dispatch_bench_event({
-      result: [desc.id, await runBench(desc)],
+      result: [desc.id, await runBench(desc, metrics)],
     });
   }
 
@@ -1179,7 +1431,22 @@ function canStreamReporting(desc) {
       return false;
     }
   }
-  return true;
+  return !isHeldBackForRetry(desc);
+}
+
+/** The steps of an attempt that is retried if it fails are held back until
+ * the attempt finished, so that the steps of a discarded attempt are never
+ * reported.
+ */
+function isHeldBackForRetry(desc) {
+  let currentDesc = desc.parent;
+  while (currentDesc != null) {
+    if (MapPrototypeGet(testStates, currentDesc.id).retrying) {
+      return true;
+    }
+    currentDesc = currentDesc.parent;
+  }
+  return false;
 }
 
 function stepReportWait(desc) {
@@ -1309,6 +1576,8 @@ function createTestContext(desc) {
       stepDesc.sanitizeOps ??= desc.sanitizeOps;
       stepDesc.sanitizeResources ??= desc.sanitizeResources;
       stepDesc.sanitizeExit ??= desc.sanitizeExit;
+      stepDesc.retries ??= 0;
+      stepDesc.timeout ??= desc.timeout;
       stepDesc.origin = getTestOrigin();
       const jsError = core.destructureError(new Error());
       stepDesc.location = {
@@ -1331,6 +1600,7 @@ function createTestContext(desc) {
         elapsed: null,
         reportedWait: false,
         reportedResult: false,
+        retrying: false,
       };
       MapPrototypeSet(testStates, stepDesc.id, state);
       ArrayPrototypePush(
@@ -1351,18 +1621,32 @@ function createTestContext(desc) {
         const testFn = wrapTestFnWithSanitizers(stepDesc.fn, stepDesc);
         const start = DateNow();
 
-        try {
-          await testFn(stepDesc);
+        for (let attempt = 0;; attempt++) {
+          state.retrying = attempt < stepDesc.retries;
+          try {
+            await testFn(stepDesc);
 
-          if (failedChildStepsCount(stepDesc) > 0) {
+            if (failedChildStepsCount(stepDesc) > 0) {
+              state.status = "failed";
+            } else {
+              state.status = "ok";
+            }
+          } catch (error) {
+            state.error = error;
             state.status = "failed";
-          } else {
-            state.status = "ok";
           }
-        } catch (error) {
-          state.error = error;
-          state.status = "failed";
+
+          if (state.status !== "failed" || attempt >= stepDesc.retries) {
+            if (state.status === "ok" && attempt > 0) {
+              MapPrototypeGet(testStates, rootId).flaky = true;
+            }
+            break;
+          }
+          // Discard the held back steps of the failed attempt and start over.
+          state.children = [];
+          state.error = null;
         }
+        state.retrying = false;
 
         state.elapsed = DateNow() - start;
 
@@ -1380,8 +1664,8 @@ function createTestContext(desc) {
 
         return state.status === "ok";
       } finally {
-        if (canStreamReporting(stepDesc.parent)) {
-          const parentState = MapPrototypeGet(testStates, stepDesc.parent.id);
+        const parentState = MapPrototypeGet(testStates, stepDesc.parent.id);
+        if (canStreamReporting(stepDesc.parent) && !parentState.retrying) {
           // flush any buffered steps
           for (const childDesc of parentState.children) {
             stepReportResult(childDesc);
@@ -1414,11 +1698,12 @@ function wrapTestFnWithSanitizers(testFn, opts) {
   if (opts.sanitizeExit) {
     testFn = assertExit(testFn, true);
   }
-  return testFn;
+  return assertTimeout(testFn);
 }
 
 internals.testing = {
   runTests,
+  reportTimedOut,
   runBenchmarks,
   enableTest,
   enableBench,
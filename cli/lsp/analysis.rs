@@ -16,6 +16,7 @@ use deno_core::error::AnyError;
 use deno_core::serde::Deserialize;
 use deno_core::serde_json::json;
 use deno_core::ModuleSpecifier;
+use deno_lint::diagnostic::LintDiagnostic;
 use deno_lint::rules::LintRule;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -125,19 +126,20 @@ pub fn get_lint_references(
   let linter = create_linter(parsed_source.media_type(), lint_rules);
   let lint_diagnostics = linter.lint_with_ast(parsed_source);
 
-  Ok(
-    lint_diagnostics
-      .into_iter()
-      .map(|d| Reference {
-        category: Category::Lint {
-          message: d.message,
-          code: d.code,
-          hint: d.hint,
-        },
-        range: as_lsp_range(&d.range),
-      })
-      .collect(),
-  )
+  Ok(lint_diagnostics.into_iter().map(Reference::from).collect())
+}
+
+impl From<LintDiagnostic> for Reference {
+  fn from(d: LintDiagnostic) -> Self {
+    Reference {
+      category: Category::Lint {
+        message: d.message,
+        code: d.code,
+        hint: d.hint,
+      },
+      range: as_lsp_range(&d.range),
+    }
+  }
 }
 
 fn code_as_string(code: &Option<lsp::NumberOrString>) -> String {
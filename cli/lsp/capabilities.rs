@@ -1,10 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
-///!
-///! Provides information about what capabilities that are supported by the
-///! language server, which helps determine what messages are sent from the
-///! client.
-///!
+//!
+//! Provides information about what capabilities that are supported by the
+//! language server, which helps determine what messages are sent from the
+//! client.
+//!
 use deno_core::serde_json::json;
 use tower_lsp::lsp_types::*;
 
@@ -107,7 +107,7 @@ pub fn server_capabilities(
       resolve_provider: Some(true),
     }),
     document_formatting_provider: Some(OneOf::Left(true)),
-    document_range_formatting_provider: None,
+    document_range_formatting_provider: Some(OneOf::Left(true)),
     document_on_type_formatting_provider: None,
     selection_range_provider: Some(SelectionRangeProviderCapability::Simple(
       true,
@@ -17,6 +17,8 @@ use crate::graph_util;
 use crate::graph_util::enhanced_resolution_error_message;
 use crate::node;
 use crate::tools::lint::get_configured_rules;
+use crate::tools::lint::is_plugin_ignore_diagnostic;
+use crate::tools::lint::LintPluginHost;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::anyhow;
@@ -472,15 +474,34 @@ async fn generate_lint_diagnostics(
       }
 
       let version = document.maybe_lsp_version();
+      let mut diagnostics = generate_document_lint_diagnostics(
+        config,
+        lint_options,
+        lint_rules.clone(),
+        &document,
+      );
+      if let Some(plugin_host) = &snapshot.maybe_lint_plugin_host {
+        diagnostics.retain(|d| {
+          !matches!(
+            &d.code,
+            Some(lsp::NumberOrString::String(code))
+              if is_plugin_ignore_diagnostic(code, &d.message)
+          )
+        });
+        diagnostics.extend(
+          generate_document_lint_plugin_diagnostics(
+            config,
+            lint_options,
+            plugin_host,
+            &document,
+          )
+          .await,
+        );
+      }
       diagnostics_vec.push((
         document.specifier().clone(),
         version,
-        generate_document_lint_diagnostics(
-          config,
-          lint_options,
-          lint_rules.clone(),
-          &document,
-        ),
+        diagnostics,
       ));
     }
   }
@@ -520,6 +541,45 @@ fn generate_document_lint_diagnostics(
   }
 }
 
+async fn generate_document_lint_plugin_diagnostics(
+  config: &ConfigSnapshot,
+  lint_options: &LintOptions,
+  plugin_host: &LintPluginHost,
+  document: &Document,
+) -> Vec<lsp::Diagnostic> {
+  if !config.specifier_enabled(document.specifier()) {
+    return Vec::new();
+  }
+  if !lint_options.files.matches_specifier(document.specifier()) {
+    return Vec::new();
+  }
+  // syntax errors are already reported by the other diagnostics
+  if !matches!(document.maybe_parsed_source(), Some(Ok(_))) {
+    return Vec::new();
+  }
+  match plugin_host
+    .lint(
+      document.specifier().to_string(),
+      document.media_type(),
+      document.content().to_string(),
+    )
+    .await
+  {
+    Ok(lint_diagnostics) => lint_diagnostics
+      .into_iter()
+      .map(|d| analysis::Reference::from(d).to_diagnostic())
+      .collect(),
+    Err(err) => {
+      error!(
+        "Unable to run lint plugins on \"{}\": {}",
+        document.specifier(),
+        err
+      );
+      Vec::new()
+    }
+  }
+}
+
 async fn generate_ts_diagnostics(
   snapshot: Arc<language_server::StateSnapshot>,
   config: &ConfigSnapshot,
@@ -1,11 +1,13 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use super::cache::calculate_fs_version;
+use super::logging::lsp_log;
 use super::text::LineIndex;
 use super::tsc;
 use super::tsc::AssetDocument;
 
 use crate::args::package_json;
+use crate::args::package_json::LocalPackages;
 use crate::args::package_json::PackageJsonDeps;
 use crate::args::ConfigFile;
 use crate::args::JsxImportSourceConfig;
@@ -1171,6 +1173,7 @@ impl Documents {
       maybe_import_map: Option<&import_map::ImportMap>,
       maybe_jsx_config: Option<&JsxImportSourceConfig>,
       maybe_package_json_deps: Option<&PackageJsonDeps>,
+      local_packages: &LocalPackages,
     ) -> u64 {
       let mut hasher = FastInsecureHasher::default();
       if let Some(import_map) = maybe_import_map {
@@ -1183,28 +1186,58 @@ impl Documents {
       if let Some(deps) = maybe_package_json_deps {
         hasher.write_hashable(&deps);
       }
+      hasher.write_hashable(local_packages);
       hasher.finish()
     }
 
+    let maybe_workspace =
+      match maybe_package_json.map(package_json::discover_workspace) {
+        Some(Ok(maybe_workspace)) => maybe_workspace,
+        Some(Err(err)) => {
+          lsp_log!("Error resolving npm workspace: {:#}", err);
+          None
+        }
+        None => None,
+      };
     let maybe_package_json_deps = maybe_package_json.map(|package_json| {
-      package_json::get_local_package_json_version_reqs(package_json)
+      package_json::get_workspace_package_json_version_reqs(
+        package_json,
+        maybe_workspace.as_ref(),
+      )
     });
+    // the tarballs of `file:` dependencies are only extracted by the cli
+    let local_packages = maybe_package_json
+      .map(|package_json| {
+        package_json::get_local_packages(
+          package_json,
+          maybe_workspace.as_ref(),
+          None,
+        )
+        .unwrap_or_default()
+      })
+      .unwrap_or_default();
     let maybe_jsx_config =
       maybe_config_file.and_then(|cf| cf.to_maybe_jsx_import_source_config());
     let new_resolver_config_hash = calculate_resolver_config_hash(
       maybe_import_map.as_deref(),
       maybe_jsx_config.as_ref(),
       maybe_package_json_deps.as_ref(),
+      &local_packages,
     );
     self.npm_package_json_reqs = Arc::new({
       match &maybe_package_json_deps {
         Some(deps) => {
+          let local_package_deps = local_packages
+            .values()
+            .flat_map(|package| package.deps.values());
           let mut reqs = deps
             .values()
+            .chain(local_package_deps)
             .filter_map(|r| r.as_ref().ok())
             .cloned()
             .collect::<Vec<_>>();
           reqs.sort();
+          reqs.dedup();
           reqs
         }
         None => Vec::new(),
@@ -1214,6 +1247,7 @@ impl Documents {
       npm_registry_api.clone(),
       npm_resolution.clone(),
       maybe_package_json_deps,
+      local_packages,
     );
     self.resolver = CliGraphResolver::new(
       maybe_jsx_config,
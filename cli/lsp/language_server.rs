@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::fmt::Write as _;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tower_lsp::jsonrpc::Error as LspError;
@@ -77,10 +78,13 @@ use crate::graph_util;
 use crate::http_util::HttpClient;
 use crate::npm::NpmCache;
 use crate::npm::NpmPackageResolver;
+use crate::npm::NpmRc;
 use crate::npm::NpmRegistryApi;
 use crate::proc_state::ProcState;
 use crate::tools::fmt::format_file;
+use crate::tools::fmt::format_lines;
 use crate::tools::fmt::format_parsed_source;
+use crate::tools::lint::LintPluginHost;
 use crate::util::fs::remove_dir_all_if_exists;
 use crate::util::path::ensure_directory_specifier;
 use crate::util::path::specifier_to_file_path;
@@ -99,6 +103,7 @@ pub struct StateSnapshot {
   pub maybe_import_map: Option<Arc<ImportMap>>,
   pub root_uri: Option<Url>,
   pub maybe_npm_resolver: Option<NpmPackageResolver>,
+  pub maybe_lint_plugin_host: Option<LintPluginHost>,
 }
 
 #[derive(Debug)]
@@ -139,6 +144,8 @@ pub struct Inner {
   fmt_options: FmtOptions,
   /// An optional configuration for linter which has been taken from specified config file.
   lint_options: LintOptions,
+  /// The worker running the lint plugins of the config file, if any.
+  maybe_lint_plugin_host: Option<LintPluginHost>,
   /// A lazily create "server" for handling test run requests.
   maybe_testing_server: Option<testing::TestServer>,
   /// Resolver for npm packages.
@@ -321,8 +328,9 @@ impl LanguageServer {
 fn create_lsp_npm_resolver(
   dir: &DenoDir,
   http_client: HttpClient,
+  maybe_root_path: Option<&Path>,
 ) -> NpmPackageResolver {
-  let registry_url = NpmRegistryApi::default_url();
+  let npmrc = Arc::new(NpmRc::load(maybe_root_path));
   let progress_bar = ProgressBar::new(ProgressBarStyle::TextOnly);
   let npm_cache = NpmCache::from_deno_dir(
     dir,
@@ -332,14 +340,11 @@ fn create_lsp_npm_resolver(
     // the user is typing.
     CacheSetting::Only,
     http_client.clone(),
+    npmrc.clone(),
     progress_bar.clone(),
   );
-  let api = NpmRegistryApi::new(
-    registry_url.clone(),
-    npm_cache.clone(),
-    http_client,
-    progress_bar,
-  );
+  let api =
+    NpmRegistryApi::new(npmrc, npm_cache.clone(), http_client, progress_bar);
   NpmPackageResolver::new(npm_cache, api)
 }
 
@@ -366,7 +371,7 @@ impl Inner {
       ts_server.clone(),
     );
     let assets = Assets::new(ts_server.clone());
-    let npm_resolver = create_lsp_npm_resolver(&dir, http_client.clone());
+    let npm_resolver = create_lsp_npm_resolver(&dir, http_client.clone(), None);
 
     Self {
       assets,
@@ -384,6 +389,7 @@ impl Inner {
       maybe_package_json: None,
       fmt_options: Default::default(),
       lint_options: Default::default(),
+      maybe_lint_plugin_host: None,
       maybe_testing_server: None,
       module_registries,
       module_registries_location,
@@ -576,6 +582,7 @@ impl Inner {
       documents: self.documents.clone(),
       maybe_import_map: self.maybe_import_map.clone(),
       maybe_npm_resolver: Some(self.npm_resolver.snapshotted()),
+      maybe_lint_plugin_host: self.maybe_lint_plugin_host.clone(),
       root_uri: self.config.root_uri.clone(),
     })
   }
@@ -631,7 +638,7 @@ impl Inner {
       .as_ref()
       .and_then(|uri| specifier_to_file_path(uri).ok());
     let root_cert_store = Some(get_root_cert_store(
-      maybe_root_path,
+      maybe_root_path.clone(),
       workspace_settings.certificate_stores,
       workspace_settings.tls_certificate.map(CaData::File),
     )?);
@@ -645,7 +652,11 @@ impl Inner {
       self.http_client.clone(),
     )?;
     self.module_registries_location = module_registries_location;
-    self.npm_resolver = create_lsp_npm_resolver(&dir, self.http_client.clone());
+    self.npm_resolver = create_lsp_npm_resolver(
+      &dir,
+      self.http_client.clone(),
+      maybe_root_path.as_deref(),
+    );
     // update the cache path
     let location = dir.deps_folder_path();
     self.documents.set_location(&location);
@@ -813,6 +824,7 @@ impl Inner {
     self.maybe_config_file = None;
     self.fmt_options = Default::default();
     self.lint_options = Default::default();
+    self.maybe_lint_plugin_host = None;
 
     if let Some(config_file) = self.get_config_file()? {
       let lint_options = config_file
@@ -832,6 +844,13 @@ impl Inner {
           anyhow!("Unable to update formatter configuration: {:?}", err)
         })?;
 
+      if !lint_options.plugins.is_empty() {
+        self.maybe_lint_plugin_host = Some(LintPluginHost::new(
+          config_file.specifier.clone(),
+          lint_options.plugins.clone(),
+          lint_options.rules.clone(),
+        ));
+      }
       self.maybe_config_file = Some(config_file);
       self.lint_options = lint_options;
       self.fmt_options = fmt_options;
@@ -1322,12 +1341,35 @@ impl Inner {
     &self,
     params: DocumentFormattingParams,
   ) -> LspResult<Option<Vec<TextEdit>>> {
-    let specifier = self.url_map.normalize_url(&params.text_document.uri);
+    let mark = self.performance.mark("formatting", Some(&params));
+    let result = self.format_document(&params.text_document.uri, None).await;
+    self.performance.measure(mark);
+    result
+  }
+
+  async fn range_formatting(
+    &self,
+    params: DocumentRangeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    let mark = self.performance.mark("range_formatting", Some(&params));
+    let result = self
+      .format_document(&params.text_document.uri, Some(params.range))
+      .await;
+    self.performance.measure(mark);
+    result
+  }
+
+  /// Formats a document, or only the lines in the given range of it.
+  async fn format_document(
+    &self,
+    uri: &Url,
+    maybe_range: Option<Range>,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    let specifier = self.url_map.normalize_url(uri);
     let document = match self.documents.get(&specifier) {
       Some(doc) if doc.is_open() => doc,
       _ => return Ok(None),
     };
-    let mark = self.performance.mark("formatting", Some(&params));
     let file_path = specifier_to_file_path(&specifier).map_err(|err| {
       error!("{}", err);
       LspError::invalid_request()
@@ -1357,6 +1399,23 @@ impl Inner {
       }
     };
 
+    let format_result = match (format_result, maybe_range) {
+      (Ok(Some(new_text)), Some(range)) => {
+        // a range ending at the start of a line doesn't include that line
+        let end_line =
+          if range.end.character == 0 && range.end.line > range.start.line {
+            range.end.line
+          } else {
+            range.end.line + 1
+          };
+        let line_range = range.start.line as usize..end_line as usize;
+        let new_text =
+          format_lines(&document.content(), &new_text, &[line_range]);
+        Ok((new_text != *document.content()).then_some(new_text))
+      }
+      (format_result, _) => format_result,
+    };
+
     let text_edits = match format_result {
       Ok(Some(new_text)) => Some(text::get_edits(
         &document.content(),
@@ -1371,7 +1430,6 @@ impl Inner {
       }
     };
 
-    self.performance.measure(mark);
     if let Some(text_edits) = text_edits {
       if text_edits.is_empty() {
         Ok(None)
@@ -2898,6 +2956,13 @@ impl tower_lsp::LanguageServer for LanguageServer {
     self.0.read().await.formatting(params).await
   }
 
+  async fn range_formatting(
+    &self,
+    params: DocumentRangeFormattingParams,
+  ) -> LspResult<Option<Vec<TextEdit>>> {
+    self.0.read().await.range_formatting(params).await
+  }
+
   async fn hover(&self, params: HoverParams) -> LspResult<Option<Hover>> {
     self.0.read().await.hover(params).await
   }
@@ -5,6 +5,7 @@ use tower_lsp::LspService;
 use tower_lsp::Server;
 
 use crate::lsp::language_server::LanguageServer;
+pub use registries::ModuleRegistry;
 pub use repl::ReplCompletionItem;
 pub use repl::ReplLanguageServer;
 
@@ -635,6 +635,51 @@ impl ModuleRegistry {
     None
   }
 
+  /// Gets the version of a module from its specifier, along with all the
+  /// versions of the module, using the completions of the `version` variable
+  /// of the registry. The registry of the origin needs to be enabled.
+  pub async fn get_module_versions(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<(String, Vec<String>)> {
+    let origin = base_url(specifier);
+    let registries = self.origins.get(&origin)?;
+    let path = &specifier[Position::BeforePath..];
+    for registry in registries {
+      let tokens = parse(&registry.schema, None).ok()?;
+      let matcher = Matcher::new(&tokens, None).ok()?;
+      let match_result = match matcher.matches(path) {
+        Some(match_result) => match_result,
+        None => continue,
+      };
+      let version = match match_result.get("version") {
+        Some(StringOrVec::String(version)) if !version.is_empty() => {
+          version.clone()
+        }
+        _ => continue,
+      };
+      let key = tokens.iter().find_map(|t| match t {
+        Token::Key(key)
+          if key.name == StringOrNumber::String("version".to_string()) =>
+        {
+          Some(key)
+        }
+        _ => None,
+      })?;
+      let url = registry.get_url_for_key(key)?;
+      let items = self
+        .get_variable_items(key, url, specifier, &tokens, &match_result)
+        .await?;
+      let versions = match items {
+        VariableItems::List(list) => list.items,
+        VariableItems::Simple(items) => items,
+      };
+      return Some((version, versions));
+    }
+
+    None
+  }
+
   /// For a string specifier from the client, provide a set of completions, if
   /// any, for the specifier.
   pub async fn get_completions(
@@ -1528,6 +1573,37 @@ mod tests {
     assert!(completions[0].command.is_some());
   }
 
+  #[tokio::test]
+  async fn test_get_module_versions() {
+    let _g = test_util::http_server();
+    let temp_dir = TempDir::new();
+    let location = temp_dir.path().join("registries");
+    let mut module_registry =
+      ModuleRegistry::new(&location, HttpClient::new(None, None).unwrap())
+        .unwrap();
+    module_registry
+      .enable("http://localhost:4545/")
+      .await
+      .expect("could not enable");
+    let specifier =
+      ModuleSpecifier::parse("http://localhost:4545/x/a@v1.0.0/mod.ts")
+        .unwrap();
+    assert_eq!(
+      module_registry.get_module_versions(&specifier).await,
+      Some((
+        "v1.0.0".to_string(),
+        vec![
+          "v1.0.0".to_string(),
+          "v1.0.1".to_string(),
+          "v2.0.0".to_string()
+        ]
+      ))
+    );
+    let specifier =
+      ModuleSpecifier::parse("http://localhost:4545/x/a/mod.ts").unwrap();
+    assert_eq!(module_registry.get_module_versions(&specifier).await, None);
+  }
+
   #[tokio::test]
   async fn test_registry_completions_key_first() {
     let _g = test_util::http_server();
@@ -400,6 +400,10 @@ impl TestRun {
               let description = tests.read().get(&id).unwrap().clone();
               match &result {
                 test::TestResult::Ok => summary.passed += 1,
+                test::TestResult::Flaky => {
+                  summary.passed += 1;
+                  summary.flaky.push(description.clone());
+                }
                 test::TestResult::Ignored => summary.ignored += 1,
                 test::TestResult::Failed(error) => {
                   summary.failed += 1;
@@ -418,11 +422,14 @@ impl TestRun {
               summary.uncaught_errors.push((origin, error));
             }
             test::TestEvent::StepRegister(description) => {
-              reporter.report_step_register(&description);
               test_steps.insert(description.id, description);
             }
             test::TestEvent::StepWait(id) => {
-              reporter.report_step_wait(test_steps.get(&id).unwrap());
+              // steps are registered with the reporter once they are waited on,
+              // since the steps of a retried attempt are discarded unreported
+              let description = test_steps.get(&id).unwrap();
+              reporter.report_step_register(description);
+              reporter.report_step_wait(description);
             }
             test::TestEvent::StepResult(id, result, duration) => {
               match &result {
@@ -701,7 +708,7 @@ impl LspTestReporter {
     assert_eq!(stack.pop(), Some(desc.into()));
     self.current_origin = None;
     match result {
-      test::TestResult::Ok => {
+      test::TestResult::Ok | test::TestResult::Flaky => {
         self.progress(lsp_custom::TestRunProgressMessage::Passed {
           test: desc.into(),
           duration: Some(elapsed as u32),
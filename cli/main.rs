@@ -47,12 +47,11 @@ use std::path::PathBuf;
 async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
   match flags.subcommand.clone() {
     DenoSubcommand::Bench(bench_flags) => {
-      let cli_options = CliOptions::from_flags(flags)?;
-      let bench_options = cli_options.resolve_bench_options(bench_flags)?;
-      if cli_options.watch_paths().is_some() {
-        tools::bench::run_benchmarks_with_watch(cli_options, bench_options)
-          .await?;
+      if flags.watch.is_some() {
+        tools::bench::run_benchmarks_with_watch(flags, bench_flags).await?;
       } else {
+        let cli_options = CliOptions::from_flags(flags)?;
+        let bench_options = cli_options.resolve_bench_options(bench_flags)?;
         tools::bench::run_benchmarks(cli_options, bench_options).await?;
       }
       Ok(0)
@@ -69,14 +68,25 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       tools::run::eval_command(flags, eval_flags).await
     }
     DenoSubcommand::Cache(cache_flags) => {
+      if let Some(archive_path) = &cache_flags.import {
+        tools::cache_archive::import(flags, archive_path)?;
+        return Ok(0);
+      }
       let ps = ProcState::build(flags).await?;
       ps.load_and_type_check_files(&cache_flags.files).await?;
       ps.cache_module_emits()?;
+      if let Some(archive_path) = &cache_flags.export {
+        tools::cache_archive::export(&ps, archive_path).await?;
+      }
       Ok(0)
     }
     DenoSubcommand::Check(check_flags) => {
-      let ps = ProcState::build(flags).await?;
-      ps.load_and_type_check_files(&check_flags.files).await?;
+      if flags.watch.is_some() {
+        tools::check::check_with_watch(flags, check_flags).await?;
+      } else {
+        let ps = ProcState::build(flags).await?;
+        ps.load_and_type_check_files(&check_flags.files).await?;
+      }
       Ok(0)
     }
     DenoSubcommand::Compile(compile_flags) => {
@@ -123,6 +133,10 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       }
       Ok(0)
     }
+    DenoSubcommand::Outdated(outdated_flags) => {
+      tools::outdated::outdated(flags, outdated_flags).await?;
+      Ok(0)
+    }
     DenoSubcommand::Repl(repl_flags) => {
       tools::repl::run(flags, repl_flags).await
     }
@@ -167,6 +181,10 @@ async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
       display::write_to_stdout_ignore_sigpipe(types.as_bytes())?;
       Ok(0)
     }
+    DenoSubcommand::Update(update_flags) => {
+      tools::outdated::update(flags, update_flags).await?;
+      Ok(0)
+    }
     DenoSubcommand::Upgrade(upgrade_flags) => {
       tools::upgrade::upgrade(flags, upgrade_flags).await?;
       Ok(0)
@@ -154,7 +154,10 @@ impl CliModuleLoader {
     maybe_referrer: Option<ModuleSpecifier>,
     is_dynamic: bool,
   ) -> Result<ModuleSource, AnyError> {
-    let code_source = if self.ps.npm_resolver.in_npm_package(specifier) {
+    // common js of local packages is loaded like the files of npm packages
+    let code_source = if self.ps.npm_resolver.in_npm_package(specifier)
+      || self.ps.cjs_resolutions.lock().contains(specifier)
+    {
       let file_path = specifier.to_file_path().unwrap();
       let code = std::fs::read_to_string(&file_path).with_context(|| {
         let mut msg = "Unable to load ".to_string();
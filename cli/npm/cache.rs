@@ -25,6 +25,7 @@ use crate::util::fs::hard_link_dir_recursive;
 use crate::util::path::root_url_to_safe_local_dirname;
 use crate::util::progress_bar::ProgressBar;
 
+use super::npmrc::NpmRc;
 use super::registry::NpmPackageVersionDistInfo;
 use super::tarball::verify_and_extract_tarball;
 
@@ -326,6 +327,7 @@ pub struct NpmCache {
   readonly: ReadonlyNpmCache,
   cache_setting: CacheSetting,
   http_client: HttpClient,
+  npmrc: Arc<NpmRc>,
   progress_bar: ProgressBar,
   /// ensures a package is only downloaded once per run
   previously_reloaded_packages: Arc<Mutex<HashSet<NpmPackageNv>>>,
@@ -336,12 +338,14 @@ impl NpmCache {
     dir: &DenoDir,
     cache_setting: CacheSetting,
     http_client: HttpClient,
+    npmrc: Arc<NpmRc>,
     progress_bar: ProgressBar,
   ) -> Self {
     Self {
       readonly: ReadonlyNpmCache::from_deno_dir(dir),
       cache_setting,
       http_client,
+      npmrc,
       progress_bar,
       previously_reloaded_packages: Default::default(),
     }
@@ -414,21 +418,57 @@ impl NpmCache {
       );
     }
 
+    let bytes = self.download_tarball(dist).await?;
+    verify_and_extract_tarball(package, &bytes, dist, &package_folder)
+  }
+
+  /// Downloads the tarball of a package without extracting it.
+  pub async fn download_tarball(
+    &self,
+    dist: &NpmPackageVersionDistInfo,
+  ) -> Result<Vec<u8>, AnyError> {
     let guard = self.progress_bar.update(&dist.tarball);
+    let maybe_auth_header = Url::parse(&dist.tarball)
+      .ok()
+      .and_then(|url| self.npmrc.auth_header_for_url(&url));
     let maybe_bytes = self
       .http_client
-      .download_with_progress(&dist.tarball, &guard)
+      .download_with_progress_and_auth(
+        &dist.tarball,
+        maybe_auth_header.as_deref(),
+        &guard,
+      )
       .await?;
     match maybe_bytes {
-      Some(bytes) => {
-        verify_and_extract_tarball(package, &bytes, dist, &package_folder)
-      }
+      Some(bytes) => Ok(bytes),
       None => {
         bail!("Could not find npm package tarball at: {}", dist.tarball);
       }
     }
   }
 
+  /// Verifies and extracts a tarball that was downloaded before, like one of
+  /// an archive of `deno cache --export`, unless the package is already
+  /// cached.
+  pub fn ensure_package_from_tarball(
+    &self,
+    package: &NpmPackageNv,
+    data: &[u8],
+    dist: &NpmPackageVersionDistInfo,
+    registry_url: &Url,
+  ) -> Result<(), AnyError> {
+    let package_folder = self
+      .readonly
+      .package_folder_for_name_and_version(package, registry_url);
+    if package_folder.exists()
+      && !package_folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME).exists()
+    {
+      return Ok(());
+    }
+    verify_and_extract_tarball(package, data, dist, &package_folder)
+      .with_context(|| format!("Failed caching npm package '{package}'."))
+  }
+
   /// Ensures a copy of the package exists in the global cache.
   ///
   /// This assumes that the original package folder being hard linked
@@ -486,6 +526,17 @@ impl NpmCache {
     self.readonly.registry_folder(registry_url)
   }
 
+  /// Gets the path of the cached information of a package from the registry.
+  pub fn package_info_file_path(
+    &self,
+    name: &str,
+    registry_url: &Url,
+  ) -> PathBuf {
+    self
+      .package_name_folder(name, registry_url)
+      .join("registry.json")
+  }
+
   pub fn resolve_package_folder_id_from_specifier(
     &self,
     specifier: &ModuleSpecifier,
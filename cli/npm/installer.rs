@@ -4,7 +4,9 @@ use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use deno_core::error::AnyError;
+use deno_graph::npm::NpmPackageReq;
 
+use crate::args::package_json::LocalPackages;
 use crate::args::package_json::PackageJsonDeps;
 
 use super::NpmRegistryApi;
@@ -16,6 +18,7 @@ struct PackageJsonDepsInstallerInner {
   npm_registry_api: NpmRegistryApi,
   npm_resolution: NpmResolution,
   package_deps: PackageJsonDeps,
+  local_packages: LocalPackages,
 }
 
 /// Holds and controls installing dependencies from package.json.
@@ -27,6 +30,7 @@ impl PackageJsonDepsInstaller {
     npm_registry_api: NpmRegistryApi,
     npm_resolution: NpmResolution,
     deps: Option<PackageJsonDeps>,
+    local_packages: LocalPackages,
   ) -> Self {
     Self(deps.map(|package_deps| {
       Arc::new(PackageJsonDepsInstallerInner {
@@ -34,6 +38,7 @@ impl PackageJsonDepsInstaller {
         npm_registry_api,
         npm_resolution,
         package_deps,
+        local_packages,
       })
     }))
   }
@@ -42,18 +47,35 @@ impl PackageJsonDepsInstaller {
     self.0.as_ref().map(|inner| &inner.package_deps)
   }
 
-  /// Gets if the package.json has the specified package name.
+  /// Gets the packages that are linked from a folder, which don't need to be
+  /// installed.
+  pub fn local_packages(&self) -> Option<&LocalPackages> {
+    self.0.as_ref().map(|inner| &inner.local_packages)
+  }
+
+  /// Gets the npm package requirements of the package.json and of the local
+  /// packages.
+  fn package_reqs(&self) -> impl Iterator<Item = &NpmPackageReq> {
+    let package_deps = self
+      .package_deps()
+      .into_iter()
+      .flat_map(|deps| deps.values());
+    let local_package_deps = self
+      .local_packages()
+      .into_iter()
+      .flat_map(|packages| packages.values())
+      .flat_map(|package| package.deps.values());
+    package_deps
+      .chain(local_package_deps)
+      .filter_map(|r| r.as_ref().ok())
+  }
+
+  /// Gets if the package.json or a local package has the specified package
+  /// name.
   pub fn has_package_name(&self, name: &str) -> bool {
-    if let Some(package_deps) = self.package_deps() {
-      // ensure this looks at the package name and not the
-      // bare specifiers (do not look at the keys!)
-      package_deps
-        .values()
-        .filter_map(|r| r.as_ref().ok())
-        .any(|v| v.name == name)
-    } else {
-      false
-    }
+    // ensure this looks at the package name and not the
+    // bare specifiers (do not look at the keys!)
+    self.package_reqs().any(|v| v.name == name)
   }
 
   /// Installs the top level dependencies in the package.json file
@@ -69,12 +91,9 @@ impl PackageJsonDepsInstaller {
       return Ok(()); // already installed by something else
     }
 
-    let mut package_reqs = inner
-      .package_deps
-      .values()
-      .filter_map(|r| r.as_ref().ok())
-      .collect::<Vec<_>>();
+    let mut package_reqs = self.package_reqs().collect::<Vec<_>>();
     package_reqs.sort(); // deterministic resolution
+    package_reqs.dedup();
 
     inner
       .npm_registry_api
@@ -2,6 +2,7 @@
 
 mod cache;
 mod installer;
+mod npmrc;
 mod registry;
 mod resolution;
 mod resolvers;
@@ -10,7 +11,8 @@ mod tarball;
 pub use cache::should_sync_download;
 pub use cache::NpmCache;
 pub use installer::PackageJsonDepsInstaller;
-#[cfg(test)]
+pub use npmrc::NpmRc;
+pub use registry::NpmPackageInfo;
 pub use registry::NpmPackageVersionDistInfo;
 pub use registry::NpmRegistryApi;
 #[cfg(test)]
@@ -21,3 +23,4 @@ pub use resolution::NpmResolutionPackage;
 pub use resolution::NpmResolutionSnapshot;
 pub use resolvers::NpmPackageResolver;
 pub use resolvers::NpmProcessState;
+pub use tarball::extract_local_tarball;
@@ -0,0 +1,348 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Registry configuration from the `.npmrc` files of the project and of the
+//! home directory. Supported are the `registry` and `@scope:registry` urls
+//! and the `_authToken`, `_auth`, `username` and `_password` credentials,
+//! keyed by the registry url without its scheme like
+//! `//registry.example.com/:_authToken=...`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use deno_core::url::Url;
+
+use super::NpmRegistryApi;
+use crate::cache::home_dir;
+
+const NPMRC_FILE_NAME: &str = ".npmrc";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryAuth {
+  /// An `_authToken`, sent as a bearer token.
+  Token(String),
+  /// The base64 encoded `username:password` of an `_auth`, or of a `username`
+  /// and `_password`.
+  Basic(String),
+}
+
+impl fmt::Display for RegistryAuth {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      RegistryAuth::Token(token) => write!(f, "Bearer {token}"),
+      RegistryAuth::Basic(credentials) => write!(f, "Basic {credentials}"),
+    }
+  }
+}
+
+#[derive(Default)]
+struct RegistryAuthEntry {
+  token: Option<String>,
+  auth: Option<String>,
+  username: Option<String>,
+  /// The base64 encoded password.
+  password: Option<String>,
+}
+
+// don't expose the credentials in logs
+impl fmt::Debug for RegistryAuthEntry {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("RegistryAuthEntry").finish_non_exhaustive()
+  }
+}
+
+impl RegistryAuthEntry {
+  fn set(&mut self, key: &str, value: String) {
+    match key {
+      "_authToken" => self.token = Some(value),
+      "_auth" => self.auth = Some(value),
+      "username" => self.username = Some(value),
+      "_password" => self.password = Some(value),
+      _ => {}
+    }
+  }
+
+  fn resolve(&self) -> Option<RegistryAuth> {
+    if let Some(token) = &self.token {
+      return Some(RegistryAuth::Token(token.clone()));
+    }
+    if let Some(auth) = &self.auth {
+      return Some(RegistryAuth::Basic(auth.clone()));
+    }
+    match (&self.username, &self.password) {
+      (Some(username), Some(password)) => {
+        let password = base64::decode(password)
+          .ok()
+          .and_then(|password| String::from_utf8(password).ok())?;
+        Some(RegistryAuth::Basic(base64::encode(format!(
+          "{username}:{password}"
+        ))))
+      }
+      _ => None,
+    }
+  }
+}
+
+/// The npm registry configuration of the `.npmrc` files.
+#[derive(Debug, Default)]
+pub struct NpmRc {
+  registry: Option<Url>,
+  scope_registries: HashMap<String, Url>,
+  /// The credentials without a registry, which are used for the default
+  /// registry.
+  default_auth: RegistryAuthEntry,
+  /// The credentials keyed by the registry url without the scheme.
+  auths: HashMap<String, RegistryAuthEntry>,
+}
+
+impl NpmRc {
+  /// Loads the `.npmrc` file of the home directory and the one of the project
+  /// directory, whose entries take precedence.
+  pub fn load(maybe_project_dir: Option<&Path>) -> Self {
+    let mut npmrc = NpmRc::default();
+    let paths = home_dir()
+      .into_iter()
+      .chain(maybe_project_dir.map(ToOwned::to_owned))
+      .map(|dir| dir.join(NPMRC_FILE_NAME));
+    for path in paths {
+      match std::fs::read_to_string(&path) {
+        Ok(text) => {
+          log::debug!("Loading npm config from {}", path.display());
+          npmrc.add_entries(&text, |name| std::env::var(name).ok());
+        }
+        Err(err) => {
+          log::debug!("No npm config at {}: {:#}", path.display(), err);
+        }
+      }
+    }
+    npmrc
+  }
+
+  fn add_entries(
+    &mut self,
+    text: &str,
+    get_env_var: impl Fn(&str) -> Option<String>,
+  ) {
+    for line in text.lines() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+        continue;
+      }
+      let (key, value) = match line.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => continue,
+      };
+      let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+      // an entry with an unset variable is dropped rather than sending a
+      // credential like `${NPM_TOKEN}` to the registry
+      let value = match expand_env_vars(value, &get_env_var) {
+        Ok(value) => value,
+        Err(name) => {
+          log::warn!(
+            "Ignoring npm config \"{}\", the variable \"{}\" is not set",
+            key,
+            name
+          );
+          continue;
+        }
+      };
+
+      if key == "registry" {
+        self.registry = parse_registry_url(key, &value);
+      } else if let Some((prefix, name)) = key.rsplit_once(':') {
+        if prefix.starts_with("//") {
+          let auth = self.auths.entry(registry_key(prefix)).or_default();
+          auth.set(name, value);
+        } else if prefix.starts_with('@') && name == "registry" {
+          if let Some(url) = parse_registry_url(key, &value) {
+            self.scope_registries.insert(prefix.to_string(), url);
+          }
+        }
+      } else {
+        self.default_auth.set(key, value);
+      }
+    }
+  }
+
+  /// Gets the default registry. The `NPM_CONFIG_REGISTRY` environment
+  /// variable takes precedence over the `.npmrc` files.
+  pub fn registry_url(&self) -> Url {
+    match &self.registry {
+      Some(url) if std::env::var_os("NPM_CONFIG_REGISTRY").is_none() => {
+        url.clone()
+      }
+      _ => NpmRegistryApi::default_url().clone(),
+    }
+  }
+
+  /// Gets the registry of a package of a scope with an `@scope:registry`
+  /// entry.
+  pub fn scope_registry_url(&self, package_name: &str) -> Option<&Url> {
+    let (scope, _) = package_name.split_once('/')?;
+    self.scope_registries.get(scope)
+  }
+
+  /// Gets the `Authorization` header to send with a request to a registry,
+  /// or for a tarball hosted by it. The credentials of the most specific
+  /// registry url are used.
+  pub fn auth_header_for_url(&self, url: &Url) -> Option<String> {
+    let url_key = registry_key(&url_without_scheme(url));
+    let maybe_auth = self
+      .auths
+      .iter()
+      .filter(|(key, _)| url_key.starts_with(key.as_str()))
+      .max_by_key(|(key, _)| key.len())
+      .and_then(|(_, auth)| auth.resolve());
+    let maybe_auth = maybe_auth.or_else(|| {
+      let registry_key =
+        registry_key(&url_without_scheme(&self.registry_url()));
+      if url_key.starts_with(&registry_key) {
+        self.default_auth.resolve()
+      } else {
+        None
+      }
+    });
+    maybe_auth.map(|auth| auth.to_string())
+  }
+}
+
+fn parse_registry_url(key: &str, value: &str) -> Option<Url> {
+  // ensure there is a trailing slash for the directory
+  let url = format!("{}/", value.trim_end_matches('/'));
+  match Url::parse(&url) {
+    Ok(url) => Some(url),
+    Err(err) => {
+      log::warn!("Invalid npm config \"{}\": {:#}", key, err);
+      None
+    }
+  }
+}
+
+/// Gets a url as `//host:port/path`, the way registries are keyed in the
+/// `.npmrc` files.
+fn url_without_scheme(url: &Url) -> String {
+  let host = url.host_str().unwrap_or_default();
+  match url.port() {
+    Some(port) => format!("//{}:{}{}", host, port, url.path()),
+    None => format!("//{}{}", host, url.path()),
+  }
+}
+
+/// Normalizes a registry key to end with a slash, so that it's only a prefix
+/// of the urls of that registry.
+fn registry_key(key: &str) -> String {
+  if key.ends_with('/') {
+    key.to_string()
+  } else {
+    format!("{key}/")
+  }
+}
+
+/// Replaces `${NAME}` with the value of the environment variable. The name of
+/// the first variable that is not set is returned as the error.
+fn expand_env_vars<'a>(
+  value: &'a str,
+  get_env_var: &impl Fn(&str) -> Option<String>,
+) -> Result<String, &'a str> {
+  let mut result = String::with_capacity(value.len());
+  let mut rest = value;
+  while let Some(start) = rest.find("${") {
+    let end = match rest[start..].find('}') {
+      Some(end) => start + end,
+      None => break,
+    };
+    result.push_str(&rest[..start]);
+    let name = &rest[start + 2..end];
+    result.push_str(&get_env_var(name).ok_or(name)?);
+    rest = &rest[end + 1..];
+  }
+  result.push_str(rest);
+  Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn npmrc(text: &str) -> NpmRc {
+    let mut npmrc = NpmRc::default();
+    npmrc.add_entries(text, |name| match name {
+      "NPM_TOKEN" => Some("secret".to_string()),
+      _ => None,
+    });
+    npmrc
+  }
+
+  fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+  }
+
+  #[test]
+  fn test_registries() {
+    let npmrc = npmrc(
+      r#"
+; comment
+registry=https://registry.example.com/npm
+@private:registry = "https://npm.example.com:8080/"
+"#,
+    );
+    assert_eq!(
+      npmrc.registry,
+      Some(url("https://registry.example.com/npm/"))
+    );
+    assert_eq!(
+      npmrc.scope_registry_url("@private/package"),
+      Some(&url("https://npm.example.com:8080/"))
+    );
+    assert_eq!(npmrc.scope_registry_url("@other/package"), None);
+    assert_eq!(npmrc.scope_registry_url("package"), None);
+  }
+
+  #[test]
+  fn test_auth_header_for_url() {
+    let npmrc = npmrc(
+      r#"
+registry=https://registry.example.com/
+_authToken=default
+//npm.example.com:8080/:_authToken=${NPM_TOKEN}
+//npm.example.com:8080/private/:_auth=dXNlcjpwYXNz
+//basic.example.com/:username=user
+//basic.example.com/:_password=cGFzcw==
+//unset.example.com/:_authToken=${UNSET}
+//npm.example.com:8080/unset/:_authToken=${UNSET}
+"#,
+    );
+    let header = |u: &str| npmrc.auth_header_for_url(&url(u));
+    assert_eq!(
+      header("https://npm.example.com:8080/package"),
+      Some("Bearer secret".to_string())
+    );
+    assert_eq!(
+      header(
+        "https://npm.example.com:8080/private/package/-/package-1.0.0.tgz"
+      ),
+      Some("Basic dXNlcjpwYXNz".to_string())
+    );
+    assert_eq!(
+      header("https://basic.example.com/package"),
+      Some("Basic dXNlcjpwYXNz".to_string())
+    );
+    assert_eq!(header("https://unset.example.com/package"), None);
+    assert_eq!(
+      header("https://npm.example.com:8080/unset/package"),
+      Some("Bearer secret".to_string())
+    );
+    assert_eq!(
+      header("https://registry.example.com/package"),
+      Some("Bearer default".to_string())
+    );
+    assert_eq!(header("https://npm.example.com/package"), None);
+    assert_eq!(
+      header("https://npm.example.com.evil.com:8080/package"),
+      None
+    );
+  }
+}
@@ -33,6 +33,7 @@ use crate::util::progress_bar::ProgressBar;
 
 use super::cache::should_sync_download;
 use super::cache::NpmCache;
+use super::npmrc::NpmRc;
 
 // npm registry docs: https://github.com/npm/registry/blob/master/docs/REGISTRY-API.md
 
@@ -216,14 +217,18 @@ impl NpmRegistryApi {
     &NPM_REGISTRY_DEFAULT_URL
   }
 
+  /// Creates the api of the default registry of the npm config. Packages of
+  /// scopes with their own registry are fetched from that registry, but are
+  /// cached along with the packages of the default registry.
   pub fn new(
-    base_url: Url,
+    npmrc: Arc<NpmRc>,
     cache: NpmCache,
     http_client: HttpClient,
     progress_bar: ProgressBar,
   ) -> Self {
     Self(Arc::new(RealNpmRegistryApiInner {
-      base_url,
+      base_url: npmrc.registry_url(),
+      npmrc,
       cache,
       mem_cache: Default::default(),
       previously_reloaded_packages: Default::default(),
@@ -352,6 +357,7 @@ impl NpmRegistryApiInner for RealNpmRegistryApiInner {
 #[derive(Debug)]
 struct RealNpmRegistryApiInner {
   base_url: Url,
+  npmrc: Arc<NpmRc>,
   cache: NpmCache,
   mem_cache: Mutex<HashMap<String, Option<Arc<NpmPackageInfo>>>>,
   previously_reloaded_packages: Mutex<HashSet<String>>,
@@ -490,10 +496,15 @@ impl RealNpmRegistryApiInner {
 
     let package_url = self.get_package_url(name);
     let guard = self.progress_bar.update(package_url.as_str());
+    let maybe_auth_header = self.npmrc.auth_header_for_url(&package_url);
 
     let maybe_bytes = self
       .http_client
-      .download_with_progress(package_url, &guard)
+      .download_with_progress_and_auth(
+        package_url,
+        maybe_auth_header.as_deref(),
+        &guard,
+      )
       .await?;
     match maybe_bytes {
       Some(bytes) => {
@@ -506,12 +517,15 @@ impl RealNpmRegistryApiInner {
   }
 
   fn get_package_url(&self, name: &str) -> Url {
-    self.base_url.join(name).unwrap()
+    let registry_url = self
+      .npmrc
+      .scope_registry_url(name)
+      .unwrap_or(&self.base_url);
+    registry_url.join(name).unwrap()
   }
 
   fn get_package_file_cache_path(&self, name: &str) -> PathBuf {
-    let name_folder_path = self.cache.package_name_folder(name, &self.base_url);
-    name_folder_path.join("registry.json")
+    self.cache.package_info_file_path(name, &self.base_url)
   }
 }
 
@@ -37,6 +37,7 @@ mod graph;
 mod snapshot;
 
 use graph::Graph;
+pub use snapshot::name_without_path;
 pub use snapshot::NpmResolutionSnapshot;
 
 #[derive(Debug, Error)]
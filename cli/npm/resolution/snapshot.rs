@@ -414,7 +414,7 @@ impl SnapshotPackageCopyIndexResolver {
   }
 }
 
-fn name_without_path(name: &str) -> &str {
+pub fn name_without_path(name: &str) -> &str {
   let mut search_start_index = 0;
   if name.starts_with('@') {
     if let Some(slash_index) = name.find('/') {
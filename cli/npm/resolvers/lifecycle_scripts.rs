@@ -0,0 +1,561 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Runs the `preinstall`, `install` and `postinstall` scripts of the npm
+//! packages of a local node_modules folder. This is opt-in per package with
+//! `--allow-scripts` or the `"allowScripts"` configuration.
+//!
+//! `node` is sandboxed by running it as `deno run`, which may only read the
+//! package and node_modules folders, write to node_modules, run the
+//! executables of the package and its dependencies, connect to the registry
+//! and read the environment variables passed to the script. Besides it, the
+//! scripts may only use the built-in commands of the shell that don't access
+//! the file system. Any other command runs unsandboxed, so it has to be
+//! allowed for the package, like `--allow-scripts=sharp:node-gyp`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_graph::npm::NpmPackageNv;
+use deno_runtime::deno_node::PackageJson;
+use deno_runtime::tokio_util::create_basic_runtime;
+use deno_task_shell::parser::Command;
+use deno_task_shell::parser::CommandInner;
+use deno_task_shell::parser::PipelineInner;
+use deno_task_shell::parser::Sequence;
+use deno_task_shell::parser::SequentialList;
+use deno_task_shell::parser::Word;
+use deno_task_shell::parser::WordPart;
+use deno_task_shell::ExecutableCommand;
+use deno_task_shell::ExecuteResult;
+use deno_task_shell::ShellCommand;
+use deno_task_shell::ShellCommandContext;
+
+use crate::args::RESOLUTION_STATE_ENV_VAR_NAME;
+use crate::colors;
+use crate::npm::NpmResolutionSnapshot;
+use crate::util::fs::canonicalize_path;
+
+use super::NpmProcessState;
+
+const LIFECYCLE_SCRIPT_NAMES: [&str; 3] =
+  ["preinstall", "install", "postinstall"];
+
+/// The environment variables passed through to the scripts, besides the
+/// `npm_config_*` ones.
+const ALLOWED_ENV_VAR_NAMES: [&str; 20] = [
+  "APPDATA",
+  "CI",
+  "COMSPEC",
+  "DENO_DIR",
+  "HOME",
+  "HTTPS_PROXY",
+  "HTTP_PROXY",
+  "LANG",
+  "LOCALAPPDATA",
+  "NO_PROXY",
+  "PATH",
+  "PATHEXT",
+  "SHELL",
+  "SYSTEMROOT",
+  "TEMP",
+  "TERM",
+  "TMP",
+  "TMPDIR",
+  "USER",
+  "USERPROFILE",
+];
+
+/// The built-in commands of the shell that the scripts may use, which don't
+/// access the file system.
+const ALLOWED_BUILTIN_COMMANDS: [&str; 8] = [
+  "cd", "echo", "exit", "export", "false", "pwd", "sleep", "true",
+];
+
+/// A file written to the folder of a package once its scripts ran.
+const SCRIPTS_RAN_FILE_NAME: &str = ".scripts-ran";
+
+/// A package of the local node_modules folder whose scripts are allowed.
+pub struct ScriptsPackage {
+  pub nv: NpmPackageNv,
+  /// The folder of the package in `node_modules/.deno`.
+  pub folder_path: PathBuf,
+  /// The path of the package itself, which the scripts run in.
+  pub package_path: PathBuf,
+  /// The commands besides `node` that the scripts may run, unsandboxed.
+  pub commands: Vec<String>,
+}
+
+/// Runs the lifecycle scripts of the packages that didn't run them yet, in
+/// the given order.
+pub async fn run_lifecycle_scripts(
+  packages: Vec<ScriptsPackage>,
+  snapshot: &NpmResolutionSnapshot,
+  registry_url: &Url,
+  root_node_modules_dir_path: &Path,
+) -> Result<(), AnyError> {
+  let packages = packages
+    .into_iter()
+    .filter(|p| !p.folder_path.join(SCRIPTS_RAN_FILE_NAME).exists())
+    .collect::<Vec<_>>();
+  if packages.is_empty() {
+    return Ok(());
+  }
+
+  // the `node` sub processes resolve npm packages from the same snapshot and
+  // node_modules folder, the same way as `child_process.fork`
+  let npm_process_state = serde_json::to_string(&NpmProcessState {
+    snapshot: snapshot.clone(),
+    local_node_modules_path: Some(
+      root_node_modules_dir_path.to_string_lossy().to_string(),
+    ),
+  })?;
+  let registry_host = match (registry_url.host_str(), registry_url.port()) {
+    (Some(host), Some(port)) => Some(format!("{host}:{port}")),
+    (Some(host), None) => Some(host.to_string()),
+    (None, _) => None,
+  };
+  let root_node_modules_dir_path = root_node_modules_dir_path.to_path_buf();
+  // the shell isn't `Send`, so it runs on its own thread
+  tokio::task::spawn_blocking(move || -> Result<(), AnyError> {
+    let runtime = create_basic_runtime();
+    let deno_exe = std::env::current_exe()?;
+    for package in packages {
+      let node_command: Rc<dyn ShellCommand> = Rc::new(NodeCommand {
+        deno_exe: deno_exe.to_string_lossy().to_string(),
+        package_path: package.package_path.clone(),
+        root_node_modules_dir_path: root_node_modules_dir_path.clone(),
+        registry_host: registry_host.clone(),
+        executables: package_executables(&package.folder_path),
+      });
+      let package_json = PackageJson::load_skip_read_permission(
+        package.package_path.join("package.json"),
+      )?;
+      let scripts = package_json.scripts.unwrap_or_default();
+      for script_name in LIFECYCLE_SCRIPT_NAMES {
+        let script = match scripts.get(script_name) {
+          Some(script) => script,
+          None => continue,
+        };
+        log::info!(
+          "{} {} script of {}",
+          colors::green("Running"),
+          colors::cyan(script_name),
+          package.nv,
+        );
+        let seq_list =
+          deno_task_shell::parser::parse(script).with_context(|| {
+            format!("Error parsing {} script of {}.", script_name, package.nv)
+          })?;
+        if let Some(command) =
+          find_disallowed_command(&seq_list, &package.commands)
+        {
+          bail!(
+            concat!(
+              "The {} script of {} runs \"{}\", which is not allowed. ",
+              "Commands besides `node` run with the full permissions of the ",
+              "user, so they need to be allowed for the package, like ",
+              "--allow-scripts={}:<command>."
+            ),
+            script_name,
+            package.nv,
+            command,
+            package.nv.name,
+          );
+        }
+        let env_vars = script_env_vars(
+          &package.nv,
+          script_name,
+          &root_node_modules_dir_path,
+          &npm_process_state,
+        );
+        let custom_commands =
+          HashMap::from([("node".to_string(), node_command.clone())]);
+        let exit_code = runtime.block_on(deno_task_shell::execute(
+          seq_list,
+          env_vars,
+          &package.package_path,
+          custom_commands,
+        ));
+        if exit_code != 0 {
+          bail!(
+            "The {} script of {} failed with exit code {}.",
+            script_name,
+            package.nv,
+            exit_code
+          );
+        }
+      }
+      fs::write(package.folder_path.join(SCRIPTS_RAN_FILE_NAME), "")?;
+    }
+    Ok(())
+  })
+  .await?
+}
+
+fn script_env_vars(
+  nv: &NpmPackageNv,
+  script_name: &str,
+  root_node_modules_dir_path: &Path,
+  npm_process_state: &str,
+) -> HashMap<String, String> {
+  let mut env_vars = std::env::vars()
+    .filter(|(name, _)| is_allowed_env_var(name))
+    .collect::<HashMap<String, String>>();
+  env_vars.insert("npm_lifecycle_event".to_string(), script_name.to_string());
+  env_vars.insert("npm_package_name".to_string(), nv.name.clone());
+  env_vars.insert("npm_package_version".to_string(), nv.version.to_string());
+  // the directory the packages are installed for
+  if let Some(project_dir) = root_node_modules_dir_path.parent() {
+    env_vars.insert(
+      "INIT_CWD".to_string(),
+      project_dir.to_string_lossy().to_string(),
+    );
+  }
+  env_vars.insert(
+    RESOLUTION_STATE_ENV_VAR_NAME.to_string(),
+    npm_process_state.to_string(),
+  );
+  env_vars
+}
+
+fn is_allowed_env_var(name: &str) -> bool {
+  let upper_name = name.to_uppercase();
+  upper_name.starts_with("NPM_CONFIG_")
+    || ALLOWED_ENV_VAR_NAMES.contains(&upper_name.as_str())
+}
+
+/// Finds a command of a script that is not allowed to run, or an output
+/// redirect other than to `/dev/null`. The names of the commands have to be
+/// written literally.
+fn find_disallowed_command(
+  list: &SequentialList,
+  commands: &[String],
+) -> Option<String> {
+  let is_allowed = |name: &str| {
+    name == "node"
+      || ALLOWED_BUILTIN_COMMANDS.contains(&name)
+      || commands.iter().any(|command| command == name)
+  };
+  disallowed_in_list(list, &is_allowed)
+}
+
+fn disallowed_in_list(
+  list: &SequentialList,
+  is_allowed: &impl Fn(&str) -> bool,
+) -> Option<String> {
+  list
+    .items
+    .iter()
+    .find_map(|item| disallowed_in_sequence(&item.sequence, is_allowed))
+}
+
+fn disallowed_in_sequence(
+  sequence: &Sequence,
+  is_allowed: &impl Fn(&str) -> bool,
+) -> Option<String> {
+  match sequence {
+    Sequence::ShellVar(var) => disallowed_in_word(&var.value, is_allowed),
+    Sequence::Pipeline(pipeline) => {
+      disallowed_in_pipeline(&pipeline.inner, is_allowed)
+    }
+    Sequence::BooleanList(list) => {
+      disallowed_in_sequence(&list.current, is_allowed)
+        .or_else(|| disallowed_in_sequence(&list.next, is_allowed))
+    }
+  }
+}
+
+fn disallowed_in_pipeline(
+  pipeline: &PipelineInner,
+  is_allowed: &impl Fn(&str) -> bool,
+) -> Option<String> {
+  match pipeline {
+    PipelineInner::Command(command) => {
+      disallowed_in_command(command, is_allowed)
+    }
+    PipelineInner::PipeSequence(sequence) => {
+      disallowed_in_command(&sequence.current, is_allowed)
+        .or_else(|| disallowed_in_pipeline(&sequence.next, is_allowed))
+    }
+  }
+}
+
+fn disallowed_in_command(
+  command: &Command,
+  is_allowed: &impl Fn(&str) -> bool,
+) -> Option<String> {
+  if let Some(redirect) = &command.redirect {
+    if word_text(&redirect.io_file).as_deref() != Some("/dev/null") {
+      return Some(format!("> {}", display_word(&redirect.io_file)));
+    }
+  }
+  match &command.inner {
+    CommandInner::Subshell(list) => disallowed_in_list(list, is_allowed),
+    CommandInner::Simple(command) => {
+      let maybe_disallowed = command
+        .env_vars
+        .iter()
+        .map(|var| &var.value)
+        .chain(&command.args)
+        .find_map(|word| disallowed_in_word(word, is_allowed));
+      if maybe_disallowed.is_some() {
+        return maybe_disallowed;
+      }
+      let name = command.args.first()?;
+      match word_text(name) {
+        Some(text) if is_allowed(&text) => None,
+        _ => Some(display_word(name)),
+      }
+    }
+  }
+}
+
+/// Checks the commands substituted in a word.
+fn disallowed_in_word(
+  word: &Word,
+  is_allowed: &impl Fn(&str) -> bool,
+) -> Option<String> {
+  fn disallowed_in_part(
+    part: &WordPart,
+    is_allowed: &impl Fn(&str) -> bool,
+  ) -> Option<String> {
+    match part {
+      WordPart::Command(list) => disallowed_in_list(list, is_allowed),
+      WordPart::Quoted(parts) => parts
+        .iter()
+        .find_map(|part| disallowed_in_part(part, is_allowed)),
+      WordPart::Text(_) | WordPart::Variable(_) => None,
+    }
+  }
+
+  word
+    .parts()
+    .iter()
+    .find_map(|part| disallowed_in_part(part, is_allowed))
+}
+
+/// Gets the text of a word, unless it contains variables or substituted
+/// commands.
+fn word_text(word: &Word) -> Option<String> {
+  fn push_text(part: &WordPart, text: &mut String) -> Option<()> {
+    match part {
+      WordPart::Text(part_text) => text.push_str(part_text),
+      WordPart::Quoted(parts) => {
+        for part in parts {
+          push_text(part, text)?;
+        }
+      }
+      WordPart::Variable(_) | WordPart::Command(_) => return None,
+    }
+    Some(())
+  }
+
+  let mut text = String::new();
+  for part in word.parts() {
+    push_text(part, &mut text)?;
+  }
+  Some(text)
+}
+
+/// Displays a word for an error message.
+fn display_word(word: &Word) -> String {
+  fn push_part(part: &WordPart, text: &mut String) {
+    match part {
+      WordPart::Text(part_text) => text.push_str(part_text),
+      WordPart::Variable(name) => {
+        text.push('$');
+        text.push_str(name);
+      }
+      WordPart::Command(_) => text.push_str("$(...)"),
+      WordPart::Quoted(parts) => {
+        for part in parts {
+          push_part(part, text);
+        }
+      }
+    }
+  }
+
+  let mut text = String::new();
+  for part in word.parts() {
+    push_part(part, &mut text);
+  }
+  text
+}
+
+/// Collects the executables of a package and of the dependencies linked into
+/// its `node_modules` folder, like the binaries of the platform specific
+/// optional dependencies that packages such as esbuild run.
+fn package_executables(folder_path: &Path) -> Vec<PathBuf> {
+  fn read_dir_paths(dir_path: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(dir_path) {
+      Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  fn add_executables(dir_path: &Path, executables: &mut Vec<PathBuf>) {
+    for path in read_dir_paths(dir_path) {
+      let metadata = match fs::symlink_metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(_) => continue,
+      };
+      if metadata.is_dir() {
+        add_executables(&path, executables);
+      } else if metadata.is_file() && is_executable(&path, &metadata) {
+        executables.push(path);
+      }
+    }
+  }
+
+  let mut executables = Vec::new();
+  for path in read_dir_paths(&folder_path.join("node_modules")) {
+    let is_scope = path
+      .file_name()
+      .map(|name| name.to_string_lossy().starts_with('@'))
+      .unwrap_or(false);
+    let package_paths = if is_scope {
+      read_dir_paths(&path)
+    } else {
+      vec![path]
+    };
+    // the dependencies are symlinked, and might be run by the path of the
+    // link or by the resolved path
+    for package_path in package_paths {
+      add_executables(&package_path, &mut executables);
+      if let Ok(resolved_path) = canonicalize_path(&package_path) {
+        if resolved_path != package_path {
+          add_executables(&resolved_path, &mut executables);
+        }
+      }
+    }
+  }
+  executables.sort();
+  executables.dedup();
+  executables
+}
+
+#[cfg(unix)]
+fn is_executable(_path: &Path, metadata: &fs::Metadata) -> bool {
+  use std::os::unix::fs::PermissionsExt;
+  metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path, _metadata: &fs::Metadata) -> bool {
+  let extension = path
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase());
+  matches!(extension.as_deref(), Some("exe" | "cmd" | "bat" | "com"))
+}
+
+/// Runs `node` as `deno run`, only allowing to read the package and
+/// node_modules folders, to write to the node_modules folder, to run the
+/// executables of the package, to connect to the registry and to read the
+/// environment variables of the script.
+struct NodeCommand {
+  deno_exe: String,
+  package_path: PathBuf,
+  root_node_modules_dir_path: PathBuf,
+  registry_host: Option<String>,
+  executables: Vec<PathBuf>,
+}
+
+impl ShellCommand for NodeCommand {
+  fn execute(
+    &self,
+    context: ShellCommandContext,
+  ) -> LocalBoxFuture<'static, ExecuteResult> {
+    let mut env_var_names =
+      context.state.env_vars().keys().cloned().collect::<Vec<_>>();
+    env_var_names.sort();
+    let mut args = vec![
+      "run".to_string(),
+      "--unstable".to_string(),
+      "--no-config".to_string(),
+      format!("--allow-env={}", env_var_names.join(",")),
+      format!(
+        "--allow-read={},{}",
+        self.package_path.display(),
+        self.root_node_modules_dir_path.display()
+      ),
+      "--allow-sys".to_string(),
+      format!(
+        "--allow-write={}",
+        self.root_node_modules_dir_path.display()
+      ),
+    ];
+    if let Some(registry_host) = &self.registry_host {
+      args.push(format!("--allow-net={registry_host}"));
+    }
+    // a comma would split the path into several entries of the list
+    let executables = self
+      .executables
+      .iter()
+      .map(|path| path.to_string_lossy())
+      .filter(|path| !path.contains(','))
+      .collect::<Vec<_>>();
+    if !executables.is_empty() {
+      args.push(format!("--allow-run={}", executables.join(",")));
+    }
+    args.extend(context.args);
+    let executable_command = ExecutableCommand::new(self.deno_exe.clone());
+    executable_command.execute(ShellCommandContext { args, ..context })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn disallowed(script: &str, commands: &[&str]) -> Option<String> {
+    let list = deno_task_shell::parser::parse(script).unwrap();
+    let commands = commands.iter().map(ToString::to_string).collect::<Vec<_>>();
+    find_disallowed_command(&list, &commands)
+  }
+
+  #[test]
+  fn test_find_disallowed_command() {
+    assert_eq!(disallowed("node install.js", &[]), None);
+    assert_eq!(
+      disallowed("node install.js || echo \"failed\" && exit 0", &[]),
+      None
+    );
+    assert_eq!(disallowed("node install.js > /dev/null", &[]), None);
+    assert_eq!(disallowed("FOO=1 node install.js", &[]), None);
+    assert_eq!(
+      disallowed("node-gyp rebuild", &[]),
+      Some("node-gyp".to_string())
+    );
+    assert_eq!(disallowed("node-gyp rebuild", &["node-gyp"]), None);
+    assert_eq!(
+      disallowed("node install.js && (cd build && make)", &["node-gyp"]),
+      Some("make".to_string())
+    );
+    assert_eq!(
+      disallowed("node install.js | xargs rm", &[]),
+      Some("xargs".to_string())
+    );
+    assert_eq!(
+      disallowed("echo $(curl https://example.com)", &[]),
+      Some("curl".to_string())
+    );
+    assert_eq!(
+      disallowed("$NODE install.js", &[]),
+      Some("$NODE".to_string())
+    );
+    assert_eq!(
+      disallowed("echo built > ~/.profile", &[]),
+      Some("> ~/.profile".to_string())
+    );
+    assert_eq!(disallowed("rm -rf build", &[]), Some("rm".to_string()));
+  }
+}
@@ -8,6 +8,7 @@ use std::collections::VecDeque;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use crate::util::fs::symlink_dir;
 use crate::util::fs::LaxSingleProcessFsFlag;
@@ -23,10 +24,13 @@ use deno_runtime::deno_node::NodeResolutionMode;
 use deno_runtime::deno_node::PackageJson;
 use tokio::task::JoinHandle;
 
+use crate::args::package_json::LocalPackages;
+use crate::args::AllowScriptsPackage;
 use crate::npm::cache::mixed_case_package_name_encode;
 use crate::npm::cache::should_sync_download;
 use crate::npm::cache::NpmPackageCacheFolderId;
 use crate::npm::resolution::NpmResolution;
+use crate::npm::resolution::NpmResolutionPackage;
 use crate::npm::resolution::NpmResolutionSnapshot;
 use crate::npm::NpmCache;
 use crate::npm::NpmPackageId;
@@ -36,6 +40,8 @@ use crate::util::fs::hard_link_dir_recursive;
 use super::common::ensure_registry_read_permission;
 use super::common::types_package_name;
 use super::common::NpmPackageFsResolver;
+use super::lifecycle_scripts::run_lifecycle_scripts;
+use super::lifecycle_scripts::ScriptsPackage;
 
 /// Resolver that creates a local node_modules directory
 /// and resolves packages from it.
@@ -46,6 +52,10 @@ pub struct LocalNpmPackageResolver {
   registry_url: Url,
   root_node_modules_path: PathBuf,
   root_node_modules_url: Url,
+  /// The packages whose lifecycle scripts are run.
+  allow_scripts: Vec<AllowScriptsPackage>,
+  /// The packages linked into the node_modules folder from a directory.
+  local_packages: Arc<LocalPackages>,
 }
 
 impl LocalNpmPackageResolver {
@@ -53,6 +63,8 @@ impl LocalNpmPackageResolver {
     cache: NpmCache,
     registry_url: Url,
     node_modules_folder: PathBuf,
+    allow_scripts: Vec<AllowScriptsPackage>,
+    local_packages: Arc<LocalPackages>,
     resolution: NpmResolution,
   ) -> Self {
     Self {
@@ -62,6 +74,8 @@ impl LocalNpmPackageResolver {
       root_node_modules_url: Url::from_directory_path(&node_modules_folder)
         .unwrap(),
       root_node_modules_path: node_modules_folder,
+      allow_scripts,
+      local_packages,
     }
   }
 
@@ -221,6 +235,8 @@ async fn sync_resolver_with_fs(
     &resolver.cache,
     &resolver.registry_url,
     &resolver.root_node_modules_path,
+    &resolver.allow_scripts,
+    &resolver.local_packages,
   )
   .await
 }
@@ -231,6 +247,8 @@ async fn sync_resolution_with_fs(
   cache: &NpmCache,
   registry_url: &Url,
   root_node_modules_dir_path: &Path,
+  allow_scripts: &[AllowScriptsPackage],
+  local_packages: &LocalPackages,
 ) -> Result<(), AnyError> {
   let deno_local_registry_dir = root_node_modules_dir_path.join(".deno");
   fs::create_dir_all(&deno_local_registry_dir).with_context(|| {
@@ -402,11 +420,95 @@ async fn sync_resolution_with_fs(
     }
   }
 
+  // 5. Symlink the local packages (workspace members and `file:`
+  // dependencies) into the node_modules folder.
+  //
+  // Symlink node_modules/<package_name> to the package's folder
+  for (name, package) in local_packages {
+    symlink_package_dir(
+      &package.dir,
+      &join_package_name(root_node_modules_dir_path, name),
+    )?;
+  }
+
   drop(single_process_lock);
 
+  // 6. Run the lifecycle scripts of the allowed packages, dependencies first.
+  //
+  // This is done after releasing the lock because the scripts run deno sub
+  // processes that sync this folder as well.
+  if !allow_scripts.is_empty() {
+    let packages = packages_in_dependency_order(snapshot, &all_packages)
+      .into_iter()
+      .filter_map(|package| {
+        let allowed = allow_scripts
+          .iter()
+          .find(|allowed| allowed.name == package.pkg_id.nv.name)?;
+        Some((package, allowed))
+      })
+      .map(|(package, allowed)| {
+        let folder_path =
+          deno_local_registry_dir.join(get_package_folder_id_folder_name(
+            &package.get_package_cache_folder_id(),
+          ));
+        let package_path = join_package_name(
+          &folder_path.join("node_modules"),
+          &package.pkg_id.nv.name,
+        );
+        ScriptsPackage {
+          nv: package.pkg_id.nv.clone(),
+          folder_path,
+          package_path,
+          commands: allowed.commands.clone(),
+        }
+      })
+      .collect();
+    run_lifecycle_scripts(
+      packages,
+      snapshot,
+      registry_url,
+      root_node_modules_dir_path,
+    )
+    .await?;
+  }
+
   Ok(())
 }
 
+/// Sorts the packages so that the dependencies of a package come before it.
+fn packages_in_dependency_order<'a>(
+  snapshot: &'a NpmResolutionSnapshot,
+  packages: &'a [NpmResolutionPackage],
+) -> Vec<&'a NpmResolutionPackage> {
+  fn visit<'a>(
+    package: &'a NpmResolutionPackage,
+    snapshot: &'a NpmResolutionSnapshot,
+    visited: &mut HashSet<&'a NpmPackageId>,
+    result: &mut Vec<&'a NpmResolutionPackage>,
+  ) {
+    if !visited.insert(&package.pkg_id) {
+      return;
+    }
+    let mut dep_ids = package.dependencies.values().collect::<Vec<_>>();
+    dep_ids.sort();
+    for dep_id in dep_ids {
+      if let Some(dep) = snapshot.package_from_id(dep_id) {
+        visit(dep, snapshot, visited, result);
+      }
+    }
+    result.push(package);
+  }
+
+  let mut packages = packages.iter().collect::<Vec<_>>();
+  packages.sort_by(|a, b| a.pkg_id.cmp(&b.pkg_id));
+  let mut visited = HashSet::new();
+  let mut result = Vec::with_capacity(packages.len());
+  for package in packages {
+    visit(package, snapshot, &mut visited, &mut result);
+  }
+  result
+}
+
 fn get_package_folder_id_folder_name(
   folder_id: &NpmPackageCacheFolderId,
 ) -> String {
@@ -2,6 +2,7 @@
 
 mod common;
 mod global;
+mod lifecycle_scripts;
 mod local;
 
 use deno_ast::ModuleSpecifier;
@@ -23,11 +24,16 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::args::package_json::LocalPackage;
+use crate::args::package_json::LocalPackages;
+use crate::args::AllowScriptsPackage;
 use crate::args::Lockfile;
+use crate::resolver::local_package_of_path;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 
 use self::common::NpmPackageFsResolver;
 use self::local::LocalNpmPackageResolver;
+use super::resolution::name_without_path;
 use super::resolution::NpmResolution;
 use super::NpmCache;
 use super::NpmPackageId;
@@ -45,6 +51,8 @@ pub struct NpmProcessState {
 pub struct NpmPackageResolver {
   fs_resolver: Arc<dyn NpmPackageFsResolver>,
   local_node_modules_path: Option<PathBuf>,
+  allow_scripts: Vec<AllowScriptsPackage>,
+  local_packages: Arc<LocalPackages>,
   api: NpmRegistryApi,
   resolution: NpmResolution,
   cache: NpmCache,
@@ -56,6 +64,8 @@ impl std::fmt::Debug for NpmPackageResolver {
     f.debug_struct("NpmPackageResolver")
       .field("fs_resolver", &"<omitted>")
       .field("local_node_modules_path", &self.local_node_modules_path)
+      .field("allow_scripts", &self.allow_scripts)
+      .field("local_packages", &self.local_packages)
       .field("api", &"<omitted>")
       .field("resolution", &"<omitted>")
       .field("cache", &"<omitted>")
@@ -66,13 +76,23 @@ impl std::fmt::Debug for NpmPackageResolver {
 
 impl NpmPackageResolver {
   pub fn new(cache: NpmCache, api: NpmRegistryApi) -> Self {
-    Self::new_inner(cache, api, None, None, None)
+    Self::new_inner(
+      cache,
+      api,
+      None,
+      Vec::new(),
+      Default::default(),
+      None,
+      None,
+    )
   }
 
   pub async fn new_with_maybe_lockfile(
     cache: NpmCache,
     api: NpmRegistryApi,
     local_node_modules_path: Option<PathBuf>,
+    allow_scripts: Vec<AllowScriptsPackage>,
+    local_packages: LocalPackages,
     initial_snapshot: Option<NpmResolutionSnapshot>,
     maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
   ) -> Result<Self, AnyError> {
@@ -99,6 +119,8 @@ impl NpmPackageResolver {
       cache,
       api,
       local_node_modules_path,
+      allow_scripts,
+      Arc::new(local_packages),
       initial_snapshot,
       maybe_lockfile,
     ))
@@ -108,6 +130,8 @@ impl NpmPackageResolver {
     cache: NpmCache,
     api: NpmRegistryApi,
     local_node_modules_path: Option<PathBuf>,
+    allow_scripts: Vec<AllowScriptsPackage>,
+    local_packages: Arc<LocalPackages>,
     maybe_snapshot: Option<NpmResolutionSnapshot>,
     maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
   ) -> Self {
@@ -120,6 +144,8 @@ impl NpmPackageResolver {
           cache.clone(),
           registry_url,
           node_modules_folder.clone(),
+          allow_scripts.clone(),
+          local_packages.clone(),
           resolution.clone(),
         )),
         None => Arc::new(GlobalNpmPackageResolver::new(
@@ -131,6 +157,8 @@ impl NpmPackageResolver {
     Self {
       fs_resolver,
       local_node_modules_path,
+      allow_scripts,
+      local_packages,
       api,
       resolution,
       cache,
@@ -178,23 +206,82 @@ impl NpmPackageResolver {
     referrer: &ModuleSpecifier,
     mode: NodeResolutionMode,
   ) -> Result<PathBuf, AnyError> {
-    let path = self
-      .fs_resolver
-      .resolve_package_folder_from_package(name, referrer, mode)?;
+    let path = match self.local_package_of_specifier(referrer) {
+      Some(package) => {
+        self.resolve_package_folder_from_local_package(name, package)?
+      }
+      None => self
+        .fs_resolver
+        .resolve_package_folder_from_package(name, referrer, mode)?,
+    };
     log::debug!("Resolved {} from {} to {}", name, referrer, path.display());
     Ok(path)
   }
 
+  /// Resolves a package required from a local package, which is either
+  /// another local package or a dependency in its package.json.
+  fn resolve_package_folder_from_local_package(
+    &self,
+    name: &str,
+    package: &LocalPackage,
+  ) -> Result<PathBuf, AnyError> {
+    let name = name_without_path(name);
+    if let Some(local_package) = self.local_packages.get(name) {
+      return Ok(local_package.dir.clone());
+    }
+    match package.deps.get(name) {
+      Some(Ok(req)) => {
+        let pkg_id = self.resolution.resolve_pkg_id_from_pkg_req(req)?;
+        self.resolve_pkg_folder_from_deno_module_at_pkg_id(&pkg_id)
+      }
+      Some(Err(err)) => Err(err.clone().into()),
+      None => bail!(
+        "could not find npm package '{}' in the dependencies of '{}'",
+        name,
+        package.dir.join("package.json").display(),
+      ),
+    }
+  }
+
+  /// Gets the local package (workspace member or `file:` dependency) the
+  /// provided specifier is in, excluding its own node_modules folder.
+  fn local_package_of_specifier(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<&LocalPackage> {
+    let path = specifier.to_file_path().ok()?;
+    let package = local_package_of_path(&path, &self.local_packages)?;
+    let in_node_modules = path
+      .strip_prefix(&package.dir)
+      .ok()?
+      .components()
+      .any(|c| c.as_os_str() == "node_modules");
+    if in_node_modules {
+      None
+    } else {
+      Some(package)
+    }
+  }
+
+  /// Gets if the provided specifier is in a local package.
+  pub fn in_local_package(&self, specifier: &ModuleSpecifier) -> bool {
+    self.local_package_of_specifier(specifier).is_some()
+  }
+
   /// Resolve the root folder of the package the provided specifier is in.
   ///
-  /// This will error when the provided specifier is not in an npm package.
+  /// This will error when the provided specifier is not in an npm package
+  /// or a local package.
   pub fn resolve_package_folder_from_specifier(
     &self,
     specifier: &ModuleSpecifier,
   ) -> Result<PathBuf, AnyError> {
-    let path = self
-      .fs_resolver
-      .resolve_package_folder_from_specifier(specifier)?;
+    let path = match self.local_package_of_specifier(specifier) {
+      Some(package) => package.dir.clone(),
+      None => self
+        .fs_resolver
+        .resolve_package_folder_from_specifier(specifier)?,
+    };
     log::debug!(
       "Resolved package folder of {} to {}",
       specifier,
@@ -272,6 +359,8 @@ impl NpmPackageResolver {
       self.cache.clone(),
       self.api.clone(),
       self.local_node_modules_path.clone(),
+      self.allow_scripts.clone(),
+      self.local_packages.clone(),
       Some(self.snapshot()),
       None,
     )
@@ -340,6 +429,13 @@ impl RequireNpmResolver for NpmPackageResolver {
     permissions: &mut dyn NodePermissions,
     path: &Path,
   ) -> Result<(), AnyError> {
+    // local packages are part of the project, so they're readable like the
+    // rest of its modules
+    if let Ok(specifier) = path_to_specifier(path) {
+      if self.in_local_package(&specifier) {
+        return Ok(());
+      }
+    }
     self.fs_resolver.ensure_read_permission(permissions, path)
   }
 }
@@ -28,6 +28,25 @@ pub fn verify_and_extract_tarball(
   })
 }
 
+/// Extracts the tarball of a `file:` dependency. It's extracted to a
+/// temporary folder first so that a partially extracted folder is never used.
+pub fn extract_local_tarball(
+  data: &[u8],
+  output_folder: &Path,
+) -> Result<(), AnyError> {
+  let temp_folder =
+    output_folder.with_extension(format!("{}.tmp", std::process::id()));
+  extract_tarball(data, &temp_folder)?;
+  if let Err(err) = fs::rename(&temp_folder, output_folder) {
+    let _ignore = fs::remove_dir_all(&temp_folder);
+    // another process might have extracted it in the meantime
+    if !output_folder.exists() {
+      return Err(err.into());
+    }
+  }
+  Ok(())
+}
+
 fn verify_tarball_integrity(
   package: &NpmPackageNv,
   data: &[u8],
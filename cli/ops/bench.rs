@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::cell::Cell;
+use std::ffi::c_void;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::time;
@@ -7,6 +9,7 @@ use std::time;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::op;
+use deno_core::v8;
 use deno_core::Extension;
 use deno_core::ModuleSpecifier;
 use deno_core::OpState;
@@ -34,6 +37,7 @@ pub fn init(
       op_register_bench::decl(),
       op_dispatch_bench_event::decl(),
       op_bench_now::decl(),
+      op_bench_metrics::decl(),
     ])
     .state(move |state| {
       state.put(sender.clone());
@@ -144,3 +148,50 @@ fn op_bench_now(state: &mut OpState) -> Result<u64, AnyError> {
   let ns_u64 = u64::try_from(ns)?;
   Ok(ns_u64)
 }
+
+thread_local! {
+  /// The number of garbage collections of the isolate of this thread, since
+  /// every bench module runs in its own thread.
+  static GC_COUNT: Cell<u64> = Cell::new(0);
+}
+
+/// Marks that `count_gc` was added to the isolate.
+struct GcCallback;
+
+extern "C" fn count_gc(
+  _isolate: *mut v8::Isolate,
+  _type: v8::GCType,
+  _flags: v8::GCCallbackFlags,
+  _data: *mut c_void,
+) {
+  GC_COUNT.with(|count| count.set(count.get() + 1));
+}
+
+/// Writes the used V8 heap size, the number of ops dispatched and the number
+/// of garbage collections so far to `buffer`, without allocating on the JS
+/// heap.
+#[op(v8)]
+fn op_bench_metrics(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+  buffer: &mut [f64],
+) {
+  if !state.has::<GcCallback>() {
+    // incremental marking steps and weak callbacks aren't collections
+    scope.add_gc_prologue_callback(
+      count_gc,
+      std::ptr::null_mut(),
+      v8::GC_TYPE_TYPE_SCAVENGE
+        | v8::GC_TYPE_MINOR_MARK_COMPACT
+        | v8::GC_TYPE_MARK_SWEEP_COMPACT,
+    );
+    state.put(GcCallback);
+  }
+
+  let mut stats = v8::HeapStatistics::default();
+  scope.get_heap_statistics(&mut stats);
+
+  buffer[0] = stats.used_heap_size() as f64;
+  buffer[1] = state.tracker.aggregate().ops_dispatched as f64;
+  buffer[2] = GC_COUNT.with(|count| count.get()) as f64;
+}
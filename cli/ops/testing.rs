@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::tools::coverage::CoverageCollector;
 use crate::tools::test::FailFastTracker;
 use crate::tools::test::TestDescription;
 use crate::tools::test::TestEvent;
@@ -8,6 +9,7 @@ use crate::tools::test::TestFilter;
 use crate::tools::test::TestLocation;
 use crate::tools::test::TestResult;
 use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestTimeoutWatchdog;
 
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
@@ -21,8 +23,11 @@ use deno_runtime::permissions::PermissionsContainer;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use uuid::Uuid;
 
 pub fn init(
@@ -39,6 +44,9 @@ pub fn init(
       op_register_test_step::decl(),
       op_dispatch_test_event::decl(),
       op_tests_should_stop::decl(),
+      op_test_coverage_snapshot::decl(),
+      op_test_timeout::decl(),
+      op_test_timeout_end::decl(),
     ])
     .state(move |state| {
       state.put(sender.clone());
@@ -204,3 +212,50 @@ fn op_dispatch_test_event(
 fn op_tests_should_stop(state: &mut OpState) -> bool {
   state.borrow::<FailFastTracker>().should_stop()
 }
+
+/// Takes a coverage snapshot after a test, or before the first test when no
+/// test is passed. This is a noop unless `--coverage-per-test` is set, in
+/// which case the worker puts its coverage collector in the op state while
+/// running tests.
+#[op]
+async fn op_test_coverage_snapshot(
+  state: Rc<RefCell<OpState>>,
+  maybe_test: Option<TestDescription>,
+) -> Result<(), AnyError> {
+  let maybe_collector = state.borrow_mut().try_take::<CoverageCollector>();
+  if let Some(mut collector) = maybe_collector {
+    let result = collector.take_snapshot(maybe_test).await;
+    state.borrow_mut().put(collector);
+    result?;
+  }
+  Ok(())
+}
+
+/// Starts the timeout of a test or step with the watchdog the worker puts in
+/// the op state while running tests, resolving once it ended within the
+/// timeout. The pending op keeps the event loop alive until then.
+#[op]
+async fn op_test_timeout(
+  state: Rc<RefCell<OpState>>,
+  id: usize,
+  timeout: u64,
+) -> Result<(), AnyError> {
+  let maybe_ended = state
+    .borrow()
+    .try_borrow::<TestTimeoutWatchdog>()
+    .map(|watchdog| watchdog.add(id, Duration::from_millis(timeout)));
+  if let Some(ended) = maybe_ended {
+    ended.notified().await;
+  }
+  Ok(())
+}
+
+/// Ends the timeout of a test or step. Returns false if a timeout passed in
+/// the meantime.
+#[op]
+fn op_test_timeout_end(state: &mut OpState, id: usize) -> bool {
+  state
+    .try_borrow::<TestTimeoutWatchdog>()
+    .map(|watchdog| watchdog.remove(id))
+    .unwrap_or(true)
+}
@@ -15,6 +15,7 @@ use crate::cache::HttpCache;
 use crate::cache::NodeAnalysisCache;
 use crate::cache::ParsedSourceCache;
 use crate::cache::TypeCheckCache;
+use crate::colors;
 use crate::emit::emit_parsed_source;
 use crate::file_fetcher::FileFetcher;
 use crate::graph_util::build_graph_with_npm_resolution;
@@ -210,23 +211,34 @@ impl ProcState {
 
     let lockfile = cli_options.maybe_lock_file();
 
-    let registry_url = NpmRegistryApi::default_url().to_owned();
+    let npmrc = cli_options.resolve_npmrc();
     let npm_cache = NpmCache::from_deno_dir(
       &dir,
       cli_options.cache_setting(),
       http_client.clone(),
+      npmrc.clone(),
       progress_bar.clone(),
     );
     let api = NpmRegistryApi::new(
-      registry_url,
+      npmrc.clone(),
       npm_cache.clone(),
       http_client.clone(),
       progress_bar.clone(),
     );
+    let allow_scripts = cli_options.resolve_allow_scripts()?;
+    if !allow_scripts.is_empty() && !cli_options.has_node_modules_dir() {
+      log::warn!(
+        "{} Lifecycle scripts of npm packages are only run with a local node_modules folder (--node-modules-dir).",
+        colors::yellow("Warning"),
+      );
+    }
+    let local_packages = cli_options.resolve_local_packages()?;
     let npm_resolver = NpmPackageResolver::new_with_maybe_lockfile(
       npm_cache.clone(),
       api,
       cli_options.node_modules_dir_path(),
+      allow_scripts,
+      local_packages.clone(),
       cli_options.get_npm_resolution_snapshot(),
       lockfile.as_ref().cloned(),
     )
@@ -235,6 +247,7 @@ impl ProcState {
       npm_resolver.api().clone(),
       npm_resolver.resolution().clone(),
       cli_options.maybe_package_json_deps(),
+      local_packages,
     );
     let maybe_import_map = cli_options
       .resolve_import_map(&file_fetcher)
@@ -270,6 +283,7 @@ impl ProcState {
       &dir,
       cli_options.cache_setting(),
       http_client.clone(),
+      npmrc,
       progress_bar.clone(),
     );
     let node_analysis_cache =
@@ -517,7 +531,25 @@ impl ProcState {
             Some(Module::Node(module)) => {
               node::resolve_builtin_node_module(&module.module_name)
             }
-            Some(Module::Esm(module)) => Ok(module.specifier.clone()),
+            Some(Module::Esm(module)) => {
+              if matches!(
+                module.media_type,
+                MediaType::JavaScript | MediaType::Cjs
+              ) && self.npm_resolver.in_local_package(&module.specifier)
+              {
+                // javascript of local packages may be common js, which is
+                // loaded like the files of an npm package
+                self.handle_node_resolve_result(
+                  node::url_to_node_resolution(
+                    module.specifier.clone(),
+                    &self.npm_resolver,
+                  )
+                  .map(Some),
+                )
+              } else {
+                Ok(module.specifier.clone())
+              }
+            }
             Some(Module::Json(module)) => Ok(module.specifier.clone()),
             Some(Module::External(module)) => {
               Ok(node::resolve_specifier_into_node_modules(&module.specifier))
@@ -6,6 +6,7 @@ use deno_core::error::AnyError;
 use deno_core::futures::future;
 use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::FutureExt;
+use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
 use deno_core::TaskQueue;
 use deno_graph::npm::NpmPackageNv;
@@ -15,9 +16,15 @@ use deno_graph::source::Resolver;
 use deno_graph::source::UnknownBuiltInNodeModuleError;
 use deno_graph::source::DEFAULT_JSX_IMPORT_SOURCE_MODULE;
 use deno_runtime::deno_node::is_builtin_node_module;
+use deno_runtime::deno_node::NodeModuleKind;
+use deno_runtime::deno_node::PackageJson;
 use import_map::ImportMap;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::args::package_json::LocalPackage;
+use crate::args::package_json::LocalPackages;
 use crate::args::package_json::PackageJsonDeps;
 use crate::args::JsxImportSourceConfig;
 use crate::npm::NpmRegistryApi;
@@ -127,9 +134,22 @@ impl Resolver for CliGraphResolver {
       None => None,
     };
 
-    // then with package.json
-    if let Some(deps) = self.package_json_deps_installer.package_deps().as_ref()
+    // then with package.json, where the local packages take precedence
+    let mut maybe_deps = self.package_json_deps_installer.package_deps();
+    if let Some(local_packages) =
+      self.package_json_deps_installer.local_packages()
     {
+      if let Some(specifier) = resolve_local_package(specifier, local_packages)?
+      {
+        return Ok(specifier);
+      }
+      // the modules of a local package are resolved with its own package.json
+      if let Some(package) = local_package_of_referrer(referrer, local_packages)
+      {
+        maybe_deps = Some(&package.deps);
+      }
+    }
+    if let Some(deps) = maybe_deps {
       if let Some(specifier) = resolve_package_json_dep(specifier, deps)? {
         return Ok(specifier);
       }
@@ -166,6 +186,120 @@ fn resolve_package_json_dep(
   Ok(None)
 }
 
+fn resolve_local_package(
+  specifier: &str,
+  local_packages: &LocalPackages,
+) -> Result<Option<ModuleSpecifier>, AnyError> {
+  for (bare_specifier, package) in local_packages {
+    if let Some(path) = specifier.strip_prefix(bare_specifier.as_str()) {
+      if path.is_empty() || path.starts_with('/') {
+        let file_path = resolve_local_package_subpath(&package.dir, path)?;
+        return ModuleSpecifier::from_file_path(&file_path)
+          .map(Some)
+          .map_err(|_| {
+            anyhow!("Invalid local package path: {}", file_path.display())
+          });
+      }
+    }
+  }
+
+  Ok(None)
+}
+
+/// Gets the local package whose folder contains the referrer, preferring the
+/// innermost one.
+pub fn local_package_of_referrer<'a>(
+  referrer: &ModuleSpecifier,
+  local_packages: &'a LocalPackages,
+) -> Option<&'a LocalPackage> {
+  local_package_of_path(&referrer.to_file_path().ok()?, local_packages)
+}
+
+/// Gets the local package whose folder contains the path, preferring the
+/// innermost one.
+pub fn local_package_of_path<'a>(
+  path: &Path,
+  local_packages: &'a LocalPackages,
+) -> Option<&'a LocalPackage> {
+  local_packages
+    .values()
+    .filter(|package| path.starts_with(&package.dir))
+    .max_by_key(|package| package.dir.components().count())
+}
+
+/// Resolves a subpath of a package linked from a folder, using the
+/// `exports`, `module` or `main` of its package.json.
+fn resolve_local_package_subpath(
+  package_dir: &Path,
+  path: &str,
+) -> Result<PathBuf, AnyError> {
+  let package_json_path = package_dir.join("package.json");
+  let package_json = match std::fs::read_to_string(&package_json_path) {
+    Ok(source) => Some(PackageJson::load_from_string(
+      package_json_path.clone(),
+      source,
+    )?),
+    Err(_) => None,
+  };
+  let subpath = format!(".{path}");
+
+  if let Some(exports) = package_json.as_ref().and_then(|p| p.exports.as_ref())
+  {
+    let maybe_target = match exports.get(&subpath) {
+      Some(target) => resolve_exports_target(target).map(ToOwned::to_owned),
+      // subpath patterns like `"./*": "./src/*.ts"`
+      None => exports.iter().find_map(|(key, target)| {
+        let (prefix, suffix) = key.split_once('*')?;
+        let matched = subpath.strip_prefix(prefix)?.strip_suffix(suffix)?;
+        let target = resolve_exports_target(target)?;
+        Some(target.replace('*', matched))
+      }),
+    };
+    return match maybe_target {
+      Some(target) => Ok(package_dir.join(target)),
+      None => bail!(
+        "Package subpath '{}' is not defined by \"exports\" in {}",
+        subpath,
+        package_json_path.display()
+      ),
+    };
+  }
+
+  if subpath != "." {
+    return Ok(package_dir.join(&subpath[2..]));
+  }
+  if let Some(main) = package_json
+    .as_ref()
+    .and_then(|p| p.main(NodeModuleKind::Esm))
+  {
+    return Ok(package_dir.join(main));
+  }
+  ["index.ts", "index.js", "index.mjs", "mod.ts"]
+    .iter()
+    .map(|file_name| package_dir.join(file_name))
+    .find(|path| path.is_file())
+    .ok_or_else(|| {
+      anyhow!(
+        "Could not find the entrypoint of the local package at {}",
+        package_dir.display()
+      )
+    })
+}
+
+/// Gets the path of an `exports` target, preferring the conditions for
+/// Deno and for ES modules.
+fn resolve_exports_target(target: &Value) -> Option<&str> {
+  match target {
+    Value::String(target) => Some(target),
+    Value::Array(targets) => targets.iter().find_map(resolve_exports_target),
+    Value::Object(conditions) => ["deno", "import", "module", "default"]
+      .iter()
+      .find_map(|condition| conditions.get(*condition))
+      .and_then(resolve_exports_target),
+    _ => None,
+  }
+}
+
 impl NpmResolver for CliGraphResolver {
   fn resolve_builtin_node_module(
     &self,
@@ -307,4 +441,73 @@ mod test {
     // non-existent bare specifier
     assert_eq!(resolve("non-existent", &deps).unwrap(), None);
   }
+
+  #[test]
+  fn test_resolve_local_package() {
+    let temp_dir = test_util::TempDir::new();
+    for dir in ["a", "b", "c"] {
+      temp_dir.create_dir_all(dir);
+    }
+    temp_dir.write(
+      "a/package.json",
+      r#"{ "name": "a", "exports": { ".": { "import": "./mod.ts" }, "./utils/*": "./src/utils/*.ts" } }"#,
+    );
+    temp_dir.write(
+      "b/package.json",
+      r#"{ "name": "b", "main": "lib/main.js" }"#,
+    );
+    temp_dir.write("c/index.ts", "");
+    let local_packages = ["a", "b", "c"]
+      .into_iter()
+      .map(|name| {
+        let package = LocalPackage {
+          dir: temp_dir.path().join(name),
+          deps: Default::default(),
+        };
+        (name.to_string(), package)
+      })
+      .collect::<LocalPackages>();
+    let resolve = |specifier: &str| {
+      resolve_local_package(specifier, &local_packages)
+        .map(|s| s.map(|s| s.to_file_path().unwrap()))
+        .map_err(|err| err.to_string())
+    };
+
+    assert_eq!(
+      resolve("a").unwrap(),
+      Some(temp_dir.path().join("a/mod.ts"))
+    );
+    assert_eq!(
+      resolve("a/utils/path").unwrap(),
+      Some(temp_dir.path().join("a/src/utils/path.ts"))
+    );
+    assert!(resolve("a/other.ts")
+      .unwrap_err()
+      .starts_with("Package subpath './other.ts' is not defined"));
+    assert_eq!(
+      resolve("b").unwrap(),
+      Some(temp_dir.path().join("b/lib/main.js"))
+    );
+    assert_eq!(
+      resolve("b/other.js").unwrap(),
+      Some(temp_dir.path().join("b/other.js"))
+    );
+    assert_eq!(
+      resolve("c").unwrap(),
+      Some(temp_dir.path().join("c/index.ts"))
+    );
+    assert_eq!(resolve("ab").unwrap(), None);
+
+    let referrer =
+      ModuleSpecifier::from_file_path(temp_dir.path().join("b/lib/main.js"))
+        .unwrap();
+    assert_eq!(
+      local_package_of_referrer(&referrer, &local_packages)
+        .map(|package| package.dir.clone()),
+      Some(temp_dir.path().join("b"))
+    );
+    let referrer =
+      ModuleSpecifier::from_file_path(temp_dir.path().join("main.ts")).unwrap();
+    assert!(local_package_of_referrer(&referrer, &local_packages).is_none());
+  }
 }
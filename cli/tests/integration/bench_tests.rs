@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
+use deno_core::serde_json::json;
 use deno_core::url::Url;
 use test_util as util;
 use util::assert_contains;
@@ -181,6 +183,12 @@ itest!(bench_with_malformed_config {
   output: "bench/collect_with_malformed_config.out",
 });
 
+itest!(metrics {
+  args: "bench --quiet --metrics bench/metrics.ts",
+  exit_code: 0,
+  output: "bench/metrics.out",
+});
+
 itest!(json_output {
   args: "bench --json bench/pass.ts",
   exit_code: 0,
@@ -215,6 +223,57 @@ fn file_protocol() {
     .assert_matches_file("bench/file_protocol.out");
 }
 
+#[test]
+fn baseline() {
+  let context = TestContext::default();
+  let baseline_path = context
+    .deno_dir()
+    .path()
+    .join("bench_baselines")
+    .join("main.json");
+
+  let output = context
+    .new_command()
+    .args("bench --baseline=main bench/baseline.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Baseline \"main\" not found");
+
+  let output = context
+    .new_command()
+    .args("bench --save-baseline=main bench/baseline.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Saved baseline to");
+  assert!(baseline_path.exists());
+
+  let output = context
+    .new_command()
+    .args("bench --baseline=main bench/baseline.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "vs baseline");
+
+  // make the saved bench impossibly fast so that the next run regresses
+  let mut baseline: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&baseline_path).unwrap())
+      .unwrap();
+  let stats = &mut baseline["benches"][0]["stats"];
+  stats["avg"] = json!(0.001);
+  stats["stdDev"] = json!(0.0);
+  std::fs::write(&baseline_path, baseline.to_string()).unwrap();
+
+  let output = context
+    .new_command()
+    .args("bench --baseline=main --regression-threshold=10 bench/baseline.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "regressed by more than 10% compared to the baseline: noop"
+  );
+}
+
 itest!(package_json_basic {
   args: "bench",
   output: "package_json/basic/lib.bench.out",
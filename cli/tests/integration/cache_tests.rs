@@ -107,3 +107,102 @@ itest!(package_json_basic {
   copy_temp_dir: Some("package_json/basic"),
   exit_code: 0,
 });
+
+#[test]
+fn export_import() {
+  use std::process::Stdio;
+  use test_util as util;
+  use test_util::TempDir;
+
+  let _server = util::http_server();
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "main.ts",
+    r#"import { returnsHi } from "http://localhost:4545/subdir/mod1.ts";
+import { getValue } from "npm:@denotest/esm-basic";
+console.log(returnsHi(), getValue());
+"#,
+  );
+
+  let online_deno_dir = util::new_deno_dir();
+  let output = util::deno_cmd_with_deno_dir(&online_deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("cache")
+    .arg("--lock=deno.lock")
+    .arg("--lock-write")
+    .arg("--export=deps.tar.gz")
+    .arg("main.ts")
+    .env("NO_COLOR", "1")
+    .envs(util::env_vars_for_npm_tests())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  util::assert_contains!(
+    stderr,
+    "Exported 3 remote modules and 1 npm packages"
+  );
+  assert!(output.status.success());
+
+  // a fresh cache is populated from the archive, without the server
+  let offline_deno_dir = util::new_deno_dir();
+  let output = util::deno_cmd_with_deno_dir(&offline_deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("cache")
+    .arg("--lock=deno.lock")
+    .arg("--import=deps.tar.gz")
+    .env("NO_COLOR", "1")
+    .envs(util::env_vars_for_npm_tests())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  util::assert_contains!(
+    stderr,
+    "Imported 3 remote modules and 1 npm packages"
+  );
+  assert!(output.status.success());
+
+  let output = util::deno_cmd_with_deno_dir(&offline_deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("run")
+    .arg("--cached-only")
+    .arg("--lock=deno.lock")
+    .arg("main.ts")
+    .envs(util::env_vars_for_npm_tests())
+    .stdout(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert_eq!(String::from_utf8_lossy(&output.stdout), "Hi 0\n");
+  assert!(output.status.success());
+
+  // the archive is rejected when it doesn't match the lockfile
+  let lockfile = std::fs::read_to_string(temp_dir.path().join("deno.lock"))
+    .unwrap()
+    .replace("\"integrity\": \"sha512-", "\"integrity\": \"sha512-AAAA");
+  temp_dir.write("deno.lock", lockfile);
+  let output = util::deno_cmd_with_deno_dir(&util::new_deno_dir())
+    .current_dir(temp_dir.path())
+    .arg("cache")
+    .arg("--lock=deno.lock")
+    .arg("--import=deps.tar.gz")
+    .env("NO_COLOR", "1")
+    .envs(util::env_vars_for_npm_tests())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  util::assert_contains!(
+    stderr,
+    "Integrity check failed for npm package \"@denotest/esm-basic@1.0.0\""
+  );
+  assert!(!output.status.success());
+}
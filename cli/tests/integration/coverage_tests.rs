@@ -24,6 +24,202 @@ fn no_snaps() {
   no_snaps_included("no_snaps_included", "ts");
 }
 
+#[test]
+fn tests_covering() {
+  let deno_dir = TempDir::new();
+  let tempdir = TempDir::new();
+  let tempdir = tempdir.path().join("cov");
+
+  let status = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(util::testdata_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg(format!("--coverage={}", tempdir.to_str().unwrap()))
+    .arg("--coverage-per-test")
+    .arg("coverage/per_test_test.ts")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::inherit())
+    .status()
+    .unwrap();
+
+  assert!(status.success());
+
+  let run_coverage = |line: &str| {
+    let output = util::deno_cmd_with_deno_dir(&deno_dir)
+      .current_dir(util::testdata_path())
+      .arg("coverage")
+      .arg(format!("--tests-covering=coverage/branch.ts:{line}"))
+      .arg(format!("{}/", tempdir.to_str().unwrap()))
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::inherit())
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+    util::strip_ansi_codes(std::str::from_utf8(&output.stdout).unwrap())
+      .to_string()
+  };
+
+  let stdout = run_coverage("3");
+  assert!(stdout.contains("Tests covering coverage/branch.ts:3"));
+  assert!(stdout.contains("branch taken => coverage/per_test_test.ts:3:"));
+  assert!(!stdout.contains("branch not taken"));
+
+  let stdout = run_coverage("5");
+  assert!(stdout.contains("branch not taken => coverage/per_test_test.ts:7:"));
+  assert!(!stdout.contains("branch taken =>"));
+
+  let stdout = run_coverage("10");
+  assert!(stdout.contains("No tests cover coverage/branch.ts:10"));
+}
+
+#[test]
+fn thresholds() {
+  let deno_dir = TempDir::new();
+  let tempdir = TempDir::new();
+  let tempdir = tempdir.path().join("cov");
+
+  let status = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(util::testdata_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg(format!("--coverage={}", tempdir.to_str().unwrap()))
+    .arg("coverage/branch_test.ts")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::inherit())
+    .status()
+    .unwrap();
+
+  assert!(status.success());
+
+  let run_coverage = |thresholds: &[&str]| {
+    util::deno_cmd_with_deno_dir(&deno_dir)
+      .current_dir(util::testdata_path())
+      .arg("coverage")
+      .args(thresholds)
+      .arg(format!("{}/", tempdir.to_str().unwrap()))
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::piped())
+      .output()
+      .unwrap()
+  };
+
+  let output = run_coverage(&["--threshold-lines=20"]);
+  assert!(output.status.success());
+  assert!(output.stderr.is_empty());
+
+  let output = run_coverage(&["--threshold-lines=50"]);
+  assert!(!output.status.success());
+  let stderr =
+    util::strip_ansi_codes(std::str::from_utf8(&output.stderr).unwrap())
+      .to_string();
+  assert!(stderr.contains("coverage/branch.ts: lines 28.571% (threshold 50%)"));
+  assert!(stderr.contains("error: Coverage thresholds not met"));
+
+  // the last matching threshold wins
+  let output = run_coverage(&[
+    "--threshold-lines=50",
+    "--threshold-lines=coverage/*.ts:20",
+  ]);
+  assert!(output.status.success());
+}
+
+#[test]
+fn html_report() {
+  let deno_dir = TempDir::new();
+  let tempdir = TempDir::new();
+  let cov_dir = tempdir.path().join("cov");
+  let html_dir = tempdir.path().join("html");
+
+  let status = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(util::testdata_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg(format!("--coverage={}", cov_dir.to_str().unwrap()))
+    .arg("coverage/branch_test.ts")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::inherit())
+    .status()
+    .unwrap();
+
+  assert!(status.success());
+
+  let output = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(util::testdata_path())
+    .arg("coverage")
+    .arg(format!("--html={}", html_dir.to_str().unwrap()))
+    .arg(format!("{}/", cov_dir.to_str().unwrap()))
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .output()
+    .unwrap();
+
+  assert!(output.status.success());
+  assert!(output.stdout.is_empty());
+
+  let index = fs::read_to_string(html_dir.join("index.html")).unwrap();
+  assert!(index.contains("<a href=\"coverage/index.html\">coverage/</a>"));
+  assert!(index.contains("28.57% (4/14)"));
+
+  let dir_index =
+    fs::read_to_string(html_dir.join("coverage/index.html")).unwrap();
+  assert!(dir_index.contains("<a href=\"branch.ts.html\">branch.ts</a>"));
+
+  let page =
+    fs::read_to_string(html_dir.join("coverage/branch.ts.html")).unwrap();
+  assert!(page.contains(
+    "<tr class=\"uncovered\" id=\"L9\"><td class=\"line-number\"><a href=\"#L9\">9</a></td><td class=\"hits\">0</td><td>export function unused(condition: boolean): boolean {</td></tr>"
+  ));
+}
+
+#[test]
+fn formats() {
+  let deno_dir = TempDir::new();
+  let tempdir = TempDir::new();
+  let tempdir = tempdir.path().join("cov");
+
+  let status = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(util::testdata_path())
+    .arg("test")
+    .arg("--quiet")
+    .arg(format!("--coverage={}", tempdir.to_str().unwrap()))
+    .arg("coverage/branch_test.ts")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::inherit())
+    .status()
+    .unwrap();
+
+  assert!(status.success());
+
+  for (format, expected_file) in [
+    ("json", "coverage/branch_expected.json"),
+    ("cobertura", "coverage/branch_expected.cobertura.xml"),
+  ] {
+    let output = util::deno_cmd_with_deno_dir(&deno_dir)
+      .current_dir(util::testdata_path())
+      .arg("coverage")
+      .arg(format!("--format={format}"))
+      .arg(format!("{}/", tempdir.to_str().unwrap()))
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::piped())
+      .output()
+      .unwrap();
+
+    assert!(output.stderr.is_empty());
+
+    let actual = std::str::from_utf8(&output.stdout).unwrap().to_string();
+    let expected =
+      fs::read_to_string(util::testdata_path().join(expected_file)).unwrap();
+
+    if !util::wildcard_match(&expected, &actual) {
+      println!("OUTPUT\n{actual}\nOUTPUT");
+      println!("EXPECTED\n{expected}\nEXPECTED");
+      panic!("pattern match failed");
+    }
+
+    assert!(output.status.success());
+  }
+}
+
 #[test]
 fn error_if_invalid_cache() {
   let deno_dir = TempDir::new();
@@ -212,6 +212,53 @@ itest!(fmt_stdin_json {
   output_str: Some("{ \"key\": \"value\" }\n"),
 });
 
+itest!(fmt_stdin_css {
+  args: "fmt --unstable --ext=css -",
+  input: Some("a{color:red;  }"),
+  output_str: Some("a {\n  color: red;\n}\n"),
+});
+
+itest!(fmt_stdin_css_requires_unstable {
+  args: "fmt --ext=css -",
+  input: Some("a{color:red;  }"),
+  output_str: Some("error: Formatting CSS, YAML and HTML is unstable and requires the --unstable flag\n"),
+  exit_code: 1,
+});
+
+itest!(fmt_stdin_yaml {
+  args: "fmt --unstable --ext=yaml -",
+  input: Some("key:     value\nlist:\n    - 1\n"),
+  output_str: Some("key: value\nlist:\n  - 1\n"),
+});
+
+itest!(fmt_stdin_markdown_css_yaml {
+  args: "fmt --unstable --ext=md -",
+  input: Some("```css\na{color:red}\n```\n\n```yaml\nkey:     value\n```\n"),
+  output_str: Some(
+    "```css\na {\n  color: red;\n}\n```\n\n```yaml\nkey: value\n```\n"
+  ),
+});
+
+itest!(fmt_stdin_markdown_css_yaml_stable {
+  args: "fmt --ext=md -",
+  input: Some("```css\na{color:red}\n```\n\n```yaml\nkey:     value\n```\n"),
+  output_str: Some(
+    "```css\na{color:red}\n```\n\n```yaml\nkey:     value\n```\n"
+  ),
+});
+
+itest!(fmt_check_css_yaml_html {
+  args: "fmt --unstable --check fmt/css_yaml_html/",
+  output_str: Some("Checked 3 files\n"),
+  exit_code: 0,
+});
+
+itest!(fmt_check_css_yaml_html_stable {
+  args: "fmt --check fmt/css_yaml_html/",
+  output_str: Some("error: No target files found.\n"),
+  exit_code: 1,
+});
+
 itest!(fmt_stdin_check_formatted {
   args: "fmt --check -",
   input: Some("const a = 1;\n"),
@@ -1,5 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use test_util::assert_contains;
+use test_util::TestContextBuilder;
+
 itest!(ignore_unexplicit_files {
   args: "lint --unstable --ignore=./",
   output_str: Some("error: No target files found.\n"),
@@ -32,6 +35,18 @@ itest!(compact {
     exit_code: 1,
 });
 
+itest!(github {
+  args: "lint --reporter=github lint/without_config/file1.js",
+  output: "lint/expected_github.out",
+  exit_code: 1,
+});
+
+itest!(sarif {
+  args: "lint --reporter=sarif lint/without_config/file1.js",
+  output: "lint/expected_sarif.out",
+  exit_code: 1,
+});
+
 itest!(ignore {
   args:
     "lint --ignore=lint/without_config/file1.js,lint/without_config/malformed.js,lint/without_config/lint_with_config/ lint/without_config/",
@@ -103,6 +118,34 @@ itest!(lint_with_config_without_tags {
   exit_code: 1,
 });
 
+itest!(lint_with_plugins {
+  args: "lint --config lint/plugins/deno.json lint/plugins/main.ts",
+  output: "lint/plugins/main.out",
+  exit_code: 1,
+});
+
+itest!(lint_with_plugins_ignore {
+  args: "lint --config lint/plugins/deno.json lint/plugins/ignore.ts",
+  output: "lint/plugins/ignore.out",
+  exit_code: 1,
+});
+
+itest!(lint_with_plugins_ignore_file {
+  args: "lint --config lint/plugins/deno.json lint/plugins/ignore_file.ts",
+  output: "lint/plugins/ignore_file.out",
+});
+
+itest!(lint_with_plugins_not_recommended {
+  args: "lint --config lint/plugins/deno.json lint/plugins/alert.ts",
+  output: "lint/plugins/alert.out",
+});
+
+itest!(lint_with_plugins_include {
+  args: "lint --config lint/plugins/deno.json --rules-include=team/no-alert lint/plugins/alert.ts",
+  output: "lint/plugins/alert_included.out",
+  exit_code: 1,
+});
+
 itest!(lint_with_malformed_config {
   args: "lint --config lint/Deno.malformed.jsonc",
   output: "lint/with_malformed_config.out",
@@ -114,3 +157,52 @@ itest!(lint_with_malformed_config2 {
   output: "lint/with_malformed_config2.out",
   exit_code: 1,
 });
+
+#[test]
+fn fix() {
+  let context = TestContextBuilder::new()
+    .use_copy_temp_dir("lint/fix")
+    .cwd("lint/fix")
+    .build();
+  let file_path = context.testdata_path().join("lint/fix/fix.ts");
+  let original_text = std::fs::read_to_string(&file_path).unwrap();
+
+  let output = context
+    .new_command()
+    .args("lint --fix-dry-run fix.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Would fix 3 problems");
+  assert_contains!(output.combined_output(), "(no-debugger)");
+  assert_eq!(std::fs::read_to_string(&file_path).unwrap(), original_text);
+
+  let output = context.new_command().args("lint --fix fix.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Fixed 3 problems");
+  assert_contains!(output.combined_output(), "Found 1 problem");
+  assert_eq!(
+    std::fs::read_to_string(&file_path).unwrap(),
+    "const a = 1;\nconst b = 2;\nconsole.log(a, b);\ndebugger;\n"
+  );
+
+  let output = context.new_command().args("lint --fix fix.ts").run();
+  output.assert_exit_code(1);
+  assert!(!output.combined_output().contains("Fixed"));
+}
+
+#[test]
+fn fix_dry_run_only_fixable_problems() {
+  let context = TestContextBuilder::new()
+    .use_copy_temp_dir("lint/fix")
+    .cwd("lint/fix")
+    .build();
+
+  // the problems are only fixed in the output, so the run still fails
+  let output = context
+    .new_command()
+    .args("lint --fix-dry-run fixable.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Would fix 2 problems");
+  assert_contains!(output.combined_output(), "Found 2 problems");
+}
@@ -6749,6 +6749,59 @@ fn lsp_format_mbc() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_format_range() {
+  let mut client = LspClientBuilder::new().build();
+  client.initialize_default();
+  did_open(
+    &mut client,
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts",
+        "languageId": "typescript",
+        "version": 1,
+        "text": "const a = 'a'\nconsole.log('b')\n"
+      }
+    }),
+  );
+  let (maybe_res, maybe_err) = client
+    .write_request(
+      "textDocument/rangeFormatting",
+      json!({
+        "textDocument": {
+          "uri": "file:///a/file.ts"
+        },
+        "range": {
+          "start": { "line": 1, "character": 0 },
+          "end": { "line": 1, "character": 16 }
+        },
+        "options": {
+          "tabSize": 2,
+          "insertSpaces": true
+        }
+      }),
+    )
+    .unwrap();
+  assert!(maybe_err.is_none());
+  assert_eq!(
+    maybe_res,
+    Some(json!([{
+      "range": {
+        "start": { "line": 1, "character": 12 },
+        "end": { "line": 1, "character": 13 }
+      },
+      "newText": "\""
+    }, {
+      "range": {
+        "start": { "line": 1, "character": 14 },
+        "end": { "line": 1, "character": 16 }
+      },
+      "newText": "\");"
+    }]))
+  );
+  client.shutdown();
+}
+
 #[test]
 fn lsp_format_exclude_with_config() {
   let context = TestContextBuilder::new().build();
@@ -727,6 +727,50 @@ itest!(node_modules_dir_require_added_node_modules_folder {
   temp_cwd: true,
 });
 
+itest!(node_modules_dir_lifecycle_scripts {
+  args: "run --allow-read --node-modules-dir --allow-scripts=@denotest/postinstall $TESTDATA/npm/lifecycle_scripts/main.js",
+  output: "npm/lifecycle_scripts/main.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  temp_cwd: true,
+});
+
+itest!(node_modules_dir_lifecycle_scripts_not_allowed {
+  args: "run --allow-read --node-modules-dir $TESTDATA/npm/lifecycle_scripts/main.js",
+  output: "npm/lifecycle_scripts/not_allowed.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  temp_cwd: true,
+});
+
+// the script spawns a shell script of the package
+#[cfg(unix)]
+itest!(node_modules_dir_lifecycle_scripts_run_binary {
+  args: "run --allow-read --node-modules-dir --allow-scripts=@denotest/postinstall-run $TESTDATA/npm/lifecycle_scripts/run_binary.js",
+  output: "npm/lifecycle_scripts/run_binary.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  temp_cwd: true,
+});
+
+itest!(node_modules_dir_lifecycle_scripts_command_not_allowed {
+  args: "run --allow-read --node-modules-dir --allow-scripts=@denotest/postinstall-command $TESTDATA/npm/lifecycle_scripts/command.js",
+  output: "npm/lifecycle_scripts/command_not_allowed.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  exit_code: 1,
+  temp_cwd: true,
+});
+
+#[cfg(unix)]
+itest!(node_modules_dir_lifecycle_scripts_command {
+  args: "run --allow-read --node-modules-dir --allow-scripts=@denotest/postinstall-command:sh $TESTDATA/npm/lifecycle_scripts/command.js",
+  output: "npm/lifecycle_scripts/command.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  temp_cwd: true,
+});
+
 itest!(node_modules_dir_with_deps {
   args: "run --allow-read --allow-env --node-modules-dir $TESTDATA/npm/cjs_with_deps/main.js",
   output: "npm/cjs_with_deps/main.out",
@@ -1585,3 +1629,90 @@ itest!(node_modules_import_check {
   copy_temp_dir: Some("npm/node_modules_import/"),
   exit_code: 1,
 });
+
+#[test]
+fn outdated_and_update_use_locked_version() {
+  let _server = http_server();
+
+  let deno_dir = util::new_deno_dir();
+  let temp_dir = util::TempDir::new();
+  temp_dir.write("deno.json", "{}");
+  temp_dir.write(
+    "main.ts",
+    "import version from 'npm:@denotest/outdated@1.0.0';\nconsole.log(version);\n",
+  );
+
+  // lock version 1.0.0
+  let output = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("cache")
+    .arg("main.ts")
+    .envs(env_vars_for_npm_tests())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+
+  // loosen the requirement the same way as the lockfile, so it's like
+  // 1.1.0 was published after locking
+  temp_dir.write(
+    "main.ts",
+    "import version from 'npm:@denotest/outdated@^1.0.0';\nconsole.log(version);\n",
+  );
+  let lockfile = std::fs::read_to_string(temp_dir.path().join("deno.lock"))
+    .unwrap()
+    .replace(
+      "\"@denotest/outdated@1.0.0\": \"@denotest/outdated@1.0.0\"",
+      "\"@denotest/outdated@^1.0.0\": \"@denotest/outdated@1.0.0\"",
+    );
+  temp_dir.write("deno.lock", lockfile);
+
+  let output = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("outdated")
+    .envs(env_vars_for_npm_tests())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stdout).unwrap(),
+    concat!(
+      "Dependency              Current  Compatible  Latest\n",
+      "npm:@denotest/outdated  1.0.0    1.1.0       2.0.0\n",
+    )
+  );
+
+  let output = util::deno_cmd_with_deno_dir(&deno_dir)
+    .current_dir(temp_dir.path())
+    .arg("update")
+    .envs(env_vars_for_npm_tests())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_contains!(
+    String::from_utf8(output.stderr).unwrap(),
+    "Update npm:@denotest/outdated ^1.0.0 -> ^1.1.0"
+  );
+  assert_eq!(
+    std::fs::read_to_string(temp_dir.path().join("main.ts")).unwrap(),
+    "import version from 'npm:@denotest/outdated@^1.1.0';\nconsole.log(version);\n",
+  );
+  let lockfile =
+    std::fs::read_to_string(temp_dir.path().join("deno.lock")).unwrap();
+  assert_contains!(
+    lockfile,
+    "\"@denotest/outdated@^1.1.0\": \"@denotest/outdated@1.1.0\""
+  );
+  assert!(!lockfile.contains("\"@denotest/outdated@1.0.0\""));
+}
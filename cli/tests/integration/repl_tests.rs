@@ -800,6 +800,41 @@ fn eval_file_flag_multiple_files() {
   assert_contains!(err, "Download");
 }
 
+#[test]
+fn dot_commands() {
+  let temp_dir = TempDir::new();
+  let session_path = temp_dir.path().join("session.ts");
+  let save_command = format!(".save {}", session_path.display());
+  let (out, err) = util::run_and_collect_output(
+    true,
+    "repl",
+    Some(vec![
+      ".load ./run/001_hello.js",
+      "const a: number = 40 + 2;",
+      "a",
+      &save_command,
+      ".clear",
+      "typeof a",
+      ".foo",
+      ".help",
+    ]),
+    Some(vec![("NO_COLOR".to_owned(), "1".to_owned())]),
+    false,
+  );
+  assert_contains!(out, "Hello World");
+  assert_contains!(out, "42");
+  assert_contains!(out, "Session saved to:");
+  assert_contains!(out, "Clearing context...");
+  assert_contains!(out, "\"undefined\"");
+  assert_contains!(out, "Invalid REPL keyword: .foo");
+  assert_contains!(out, ".editor    Enter editor mode");
+  assert!(err.is_empty());
+
+  let session = std::fs::read_to_string(session_path).unwrap();
+  assert_contains!(session, "console.log(\"Hello World\");");
+  assert_contains!(session, "const a: number = 40 + 2;\na\n");
+}
+
 #[test]
 fn pty_clear_function() {
   util::with_pty(&["repl"], |mut console| {
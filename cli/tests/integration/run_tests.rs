@@ -2828,6 +2828,13 @@ itest!(package_json_auto_discovered_no_package_json_imports {
   copy_temp_dir: Some("run/with_package_json/no_deno_json"),
 });
 
+itest!(package_json_workspace_cjs {
+  args: "run --quiet main.ts",
+  output: "package_json/workspace_cjs/main.out",
+  cwd: Some("package_json/workspace_cjs/"),
+  copy_temp_dir: Some("package_json/workspace_cjs/"),
+});
+
 itest!(package_json_with_deno_json {
   args: "run --quiet -A main.ts",
   output: "package_json/deno_json/main.out",
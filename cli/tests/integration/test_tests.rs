@@ -166,6 +166,51 @@ itest!(quiet {
   output: "test/quiet.out",
 });
 
+itest!(retries {
+  args: "test --quiet --retries=2 test/retries.ts",
+  exit_code: 1,
+  output: "test/retries.out",
+});
+
+itest!(retries_steps {
+  args: "test --quiet --retries=1 test/retries_steps.ts",
+  output: "test/retries_steps.out",
+});
+
+itest!(retries_steps_json {
+  args: "test --quiet --json --retries=1 test/retries_steps.ts",
+  output: "test/retries_steps_json.out",
+});
+
+itest!(timeout {
+  args: "test --quiet --timeout=100 test/timeout.ts",
+  exit_code: 1,
+  output: "test/timeout.out",
+});
+
+itest!(timeout_blocking {
+  args: "test --quiet test/timeout_blocking.ts",
+  exit_code: 1,
+  output: "test/timeout_blocking.out",
+});
+
+// The timings were recorded with the modules in another directory.
+itest!(shard_with_timings_1 {
+  args:
+    "test --quiet --shard=1/2 --shard-timings=timings.jsonl a.ts b.ts c.ts d.ts",
+  exit_code: 0,
+  output: "test/shard/shard1.out",
+  cwd: Some("test/shard"),
+});
+
+itest!(shard_with_timings_2 {
+  args:
+    "test --quiet --shard=2/2 --shard-timings=timings.jsonl a.ts b.ts c.ts d.ts",
+  exit_code: 0,
+  output: "test/shard/shard2.out",
+  cwd: Some("test/shard"),
+});
+
 itest!(fail_fast {
   args: "test --fail-fast test/fail_fast.ts",
   exit_code: 1,
@@ -335,6 +380,30 @@ itest!(steps_output_within {
   output: "test/steps/output_within.out",
 });
 
+itest!(reporter_dot {
+  args: "test --reporter=dot test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/dot.out",
+});
+
+itest!(reporter_junit {
+  args: "test --reporter=junit test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/junit.out",
+});
+
+itest!(reporter_tap {
+  args: "test --reporter=tap test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/tap.out",
+});
+
+itest!(json {
+  args: "test --json test/reporters/reporters.ts",
+  exit_code: 1,
+  output: "test/reporters/json.out",
+});
+
 itest!(no_prompt_by_default {
   args: "test --quiet test/no_prompt_by_default.ts",
   exit_code: 1,
@@ -463,3 +532,71 @@ itest!(package_json_basic {
   copy_temp_dir: Some("package_json/basic"),
   exit_code: 0,
 });
+
+#[test]
+fn changed() {
+  let temp_dir = util::TempDir::new();
+  temp_dir.write(
+    "add.ts",
+    "export const add = (a: number, b: number) => a + b;",
+  );
+  temp_dir.write(
+    "mul.ts",
+    "export const mul = (a: number, b: number) => a * b;",
+  );
+  temp_dir.write(
+    "add_test.ts",
+    "import { add } from './add.ts';\nDeno.test('add', () => { add(1, 2); });",
+  );
+  temp_dir.write(
+    "mul_test.ts",
+    "import { mul } from './mul.ts';\nDeno.test('mul', () => { mul(1, 2); });",
+  );
+  let git = |args: &[&str]| {
+    let status = std::process::Command::new("git")
+      .args(["-c", "user.name=deno", "-c", "user.email=deno@example.com"])
+      .args(args)
+      .current_dir(temp_dir.path())
+      .stdout(std::process::Stdio::null())
+      .status()
+      .unwrap();
+    assert!(status.success());
+  };
+  git(&["init", "-q"]);
+  git(&["add", "."]);
+  git(&["commit", "-q", "-m", "initial"]);
+
+  let run_changed = |arg: &str| {
+    let output = util::deno_cmd()
+      .current_dir(temp_dir.path())
+      .arg("test")
+      .arg(arg)
+      .env("NO_COLOR", "1")
+      .stdout(std::process::Stdio::piped())
+      .stderr(std::process::Stdio::piped())
+      .output()
+      .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    (stdout, stderr)
+  };
+
+  let (stdout, stderr) = run_changed("--changed");
+  assert!(stderr.contains("Running 0 of 2 test modules"));
+  assert!(!stdout.contains("add ..."));
+
+  temp_dir.write(
+    "add.ts",
+    "export const add = (a: number, b: number) => b + a;",
+  );
+  let (stdout, stderr) = run_changed("--changed");
+  assert!(stderr.contains("Running 1 of 2 test modules"));
+  assert!(stdout.contains("add ... ok"));
+  assert!(!stdout.contains("mul ..."));
+
+  git(&["commit", "-q", "-a", "-m", "change add"]);
+  let (stdout, _) = run_changed("--changed=HEAD~1");
+  assert!(stdout.contains("add ... ok"));
+  assert!(!stdout.contains("mul ..."));
+}
@@ -719,6 +719,52 @@ fn run_watch_load_unload_events() {
   check_alive_then_kill(child);
 }
 
+#[test]
+fn run_watch_hmr() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("file_to_watch.js");
+  let source = r#"
+    function getMessage() {
+      return "hello";
+    }
+    setInterval(() => {
+      console.log(getMessage());
+    }, 100);
+    addEventListener("hmr", (e) => {
+      console.log("hmr", e.detail.path.endsWith("file_to_watch.js"));
+    });
+  "#;
+  write(&file_to_watch, source).unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .arg("run")
+    .arg("--watch-hmr")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+
+  wait_contains("hello", &mut stdout_lines);
+  wait_for(
+    |m| m.contains("Watching paths") && m.contains("file_to_watch.js"),
+    &mut stderr_lines,
+  );
+
+  // only a function changes, so it's replaced without a restart
+  write(&file_to_watch, source.replace("\"hello\"", "\"world\"")).unwrap();
+
+  wait_contains("Replaced changed module", &mut stderr_lines);
+  wait_contains("hmr true", &mut stdout_lines);
+  wait_contains("world", &mut stdout_lines);
+  check_alive_then_kill(child);
+}
+
 /// Confirm that the watcher continues to work even if module resolution fails at the *first* attempt
 #[test]
 fn run_watch_not_exit() {
@@ -837,6 +883,46 @@ fn run_watch_error_messages() {
   check_alive_then_kill(child);
 }
 
+#[test]
+fn check_watch() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("file_to_watch.ts");
+  write(&file_to_watch, "const a: string = 1;\nconsole.log(a);").unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .arg("check")
+    .arg("--watch")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (_stdout_lines, mut stderr_lines) = child_lines(&mut child);
+
+  wait_contains("Check started", &mut stderr_lines);
+  wait_contains("TS2322", &mut stderr_lines);
+  wait_contains("Check finished", &mut stderr_lines);
+  wait_for(
+    |m| m.contains("Watching paths") && m.contains("file_to_watch.ts"),
+    &mut stderr_lines,
+  );
+
+  write(&file_to_watch, "const a: string = \"1\";\nconsole.log(a);").unwrap();
+  wait_contains("Restarting", &mut stderr_lines);
+  loop {
+    let line = stderr_lines.next().unwrap();
+    assert_not_contains!(line, "TS2322");
+    if line.contains("Check finished") {
+      break;
+    }
+  }
+  check_alive_then_kill(child);
+}
+
 #[test]
 fn test_watch() {
   let t = TempDir::new();
@@ -1223,3 +1309,69 @@ fn run_watch_dynamic_imports() {
 
   check_alive_then_kill(child);
 }
+
+#[test]
+fn task_watch_ignores_files_written_to_node_modules_and_git() {
+  let t = TempDir::new();
+  write(
+    t.path().join("deno.json"),
+    r#"{
+      "tasks": {
+        "build": "cat input.txt && mkdir -p node_modules/.cache .git && echo built > node_modules/.cache/out.txt && echo built > .git/out.txt"
+      }
+    }"#,
+  )
+  .unwrap();
+  let input = t.path().join("input.txt");
+  write(&input, "1\n").unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("task")
+    .arg("--watch=.")
+    .arg("build")
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+  wait_contains("Task started", &mut stderr_lines);
+  assert_eq!(stdout_lines.next().unwrap(), "1");
+  wait_contains("Task finished", &mut stderr_lines);
+
+  // the files written by the task must not restart it, so the next run
+  // is the one caused by the change of the input
+  std::thread::sleep(std::time::Duration::from_secs(1));
+  write(&input, "2\n").unwrap();
+  wait_contains("Restarting", &mut stderr_lines);
+  assert_eq!(stdout_lines.next().unwrap(), "2");
+  wait_contains("Task finished", &mut stderr_lines);
+  check_alive_then_kill(child);
+}
+
+#[test]
+fn task_watch_requires_paths() {
+  let t = TempDir::new();
+  write(
+    t.path().join("deno.json"),
+    r#"{ "tasks": { "build": "echo built" } }"#,
+  )
+  .unwrap();
+
+  let output = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("task")
+    .arg("--watch")
+    .arg("build")
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .output()
+    .unwrap();
+  assert!(!output.status.success());
+  assert_contains!(
+    String::from_utf8(output.stderr).unwrap(),
+    "Watching a task requires the paths of its inputs"
+  );
+}
@@ -0,0 +1,5 @@
+Deno.bench("noop", () => {});
+
+Deno.bench("memoryUsage", () => {
+  Deno.memoryUsage();
+});
@@ -0,0 +1,9 @@
+import { branch } from "./branch.ts";
+
+Deno.test("branch taken", function () {
+  branch(true);
+});
+
+Deno.test("branch not taken", function () {
+  branch(false);
+});
@@ -0,0 +1,4 @@
+let a = 1;;
+const b: number = 2;
+console.log(a, b);
+debugger;
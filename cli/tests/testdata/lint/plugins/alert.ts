@@ -0,0 +1 @@
+alert("hello");
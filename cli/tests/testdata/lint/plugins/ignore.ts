@@ -0,0 +1,3 @@
+// deno-lint-ignore team/no-console-log
+console.log("ignored");
+console.log("reported");
@@ -0,0 +1,2 @@
+// deno-lint-ignore-file team/no-console-log
+console.log("ignored");
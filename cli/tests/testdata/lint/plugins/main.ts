@@ -0,0 +1,2 @@
+const message = "hello";
+console.log(message);
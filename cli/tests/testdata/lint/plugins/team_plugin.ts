@@ -0,0 +1,44 @@
+// deno-lint-ignore-file no-explicit-any
+export default {
+  name: "team",
+  rules: {
+    "no-console-log": {
+      create(context: any) {
+        return {
+          CallExpression(node: any) {
+            const { callee } = node;
+            if (
+              callee.type === "MemberExpression" &&
+              callee.object.type === "Identifier" &&
+              callee.object.value === "console" &&
+              callee.property.type === "Identifier" &&
+              callee.property.value === "log"
+            ) {
+              context.report({
+                node,
+                message: "Use the team logger instead of console.log",
+                hint: "Import `logger` from \"./logger.ts\"",
+              });
+            }
+          },
+        };
+      },
+    },
+    "no-alert": {
+      tags: ["team-strict"],
+      create(context: any) {
+        return {
+          CallExpression(node: any) {
+            const { callee } = node;
+            if (callee.type === "Identifier" && callee.value === "alert") {
+              context.report({
+                node,
+                message: "Don't use alert",
+              });
+            }
+          },
+        };
+      },
+    },
+  },
+};
@@ -0,0 +1,3 @@
+import { getValue } from "npm:@denotest/postinstall";
+
+console.log(getValue());
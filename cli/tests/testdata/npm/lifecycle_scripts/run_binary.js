@@ -0,0 +1,3 @@
+import { getValue } from "npm:@denotest/postinstall-run";
+
+console.log(getValue());
@@ -0,0 +1,9 @@
+const fs = require("fs");
+const path = require("path");
+
+exports.getValue = () => {
+  const filePath = path.join(__dirname, "installed.txt");
+  return fs.existsSync(filePath)
+    ? fs.readFileSync(filePath, "utf8")
+    : "not installed";
+};
@@ -0,0 +1 @@
+echo "installed by sh" > installed.txt
@@ -0,0 +1,6 @@
+const childProcess = require("child_process");
+const fs = require("fs");
+const path = require("path");
+
+const output = childProcess.execFileSync(path.join(__dirname, "bin", "hello"));
+fs.writeFileSync(path.join(__dirname, "installed.txt"), output.toString());
@@ -0,0 +1,7 @@
+const fs = require("fs");
+const path = require("path");
+
+fs.writeFileSync(
+  path.join(__dirname, "installed.txt"),
+  `installed by ${process.env.npm_lifecycle_event}`,
+);
@@ -0,0 +1,3 @@
+import { sum } from "@denotest/calc";
+
+console.log(sum(1, 2, 3));
@@ -0,0 +1,3 @@
+const { add } = require("@denotest/add");
+
+module.exports.sum = (...values) => values.reduce(add, 0);
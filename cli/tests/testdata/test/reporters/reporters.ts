@@ -0,0 +1,11 @@
+Deno.test("passing test", () => {});
+Deno.test("failing test", () => {
+  throw new Error("Fail");
+});
+Deno.test({ name: "ignored test", ignore: true, fn() {} });
+Deno.test("test with steps", async (t) => {
+  await t.step("step 1", () => {});
+  await t.step("step 2", async (t) => {
+    await t.step("nested step", () => {});
+  });
+});
@@ -0,0 +1,29 @@
+let attempts = 0;
+Deno.test("flaky", () => {
+  attempts++;
+  if (attempts < 3) {
+    throw new Error(`attempt ${attempts} failed`);
+  }
+});
+
+Deno.test({
+  name: "always fails",
+  retries: 1,
+  fn() {
+    throw new Error("always fails");
+  },
+});
+
+let stepAttempts = 0;
+Deno.test("flaky step", async (t) => {
+  await t.step({
+    name: "step",
+    retries: 1,
+    fn() {
+      stepAttempts++;
+      if (stepAttempts < 2) {
+        throw new Error(`attempt ${stepAttempts} failed`);
+      }
+    },
+  });
+});
@@ -0,0 +1,10 @@
+let attempts = 0;
+Deno.test("flaky with steps", async (t) => {
+  attempts++;
+  await t.step("passes", () => {});
+  await t.step("fails on the first attempt", () => {
+    if (attempts < 2) {
+      throw new Error(`attempt ${attempts} failed`);
+    }
+  });
+});
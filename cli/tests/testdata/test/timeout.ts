@@ -0,0 +1,14 @@
+Deno.test({
+  name: "passes",
+  timeout: 10_000,
+  async fn() {
+    await new Promise((resolve) => setTimeout(resolve, 10));
+  },
+});
+
+Deno.test("times out", async () => {
+  await new Promise((resolve) => setTimeout(resolve, 60_000));
+});
+
+// The timed out test can't be resumed, so the rest of the module is cancelled.
+Deno.test("is cancelled", () => {});
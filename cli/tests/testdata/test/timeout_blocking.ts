@@ -0,0 +1,13 @@
+Deno.test("blocks the event loop", async (t) => {
+  await t.step({
+    name: "step",
+    timeout: 100,
+    fn() {
+      while (true) {
+        // never yields to the event loop
+      }
+    },
+  });
+});
+
+Deno.test("is cancelled", () => {});
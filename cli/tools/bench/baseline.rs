@@ -0,0 +1,238 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::BenchDescription;
+use super::BenchStats;
+use crate::cache::DenoDir;
+use crate::util::path::to_relative_path_or_remote_url;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Critical value of the two-sided test at a 95% confidence level. Benches
+/// run enough iterations for the t-distribution to be close to the normal
+/// distribution.
+const SIGNIFICANCE_CRITICAL_VALUE: f64 = 1.96;
+
+/// Results saved with `deno bench --save-baseline`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BenchBaseline {
+  benches: Vec<BaselineBench>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineBench {
+  /// The path of the module relative to the current directory, so that the
+  /// baseline can be compared to on another machine.
+  origin: String,
+  group: Option<String>,
+  name: String,
+  stats: BenchStats,
+}
+
+impl BaselineBench {
+  fn matches(&self, origin: &str, desc: &BenchDescription) -> bool {
+    self.origin == origin && self.group == desc.group && self.name == desc.name
+  }
+}
+
+/// Gets the path of a module relative to `cwd`, or its url for a remote
+/// module.
+fn baseline_origin(cwd: &ModuleSpecifier, origin: &str) -> String {
+  to_relative_path_or_remote_url(cwd, origin)
+}
+
+impl BenchBaseline {
+  /// Gets the path of the baseline with the given name.
+  pub fn path(deno_dir: &DenoDir, name: &str) -> PathBuf {
+    deno_dir
+      .bench_baselines_folder_path()
+      .join(format!("{name}.json"))
+  }
+
+  /// Reads a baseline, or returns `None` if it was never saved.
+  pub fn load(path: &Path) -> Result<Option<Self>, AnyError> {
+    let text = match std::fs::read_to_string(path) {
+      Ok(text) => text,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+        return Ok(None)
+      }
+      Err(err) => {
+        return Err(err).with_context(|| {
+          format!("Failed reading baseline: {}", path.display())
+        })
+      }
+    };
+    let baseline = serde_json::from_str(&text).with_context(|| {
+      format!("Failed parsing baseline: {}", path.display())
+    })?;
+    Ok(Some(baseline))
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), AnyError> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(self)?)
+      .with_context(|| format!("Failed writing baseline: {}", path.display()))
+  }
+
+  /// Gets the stats of a bench, whose module is looked up relative to `cwd`.
+  pub fn get(
+    &self,
+    cwd: &ModuleSpecifier,
+    desc: &BenchDescription,
+  ) -> Option<&BenchStats> {
+    let origin = baseline_origin(cwd, &desc.origin);
+    self
+      .benches
+      .iter()
+      .find(|bench| bench.matches(&origin, desc))
+      .map(|bench| &bench.stats)
+  }
+
+  /// Adds the stats of a bench, replacing the ones of a previous run.
+  pub fn insert(
+    &mut self,
+    cwd: &ModuleSpecifier,
+    desc: &BenchDescription,
+    stats: BenchStats,
+  ) {
+    let origin = baseline_origin(cwd, &desc.origin);
+    match self
+      .benches
+      .iter_mut()
+      .find(|bench| bench.matches(&origin, desc))
+    {
+      Some(bench) => bench.stats = stats,
+      None => self.benches.push(BaselineBench {
+        origin,
+        group: desc.group.clone(),
+        name: desc.name.clone(),
+        stats,
+      }),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchComparison {
+  /// Change of the average time compared to the baseline, in percent.
+  /// Positive values are regressions.
+  pub delta: f64,
+  /// Whether the difference of the averages is statistically significant
+  /// according to Welch's t-test.
+  pub significant: bool,
+}
+
+impl BenchComparison {
+  pub fn new(baseline: &BenchStats, stats: &BenchStats) -> Self {
+    let delta = if baseline.avg == 0.0 {
+      0.0
+    } else {
+      (stats.avg - baseline.avg) / baseline.avg * 100.0
+    };
+    let standard_error = (baseline.std_dev.powi(2) / baseline.n as f64
+      + stats.std_dev.powi(2) / stats.n as f64)
+      .sqrt();
+    let difference = (stats.avg - baseline.avg).abs();
+    let significant = if standard_error == 0.0 {
+      difference > 0.0
+    } else {
+      difference / standard_error > SIGNIFICANCE_CRITICAL_VALUE
+    };
+    Self { delta, significant }
+  }
+
+  /// Whether the bench is significantly slower than the baseline by more
+  /// than `threshold` percent.
+  pub fn is_regression(&self, threshold: u32) -> bool {
+    self.significant && self.delta > threshold as f64
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn stats(n: u64, avg: f64, std_dev: f64) -> BenchStats {
+    BenchStats {
+      n,
+      min: avg,
+      max: avg,
+      avg,
+      p75: avg,
+      p99: avg,
+      p995: avg,
+      p999: avg,
+      std_dev,
+      metrics: None,
+    }
+  }
+
+  fn desc(origin: &str, name: &str) -> BenchDescription {
+    BenchDescription {
+      id: 0,
+      name: name.to_string(),
+      origin: origin.to_string(),
+      baseline: false,
+      group: None,
+    }
+  }
+
+  #[test]
+  fn test_comparison() {
+    let comparison = BenchComparison::new(
+      &stats(1000, 100.0, 10.0),
+      &stats(1000, 120.0, 10.0),
+    );
+    assert_eq!(comparison.delta, 20.0);
+    assert!(comparison.significant);
+    assert!(comparison.is_regression(10));
+    assert!(!comparison.is_regression(20));
+
+    // the difference is within the noise
+    let comparison =
+      BenchComparison::new(&stats(10, 100.0, 50.0), &stats(10, 120.0, 50.0));
+    assert_eq!(comparison.delta, 20.0);
+    assert!(!comparison.significant);
+    assert!(!comparison.is_regression(10));
+
+    let comparison =
+      BenchComparison::new(&stats(1000, 100.0, 10.0), &stats(1000, 50.0, 10.0));
+    assert_eq!(comparison.delta, -50.0);
+    assert!(comparison.significant);
+    assert!(!comparison.is_regression(0));
+  }
+
+  #[test]
+  fn test_baseline_insert() {
+    let cwd = ModuleSpecifier::parse("file:///repo/").unwrap();
+    let origin = "file:///repo/a_bench.ts";
+    let mut baseline = BenchBaseline::default();
+    baseline.insert(&cwd, &desc(origin, "a"), stats(10, 1.0, 0.0));
+    baseline.insert(&cwd, &desc(origin, "b"), stats(10, 2.0, 0.0));
+    baseline.insert(&cwd, &desc(origin, "a"), stats(10, 3.0, 0.0));
+    assert_eq!(baseline.benches.len(), 2);
+    assert_eq!(baseline.benches[0].origin, "./a_bench.ts");
+    assert_eq!(baseline.get(&cwd, &desc(origin, "a")).unwrap().avg, 3.0);
+    assert_eq!(baseline.get(&cwd, &desc(origin, "b")).unwrap().avg, 2.0);
+    assert!(baseline.get(&cwd, &desc(origin, "c")).is_none());
+
+    // compared on another machine with the repository in another directory
+    let other_cwd = ModuleSpecifier::parse("file:///other/repo/").unwrap();
+    let other_origin = "file:///other/repo/a_bench.ts";
+    assert_eq!(
+      baseline
+        .get(&other_cwd, &desc(other_origin, "a"))
+        .unwrap()
+        .avg,
+      3.0
+    );
+  }
+}
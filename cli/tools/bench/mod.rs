@@ -1,17 +1,17 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::BenchFlags;
 use crate::args::BenchOptions;
 use crate::args::CliOptions;
-use crate::args::TypeCheckMode;
+use crate::args::Flags;
 use crate::colors;
+use crate::display::human_size;
 use crate::display::write_json_to_stdout;
-use crate::graph_util::graph_valid_with_cli_options;
 use crate::ops;
 use crate::proc_state::ProcState;
 use crate::tools::test::format_test_error;
 use crate::tools::test::TestFilter;
 use crate::util::file_watcher;
-use crate::util::file_watcher::ResolutionResult;
 use crate::util::fs::collect_specifiers;
 use crate::util::path::is_supported_ext;
 use crate::version::get_user_agent;
@@ -22,7 +22,6 @@ use deno_core::error::AnyError;
 use deno_core::error::JsError;
 use deno_core::futures::future;
 use deno_core::futures::stream;
-use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::ModuleSpecifier;
 use deno_runtime::permissions::Permissions;
@@ -32,18 +31,56 @@ use indexmap::IndexMap;
 use log::Level;
 use serde::Deserialize;
 use serde::Serialize;
-use std::cell::RefCell;
-use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 
+mod baseline;
+
+use baseline::BenchBaseline;
+use baseline::BenchComparison;
+
 #[derive(Debug, Clone)]
 struct BenchSpecifierOptions {
   filter: TestFilter,
   json: bool,
+  baseline: Option<Arc<BenchBaseline>>,
+  regression_threshold: Option<u32>,
+  save_baseline: Option<PathBuf>,
+}
+
+impl BenchSpecifierOptions {
+  fn resolve(
+    ps: &ProcState,
+    bench_options: &BenchOptions,
+  ) -> Result<Self, AnyError> {
+    let baseline = match &bench_options.baseline {
+      Some(name) => {
+        let path = BenchBaseline::path(&ps.dir, name);
+        match BenchBaseline::load(&path)? {
+          Some(baseline) => Some(Arc::new(baseline)),
+          None => {
+            return Err(generic_error(format!(
+              "Baseline \"{name}\" not found. Save it first with --save-baseline={name}"
+            )))
+          }
+        }
+      }
+      None => None,
+    };
+    Ok(Self {
+      filter: TestFilter::from_flag(&bench_options.filter),
+      json: bench_options.json,
+      baseline,
+      regression_threshold: bench_options.regression_threshold,
+      save_baseline: bench_options
+        .save_baseline
+        .as_ref()
+        .map(|name| BenchBaseline::path(&ps.dir, name)),
+    })
+  }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
@@ -99,6 +136,24 @@ pub struct BenchStats {
   pub p99: f64,
   pub p995: f64,
   pub p999: f64,
+  #[serde(rename = "stdDev")]
+  pub std_dev: f64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub metrics: Option<BenchMetrics>,
+}
+
+/// Recorded with `--metrics`, in iterations that are run after the timed ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchMetrics {
+  pub iterations: u64,
+  /// Average growth of the V8 heap per iteration, in bytes. Iterations during
+  /// which a garbage collection ran are left out.
+  pub allocated_bytes: f64,
+  /// Number of garbage collections that ran during the iterations.
+  pub gc_count: u64,
+  /// Average number of ops dispatched per iteration.
+  pub op_calls: f64,
 }
 
 impl BenchReport {
@@ -115,11 +170,12 @@ impl BenchReport {
 fn create_reporter(
   show_output: bool,
   json: bool,
+  baseline: Option<Arc<BenchBaseline>>,
 ) -> Box<dyn BenchReporter + Send> {
   if json {
     return Box::new(JsonReporter::new());
   }
-  Box::new(ConsoleReporter::new(show_output))
+  Box::new(ConsoleReporter::new(show_output, baseline))
 }
 
 pub trait BenchReporter {
@@ -215,12 +271,23 @@ struct ConsoleReporter {
   baseline: bool,
   group_measurements: Vec<(BenchDescription, BenchStats)>,
   options: Option<mitata::reporter::Options>,
+  /// Saved results passed with `--baseline` to compare the benches to.
+  saved_baseline: Option<Arc<BenchBaseline>>,
+  cwd: ModuleSpecifier,
 }
 
 impl ConsoleReporter {
-  fn new(show_output: bool) -> Self {
+  fn new(
+    show_output: bool,
+    saved_baseline: Option<Arc<BenchBaseline>>,
+  ) -> Self {
     Self {
       show_output,
+      saved_baseline,
+      cwd: ModuleSpecifier::from_directory_path(
+        std::env::current_dir().unwrap(),
+      )
+      .unwrap(),
       group: None,
       options: None,
       baseline: false,
@@ -346,6 +413,26 @@ impl BenchReporter for ConsoleReporter {
           )
         );
 
+        if let Some(saved_baseline) = &self.saved_baseline {
+          println!(
+            "{}",
+            format_comparison(saved_baseline.get(&self.cwd, &desc), stats)
+          );
+        }
+
+        if let Some(metrics) = &stats.metrics {
+          println!(
+            "{}",
+            colors::gray(format!(
+              "  heap: {}/iter | gc: {} in {} iter | ops: {:.1}/iter",
+              human_size(metrics.allocated_bytes),
+              metrics.gc_count,
+              metrics.iterations,
+              metrics.op_calls
+            ))
+          );
+        }
+
         self.group_measurements.push((desc, stats.clone()));
       }
 
@@ -409,6 +496,34 @@ impl BenchReporter for ConsoleReporter {
   }
 }
 
+fn format_comparison(
+  maybe_baseline_stats: Option<&BenchStats>,
+  stats: &BenchStats,
+) -> String {
+  let baseline_stats = match maybe_baseline_stats {
+    Some(baseline_stats) => baseline_stats,
+    None => return colors::gray("  not in baseline").to_string(),
+  };
+  let comparison = BenchComparison::new(baseline_stats, stats);
+  let delta = format!("{:+.2}%", comparison.delta);
+  let delta = if !comparison.significant {
+    delta
+  } else if comparison.delta > 0.0 {
+    colors::red(delta).to_string()
+  } else {
+    colors::green(delta).to_string()
+  };
+  format!(
+    "  {} {}",
+    delta,
+    colors::gray(if comparison.significant {
+      "vs baseline (significant)"
+    } else {
+      "vs baseline (not significant)"
+    })
+  )
+}
+
 /// Type check a collection of module and document specifiers.
 async fn check_specifiers(
   ps: &ProcState,
@@ -484,8 +599,11 @@ async fn bench_specifiers(
     tokio::task::spawn(async move {
       let mut used_only = false;
       let mut report = BenchReport::new();
-      let mut reporter =
-        create_reporter(log_level != Some(Level::Error), options.json);
+      let mut reporter = create_reporter(
+        log_level != Some(Level::Error),
+        options.json,
+        options.baseline.clone(),
+      );
       let mut benches = IndexMap::new();
 
       while let Some(event) = receiver.recv().await {
@@ -541,6 +659,40 @@ async fn bench_specifiers(
         return Err(generic_error("Bench failed"));
       }
 
+      let cwd =
+        ModuleSpecifier::from_directory_path(std::env::current_dir()?).unwrap();
+      if let (Some(baseline), Some(threshold)) =
+        (&options.baseline, options.regression_threshold)
+      {
+        let regressions = report
+          .measurements
+          .iter()
+          .filter_map(|(desc, stats)| {
+            let comparison =
+              BenchComparison::new(baseline.get(&cwd, desc)?, stats);
+            comparison
+              .is_regression(threshold)
+              .then(|| format!("{} ({:+.2}%)", desc.name, comparison.delta))
+          })
+          .collect::<Vec<_>>();
+        if !regressions.is_empty() {
+          return Err(generic_error(format!(
+            "Bench failed because the following benches regressed by more than {}% compared to the baseline: {}",
+            threshold,
+            regressions.join(", ")
+          )));
+        }
+      }
+
+      if let Some(path) = &options.save_baseline {
+        let mut baseline = BenchBaseline::load(path)?.unwrap_or_default();
+        for (desc, stats) in &report.measurements {
+          baseline.insert(&cwd, desc, stats.clone());
+        }
+        baseline.save(path)?;
+        log::info!("{} baseline to {}", colors::green("Saved"), path.display());
+      }
+
       Ok(())
     })
   };
@@ -588,184 +740,60 @@ pub async fn run_benchmarks(
     return Err(generic_error("No bench modules found"));
   }
 
+  let options = BenchSpecifierOptions::resolve(&ps, &bench_options)?;
+
   check_specifiers(&ps, permissions.clone(), specifiers.clone()).await?;
 
-  bench_specifiers(
-    &ps,
-    &permissions,
-    specifiers,
-    BenchSpecifierOptions {
-      filter: TestFilter::from_flag(&bench_options.filter),
-      json: bench_options.json,
-    },
-  )
-  .await?;
+  bench_specifiers(&ps, &permissions, specifiers, options).await?;
 
   Ok(())
 }
 
 // TODO(bartlomieju): heavy duplication of code with `cli/tools/test.rs`
 pub async fn run_benchmarks_with_watch(
-  cli_options: CliOptions,
-  bench_options: BenchOptions,
+  flags: Flags,
+  bench_flags: BenchFlags,
 ) -> Result<(), AnyError> {
-  let ps = ProcState::from_options(Arc::new(cli_options)).await?;
+  let clear_screen = !flags.no_clear_screen;
+  let (sender, receiver) = unbounded_channel();
+  let mut ps = ProcState::build_for_file_watcher(flags, sender.clone()).await?;
+  let bench_options = ps.options.resolve_bench_options(bench_flags)?;
   // Various bench files should not share the same permissions in terms of
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
   // file would have impact on other files, which is undesirable.
   let permissions =
     Permissions::from_options(&ps.options.permissions_options())?;
-  let no_check = ps.options.type_check_mode() == TypeCheckMode::None;
-
-  let ps = RefCell::new(ps);
-
-  let resolver = |changed: Option<Vec<PathBuf>>| {
-    let paths_to_watch = bench_options.files.include.clone();
-    let paths_to_watch_clone = paths_to_watch.clone();
-    let files_changed = changed.is_some();
-    let bench_options = &bench_options;
-    let ps = ps.borrow().clone();
-
-    async move {
-      let bench_modules =
-        collect_specifiers(&bench_options.files, is_supported_bench_path)?;
+  let options = BenchSpecifierOptions::resolve(&ps, &bench_options)?;
 
-      let mut paths_to_watch = paths_to_watch_clone;
-      let mut modules_to_reload = if files_changed {
-        Vec::new()
-      } else {
-        bench_modules.clone()
-      };
-      let graph = ps.create_graph(bench_modules.clone()).await?;
-      graph_valid_with_cli_options(&graph, &bench_modules, &ps.options)?;
-
-      // TODO(@kitsonk) - This should be totally derivable from the graph.
-      for specifier in bench_modules {
-        fn get_dependencies<'a>(
-          graph: &'a deno_graph::ModuleGraph,
-          maybe_module: Option<&'a deno_graph::Module>,
-          // This needs to be accessible to skip getting dependencies if they're already there,
-          // otherwise this will cause a stack overflow with circular dependencies
-          output: &mut HashSet<&'a ModuleSpecifier>,
-          no_check: bool,
-        ) {
-          if let Some(module) = maybe_module.and_then(|m| m.esm()) {
-            for dep in module.dependencies.values() {
-              if let Some(specifier) = &dep.get_code() {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-                  get_dependencies(
-                    graph,
-                    graph.get(specifier),
-                    output,
-                    no_check,
-                  );
-                }
-              }
-              if !no_check {
-                if let Some(specifier) = &dep.get_type() {
-                  if !output.contains(specifier) {
-                    output.insert(specifier);
-                    get_dependencies(
-                      graph,
-                      graph.get(specifier),
-                      output,
-                      no_check,
-                    );
-                  }
-                }
-              }
-            }
-          }
-        }
-
-        // This bench module and all it's dependencies
-        let mut modules = HashSet::new();
-        modules.insert(&specifier);
-        get_dependencies(&graph, graph.get(&specifier), &mut modules, no_check);
-
-        paths_to_watch.extend(
-          modules
-            .iter()
-            .filter_map(|specifier| specifier.to_file_path().ok()),
-        );
-
-        if let Some(changed) = &changed {
-          for path in changed.iter().filter_map(|path| {
-            deno_core::resolve_url_or_path(&path.to_string_lossy()).ok()
-          }) {
-            if modules.contains(&path) {
-              modules_to_reload.push(specifier);
-              break;
-            }
-          }
-        }
-      }
-
-      Ok((paths_to_watch, modules_to_reload))
-    }
-    .map(move |result| {
-      if files_changed
-        && matches!(result, Ok((_, ref modules)) if modules.is_empty())
-      {
-        ResolutionResult::Ignore
-      } else {
-        match result {
-          Ok((paths_to_watch, modules_to_reload)) => {
-            ResolutionResult::Restart {
-              paths_to_watch,
-              result: Ok(modules_to_reload),
-            }
-          }
-          Err(e) => ResolutionResult::Restart {
-            paths_to_watch,
-            result: Err(e),
-          },
-        }
-      }
-    })
-  };
-
-  let operation = |modules_to_reload: Vec<ModuleSpecifier>| {
-    let permissions = &permissions;
-    let bench_options = &bench_options;
-    ps.borrow_mut().reset_for_file_watcher();
-    let ps = ps.borrow().clone();
+  // The modules of the benches are watched as they are loaded, the included
+  // directories are watched for new bench modules.
+  let mut paths_to_watch = bench_options.files.include.clone();
+  if paths_to_watch.is_empty() {
+    paths_to_watch.push(PathBuf::from("."));
+  }
+  sender.send(paths_to_watch)?;
 
-    async move {
+  let operation = |bench_options: BenchOptions| {
+    ps.reset_for_file_watcher();
+    let ps = ps.clone();
+    let permissions = permissions.clone();
+    let options = options.clone();
+    Ok(async move {
       let specifiers =
-        collect_specifiers(&bench_options.files, is_supported_bench_path)?
-          .into_iter()
-          .filter(|specifier| modules_to_reload.contains(specifier))
-          .collect::<Vec<ModuleSpecifier>>();
-
+        collect_specifiers(&bench_options.files, is_supported_bench_path)?;
       check_specifiers(&ps, permissions.clone(), specifiers.clone()).await?;
-
-      bench_specifiers(
-        &ps,
-        permissions,
-        specifiers,
-        BenchSpecifierOptions {
-          filter: TestFilter::from_flag(&bench_options.filter),
-          json: bench_options.json,
-        },
-      )
-      .await?;
-
-      Ok(())
-    }
+      bench_specifiers(&ps, &permissions, specifiers, options).await
+    })
   };
 
-  let clear_screen = !ps.borrow().options.no_clear_screen();
-  file_watcher::watch_func(
-    resolver,
+  file_watcher::watch_func2(
+    receiver,
     operation,
+    bench_options,
     file_watcher::PrintConfig {
       job_name: "Bench".to_string(),
       clear_screen,
     },
   )
-  .await?;
-
-  Ok(())
+  .await
 }
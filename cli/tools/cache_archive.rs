@@ -0,0 +1,453 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! `deno cache --export` and `deno cache --import`, which move the remote
+//! modules and npm packages of a project to a machine without internet access.
+//!
+//! The archive is a gzipped tarball of, in this order:
+//!
+//! - `manifest.json`, which lists the remote modules and npm packages.
+//! - `remote/<path>` and `remote/<path>.metadata.json`, the files of the
+//!   remote modules with the same paths as in `$DENO_DIR/deps`.
+//! - `npm/<name>/registry.json` and `npm/<name>/<version>.tgz`, the package
+//!   information from the registry and the tarballs of the npm packages.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use deno_graph::npm::NpmPackageNv;
+use deno_graph::semver::Version;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::args::CliOptions;
+use crate::args::Flags;
+use crate::cache::url_to_filename;
+use crate::cache::CachedUrlMetadata;
+use crate::cache::HttpCache;
+use crate::colors;
+use crate::http_util::HttpClient;
+use crate::npm::NpmCache;
+use crate::npm::NpmPackageInfo;
+use crate::npm::NpmPackageVersionDistInfo;
+use crate::proc_state::ProcState;
+use crate::util::checksum;
+use crate::util::progress_bar::ProgressBar;
+use crate::util::progress_bar::ProgressBarStyle;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ArchiveManifest {
+  version: u32,
+  remote: Vec<String>,
+  npm: Vec<ArchivedNpmPackage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedNpmPackage {
+  name: String,
+  version: String,
+}
+
+/// Writes the remote modules of the graph and of the lockfile, along with
+/// the resolved npm packages, to an archive. The modules need to be loaded.
+pub async fn export(
+  ps: &ProcState,
+  archive_path: &Path,
+) -> Result<(), AnyError> {
+  if ps.lockfile.is_none() {
+    log::warn!(
+      "{} No lockfile is used, so the archive can't be verified when imported.",
+      colors::yellow("Warning"),
+    );
+  }
+
+  let mut urls = ps
+    .graph()
+    .specifiers()
+    .map(|(specifier, _)| specifier.clone())
+    .filter(|s| s.scheme() == "http" || s.scheme() == "https")
+    .collect::<BTreeSet<_>>();
+  if let Some(lockfile) = &ps.lockfile {
+    urls.extend(
+      lockfile
+        .lock()
+        .remote()
+        .keys()
+        .filter_map(|url| ModuleSpecifier::parse(url).ok()),
+    );
+  }
+
+  let http_cache = HttpCache::new(&ps.dir.deps_folder_path());
+  let mut remote_files = Vec::new();
+  for url in urls {
+    match http_cache.get_cache_filename(&url) {
+      Some(cache_filename) if cache_filename.exists() => {
+        remote_files.push((url, cache_filename));
+      }
+      _ => {
+        log::warn!(
+          "{} Skipping {}, which is not cached.",
+          colors::yellow("Warning"),
+          url
+        );
+      }
+    }
+  }
+
+  let api = ps.npm_resolver.api();
+  let mut packages = ps
+    .npm_resolver
+    .snapshot()
+    .all_packages()
+    .into_iter()
+    // the copies of a package for other peer dependencies share the tarball
+    .filter(|package| package.copy_index == 0)
+    .collect::<Vec<_>>();
+  packages.sort_by(|a, b| {
+    let (a, b) = (&a.pkg_id.nv, &b.pkg_id.nv);
+    a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version))
+  });
+
+  let manifest = ArchiveManifest {
+    version: MANIFEST_VERSION,
+    remote: remote_files
+      .iter()
+      .map(|(url, _)| url.to_string())
+      .collect(),
+    npm: packages
+      .iter()
+      .map(|package| ArchivedNpmPackage {
+        name: package.pkg_id.nv.name.clone(),
+        version: package.pkg_id.nv.version.to_string(),
+      })
+      .collect(),
+  };
+
+  let file = fs::File::create(archive_path)
+    .with_context(|| format!("Failed creating {}", archive_path.display()))?;
+  let mut builder =
+    tar::Builder::new(GzEncoder::new(file, Compression::default()));
+  // the manifest comes first, so that the import can verify every other
+  // entry as soon as it's read
+  append_file(
+    &mut builder,
+    MANIFEST_FILE_NAME,
+    serde_json::to_string_pretty(&manifest)?.as_bytes(),
+  )?;
+
+  for (url, cache_filename) in &remote_files {
+    let archive_filename = remote_archive_path(url)?;
+    append_file(
+      &mut builder,
+      &format!("{archive_filename}.metadata.json"),
+      &fs::read(CachedUrlMetadata::filename(cache_filename))?,
+    )?;
+    append_file(&mut builder, &archive_filename, &fs::read(cache_filename)?)?;
+  }
+
+  let mut archived_package_infos = BTreeSet::new();
+  for package in &packages {
+    let nv = &package.pkg_id.nv;
+    // the information of a package comes before its tarballs
+    if archived_package_infos.insert(nv.name.clone()) {
+      // ensures the package information is cached
+      api.package_info(&nv.name).await?;
+      let path = ps
+        .npm_cache
+        .package_info_file_path(&nv.name, api.base_url());
+      let data = fs::read(&path)
+        .with_context(|| format!("Failed reading {}", path.display()))?;
+      append_file(
+        &mut builder,
+        &npm_package_info_archive_path(&nv.name),
+        &data,
+      )?;
+    }
+    let tarball = ps
+      .npm_cache
+      .download_tarball(&package.dist)
+      .await
+      .with_context(|| format!("Failed downloading npm package '{nv}'."))?;
+    append_file(&mut builder, &npm_tarball_archive_path(nv), &tarball)?;
+  }
+  builder.into_inner()?.finish()?;
+
+  log::info!(
+    "{} {} remote modules and {} npm packages to {}",
+    colors::green("Exported"),
+    manifest.remote.len(),
+    manifest.npm.len(),
+    archive_path.display(),
+  );
+  Ok(())
+}
+
+/// Populates the cache from an archive of `deno cache --export`. Every remote
+/// module and npm package needs to match the checksum or integrity of the
+/// lockfile. The entries are verified and cached one by one while the archive
+/// is read, in the order they were exported.
+pub fn import(flags: Flags, archive_path: &Path) -> Result<(), AnyError> {
+  // the process state isn't used, because it resolves the npm packages of the
+  // lockfile from the registry
+  let cli_options = CliOptions::from_flags(flags)?;
+  let dir = cli_options.resolve_deno_dir()?;
+  let npmrc = cli_options.resolve_npmrc();
+  let registry_url = npmrc.registry_url();
+  // nothing is downloaded with this cache
+  let npm_cache = NpmCache::from_deno_dir(
+    &dir,
+    cli_options.cache_setting(),
+    HttpClient::new(None, None)?,
+    npmrc,
+    ProgressBar::new(ProgressBarStyle::TextOnly),
+  );
+  let maybe_lockfile = cli_options.maybe_lock_file();
+  let lockfile = match &maybe_lockfile {
+    Some(lockfile) => lockfile.lock(),
+    None => bail!(
+      "Importing a cache archive requires a lockfile to verify it. Specify one with --lock."
+    ),
+  };
+
+  let file = fs::File::open(archive_path)
+    .with_context(|| format!("Failed reading {}", archive_path.display()))?;
+  let mut archive = tar::Archive::new(GzDecoder::new(file));
+  let mut entries = archive
+    .entries()
+    .with_context(|| format!("Failed reading {}", archive_path.display()))?;
+  let manifest: ArchiveManifest = match entries.next() {
+    Some(entry) => {
+      let (path, data) = read_entry(entry)?;
+      if path != MANIFEST_FILE_NAME {
+        bail!(
+          "The archive doesn't start with {}. Export it again with this version of Deno.",
+          MANIFEST_FILE_NAME
+        );
+      }
+      serde_json::from_slice(&data)?
+    }
+    None => bail!("The archive has no {}.", MANIFEST_FILE_NAME),
+  };
+  if manifest.version != MANIFEST_VERSION {
+    bail!(
+      "Unsupported version {} of the archive. Export it again with this version of Deno.",
+      manifest.version
+    );
+  }
+
+  // the entries of the manifest that weren't read yet, by their path
+  let mut remote_modules = BTreeMap::new();
+  for url in &manifest.remote {
+    let specifier = ModuleSpecifier::parse(url)?;
+    remote_modules.insert(remote_archive_path(&specifier)?, specifier);
+  }
+  let mut package_infos = BTreeMap::<String, Vec<NpmPackageNv>>::new();
+  for package in &manifest.npm {
+    let nv = NpmPackageNv {
+      name: package.name.clone(),
+      version: Version::parse_from_npm(&package.version)?,
+    };
+    package_infos
+      .entry(npm_package_info_archive_path(&nv.name))
+      .or_default()
+      .push(nv);
+  }
+  // the metadata of remote modules and the verified distribution information
+  // of npm packages, until the file or tarball is read
+  let mut remote_metadata = HashMap::<String, CachedUrlMetadata>::new();
+  let mut tarballs =
+    BTreeMap::<String, (NpmPackageNv, NpmPackageVersionDistInfo)>::new();
+
+  let http_cache = HttpCache::new(&dir.deps_folder_path());
+  for entry in entries {
+    let (path, data) = read_entry(entry)?;
+    if let Some(archive_filename) = path
+      .strip_suffix(".metadata.json")
+      .filter(|filename| remote_modules.contains_key(*filename))
+    {
+      remote_metadata
+        .insert(archive_filename.to_string(), serde_json::from_slice(&data)?);
+    } else if let Some(specifier) = remote_modules.remove(&path) {
+      let metadata = match remote_metadata.remove(&path) {
+        Some(metadata) => metadata,
+        None => bail!("The archive is missing {path}.metadata.json."),
+      };
+      // redirects have no content to verify
+      if !metadata.headers.contains_key("location") {
+        match lockfile.remote().get(specifier.as_str()) {
+          Some(expected) if *expected == checksum::gen(&[&data]) => {}
+          Some(_) => bail!(
+            "Integrity check failed for remote module \"{}\" of the archive.",
+            specifier
+          ),
+          None => bail!(
+            "The remote module \"{}\" is not in the lockfile.",
+            specifier
+          ),
+        }
+      }
+      http_cache.set(&specifier, metadata.headers, &data)?;
+    } else if let Some(nvs) = package_infos.remove(&path) {
+      let package_info: NpmPackageInfo = serde_json::from_slice(&data)?;
+      let package_info_path =
+        npm_cache.package_info_file_path(&nvs[0].name, &registry_url);
+      let mut is_cached = true;
+      for nv in nvs {
+        let version = nv.version.to_string();
+        let dist = match package_info.versions.get(&version) {
+          Some(version_info) => version_info.dist.clone(),
+          None => {
+            bail!("The archived information of '{}' has no such version.", nv)
+          }
+        };
+        let lockfile_integrity = lockfile
+          .content
+          .npm
+          .packages
+          .iter()
+          .find(|(id, _)| is_id_of_nv(id, &nv))
+          .map(|(_, info)| info.integrity.as_str());
+        match lockfile_integrity {
+          Some(integrity) if integrity == dist.integrity().as_str() => {}
+          Some(_) => bail!(
+            "Integrity check failed for npm package \"{}\" of the archive.",
+            nv
+          ),
+          None => bail!("The npm package \"{}\" is not in the lockfile.", nv),
+        }
+        is_cached &= has_cached_version(&package_info_path, &version);
+        tarballs.insert(npm_tarball_archive_path(&nv), (nv, dist));
+      }
+      if !is_cached {
+        fs::create_dir_all(package_info_path.parent().unwrap())?;
+        fs::write(&package_info_path, &data)?;
+      }
+    } else if let Some((nv, dist)) = tarballs.remove(&path) {
+      npm_cache.ensure_package_from_tarball(
+        &nv,
+        &data,
+        &dist,
+        &registry_url,
+      )?;
+    }
+  }
+
+  if let Some(path) = remote_modules
+    .keys()
+    .chain(package_infos.keys())
+    .chain(tarballs.keys())
+    .next()
+  {
+    bail!("The archive is missing {path}.");
+  }
+
+  log::info!(
+    "{} {} remote modules and {} npm packages from {}",
+    colors::green("Imported"),
+    manifest.remote.len(),
+    manifest.npm.len(),
+    archive_path.display(),
+  );
+  Ok(())
+}
+
+fn append_file<W: std::io::Write>(
+  builder: &mut tar::Builder<W>,
+  path: &str,
+  data: &[u8],
+) -> Result<(), AnyError> {
+  let mut header = tar::Header::new_gnu();
+  header.set_size(data.len() as u64);
+  header.set_mode(0o644);
+  header.set_cksum();
+  builder.append_data(&mut header, path, data)?;
+  Ok(())
+}
+
+fn read_entry<R: Read>(
+  entry: std::io::Result<tar::Entry<R>>,
+) -> Result<(String, Vec<u8>), AnyError> {
+  let mut entry = entry?;
+  let path = entry.path()?.to_string_lossy().to_string();
+  let mut data = Vec::with_capacity(entry.size() as usize);
+  entry.read_to_end(&mut data)?;
+  Ok((path, data))
+}
+
+fn remote_archive_path(url: &ModuleSpecifier) -> Result<String, AnyError> {
+  let filename = match url_to_filename(url) {
+    Some(filename) => filename,
+    None => bail!("Can't convert url to filename: {}", url),
+  };
+  let components = filename
+    .components()
+    .map(|c| c.as_os_str().to_string_lossy().to_string())
+    .collect::<Vec<_>>();
+  Ok(format!("remote/{}", components.join("/")))
+}
+
+fn npm_package_info_archive_path(name: &str) -> String {
+  format!("npm/{name}/registry.json")
+}
+
+fn npm_tarball_archive_path(nv: &NpmPackageNv) -> String {
+  format!("npm/{}/{}.tgz", nv.name, nv.version)
+}
+
+/// Checks if a serialized package id of the lockfile, like
+/// `react-dom@18.2.0_react@18.2.0`, is of the given package.
+fn is_id_of_nv(id: &str, nv: &NpmPackageNv) -> bool {
+  let nv = nv.to_string();
+  match id.strip_prefix(&nv) {
+    Some(rest) => rest.is_empty() || rest.starts_with('_'),
+    None => false,
+  }
+}
+
+/// Checks if the cached information of a package has the version, in which
+/// case it's kept because it might be newer than the archived one.
+fn has_cached_version(package_info_path: &Path, version: &str) -> bool {
+  fs::read(package_info_path)
+    .ok()
+    .and_then(|data| serde_json::from_slice::<NpmPackageInfo>(&data).ok())
+    .map(|info| info.versions.contains_key(version))
+    .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_is_id_of_nv() {
+    let nv = NpmPackageNv {
+      name: "react-dom".to_string(),
+      version: Version::parse_from_npm("18.2.0").unwrap(),
+    };
+    assert!(is_id_of_nv("react-dom@18.2.0", &nv));
+    assert!(is_id_of_nv("react-dom@18.2.0_react@18.2.0", &nv));
+    assert!(!is_id_of_nv("react-dom@18.2.01", &nv));
+    assert!(!is_id_of_nv("react@18.2.0", &nv));
+  }
+
+  #[test]
+  fn test_remote_archive_path() {
+    let url = ModuleSpecifier::parse("https://deno.land/x/mod.ts").unwrap();
+    let path = remote_archive_path(&url).unwrap();
+    assert!(path.starts_with("remote/https/deno.land/"));
+  }
+}
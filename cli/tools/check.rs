@@ -11,14 +11,18 @@ use deno_runtime::colors;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::args::CheckFlags;
+use crate::args::Flags;
 use crate::args::TsConfig;
 use crate::args::TypeCheckMode;
 use crate::cache::FastInsecureHasher;
 use crate::cache::TypeCheckCache;
 use crate::npm::NpmPackageResolver;
+use crate::proc_state::ProcState;
 use crate::tsc;
 use crate::tsc::Diagnostics;
 use crate::tsc::Stats;
+use crate::util::file_watcher;
 use crate::version;
 
 /// Options for performing a check of a module graph. Note that the decision to
@@ -354,6 +358,34 @@ fn get_leading_comments(file_text: &str) -> Vec<String> {
   results
 }
 
+/// Type checks the files of `deno check --watch` again whenever a file of
+/// their module graph changes.
+pub async fn check_with_watch(
+  flags: Flags,
+  check_flags: CheckFlags,
+) -> Result<(), AnyError> {
+  let clear_screen = !flags.no_clear_screen;
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  let mut ps = ProcState::build_for_file_watcher(flags, sender).await?;
+
+  let operation = |files: Vec<String>| {
+    ps.reset_for_file_watcher();
+    let ps = ps.clone();
+    Ok(async move { ps.load_and_type_check_files(&files).await })
+  };
+
+  file_watcher::watch_func2(
+    receiver,
+    operation,
+    check_flags.files,
+    file_watcher::PrintConfig {
+      job_name: "Check".to_string(),
+      clear_screen,
+    },
+  )
+  .await
+}
+
 #[cfg(test)]
 mod test {
   use deno_ast::MediaType;
@@ -0,0 +1,128 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::generate_coverage_report;
+use super::load_source;
+use super::relative_file_name;
+use super::TestCoverage;
+use crate::args::CoverageLineFlag;
+use crate::args::FileFlags;
+use crate::colors;
+use crate::proc_state::ProcState;
+use crate::util::fs::FileCollector;
+use crate::util::text_encoding::source_map_from_code;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use std::fs;
+use std::path::PathBuf;
+
+const TESTS_FILE_SUFFIX: &str = ".tests.jsonl";
+
+/// Reads the per test coverage written by
+/// `deno test --coverage --coverage-per-test`.
+fn collect_test_coverages(
+  files: &FileFlags,
+) -> Result<Vec<TestCoverage>, AnyError> {
+  let mut test_coverages = Vec::new();
+  let file_paths = FileCollector::new(|file_path| {
+    file_path.file_name().map_or(false, |name| {
+      name.to_string_lossy().ends_with(TESTS_FILE_SUFFIX)
+    })
+  })
+  .ignore_git_folder()
+  .ignore_node_modules()
+  .add_ignore_paths(&files.ignore)
+  .collect_files(&files.include)?;
+
+  for file_path in file_paths {
+    let text = fs::read_to_string(&file_path)?;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+      let test_coverage: TestCoverage = serde_json::from_str(line)
+        .with_context(|| {
+          format!("Failed parsing test coverage: {}", file_path.display())
+        })?;
+      test_coverages.push(test_coverage);
+    }
+  }
+
+  Ok(test_coverages)
+}
+
+/// Prints the tests that executed the line passed with `--tests-covering`.
+pub fn print_tests_covering(
+  ps: &ProcState,
+  files: &FileFlags,
+  tests_covering: &CoverageLineFlag,
+) -> Result<(), AnyError> {
+  let specifier = deno_core::resolve_url_or_path(&tests_covering.file)?;
+  let line_index = tests_covering.line - 1;
+  let test_coverages = collect_test_coverages(files)?;
+
+  // the module is usually covered by many tests, so only load it once
+  let mut maybe_transpiled_code: Option<String> = None;
+  let mut covering_tests = Vec::new();
+  for test_coverage in &test_coverages {
+    let mut is_covering = false;
+    for script_coverage in &test_coverage.result {
+      let is_same_module = Url::parse(&script_coverage.url)
+        .map(|url| url == specifier)
+        .unwrap_or(false);
+      if !is_same_module {
+        continue;
+      }
+      if maybe_transpiled_code.is_none() {
+        maybe_transpiled_code = Some(load_source(ps, &specifier)?.1);
+      }
+      let transpiled_code = maybe_transpiled_code.as_ref().unwrap();
+      let coverage_report = generate_coverage_report(
+        script_coverage,
+        transpiled_code,
+        &source_map_from_code(transpiled_code),
+        &None,
+      );
+      if coverage_report
+        .found_lines
+        .iter()
+        .any(|(index, count)| *index == line_index && *count > 0)
+      {
+        is_covering = true;
+        break;
+      }
+    }
+    if is_covering {
+      covering_tests.push(test_coverage);
+    }
+  }
+
+  let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+  let file_name = relative_file_name(&cwd, &specifier);
+  if covering_tests.is_empty() {
+    println!("No tests cover {}:{}", file_name, tests_covering.line);
+    return Ok(());
+  }
+
+  println!(
+    "{} {}:{}",
+    colors::intense_blue("Tests covering"),
+    file_name,
+    tests_covering.line
+  );
+  for test_coverage in covering_tests {
+    let location = &test_coverage.location;
+    let test_file_name = match Url::parse(&location.file_name) {
+      Ok(url) => relative_file_name(&cwd, &url),
+      Err(_) => location.file_name.clone(),
+    };
+    println!(
+      "  {} {}",
+      test_coverage.name,
+      colors::gray(format!(
+        "=> {}:{}:{}",
+        test_file_name, location.line_number, location.column_number
+      ))
+    );
+  }
+  Ok(())
+}
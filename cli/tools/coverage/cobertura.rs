@@ -0,0 +1,299 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::relative_file_name;
+use super::write_report;
+use super::CoverageReport;
+use super::CoverageReporter;
+use super::CoverageStats;
+use crate::util::xml::escape_xml;
+
+use deno_core::error::AnyError;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+struct CoberturaFunction {
+  name: String,
+  line_index: usize,
+  execution_count: i64,
+}
+
+struct CoberturaClass {
+  file_name: String,
+  stats: CoverageStats,
+  functions: Vec<CoberturaFunction>,
+  lines: Vec<(usize, i64)>,
+  /// Number of branches found and hit, by line index.
+  branches: BTreeMap<usize, (usize, usize)>,
+}
+
+/// Writes a report in the Cobertura XML format, which is understood by most
+/// CI services. Every directory is reported as a package and every file as a
+/// class.
+pub struct CoberturaCoverageReporter {
+  output: Option<PathBuf>,
+  cwd: PathBuf,
+  classes: Vec<CoberturaClass>,
+}
+
+impl CoberturaCoverageReporter {
+  pub fn new(output: Option<PathBuf>) -> CoberturaCoverageReporter {
+    CoberturaCoverageReporter {
+      output,
+      cwd: std::env::current_dir().unwrap(),
+      classes: Vec::new(),
+    }
+  }
+
+  fn serialize(&self, timestamp: u128) -> String {
+    let mut packages: BTreeMap<&str, Vec<&CoberturaClass>> = BTreeMap::new();
+    let mut total = CoverageStats::default();
+    for class in &self.classes {
+      let package_name = match class.file_name.rsplit_once('/') {
+        Some((dir, _)) => dir,
+        None => ".",
+      };
+      packages.entry(package_name).or_default().push(class);
+      total.add(&class.stats);
+    }
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" ?>"#).unwrap();
+    writeln!(
+      xml,
+      r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )
+    .unwrap();
+    writeln!(
+      xml,
+      r#"<coverage lines-valid="{}" lines-covered="{}" line-rate="{}" branches-valid="{}" branches-covered="{}" branch-rate="{}" timestamp="{}" complexity="0" version="0.1">"#,
+      total.lines_found,
+      total.lines_hit,
+      rate(total.lines_hit, total.lines_found),
+      total.branches_found,
+      total.branches_hit,
+      rate(total.branches_hit, total.branches_found),
+      timestamp,
+    )
+    .unwrap();
+    writeln!(xml, "  <sources>").unwrap();
+    writeln!(
+      xml,
+      "    <source>{}</source>",
+      escape_xml(&self.cwd.to_string_lossy())
+    )
+    .unwrap();
+    writeln!(xml, "  </sources>").unwrap();
+    writeln!(xml, "  <packages>").unwrap();
+    for (package_name, classes) in packages {
+      let mut stats = CoverageStats::default();
+      for class in &classes {
+        stats.add(&class.stats);
+      }
+      writeln!(
+        xml,
+        r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        escape_xml(package_name),
+        rate(stats.lines_hit, stats.lines_found),
+        rate(stats.branches_hit, stats.branches_found),
+      )
+      .unwrap();
+      writeln!(xml, "      <classes>").unwrap();
+      for class in classes {
+        write_class(&mut xml, class);
+      }
+      writeln!(xml, "      </classes>").unwrap();
+      writeln!(xml, "    </package>").unwrap();
+    }
+    writeln!(xml, "  </packages>").unwrap();
+    writeln!(xml, "</coverage>").unwrap();
+    xml
+  }
+}
+
+fn write_class(xml: &mut String, class: &CoberturaClass) {
+  let class_name = class
+    .file_name
+    .rsplit_once('/')
+    .map(|(_, name)| name)
+    .unwrap_or(&class.file_name);
+  writeln!(
+    xml,
+    r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+    escape_xml(class_name),
+    escape_xml(&class.file_name),
+    rate(class.stats.lines_hit, class.stats.lines_found),
+    rate(class.stats.branches_hit, class.stats.branches_found),
+  )
+  .unwrap();
+
+  writeln!(xml, "          <methods>").unwrap();
+  for function in &class.functions {
+    let is_hit = function.execution_count > 0;
+    writeln!(
+      xml,
+      r#"            <method name="{}" signature="" line-rate="{}" branch-rate="0" complexity="0">"#,
+      escape_xml(&function.name),
+      if is_hit { "1" } else { "0" },
+    )
+    .unwrap();
+    writeln!(xml, "              <lines>").unwrap();
+    writeln!(
+      xml,
+      r#"                <line number="{}" hits="{}"/>"#,
+      function.line_index + 1,
+      function.execution_count,
+    )
+    .unwrap();
+    writeln!(xml, "              </lines>").unwrap();
+    writeln!(xml, "            </method>").unwrap();
+  }
+  writeln!(xml, "          </methods>").unwrap();
+
+  writeln!(xml, "          <lines>").unwrap();
+  for (line_index, count) in &class.lines {
+    match class.branches.get(line_index) {
+      Some((found, hit)) => writeln!(
+        xml,
+        r#"            <line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+        line_index + 1,
+        count,
+        hit * 100 / found,
+        hit,
+        found,
+      ),
+      None => writeln!(
+        xml,
+        r#"            <line number="{}" hits="{}" branch="false"/>"#,
+        line_index + 1,
+        count,
+      ),
+    }
+    .unwrap();
+  }
+  writeln!(xml, "          </lines>").unwrap();
+  writeln!(xml, "        </class>").unwrap();
+}
+
+/// Formats a ratio between 0 and 1 the way Cobertura expects, where nothing
+/// to cover counts as fully covered.
+fn rate(hit: usize, found: usize) -> String {
+  if found == 0 {
+    "1".to_string()
+  } else {
+    format!("{:.4}", hit as f64 / found as f64)
+  }
+}
+
+impl CoverageReporter for CoberturaCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    _file_text: &str,
+  ) -> Result<(), AnyError> {
+    let mut branches = BTreeMap::new();
+    for branch in &coverage_report.branches {
+      let (found, hit) = branches.entry(branch.line_index).or_insert((0, 0));
+      *found += 1;
+      if branch.is_hit {
+        *hit += 1;
+      }
+    }
+    self.classes.push(CoberturaClass {
+      file_name: relative_file_name(&self.cwd, &coverage_report.url),
+      stats: CoverageStats::from_report(coverage_report),
+      functions: coverage_report
+        .named_functions
+        .iter()
+        .map(|function| CoberturaFunction {
+          name: function.name.clone(),
+          line_index: function.line_index,
+          execution_count: function.execution_count,
+        })
+        .collect(),
+      lines: coverage_report.found_lines.clone(),
+      branches,
+    });
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis())
+      .unwrap_or(0);
+    write_report(&self.output, &self.serialize(timestamp))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_rate() {
+    assert_eq!(rate(0, 0), "1");
+    assert_eq!(rate(1, 3), "0.3333");
+  }
+
+  #[test]
+  fn test_serialize() {
+    let mut branches = BTreeMap::new();
+    branches.insert(1, (2, 1));
+    let reporter = CoberturaCoverageReporter {
+      output: None,
+      cwd: PathBuf::from("/project"),
+      classes: vec![CoberturaClass {
+        file_name: "src/mod.ts".to_string(),
+        stats: CoverageStats {
+          lines_found: 3,
+          lines_hit: 2,
+          branches_found: 2,
+          branches_hit: 1,
+          functions_found: 1,
+          functions_hit: 1,
+        },
+        functions: vec![CoberturaFunction {
+          name: "add".to_string(),
+          line_index: 0,
+          execution_count: 1,
+        }],
+        lines: vec![(0, 1), (1, 1), (2, 0)],
+        branches,
+      }],
+    };
+    assert_eq!(
+      reporter.serialize(0),
+      r#"<?xml version="1.0" ?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+<coverage lines-valid="3" lines-covered="2" line-rate="0.6667" branches-valid="2" branches-covered="1" branch-rate="0.5000" timestamp="0" complexity="0" version="0.1">
+  <sources>
+    <source>/project</source>
+  </sources>
+  <packages>
+    <package name="src" line-rate="0.6667" branch-rate="0.5000" complexity="0">
+      <classes>
+        <class name="mod.ts" filename="src/mod.ts" line-rate="0.6667" branch-rate="0.5000" complexity="0">
+          <methods>
+            <method name="add" signature="" line-rate="1" branch-rate="0" complexity="0">
+              <lines>
+                <line number="1" hits="1"/>
+              </lines>
+            </method>
+          </methods>
+          <lines>
+            <line number="1" hits="1" branch="false"/>
+            <line number="2" hits="1" branch="true" condition-coverage="50% (1/2)"/>
+            <line number="3" hits="0" branch="false"/>
+          </lines>
+        </class>
+      </classes>
+    </package>
+  </packages>
+</coverage>
+"#
+    );
+  }
+}
@@ -0,0 +1,386 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::CoverageReport;
+use super::CoverageReporter;
+use super::CoverageStats;
+use crate::util::path::root_url_to_safe_local_dirname;
+use crate::util::xml::escape_xml;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Component;
+use std::path::PathBuf;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em; color: #24292f; }
+a { color: #0969da; text-decoration: none; }
+a:hover { text-decoration: underline; }
+h1 { font-size: 1.4em; }
+table { border-collapse: collapse; }
+.summary td, .summary th { padding: 0.3em 1em; border-bottom: 1px solid #d0d7de; text-align: left; }
+.summary td.pct { text-align: right; font-family: monospace; }
+.high { background: #dafbe1; }
+.medium { background: #fff8c5; }
+.low { background: #ffebe9; }
+.source { font-family: monospace; font-size: 0.9em; width: 100%; }
+.source td { padding: 0 0.5em; white-space: pre; vertical-align: top; }
+.source td.line-number, .source td.hits { color: #6e7781; text-align: right; user-select: none; }
+.source tr.covered td.hits { background: #dafbe1; }
+.source tr.uncovered td { background: #ffebe9; }
+.source tr.partial td.hits { background: #fff8c5; }
+"#;
+
+struct HtmlFile {
+  /// Path of the file in the report, relative to the report root.
+  segments: Vec<String>,
+  url: Url,
+  stats: CoverageStats,
+  source: String,
+  line_counts: HashMap<usize, i64>,
+  partial_lines: HashSet<usize>,
+}
+
+/// Writes a static site to the output directory with an `index.html`
+/// summary for every directory and an annotated page for every source file.
+pub struct HtmlCoverageReporter {
+  dir: PathBuf,
+  cwd: PathBuf,
+  files: Vec<HtmlFile>,
+}
+
+impl HtmlCoverageReporter {
+  pub fn new(dir: PathBuf) -> HtmlCoverageReporter {
+    HtmlCoverageReporter {
+      dir,
+      cwd: std::env::current_dir().unwrap(),
+      files: Vec::new(),
+    }
+  }
+
+  /// Gets the path of the source file inside the report. Local files are
+  /// placed relative to the current directory when possible and remote
+  /// modules are placed in a directory named after their host.
+  fn url_to_segments(&self, url: &Url) -> Vec<String> {
+    let path = match url.to_file_path() {
+      Ok(path) => match path.strip_prefix(&self.cwd) {
+        Ok(relative_path) => relative_path.to_path_buf(),
+        Err(_) => path,
+      },
+      Err(_) => root_url_to_safe_local_dirname(url),
+    };
+    path
+      .components()
+      .filter_map(|component| match component {
+        Component::Normal(segment) => {
+          Some(segment.to_string_lossy().to_string())
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
+  fn write_file(
+    &self,
+    segments: &[String],
+    html: String,
+  ) -> Result<(), AnyError> {
+    let path = segments
+      .iter()
+      .fold(self.dir.clone(), |path, segment| path.join(segment));
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).with_context(|| {
+        format!("Failed creating directory: {}", parent.display())
+      })?;
+    }
+    fs::write(&path, html)
+      .with_context(|| format!("Failed writing: {}", path.display()))?;
+    Ok(())
+  }
+
+  fn render_directory(
+    &self,
+    dir_segments: &[String],
+    subdirs: &BTreeMap<String, CoverageStats>,
+    files: &[&HtmlFile],
+  ) -> String {
+    let mut total = CoverageStats::default();
+    for stats in subdirs.values() {
+      total.add(stats);
+    }
+    for file in files {
+      total.add(&file.stats);
+    }
+
+    let title = if dir_segments.is_empty() {
+      "All files".to_string()
+    } else {
+      dir_segments.join("/")
+    };
+    let mut body = String::new();
+    write!(
+      body,
+      "{}<h1>{}</h1>\n{}<table class=\"summary\">\n<tr><th>File</th><th>Lines</th><th>Branches</th><th>Functions</th></tr>\n",
+      breadcrumbs(dir_segments, dir_segments.len()),
+      escape_xml(&title),
+      render_totals(&total),
+    )
+    .unwrap();
+    for (name, stats) in subdirs {
+      write_summary_row(
+        &mut body,
+        &format!("{}/index.html", url_encode_segment(name)),
+        &format!("{name}/"),
+        stats,
+      );
+    }
+    for file in files {
+      let name = file.segments.last().unwrap();
+      write_summary_row(
+        &mut body,
+        &format!("{}.html", url_encode_segment(name)),
+        name,
+        &file.stats,
+      );
+    }
+    body.push_str("</table>\n");
+    render_page(&title, &body)
+  }
+
+  fn render_file(&self, file: &HtmlFile) -> String {
+    let title = file.segments.join("/");
+    let mut body = String::new();
+    write!(
+      body,
+      "{}<h1>{}</h1>\n<p><a href=\"{}\">{}</a></p>\n{}<table class=\"source\">\n",
+      breadcrumbs(&file.segments, file.segments.len() - 1),
+      escape_xml(&title),
+      escape_xml(file.url.as_str()),
+      escape_xml(file.url.as_str()),
+      render_totals(&file.stats),
+    )
+    .unwrap();
+    for (line_index, line) in file.source.split('\n').enumerate() {
+      let (class, hits) = match file.line_counts.get(&line_index) {
+        Some(0) => ("uncovered", "0".to_string()),
+        Some(count) if file.partial_lines.contains(&line_index) => {
+          ("partial", format!("{count}x"))
+        }
+        Some(count) => ("covered", format!("{count}x")),
+        None => ("", String::new()),
+      };
+      writeln!(
+        body,
+        "<tr class=\"{class}\" id=\"L{line}\"><td class=\"line-number\"><a href=\"#L{line}\">{line}</a></td><td class=\"hits\">{hits}</td><td>{}</td></tr>",
+        escape_xml(line.trim_end_matches('\r')),
+        line = line_index + 1,
+      )
+      .unwrap();
+    }
+    body.push_str("</table>\n");
+    render_page(&title, &body)
+  }
+}
+
+impl CoverageReporter for HtmlCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    file_text: &str,
+  ) -> Result<(), AnyError> {
+    let partial_lines = coverage_report
+      .branches
+      .iter()
+      .filter(|branch| !branch.is_hit)
+      .map(|branch| branch.line_index)
+      .collect();
+    self.files.push(HtmlFile {
+      segments: self.url_to_segments(&coverage_report.url),
+      url: coverage_report.url.clone(),
+      stats: CoverageStats::from_report(coverage_report),
+      source: file_text.to_string(),
+      line_counts: coverage_report.found_lines.iter().copied().collect(),
+      partial_lines,
+    });
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    // every directory containing a file, directly or in a subdirectory
+    let mut dirs = BTreeSet::new();
+    for file in &self.files {
+      for i in 0..file.segments.len() {
+        dirs.insert(file.segments[..i].to_vec());
+      }
+    }
+
+    for dir in dirs {
+      let mut subdirs = BTreeMap::<String, CoverageStats>::new();
+      let mut files = Vec::new();
+      for file in &self.files {
+        if !file.segments.starts_with(&dir) {
+          continue;
+        }
+        if file.segments.len() == dir.len() + 1 {
+          files.push(file);
+        } else {
+          subdirs
+            .entry(file.segments[dir.len()].clone())
+            .or_default()
+            .add(&file.stats);
+        }
+      }
+      files.sort_by(|a, b| a.segments.cmp(&b.segments));
+
+      let html = self.render_directory(&dir, &subdirs, &files);
+      let mut index_segments = dir.clone();
+      index_segments.push("index.html".to_string());
+      self.write_file(&index_segments, html)?;
+    }
+
+    for file in &self.files {
+      let html = self.render_file(file);
+      let mut page_segments = file.segments.clone();
+      let name = page_segments.pop().unwrap();
+      page_segments.push(format!("{name}.html"));
+      self.write_file(&page_segments, html)?;
+    }
+
+    Ok(())
+  }
+}
+
+fn render_page(title: &str, body: &str) -> String {
+  format!(
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Coverage report - {}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+    escape_xml(title),
+    STYLE,
+    body
+  )
+}
+
+/// Renders links to every parent directory of a page that is `depth`
+/// directories deep.
+fn breadcrumbs(segments: &[String], depth: usize) -> String {
+  let mut html = String::from("<p>");
+  write!(
+    html,
+    "<a href=\"{}index.html\">All files</a>",
+    "../".repeat(depth)
+  )
+  .unwrap();
+  for (i, segment) in segments.iter().take(depth).enumerate() {
+    write!(
+      html,
+      " / <a href=\"{}index.html\">{}</a>",
+      "../".repeat(depth - i - 1),
+      escape_xml(segment)
+    )
+    .unwrap();
+  }
+  html.push_str("</p>\n");
+  html
+}
+
+fn render_totals(stats: &CoverageStats) -> String {
+  format!(
+    "<p>Lines: {} &middot; Branches: {} &middot; Functions: {}</p>\n",
+    format_ratio(stats.lines_hit, stats.lines_found),
+    format_ratio(stats.branches_hit, stats.branches_found),
+    format_ratio(stats.functions_hit, stats.functions_found),
+  )
+}
+
+fn write_summary_row(
+  html: &mut String,
+  href: &str,
+  name: &str,
+  stats: &CoverageStats,
+) {
+  write!(
+    html,
+    "<tr><td><a href=\"{}\">{}</a></td>",
+    escape_xml(href),
+    escape_xml(name)
+  )
+  .unwrap();
+  for (hit, found) in [
+    (stats.lines_hit, stats.lines_found),
+    (stats.branches_hit, stats.branches_found),
+    (stats.functions_hit, stats.functions_found),
+  ] {
+    write!(
+      html,
+      "<td class=\"pct {}\">{}</td>",
+      ratio_class(hit, found),
+      format_ratio(hit, found)
+    )
+    .unwrap();
+  }
+  html.push_str("</tr>\n");
+}
+
+fn format_ratio(hit: usize, found: usize) -> String {
+  if found == 0 {
+    "-".to_string()
+  } else {
+    format!(
+      "{:.2}% ({}/{})",
+      hit as f64 / found as f64 * 100.0,
+      hit,
+      found
+    )
+  }
+}
+
+/// Uses the same ratios as the pretty reporter to pick a color.
+fn ratio_class(hit: usize, found: usize) -> &'static str {
+  if found == 0 {
+    return "";
+  }
+  let ratio = hit as f64 / found as f64;
+  if ratio >= 0.9 {
+    "high"
+  } else if ratio >= 0.75 {
+    "medium"
+  } else {
+    "low"
+  }
+}
+
+fn url_encode_segment(segment: &str) -> String {
+  segment
+    .replace('%', "%25")
+    .replace('#', "%23")
+    .replace('?', "%3F")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_breadcrumbs() {
+    let segments = vec!["src".to_string(), "mod.ts".to_string()];
+    assert_eq!(
+      breadcrumbs(&segments, 1),
+      "<p><a href=\"../index.html\">All files</a> / <a href=\"index.html\">src</a></p>\n"
+    );
+    assert_eq!(
+      breadcrumbs(&[], 0),
+      "<p><a href=\"index.html\">All files</a></p>\n"
+    );
+  }
+
+  #[test]
+  fn test_format_ratio() {
+    assert_eq!(format_ratio(0, 0), "-");
+    assert_eq!(format_ratio(1, 3), "33.33% (1/3)");
+  }
+}
@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::tools::test::TestLocation;
+
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -31,6 +33,17 @@ pub struct ScriptCoverage {
   pub functions: Vec<FunctionCoverage>,
 }
 
+/// The coverage of a single test, recorded by `deno test --coverage-per-test`
+/// as one line of a `.tests.jsonl` file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestCoverage {
+  pub name: String,
+  pub origin: String,
+  pub location: TestLocation,
+  pub result: Vec<ScriptCoverage>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StartPreciseCoverageParameters {
@@ -1,13 +1,16 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CoverageFlags;
+use crate::args::CoverageFormat;
 use crate::args::FileFlags;
 use crate::args::Flags;
 use crate::colors;
 use crate::emit::get_source_hash;
 use crate::proc_state::ProcState;
 use crate::tools::fmt::format_json;
+use crate::tools::test::TestDescription;
 use crate::util::fs::FileCollector;
+use crate::util::path::to_relative_path_or_remote_url;
 use crate::util::text_encoding::source_map_from_code;
 
 use deno_ast::MediaType;
@@ -27,24 +30,40 @@ use std::io::BufWriter;
 use std::io::Error;
 use std::io::Write;
 use std::io::{self};
+use std::path::Path;
 use std::path::PathBuf;
 use text_lines::TextLines;
 use uuid::Uuid;
 
+mod attribution;
+mod cobertura;
+mod html;
 mod json_types;
 mod merge;
 mod range_tree;
+mod summary;
+mod threshold;
 
+use attribution::print_tests_covering;
+use cobertura::CoberturaCoverageReporter;
+use html::HtmlCoverageReporter;
 use json_types::*;
+use summary::JsonSummaryCoverageReporter;
+use threshold::CoverageThresholds;
 
 pub struct CoverageCollector {
   pub dir: PathBuf,
   session: LocalInspectorSession,
+  maybe_tests_file: Option<File>,
 }
 
 impl CoverageCollector {
   pub fn new(dir: PathBuf, session: LocalInspectorSession) -> Self {
-    Self { dir, session }
+    Self {
+      dir,
+      session,
+      maybe_tests_file: None,
+    }
   }
 
   async fn enable_debugger(&mut self) -> Result<(), AnyError> {
@@ -120,10 +139,72 @@ impl CoverageCollector {
     Ok(())
   }
 
+  /// Takes the coverage collected since the previous snapshot, which resets
+  /// the counters in V8. When taken right after a test, the coverage is also
+  /// recorded for that test so that `deno coverage` can tell which tests
+  /// exercise a line.
+  pub async fn take_snapshot(
+    &mut self,
+    maybe_test: Option<TestDescription>,
+  ) -> Result<(), AnyError> {
+    fs::create_dir_all(&self.dir)?;
+
+    let script_coverages = self.take_precise_coverage().await?.result;
+    let test = match maybe_test {
+      Some(test) => test,
+      None => return self.write_script_coverages(&script_coverages),
+    };
+
+    // only keep the scripts that were executed by the test
+    let script_coverages = script_coverages
+      .into_iter()
+      .filter(|script_coverage| {
+        script_coverage
+          .functions
+          .iter()
+          .any(|function| function.ranges.iter().any(|range| range.count > 0))
+      })
+      .collect::<Vec<_>>();
+    if script_coverages.is_empty() {
+      return Ok(());
+    }
+    self.write_script_coverages(&script_coverages)?;
+
+    let tests_file = match self.maybe_tests_file.as_mut() {
+      Some(file) => file,
+      None => {
+        let filepath = self.dir.join(format!("{}.tests.jsonl", Uuid::new_v4()));
+        self.maybe_tests_file.insert(File::create(filepath)?)
+      }
+    };
+    let test_coverage = TestCoverage {
+      name: test.name,
+      origin: test.origin,
+      location: test.location,
+      result: script_coverages,
+    };
+    let mut line = serde_json::to_string(&test_coverage)?;
+    line.push('\n');
+    tests_file.write_all(line.as_bytes())?;
+    Ok(())
+  }
+
   pub async fn stop_collecting(&mut self) -> Result<(), AnyError> {
     fs::create_dir_all(&self.dir)?;
 
     let script_coverages = self.take_precise_coverage().await?.result;
+    self.write_script_coverages(&script_coverages)?;
+
+    self.disable_debugger().await?;
+    self.disable_profiler().await?;
+
+    Ok(())
+  }
+
+  fn write_script_coverages(
+    &self,
+    script_coverages: &[ScriptCoverage],
+  ) -> Result<(), AnyError> {
     for script_coverage in script_coverages {
       let filename = format!("{}.json", Uuid::new_v4());
       let filepath = self.dir.join(filename);
@@ -138,10 +219,6 @@ impl CoverageCollector {
       out.write_all(formated_coverage.as_bytes())?;
       out.flush()?;
     }
-
-    self.disable_debugger().await?;
-    self.disable_profiler().await?;
-
     Ok(())
   }
 }
@@ -160,7 +237,7 @@ struct FunctionCoverageItem {
   execution_count: i64,
 }
 
-struct CoverageReport {
+pub struct CoverageReport {
   url: ModuleSpecifier,
   named_functions: Vec<FunctionCoverageItem>,
   branches: Vec<BranchCoverageItem>,
@@ -168,6 +245,66 @@ struct CoverageReport {
   output: Option<PathBuf>,
 }
 
+/// Number of lines, branches and functions found and hit in one or more
+/// files.
+#[derive(Clone, Copy, Default)]
+struct CoverageStats {
+  lines_found: usize,
+  lines_hit: usize,
+  branches_found: usize,
+  branches_hit: usize,
+  functions_found: usize,
+  functions_hit: usize,
+}
+
+impl CoverageStats {
+  fn from_report(coverage_report: &CoverageReport) -> CoverageStats {
+    CoverageStats {
+      lines_found: coverage_report.found_lines.len(),
+      lines_hit: coverage_report
+        .found_lines
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .count(),
+      branches_found: coverage_report.branches.len(),
+      branches_hit: coverage_report
+        .branches
+        .iter()
+        .filter(|b| b.is_hit)
+        .count(),
+      functions_found: coverage_report.named_functions.len(),
+      functions_hit: coverage_report
+        .named_functions
+        .iter()
+        .filter(|f| f.execution_count > 0)
+        .count(),
+    }
+  }
+
+  fn add(&mut self, other: &CoverageStats) {
+    self.lines_found += other.lines_found;
+    self.lines_hit += other.lines_hit;
+    self.branches_found += other.branches_found;
+    self.branches_hit += other.branches_hit;
+    self.functions_found += other.functions_found;
+    self.functions_hit += other.functions_hit;
+  }
+}
+
+/// Gets the name of a covered file for display, which is its path relative to
+/// `cwd` without a leading `./`, or its url for a remote file.
+fn relative_file_name(cwd: &Path, url: &Url) -> String {
+  let cwd = match Url::from_directory_path(cwd) {
+    Ok(cwd) => cwd,
+    Err(_) => return url.to_string(),
+  };
+  let name = to_relative_path_or_remote_url(&cwd, url.as_str());
+  match name.strip_prefix("./") {
+    Some(name) => name.to_string(),
+    None => name,
+  }
+}
+
 fn generate_coverage_report(
   script_coverage: &ScriptCoverage,
   script_source: &str,
@@ -361,6 +498,9 @@ fn generate_coverage_report(
 enum CoverageReporterKind {
   Pretty,
   Lcov,
+  Html(PathBuf),
+  Cobertura(Option<PathBuf>),
+  Json(Option<PathBuf>),
 }
 
 fn create_reporter(
@@ -369,6 +509,13 @@ fn create_reporter(
   match kind {
     CoverageReporterKind::Lcov => Box::new(LcovCoverageReporter::new()),
     CoverageReporterKind::Pretty => Box::new(PrettyCoverageReporter::new()),
+    CoverageReporterKind::Html(dir) => Box::new(HtmlCoverageReporter::new(dir)),
+    CoverageReporterKind::Cobertura(output) => {
+      Box::new(CoberturaCoverageReporter::new(output))
+    }
+    CoverageReporterKind::Json(output) => {
+      Box::new(JsonSummaryCoverageReporter::new(output))
+    }
   }
 }
 
@@ -379,7 +526,22 @@ trait CoverageReporter {
     file_text: &str,
   ) -> Result<(), AnyError>;
 
-  fn done(&mut self);
+  fn done(&mut self) -> Result<(), AnyError>;
+}
+
+/// Writes a report that is only known once all files were reported to the
+/// output file, or to stdout when no file is specified.
+fn write_report(
+  output: &Option<PathBuf>,
+  report: &str,
+) -> Result<(), AnyError> {
+  match output {
+    Some(path) => fs::write(path, report).with_context(|| {
+      format!("Failed to write coverage report: {}", path.display())
+    })?,
+    None => io::stdout().write_all(report.as_bytes())?,
+  }
+  Ok(())
 }
 
 struct LcovCoverageReporter {}
@@ -481,7 +643,9 @@ impl CoverageReporter for LcovCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
 }
 
 struct PrettyCoverageReporter {}
@@ -554,7 +718,9 @@ impl CoverageReporter for PrettyCoverageReporter {
     Ok(())
   }
 
-  fn done(&mut self) {}
+  fn done(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
 }
 
 fn collect_coverages(
@@ -607,6 +773,61 @@ fn filter_coverages(
     .collect::<Vec<ScriptCoverage>>()
 }
 
+/// Loads the original source of a covered module and the code that was
+/// executed, which differs for modules that were transpiled.
+fn load_source(
+  ps: &ProcState,
+  module_specifier: &ModuleSpecifier,
+) -> Result<(String, String), AnyError> {
+  let maybe_file = if module_specifier.scheme() == "file" {
+    ps.file_fetcher.get_source(module_specifier)
+  } else {
+    ps.file_fetcher
+      .fetch_cached(module_specifier, 10)
+      .with_context(|| {
+        format!("Failed to fetch \"{module_specifier}\" from cache.")
+      })?
+  };
+  let file = maybe_file.ok_or_else(|| {
+    anyhow!("Failed to fetch \"{}\" from cache.
+        Before generating coverage report, run `deno test --coverage` to ensure consistent state.",
+        module_specifier
+      )
+  })?;
+
+  // Check if file was transpiled
+  let transpiled_code = match file.media_type {
+    MediaType::JavaScript
+    | MediaType::Unknown
+    | MediaType::Cjs
+    | MediaType::Mjs
+    | MediaType::Json => file.source.as_ref().to_string(),
+    MediaType::Dts | MediaType::Dmts | MediaType::Dcts => "".to_string(),
+    MediaType::TypeScript
+    | MediaType::Jsx
+    | MediaType::Mts
+    | MediaType::Cts
+    | MediaType::Tsx => {
+      let source_hash = get_source_hash(&file.source, ps.emit_options_hash);
+      match ps.emit_cache.get_emit_code(&file.specifier, source_hash) {
+        Some(code) => code,
+        None => {
+          return Err(anyhow!(
+            "Missing transpiled source code for: \"{}\".
+            Before generating coverage report, run `deno test --coverage` to ensure consistent state.",
+            file.specifier,
+          ))
+        }
+      }
+    }
+    MediaType::Wasm | MediaType::TsBuildInfo | MediaType::SourceMap => {
+      unreachable!()
+    }
+  };
+
+  Ok((file.source.to_string(), transpiled_code))
+}
+
 pub async fn cover_files(
   flags: Flags,
   coverage_flags: CoverageFlags,
@@ -617,6 +838,10 @@ pub async fn cover_files(
 
   let ps = ProcState::build(flags).await?;
 
+  if let Some(tests_covering) = coverage_flags.tests_covering {
+    return print_tests_covering(&ps, &coverage_flags.files, &tests_covering);
+  }
+
   let script_coverages = collect_coverages(coverage_flags.files)?;
   let script_coverages = filter_coverages(
     script_coverages,
@@ -636,13 +861,13 @@ pub async fn cover_files(
     vec![]
   };
 
-  let reporter_kind = if coverage_flags.lcov {
-    CoverageReporterKind::Lcov
-  } else {
-    CoverageReporterKind::Pretty
-  };
-
-  let mut reporter = create_reporter(reporter_kind);
+  if coverage_flags.output.is_some()
+    && coverage_flags.format == CoverageFormat::Pretty
+  {
+    return Err(generic_error(
+      "The --output flag requires --lcov or a --format other than pretty",
+    ));
+  }
 
   let out_mode = match coverage_flags.output {
     Some(ref path) => match File::create(path) {
@@ -654,56 +879,28 @@ pub async fn cover_files(
     None => None,
   };
 
+  let reporter_kind = if let Some(dir) = coverage_flags.html {
+    CoverageReporterKind::Html(dir)
+  } else {
+    match coverage_flags.format {
+      CoverageFormat::Pretty => CoverageReporterKind::Pretty,
+      CoverageFormat::Lcov => CoverageReporterKind::Lcov,
+      CoverageFormat::Cobertura => {
+        CoverageReporterKind::Cobertura(out_mode.clone())
+      }
+      CoverageFormat::Json => CoverageReporterKind::Json(out_mode.clone()),
+    }
+  };
+
+  let mut reporter = create_reporter(reporter_kind);
+  let mut thresholds = CoverageThresholds::new(&coverage_flags.thresholds)?;
+
   for script_coverage in script_coverages {
     let module_specifier =
       deno_core::resolve_url_or_path(&script_coverage.url)?;
 
-    let maybe_file = if module_specifier.scheme() == "file" {
-      ps.file_fetcher.get_source(&module_specifier)
-    } else {
-      ps.file_fetcher
-        .fetch_cached(&module_specifier, 10)
-        .with_context(|| {
-          format!("Failed to fetch \"{module_specifier}\" from cache.")
-        })?
-    };
-    let file = maybe_file.ok_or_else(|| {
-      anyhow!("Failed to fetch \"{}\" from cache.
-          Before generating coverage report, run `deno test --coverage` to ensure consistent state.",
-          module_specifier
-        )
-    })?;
-
-    // Check if file was transpiled
-    let original_source = &file.source;
-    let transpiled_code = match file.media_type {
-      MediaType::JavaScript
-      | MediaType::Unknown
-      | MediaType::Cjs
-      | MediaType::Mjs
-      | MediaType::Json => file.source.as_ref().to_string(),
-      MediaType::Dts | MediaType::Dmts | MediaType::Dcts => "".to_string(),
-      MediaType::TypeScript
-      | MediaType::Jsx
-      | MediaType::Mts
-      | MediaType::Cts
-      | MediaType::Tsx => {
-        let source_hash = get_source_hash(&file.source, ps.emit_options_hash);
-        match ps.emit_cache.get_emit_code(&file.specifier, source_hash) {
-          Some(code) => code,
-          None => {
-            return Err(anyhow!(
-              "Missing transpiled source code for: \"{}\".
-              Before generating coverage report, run `deno test --coverage` to ensure consistent state.",
-              file.specifier,
-            ))
-          }
-        }
-      }
-      MediaType::Wasm | MediaType::TsBuildInfo | MediaType::SourceMap => {
-        unreachable!()
-      }
-    };
+    let (original_source, transpiled_code) =
+      load_source(&ps, &module_specifier)?;
 
     let coverage_report = generate_coverage_report(
       &script_coverage,
@@ -713,11 +910,12 @@ pub async fn cover_files(
     );
 
     if !coverage_report.found_lines.is_empty() {
-      reporter.report(&coverage_report, original_source)?;
+      reporter.report(&coverage_report, &original_source)?;
+      thresholds.check(&coverage_report);
     }
   }
 
-  reporter.done();
+  reporter.done()?;
 
-  Ok(())
+  thresholds.report()
 }
@@ -0,0 +1,143 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::relative_file_name;
+use super::write_report;
+use super::CoverageReport;
+use super::CoverageReporter;
+use super::CoverageStats;
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq, Serialize)]
+struct SummaryCount {
+  found: usize,
+  hit: usize,
+  percent: f64,
+}
+
+impl SummaryCount {
+  fn new(hit: usize, found: usize) -> SummaryCount {
+    let percent = if found == 0 {
+      100.0
+    } else {
+      // rounded to two decimals to keep the output compact
+      (hit as f64 / found as f64 * 10000.0).round() / 100.0
+    };
+    SummaryCount {
+      found,
+      hit,
+      percent,
+    }
+  }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct SummaryEntry {
+  lines: SummaryCount,
+  branches: SummaryCount,
+  functions: SummaryCount,
+}
+
+impl SummaryEntry {
+  fn new(stats: &CoverageStats) -> SummaryEntry {
+    SummaryEntry {
+      lines: SummaryCount::new(stats.lines_hit, stats.lines_found),
+      branches: SummaryCount::new(stats.branches_hit, stats.branches_found),
+      functions: SummaryCount::new(stats.functions_hit, stats.functions_found),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+struct SummaryFile {
+  file: String,
+  #[serde(flatten)]
+  summary: SummaryEntry,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+  files: Vec<SummaryFile>,
+  total: SummaryEntry,
+}
+
+/// Writes a JSON object with the line, branch and function coverage of every
+/// file and of all files combined.
+pub struct JsonSummaryCoverageReporter {
+  output: Option<PathBuf>,
+  cwd: PathBuf,
+  files: Vec<(String, CoverageStats)>,
+}
+
+impl JsonSummaryCoverageReporter {
+  pub fn new(output: Option<PathBuf>) -> JsonSummaryCoverageReporter {
+    JsonSummaryCoverageReporter {
+      output,
+      cwd: std::env::current_dir().unwrap(),
+      files: Vec::new(),
+    }
+  }
+}
+
+impl CoverageReporter for JsonSummaryCoverageReporter {
+  fn report(
+    &mut self,
+    coverage_report: &CoverageReport,
+    _file_text: &str,
+  ) -> Result<(), AnyError> {
+    self.files.push((
+      relative_file_name(&self.cwd, &coverage_report.url),
+      CoverageStats::from_report(coverage_report),
+    ));
+    Ok(())
+  }
+
+  fn done(&mut self) -> Result<(), AnyError> {
+    let mut total = CoverageStats::default();
+    for (_, stats) in &self.files {
+      total.add(stats);
+    }
+    let summary = Summary {
+      files: self
+        .files
+        .iter()
+        .map(|(file, stats)| SummaryFile {
+          file: file.clone(),
+          summary: SummaryEntry::new(stats),
+        })
+        .collect(),
+      total: SummaryEntry::new(&total),
+    };
+    let mut json = serde_json::to_string(&summary)?;
+    json.push('\n');
+    write_report(&self.output, &json)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_summary_count() {
+    assert_eq!(
+      SummaryCount::new(0, 0),
+      SummaryCount {
+        found: 0,
+        hit: 0,
+        percent: 100.0
+      }
+    );
+    assert_eq!(
+      SummaryCount::new(2, 3),
+      SummaryCount {
+        found: 3,
+        hit: 2,
+        percent: 66.67
+      }
+    );
+  }
+}
@@ -0,0 +1,155 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::relative_file_name;
+use super::CoverageReport;
+use super::CoverageStats;
+use crate::args::CoverageThresholdFlag;
+use crate::args::CoverageThresholdsFlags;
+use crate::colors;
+use crate::util::path::glob_to_regex;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use regex::Regex;
+use std::path::PathBuf;
+
+struct Threshold {
+  maybe_glob: Option<Regex>,
+  percent: u8,
+}
+
+impl Threshold {
+  fn from_flags(
+    flags: &[CoverageThresholdFlag],
+  ) -> Result<Vec<Threshold>, AnyError> {
+    flags
+      .iter()
+      .map(|flag| {
+        let maybe_glob = match &flag.glob {
+          Some(glob) => Some(glob_to_regex(glob)?),
+          None => None,
+        };
+        Ok(Threshold {
+          maybe_glob,
+          percent: flag.percent,
+        })
+      })
+      .collect()
+  }
+}
+
+struct ThresholdFailure {
+  file: String,
+  kind: &'static str,
+  actual: f64,
+  expected: u8,
+}
+
+/// Checks the coverage of each reported file against the minimums passed
+/// with the `--threshold-*` flags. When several thresholds match a file the
+/// one passed last is used.
+pub struct CoverageThresholds {
+  cwd: PathBuf,
+  lines: Vec<Threshold>,
+  branches: Vec<Threshold>,
+  functions: Vec<Threshold>,
+  failures: Vec<ThresholdFailure>,
+}
+
+impl CoverageThresholds {
+  pub fn new(
+    flags: &CoverageThresholdsFlags,
+  ) -> Result<CoverageThresholds, AnyError> {
+    Ok(CoverageThresholds {
+      cwd: std::env::current_dir()?,
+      lines: Threshold::from_flags(&flags.lines)?,
+      branches: Threshold::from_flags(&flags.branches)?,
+      functions: Threshold::from_flags(&flags.functions)?,
+      failures: Vec::new(),
+    })
+  }
+
+  pub fn check(&mut self, coverage_report: &CoverageReport) {
+    let names = self.file_names(&coverage_report.url);
+    let stats = CoverageStats::from_report(coverage_report);
+
+    let mut failures = Vec::new();
+    for (kind, thresholds, hit, found) in [
+      ("lines", &self.lines, stats.lines_hit, stats.lines_found),
+      (
+        "branches",
+        &self.branches,
+        stats.branches_hit,
+        stats.branches_found,
+      ),
+      (
+        "functions",
+        &self.functions,
+        stats.functions_hit,
+        stats.functions_found,
+      ),
+    ] {
+      // files without anything to cover can't fall below a threshold
+      if found == 0 {
+        continue;
+      }
+      let maybe_threshold =
+        thresholds
+          .iter()
+          .rev()
+          .find(|threshold| match &threshold.maybe_glob {
+            Some(glob) => names.iter().any(|name| glob.is_match(name)),
+            None => true,
+          });
+      if let Some(threshold) = maybe_threshold {
+        let actual = hit as f64 / found as f64 * 100.0;
+        if actual < threshold.percent as f64 {
+          failures.push(ThresholdFailure {
+            file: names[0].clone(),
+            kind,
+            actual,
+            expected: threshold.percent,
+          });
+        }
+      }
+    }
+    self.failures.extend(failures);
+  }
+
+  /// Prints every file that is below its thresholds and returns an error if
+  /// there were any.
+  pub fn report(&self) -> Result<(), AnyError> {
+    if self.failures.is_empty() {
+      return Ok(());
+    }
+
+    eprintln!(
+      "\n{}",
+      colors::red_bold("Coverage is below the threshold in these files:")
+    );
+    for failure in &self.failures {
+      eprintln!(
+        "  {}: {} {} {}",
+        failure.file,
+        failure.kind,
+        colors::red(format!("{:.3}%", failure.actual)),
+        colors::gray(format!("(threshold {}%)", failure.expected)),
+      );
+    }
+    eprintln!();
+    Err(generic_error("Coverage thresholds not met"))
+  }
+
+  /// Returns the names a glob is matched against: the path relative to the
+  /// current directory, which is also used for display, followed by the
+  /// absolute path and the url of the file.
+  fn file_names(&self, url: &Url) -> Vec<String> {
+    let mut names = vec![relative_file_name(&self.cwd, url)];
+    if let Ok(path) = url.to_file_path() {
+      names.push(path.to_string_lossy().replace('\\', "/"));
+    }
+    names.push(url.to_string());
+    names
+  }
+}
@@ -16,7 +16,9 @@ use crate::colors;
 use crate::util::diff::diff;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
+use crate::util::fs::canonicalize_path;
 use crate::util::fs::FileCollector;
+use crate::util::git;
 use crate::util::path::get_extension;
 use crate::util::text_encoding;
 use deno_ast::ParsedSource;
@@ -27,14 +29,19 @@ use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures;
 use deno_core::parking_lot::Mutex;
+use dissimilar::Chunk;
 use log::debug;
 use log::info;
 use log::warn;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs;
 use std::io::stdin;
 use std::io::stdout;
 use std::io::Read;
 use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicUsize;
@@ -43,7 +50,7 @@ use std::sync::Arc;
 
 use crate::cache::IncrementalCache;
 
-/// Format JavaScript/TypeScript files.
+/// Format JavaScript/TypeScript, Markdown, JSON, CSS, YAML and HTML files.
 pub async fn format(
   cli_options: CliOptions,
   fmt_options: FmtOptions,
@@ -52,14 +59,16 @@ pub async fn format(
     return format_stdin(fmt_options);
   }
 
+  let maybe_git_changes = GitChanges::resolve(&fmt_options)?;
   let files = fmt_options.files;
   let check = fmt_options.check;
   let fmt_config_options = fmt_options.options;
+  let unstable = fmt_config_options.unstable;
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
 
-    let result = collect_fmt_files(&files).map(|files| {
+    let result = collect_fmt_files(&files, unstable).map(|files| {
       let refmt_files = if let Some(paths) = changed {
         if check {
           files
@@ -94,17 +103,37 @@ pub async fn format(
     }
   };
   let deno_dir = &cli_options.resolve_deno_dir()?;
+  let maybe_changed_lines = &maybe_git_changes
+    .as_ref()
+    .and_then(|changes| changes.maybe_lines.clone());
   let operation = |(paths, fmt_options): (Vec<PathBuf>, FmtOptionsConfig)| async move {
-    let incremental_cache = Arc::new(IncrementalCache::new(
-      &deno_dir.fmt_incremental_cache_db_file_path(),
-      &fmt_options,
-      &paths,
-    ));
+    let incremental_cache = Arc::new(if maybe_changed_lines.is_some() {
+      // a file where only the changed lines were formatted might still not
+      // be formatted, so it must not be stored as such
+      IncrementalCache::disabled()
+    } else {
+      IncrementalCache::new(
+        &deno_dir.fmt_incremental_cache_db_file_path(),
+        &fmt_options,
+        &paths,
+      )
+    });
     if check {
-      check_source_files(paths, fmt_options, incremental_cache.clone()).await?;
+      check_source_files(
+        paths,
+        fmt_options,
+        incremental_cache.clone(),
+        maybe_changed_lines.clone(),
+      )
+      .await?;
     } else {
-      format_source_files(paths, fmt_options, incremental_cache.clone())
-        .await?;
+      format_source_files(
+        paths,
+        fmt_options,
+        incremental_cache.clone(),
+        maybe_changed_lines.clone(),
+      )
+      .await?;
     }
     incremental_cache.wait_completion().await;
     Ok(())
@@ -121,21 +150,73 @@ pub async fn format(
     )
     .await?;
   } else {
-    let files = collect_fmt_files(&files).and_then(|files| {
+    let files = collect_fmt_files(&files, unstable).and_then(|files| {
       if files.is_empty() {
         Err(generic_error("No target files found."))
       } else {
         Ok(files)
       }
     })?;
+    let files = match &maybe_git_changes {
+      Some(git_changes) => files
+        .into_iter()
+        .filter(|path| git_changes.files.contains(path))
+        .collect(),
+      None => files,
+    };
     operation((files, fmt_config_options)).await?;
   }
 
   Ok(())
 }
 
-fn collect_fmt_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
-  FileCollector::new(is_supported_ext_fmt)
+type ChangedLines = HashMap<PathBuf, Vec<Range<usize>>>;
+
+/// The files, and optionally lines, changed in git that formatting is
+/// restricted to with `--staged` or `--changed-since`.
+struct GitChanges {
+  files: HashSet<PathBuf>,
+  /// The changed lines of each file with `--changed-lines`. Files without
+  /// an entry, like untracked files, are formatted entirely.
+  maybe_lines: Option<Arc<ChangedLines>>,
+}
+
+impl GitChanges {
+  fn resolve(fmt_options: &FmtOptions) -> Result<Option<Self>, AnyError> {
+    let cwd = std::env::current_dir()?;
+    let maybe_git_ref = fmt_options.changed_since.as_deref();
+    let files = match maybe_git_ref {
+      Some(git_ref) => git::changed_files(&cwd, git_ref)?,
+      None if fmt_options.staged => git::staged_files(&cwd)?,
+      None => return Ok(None),
+    };
+    let maybe_lines = if fmt_options.changed_lines {
+      let changed_lines = git::changed_lines(&cwd, maybe_git_ref)?
+        .into_iter()
+        .filter_map(|(path, lines)| {
+          Some((canonicalize_path(&path).ok()?, lines))
+        })
+        .collect();
+      Some(Arc::new(changed_lines))
+    } else {
+      None
+    };
+    Ok(Some(Self {
+      // paths of deleted files fail to canonicalize and are dropped
+      files: files
+        .iter()
+        .filter_map(|path| canonicalize_path(path).ok())
+        .collect(),
+      maybe_lines,
+    }))
+  }
+}
+
+fn collect_fmt_files(
+  files: &FilesConfig,
+  unstable: bool,
+) -> Result<Vec<PathBuf>, AnyError> {
+  FileCollector::new(|path| is_supported_ext_fmt(path, unstable))
     .ignore_git_folder()
     .ignore_node_modules()
     .add_ignore_paths(&files.exclude)
@@ -143,7 +224,7 @@ fn collect_fmt_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
 }
 
 /// Formats markdown (using <https://github.com/dprint/dprint-plugin-markdown>) and its code blocks
-/// (ts/tsx, js/jsx).
+/// (ts/tsx, js/jsx, json/jsonc and, with `--unstable`, css/scss/sass/less, yaml and html).
 fn format_markdown(
   file_text: &str,
   fmt_options: &FmtOptionsConfig,
@@ -168,7 +249,8 @@ fn format_markdown(
           | "typescript"
           | "json"
           | "jsonc"
-      ) {
+      ) || fmt_options.unstable && is_unstable_ext_fmt(&tag)
+      {
         // It's important to tell dprint proper file extension, otherwise
         // it might parse the file twice.
         let extension = match tag.as_str() {
@@ -177,13 +259,30 @@ fn format_markdown(
           rest => rest,
         };
 
+        let fake_filename =
+          PathBuf::from(format!("deno_fmt_stdin.{extension}"));
         if matches!(extension, "json" | "jsonc") {
           let mut json_config = get_resolved_json_config(fmt_options);
           json_config.line_width = line_width;
           dprint_plugin_json::format_text(text, &json_config)
+        } else if matches!(extension, "css" | "scss" | "sass" | "less") {
+          let mut malva_config = get_resolved_malva_config(fmt_options);
+          malva_config.layout.print_width = line_width as usize;
+          format_css_with_config(&fake_filename, text, &malva_config)
+        } else if matches!(extension, "yml" | "yaml") {
+          let mut yaml_config = get_resolved_yaml_config(fmt_options);
+          yaml_config.layout.print_width = line_width as usize;
+          format_yaml_with_config(text, &yaml_config)
+        } else if extension == "html" {
+          let mut markup_config = get_resolved_markup_fmt_config(fmt_options);
+          markup_config.layout.print_width = line_width as usize;
+          format_html_with_config(
+            &fake_filename,
+            text,
+            &markup_config,
+            fmt_options,
+          )
         } else {
-          let fake_filename =
-            PathBuf::from(format!("deno_fmt_stdin.{extension}"));
           let mut codeblock_config =
             get_resolved_typescript_config(fmt_options);
           codeblock_config.line_width = line_width;
@@ -211,7 +310,148 @@ pub fn format_json(
   dprint_plugin_json::format_text(file_text, &config)
 }
 
-/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, or MD file.
+/// Formats CSS, SCSS, Sass and Less using <https://github.com/g-plane/malva>.
+pub fn format_css(
+  file_path: &Path,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let config = get_resolved_malva_config(fmt_options);
+  format_css_with_config(file_path, file_text, &config)
+}
+
+fn format_css_with_config(
+  file_path: &Path,
+  file_text: &str,
+  config: &malva::config::FormatOptions,
+) -> Result<Option<String>, AnyError> {
+  let syntax = malva::detect_syntax(file_path).unwrap_or(malva::Syntax::Css);
+  let formatted_text = malva::format_text(file_text, syntax, config)
+    .map_err(|err| anyhow!("{}", err))?;
+  Ok(changed_text(file_text, formatted_text))
+}
+
+/// Formats YAML using <https://github.com/g-plane/pretty_yaml>.
+pub fn format_yaml(
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let config = get_resolved_yaml_config(fmt_options);
+  format_yaml_with_config(file_text, &config)
+}
+
+fn format_yaml_with_config(
+  file_text: &str,
+  config: &pretty_yaml::config::FormatOptions,
+) -> Result<Option<String>, AnyError> {
+  // the other formatters handle `deno-fmt-ignore-file` themselves, for YAML
+  // look for it in the comments at the top of the file
+  let ignore_file = file_text
+    .lines()
+    .take_while(|line| line.starts_with('#'))
+    .any(|line| line[1..].trim_start().starts_with("deno-fmt-ignore-file"));
+  if ignore_file {
+    return Ok(None);
+  }
+  let formatted_text = pretty_yaml::format_text(file_text, config)
+    .map_err(|err| anyhow!("{}", err))?;
+  Ok(changed_text(file_text, formatted_text))
+}
+
+/// Formats HTML using <https://github.com/g-plane/markup_fmt>, along with
+/// the code of its `<script>` and `<style>` elements.
+pub fn format_html(
+  file_path: &Path,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let config = get_resolved_markup_fmt_config(fmt_options);
+  format_html_with_config(file_path, file_text, &config, fmt_options)
+}
+
+fn format_html_with_config(
+  file_path: &Path,
+  file_text: &str,
+  config: &markup_fmt::config::FormatOptions,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  // markup_fmt has no ignore directives of its own, so look for
+  // `deno-fmt-ignore-file` in a comment at the top of the file
+  let ignore_file = file_text
+    .trim_start()
+    .strip_prefix("<!--")
+    .and_then(|comment| comment.split_once("-->"))
+    .map(|(comment, _)| comment.trim() == "deno-fmt-ignore-file")
+    .unwrap_or(false);
+  if ignore_file {
+    return Ok(None);
+  }
+  let formatted_text = markup_fmt::format_text(
+    file_text,
+    markup_fmt::Language::Html,
+    config,
+    |path, text, print_width| {
+      // It's important to tell the formatters the language of the embedded
+      // code, which is done through the file extension.
+      let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("js");
+      let fake_filename = file_path.with_extension(ext);
+      let result = match ext {
+        "css" | "scss" | "sass" | "less" => {
+          let mut malva_config = get_resolved_malva_config(fmt_options);
+          malva_config.layout.print_width = print_width;
+          format_css_with_config(&fake_filename, text, &malva_config)
+        }
+        "json" | "jsonc" => {
+          let mut json_config = get_resolved_json_config(fmt_options);
+          json_config.line_width = print_width as u32;
+          dprint_plugin_json::format_text(text, &json_config)
+        }
+        _ => {
+          let mut typescript_config =
+            get_resolved_typescript_config(fmt_options);
+          typescript_config.line_width = print_width as u32;
+          dprint_plugin_typescript::format_text(
+            &fake_filename,
+            text,
+            &typescript_config,
+          )
+        }
+      };
+      result.map(|formatted_text| match formatted_text {
+        Some(formatted_text) => Cow::from(formatted_text),
+        None => Cow::from(text),
+      })
+    },
+  )
+  .map_err(|err| match err {
+    markup_fmt::FormatError::Syntax(err) => anyhow!("{}", err),
+    markup_fmt::FormatError::External(errors) => anyhow!(
+      "{}",
+      errors
+        .iter()
+        .map(|err| format!("{err:#}"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+    ),
+  })?;
+  Ok(changed_text(file_text, formatted_text))
+}
+
+/// Unlike the dprint plugins, the CSS, YAML and HTML formatters return the
+/// text even if it didn't change.
+fn changed_text(file_text: &str, formatted_text: String) -> Option<String> {
+  if formatted_text == file_text {
+    None
+  } else {
+    Some(formatted_text)
+  }
+}
+
+/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, MD, CSS, SCSS, Sass, Less,
+/// YAML or HTML file.
 pub fn format_file(
   file_path: &Path,
   file_text: &str,
@@ -225,6 +465,12 @@ pub fn format_file(
     format_markdown(file_text, fmt_options)
   } else if matches!(ext.as_str(), "json" | "jsonc") {
     format_json(file_text, fmt_options)
+  } else if matches!(ext.as_str(), "css" | "scss" | "sass" | "less") {
+    format_css(file_path, file_text, fmt_options)
+  } else if matches!(ext.as_str(), "yml" | "yaml") {
+    format_yaml(file_text, fmt_options)
+  } else if ext == "html" {
+    format_html(file_path, file_text, fmt_options)
   } else {
     let config = get_resolved_typescript_config(fmt_options);
     dprint_plugin_typescript::format_text(file_path, file_text, &config)
@@ -245,6 +491,7 @@ async fn check_source_files(
   paths: Vec<PathBuf>,
   fmt_options: FmtOptionsConfig,
   incremental_cache: Arc<IncrementalCache>,
+  maybe_changed_lines: Option<Arc<ChangedLines>>,
 ) -> Result<(), AnyError> {
   let not_formatted_files_count = Arc::new(AtomicUsize::new(0));
   let checked_files_count = Arc::new(AtomicUsize::new(0));
@@ -264,7 +511,18 @@ async fn check_source_files(
         return Ok(());
       }
 
-      match format_file(&file_path, &file_text, &fmt_options) {
+      let format_result = format_file(&file_path, &file_text, &fmt_options)
+        .map(|maybe_formatted_text| {
+          maybe_formatted_text.and_then(|formatted_text| {
+            keep_changed_lines(
+              &file_path,
+              &file_text,
+              formatted_text,
+              maybe_changed_lines.as_deref(),
+            )
+          })
+        });
+      match format_result {
         Ok(Some(formatted_text)) => {
           not_formatted_files_count.fetch_add(1, Ordering::Relaxed);
           let _g = output_lock.lock();
@@ -326,6 +584,7 @@ async fn format_source_files(
   paths: Vec<PathBuf>,
   fmt_options: FmtOptionsConfig,
   incremental_cache: Arc<IncrementalCache>,
+  maybe_changed_lines: Option<Arc<ChangedLines>>,
 ) -> Result<(), AnyError> {
   let formatted_files_count = Arc::new(AtomicUsize::new(0));
   let checked_files_count = Arc::new(AtomicUsize::new(0));
@@ -343,12 +602,23 @@ async fn format_source_files(
         return Ok(());
       }
 
-      match format_ensure_stable(
+      let format_result = format_ensure_stable(
         &file_path,
         &file_contents.text,
         &fmt_options,
         format_file,
-      ) {
+      )
+      .map(|maybe_formatted_text| {
+        maybe_formatted_text.and_then(|formatted_text| {
+          keep_changed_lines(
+            &file_path,
+            &file_contents.text,
+            formatted_text,
+            maybe_changed_lines.as_deref(),
+          )
+        })
+      });
+      match format_result {
         Ok(Some(formatted_text)) => {
           incremental_cache.update_file(&file_path, &formatted_text);
           write_file_contents(
@@ -393,6 +663,83 @@ async fn format_source_files(
   Ok(())
 }
 
+/// Keeps only the formatting of the changed lines of a file when formatting
+/// is restricted to them. Returns `None` when nothing is left to change.
+fn keep_changed_lines(
+  file_path: &Path,
+  file_text: &str,
+  formatted_text: String,
+  maybe_changed_lines: Option<&ChangedLines>,
+) -> Option<String> {
+  match maybe_changed_lines.and_then(|lines| lines.get(file_path)) {
+    Some(line_ranges) => {
+      let text = format_lines(file_text, &formatted_text, line_ranges);
+      (text != file_text).then_some(text)
+    }
+    None => Some(formatted_text),
+  }
+}
+
+/// Applies the changes between a file's text and its formatted text that
+/// touch the given zero-based line ranges, leaving the other lines as they
+/// are.
+pub fn format_lines(
+  file_text: &str,
+  formatted_text: &str,
+  line_ranges: &[Range<usize>],
+) -> String {
+  let mut line_starts = vec![0];
+  line_starts.extend(file_text.match_indices('\n').map(|(i, _)| i + 1));
+  let byte_ranges = line_ranges
+    .iter()
+    .map(|lines| {
+      let byte_index =
+        |line: usize| line_starts.get(line).copied().unwrap_or(file_text.len());
+      byte_index(lines.start)..byte_index(lines.end)
+    })
+    .collect::<Vec<_>>();
+  let touches_ranges = |edit: Range<usize>| {
+    byte_ranges.iter().any(|range| {
+      if edit.is_empty() {
+        range.start <= edit.start && edit.start < range.end
+      } else {
+        edit.start < range.end && range.start < edit.end
+      }
+    })
+  };
+
+  let chunks = dissimilar::diff(file_text, formatted_text);
+  let mut text = String::with_capacity(formatted_text.len());
+  let mut index = 0;
+  let mut i = 0;
+  while i < chunks.len() {
+    if let Chunk::Equal(s) = chunks[i] {
+      text.push_str(s);
+      index += s.len();
+      i += 1;
+      continue;
+    }
+    // the deletions and insertions up to the next equal chunk replace one
+    // range of the original text
+    let start = index;
+    let mut inserted = String::new();
+    while let Some(chunk) = chunks.get(i) {
+      match chunk {
+        Chunk::Equal(_) => break,
+        Chunk::Delete(s) => index += s.len(),
+        Chunk::Insert(s) => inserted.push_str(s),
+      }
+      i += 1;
+    }
+    if touches_ranges(start..index) {
+      text.push_str(&inserted);
+    } else {
+      text.push_str(&file_text[start..index]);
+    }
+  }
+  text
+}
+
 /// When storing any formatted text in the incremental cache, we want
 /// to ensure that anything stored when formatted will have itself as
 /// the output as well. This is to prevent "double format" issues where
@@ -463,6 +810,11 @@ fn format_stdin(fmt_options: FmtOptions) -> Result<(), AnyError> {
   if stdin().read_to_string(&mut source).is_err() {
     bail!("Failed to read from stdin");
   }
+  if !fmt_options.options.unstable && is_unstable_ext_fmt(&fmt_options.ext) {
+    bail!(
+      "Formatting CSS, YAML and HTML is unstable and requires the --unstable flag"
+    );
+  }
   let file_path = PathBuf::from(format!("_stdin.{}", fmt_options.ext));
   let formatted_text = format_file(&file_path, &source, &fmt_options.options)?;
   if fmt_options.check {
@@ -572,6 +924,60 @@ fn get_resolved_json_config(
   builder.build()
 }
 
+fn get_resolved_malva_config(
+  options: &FmtOptionsConfig,
+) -> malva::config::FormatOptions {
+  let mut config = malva::config::FormatOptions::default();
+
+  config.layout.print_width = options.line_width.unwrap_or(80) as usize;
+  config.layout.use_tabs = options.use_tabs.unwrap_or_default();
+  config.layout.indent_width = options.indent_width.unwrap_or(2) as usize;
+  config.layout.line_break = malva::config::LineBreak::Lf;
+
+  config.language.quotes = match options.single_quote {
+    Some(true) => malva::config::Quotes::PreferSingle,
+    _ => malva::config::Quotes::PreferDouble,
+  };
+  config.language.ignore_comment_directive = "deno-fmt-ignore".into();
+
+  config
+}
+
+fn get_resolved_yaml_config(
+  options: &FmtOptionsConfig,
+) -> pretty_yaml::config::FormatOptions {
+  let mut config = pretty_yaml::config::FormatOptions::default();
+
+  // YAML doesn't allow indenting with tabs, so `useTabs` is ignored
+  config.layout.print_width = options.line_width.unwrap_or(80) as usize;
+  config.layout.indent_width = options.indent_width.unwrap_or(2) as usize;
+  config.layout.line_break = pretty_yaml::config::LineBreak::Lf;
+
+  config.language.quotes = match options.single_quote {
+    Some(true) => pretty_yaml::config::Quotes::PreferSingle,
+    _ => pretty_yaml::config::Quotes::PreferDouble,
+  };
+  config.language.ignore_comment_directive = "deno-fmt-ignore".into();
+
+  config
+}
+
+fn get_resolved_markup_fmt_config(
+  options: &FmtOptionsConfig,
+) -> markup_fmt::config::FormatOptions {
+  let mut config = markup_fmt::config::FormatOptions::default();
+
+  config.layout.print_width = options.line_width.unwrap_or(80) as usize;
+  config.layout.use_tabs = options.use_tabs.unwrap_or_default();
+  config.layout.indent_width = options.indent_width.unwrap_or(2) as usize;
+  config.layout.line_break = markup_fmt::config::LineBreak::Lf;
+
+  config.language.script_indent = true;
+  config.language.style_indent = true;
+
+  config
+}
+
 struct FileContents {
   text: String,
   had_bom: bool,
@@ -654,7 +1060,7 @@ where
 
 /// This function is similar to is_supported_ext but adds additional extensions
 /// supported by `deno fmt`.
-fn is_supported_ext_fmt(path: &Path) -> bool {
+fn is_supported_ext_fmt(path: &Path, unstable: bool) -> bool {
   if let Some(ext) = get_extension(path) {
     matches!(
       ext.as_str(),
@@ -674,41 +1080,94 @@ fn is_supported_ext_fmt(path: &Path) -> bool {
         | "mdwn"
         | "mdown"
         | "markdown"
-    )
+    ) || unstable && is_unstable_ext_fmt(&ext)
   } else {
     false
   }
 }
 
+/// CSS, YAML and HTML files are only formatted with `--unstable`, so that the
+/// files formatted by default stay the same.
+fn is_unstable_ext_fmt(ext: &str) -> bool {
+  matches!(
+    ext,
+    "css" | "scss" | "sass" | "less" | "yml" | "yaml" | "html"
+  )
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
 
+  #[test]
+  fn test_format_lines() {
+    let file_text = "const a=1\nconst b=2\nconst c=3\n";
+    let formatted_text = "const a = 1;\nconst b = 2;\nconst c = 3;\n";
+    assert_eq!(
+      format_lines(file_text, formatted_text, &[1..2]),
+      "const a=1\nconst b = 2;\nconst c=3\n"
+    );
+    assert_eq!(
+      format_lines(file_text, formatted_text, &[0..1, 2..10]),
+      "const a = 1;\nconst b=2\nconst c = 3;\n"
+    );
+    assert_eq!(format_lines(file_text, formatted_text, &[]), file_text);
+  }
+
   #[test]
   fn test_is_supported_ext_fmt() {
-    assert!(!is_supported_ext_fmt(Path::new("tests/subdir/redirects")));
-    assert!(is_supported_ext_fmt(Path::new("README.md")));
-    assert!(is_supported_ext_fmt(Path::new("readme.MD")));
-    assert!(is_supported_ext_fmt(Path::new("readme.mkd")));
-    assert!(is_supported_ext_fmt(Path::new("readme.mkdn")));
-    assert!(is_supported_ext_fmt(Path::new("readme.mdwn")));
-    assert!(is_supported_ext_fmt(Path::new("readme.mdown")));
-    assert!(is_supported_ext_fmt(Path::new("readme.markdown")));
-    assert!(is_supported_ext_fmt(Path::new("lib/typescript.d.ts")));
-    assert!(is_supported_ext_fmt(Path::new("testdata/run/001_hello.js")));
-    assert!(is_supported_ext_fmt(Path::new("testdata/run/002_hello.ts")));
-    assert!(is_supported_ext_fmt(Path::new("foo.jsx")));
-    assert!(is_supported_ext_fmt(Path::new("foo.tsx")));
-    assert!(is_supported_ext_fmt(Path::new("foo.TS")));
-    assert!(is_supported_ext_fmt(Path::new("foo.TSX")));
-    assert!(is_supported_ext_fmt(Path::new("foo.JS")));
-    assert!(is_supported_ext_fmt(Path::new("foo.JSX")));
-    assert!(is_supported_ext_fmt(Path::new("foo.mjs")));
-    assert!(!is_supported_ext_fmt(Path::new("foo.mjsx")));
-    assert!(is_supported_ext_fmt(Path::new("foo.jsonc")));
-    assert!(is_supported_ext_fmt(Path::new("foo.JSONC")));
-    assert!(is_supported_ext_fmt(Path::new("foo.json")));
-    assert!(is_supported_ext_fmt(Path::new("foo.JsON")));
+    assert!(!is_supported_ext_fmt(
+      Path::new("tests/subdir/redirects"),
+      false
+    ));
+    assert!(is_supported_ext_fmt(Path::new("README.md"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.MD"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.mkd"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.mkdn"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.mdwn"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.mdown"), false));
+    assert!(is_supported_ext_fmt(Path::new("readme.markdown"), false));
+    assert!(is_supported_ext_fmt(
+      Path::new("lib/typescript.d.ts"),
+      false
+    ));
+    assert!(is_supported_ext_fmt(
+      Path::new("testdata/run/001_hello.js"),
+      false
+    ));
+    assert!(is_supported_ext_fmt(
+      Path::new("testdata/run/002_hello.ts"),
+      false
+    ));
+    assert!(is_supported_ext_fmt(Path::new("foo.jsx"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.tsx"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.TS"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.TSX"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.JS"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.JSX"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.mjs"), false));
+    assert!(!is_supported_ext_fmt(Path::new("foo.mjsx"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.jsonc"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.JSONC"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.json"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.JsON"), false));
+    assert!(!is_supported_ext_fmt(Path::new("foo.css"), false));
+    assert!(!is_supported_ext_fmt(Path::new("foo.yml"), false));
+    assert!(!is_supported_ext_fmt(Path::new("foo.html"), false));
+    assert!(is_supported_ext_fmt(Path::new("foo.css"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.scss"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.sass"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.less"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.yml"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.YAML"), true));
+    assert!(is_supported_ext_fmt(Path::new("foo.html"), true));
+    assert!(!is_supported_ext_fmt(Path::new("foo.htmlx"), true));
+  }
+
+  #[test]
+  fn test_format_yaml_ignore_file() {
+    let text = "# deno-fmt-ignore-file\nfoo:   bar\n";
+    assert_eq!(format_yaml(text, &Default::default()).unwrap(), None);
   }
 
   #[test]
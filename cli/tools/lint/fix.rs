@@ -0,0 +1,345 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Fixes for the diagnostics of lint rules that have a mechanical fix which
+//! doesn't change the behavior of the code.
+
+use super::create_linter;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_core::error::AnyError;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::rules::LintRule;
+use std::ops::Range;
+
+/// Fixing a diagnostic can uncover another one, so the file is linted again
+/// after the fixes are applied, up to this many times.
+const MAX_FIX_PASSES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFix {
+  /// Byte range of the text to replace.
+  pub range: Range<usize>,
+  pub new_text: String,
+}
+
+/// Lints the source code and applies the fixes of its diagnostics. Returns
+/// the diagnostics that are left, the fixed source code and the number of
+/// applied fixes.
+pub fn lint_with_fixes(
+  file_name: String,
+  media_type: MediaType,
+  mut source_code: String,
+  lint_rules: Vec<&'static dyn LintRule>,
+) -> Result<(Vec<LintDiagnostic>, String, usize), AnyError> {
+  let mut fix_count = 0;
+  let mut pass = 0;
+  loop {
+    let linter = create_linter(media_type, lint_rules.clone());
+    let (parsed_source, diagnostics) =
+      linter.lint(file_name.clone(), source_code.clone())?;
+    pass += 1;
+    let fixes = get_fixes(&parsed_source, &diagnostics);
+    if fixes.is_empty() || pass > MAX_FIX_PASSES {
+      return Ok((diagnostics, source_code, fix_count));
+    }
+    let (fixed_source_code, count) = apply_fixes(&source_code, fixes);
+    source_code = fixed_source_code;
+    fix_count += count;
+  }
+}
+
+/// Gets the fixes of the diagnostics that can be fixed.
+pub fn get_fixes(
+  parsed_source: &ParsedSource,
+  diagnostics: &[LintDiagnostic],
+) -> Vec<LintFix> {
+  let text_info = parsed_source.text_info();
+  let text = text_info.text_str();
+  let ranges_of = |code: &str| {
+    diagnostics
+      .iter()
+      .filter(|d| d.code == code)
+      .map(|d| d.range.start.byte_index..d.range.end.byte_index)
+      .collect::<Vec<_>>()
+  };
+
+  let mut fixes = ranges_of("no-extra-semi")
+    .into_iter()
+    .filter(|range| text.get(range.clone()) == Some(";"))
+    .map(|range| LintFix {
+      range,
+      new_text: String::new(),
+    })
+    .collect::<Vec<_>>();
+
+  let mut collector = FixCollector {
+    text_info,
+    prefer_const: ranges_of("prefer-const"),
+    no_inferrable_types: ranges_of("no-inferrable-types"),
+    fixes: Vec::new(),
+  };
+  if !collector.prefer_const.is_empty()
+    || !collector.no_inferrable_types.is_empty()
+  {
+    parsed_source.program_ref().visit_with(&mut collector);
+  }
+  fixes.extend(collector.fixes);
+  fixes
+}
+
+/// Applies the fixes to the text. Fixes that overlap an already applied fix
+/// are skipped, they are applied in the next pass if still needed. Returns
+/// the fixed text and the number of applied fixes.
+pub fn apply_fixes(text: &str, mut fixes: Vec<LintFix>) -> (String, usize) {
+  fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+  let mut output = String::with_capacity(text.len());
+  let mut last_end = 0;
+  let mut count = 0;
+  for fix in fixes {
+    if fix.range.start < last_end {
+      continue;
+    }
+    output.push_str(&text[last_end..fix.range.start]);
+    output.push_str(&fix.new_text);
+    last_end = fix.range.end;
+    count += 1;
+  }
+  output.push_str(&text[last_end..]);
+  (output, count)
+}
+
+struct FixCollector<'a> {
+  text_info: &'a SourceTextInfo,
+  prefer_const: Vec<Range<usize>>,
+  no_inferrable_types: Vec<Range<usize>>,
+  fixes: Vec<LintFix>,
+}
+
+impl FixCollector<'_> {
+  fn byte_index(&self, pos: SourcePos) -> usize {
+    pos.as_byte_index(self.text_info.range().start)
+  }
+
+  fn byte_range(&self, node: &impl SourceRangedForSpanned) -> Range<usize> {
+    self.byte_index(node.start())..self.byte_index(node.end())
+  }
+
+  /// Removes a type annotation along with its colon.
+  fn remove_type_ann(&mut self, type_ann: &ast::TsTypeAnn) {
+    let text = self.text_info.text_str();
+    let range = self.byte_range(type_ann);
+    let start = if text[range.start..].starts_with(':') {
+      range.start
+    } else {
+      match text[..range.start].trim_end().strip_suffix(':') {
+        Some(before) => before.len(),
+        None => return,
+      }
+    };
+    let start = text[..start].trim_end().len();
+    self.fixes.push(LintFix {
+      range: start..range.end,
+      new_text: String::new(),
+    });
+  }
+
+  /// Removes the type annotation of a binding with an initializer if the
+  /// type can be inferred from it.
+  fn fix_inferrable_type(&mut self, ident: &ast::BindingIdent) {
+    if let Some(type_ann) = &ident.type_ann {
+      let range =
+        self.byte_index(ident.start())..self.byte_index(type_ann.end());
+      if overlaps_any(&self.no_inferrable_types, &range) {
+        self.remove_type_ann(type_ann);
+      }
+    }
+  }
+}
+
+impl Visit for FixCollector<'_> {
+  fn visit_var_decl(&mut self, node: &ast::VarDecl) {
+    // only declarations where every binding is never reassigned can become
+    // `const`, and `const` requires an initializer
+    if node.kind == ast::VarDeclKind::Let
+      && !node.declare
+      && !self.prefer_const.is_empty()
+      && node.decls.iter().all(|decl| decl.init.is_some())
+    {
+      let mut idents = Vec::new();
+      for decl in &node.decls {
+        collect_binding_idents(&decl.name, &mut idents);
+      }
+      let all_reported = !idents.is_empty()
+        && idents.iter().all(|ident| {
+          let range = self.byte_range(*ident);
+          overlaps_any(&self.prefer_const, &range)
+        });
+      let start = self.byte_index(node.start());
+      if all_reported && self.text_info.text_str()[start..].starts_with("let") {
+        self.fixes.push(LintFix {
+          range: start..start + 3,
+          new_text: "const".to_string(),
+        });
+      }
+    }
+    node.visit_children_with(self);
+  }
+
+  fn visit_var_declarator(&mut self, node: &ast::VarDeclarator) {
+    if node.init.is_some() {
+      if let ast::Pat::Ident(ident) = &node.name {
+        self.fix_inferrable_type(ident);
+      }
+    }
+    node.visit_children_with(self);
+  }
+
+  fn visit_assign_pat(&mut self, node: &ast::AssignPat) {
+    if let ast::Pat::Ident(ident) = node.left.as_ref() {
+      self.fix_inferrable_type(ident);
+    }
+    node.visit_children_with(self);
+  }
+
+  fn visit_class_prop(&mut self, node: &ast::ClassProp) {
+    if let (Some(type_ann), Some(_)) = (&node.type_ann, &node.value) {
+      let range =
+        self.byte_index(node.key.start())..self.byte_index(type_ann.end());
+      if overlaps_any(&self.no_inferrable_types, &range) {
+        self.remove_type_ann(type_ann);
+      }
+    }
+    node.visit_children_with(self);
+  }
+}
+
+fn overlaps_any(ranges: &[Range<usize>], range: &Range<usize>) -> bool {
+  ranges
+    .iter()
+    .any(|r| r.start < range.end && range.start < r.end)
+}
+
+fn collect_binding_idents<'a>(
+  pat: &'a ast::Pat,
+  idents: &mut Vec<&'a ast::Ident>,
+) {
+  match pat {
+    ast::Pat::Ident(ident) => idents.push(&ident.id),
+    ast::Pat::Array(array) => {
+      for elem in array.elems.iter().flatten() {
+        collect_binding_idents(elem, idents);
+      }
+    }
+    ast::Pat::Object(object) => {
+      for prop in &object.props {
+        match prop {
+          ast::ObjectPatProp::KeyValue(kv) => {
+            collect_binding_idents(&kv.value, idents)
+          }
+          ast::ObjectPatProp::Assign(assign) => idents.push(&assign.key),
+          ast::ObjectPatProp::Rest(rest) => {
+            collect_binding_idents(&rest.arg, idents)
+          }
+        }
+      }
+    }
+    ast::Pat::Rest(rest) => collect_binding_idents(&rest.arg, idents),
+    ast::Pat::Assign(assign) => collect_binding_idents(&assign.left, idents),
+    ast::Pat::Invalid(_) | ast::Pat::Expr(_) => {}
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_lint::rules::get_recommended_rules;
+
+  fn fix(source_code: &str) -> (String, usize) {
+    let (_, fixed, count) = lint_with_fixes(
+      "file:///a.ts".to_string(),
+      MediaType::TypeScript,
+      source_code.to_string(),
+      get_recommended_rules(),
+    )
+    .unwrap();
+    (fixed, count)
+  }
+
+  #[test]
+  fn test_fix_prefer_const() {
+    assert_eq!(
+      fix(concat!(
+        "let a = { b: 1, c: [2] };\n",
+        "let { b, c: [d] } = a;\n",
+        "console.log(b, d);\n",
+      )),
+      (
+        concat!(
+          "const a = { b: 1, c: [2] };\n",
+          "const { b, c: [d] } = a;\n",
+          "console.log(b, d);\n",
+        )
+        .to_string(),
+        2
+      )
+    );
+    // `a` can't be const because `b` is reassigned
+    let source_code = "let a = 1, b = 2;\nb = 3;\nconsole.log(a, b);\n";
+    assert_eq!(fix(source_code), (source_code.to_string(), 0));
+  }
+
+  #[test]
+  fn test_fix_no_extra_semi() {
+    assert_eq!(
+      fix("console.log(1);;\nclass A {\n  b() {};\n};\nnew A();\n"),
+      (
+        "console.log(1);\nclass A {\n  b() {}\n}\nnew A();\n".to_string(),
+        3
+      )
+    );
+  }
+
+  #[test]
+  fn test_fix_no_inferrable_types() {
+    assert_eq!(
+      fix(concat!(
+        "const a: number = 1;\n",
+        "function b(c : string = \"\") {\n  return c;\n}\n",
+        "class D {\n  e: boolean = true;\n}\n",
+        "console.log(a, b, D);\n",
+      )),
+      (
+        concat!(
+          "const a = 1;\n",
+          "function b(c = \"\") {\n  return c;\n}\n",
+          "class D {\n  e = true;\n}\n",
+          "console.log(a, b, D);\n",
+        )
+        .to_string(),
+        3
+      )
+    );
+  }
+
+  #[test]
+  fn test_apply_fixes_skips_overlapping() {
+    let fixes = vec![
+      LintFix {
+        range: 4..7,
+        new_text: "d".to_string(),
+      },
+      LintFix {
+        range: 0..5,
+        new_text: "e".to_string(),
+      },
+    ];
+    assert_eq!(apply_fixes("abc def", fixes), ("eef".to_string(), 1));
+  }
+}
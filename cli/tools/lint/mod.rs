@@ -13,8 +13,8 @@ use crate::args::LintReporterKind;
 use crate::args::LintRulesConfig;
 use crate::colors;
 use crate::tools::fmt::run_parallelized;
+use crate::util::diff::diff;
 use crate::util::file_watcher;
-use crate::util::file_watcher::ResolutionResult;
 use crate::util::fs::FileCollector;
 use crate::util::path::is_supported_ext;
 use deno_ast::MediaType;
@@ -23,6 +23,7 @@ use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsStackFrame;
 use deno_core::serde_json;
+use deno_core::url::Url;
 use deno_lint::diagnostic::LintDiagnostic;
 use deno_lint::linter::Linter;
 use deno_lint::linter::LinterBuilder;
@@ -35,14 +36,23 @@ use serde::Serialize;
 use std::fs;
 use std::io::stdin;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
+use tokio::sync::mpsc::unbounded_channel;
 
 use crate::cache::IncrementalCache;
 
+mod fix;
+mod plugins;
+
+pub use plugins::is_plugin_ignore_diagnostic;
+pub use plugins::LintPluginHost;
+
 static STDIN_FILE_NAME: &str = "_stdin.ts";
 
 fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
@@ -50,6 +60,8 @@ fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
     LintReporterKind::Pretty => Box::new(PrettyLintReporter::new()),
     LintReporterKind::Json => Box::new(JsonLintReporter::new()),
     LintReporterKind::Compact => Box::new(CompactLintReporter::new()),
+    LintReporterKind::Sarif => Box::new(SarifLintReporter::new()),
+    LintReporterKind::Github => Box::new(GithubLintReporter::new()),
   }
 }
 
@@ -57,6 +69,7 @@ pub async fn lint(
   cli_options: CliOptions,
   lint_options: LintOptions,
 ) -> Result<(), AnyError> {
+  let rules_config = lint_options.rules.clone();
   // Try to get lint rules. If none were set use recommended rules.
   let lint_rules = get_configured_rules(lint_options.rules);
 
@@ -66,33 +79,26 @@ pub async fn lint(
 
   let files = lint_options.files;
   let reporter_kind = lint_options.reporter_kind;
+  let fix_dry_run = lint_options.fix_dry_run;
+  let fix = lint_options.fix || fix_dry_run;
 
-  let resolver = |changed: Option<Vec<PathBuf>>| {
-    let files_changed = changed.is_some();
-    let result = collect_lint_files(&files).map(|files| {
-      if let Some(paths) = changed {
-        files
-          .iter()
-          .any(|path| paths.contains(path))
-          .then_some(files)
-          .unwrap_or_else(|| [].to_vec())
-      } else {
-        files
-      }
-    });
-
-    let paths_to_watch = files.include.clone();
+  if fix && lint_options.is_stdin {
+    return Err(generic_error(
+      "Lint fix on standard input is not supported.",
+    ));
+  }
 
-    async move {
-      if files_changed && matches!(result, Ok(ref files) if files.is_empty()) {
-        ResolutionResult::Ignore
-      } else {
-        ResolutionResult::Restart {
-          paths_to_watch,
-          result,
-        }
-      }
+  let maybe_plugin_host = match cli_options.maybe_config_file_specifier() {
+    Some(config_specifier) if !lint_options.plugins.is_empty() => {
+      let plugin_host = LintPluginHost::new(
+        config_specifier,
+        lint_options.plugins,
+        rules_config,
+      );
+      plugin_host.ready().await?;
+      Some(plugin_host)
     }
+    _ => None,
   };
 
   let has_error = Arc::new(AtomicBool::new(false));
@@ -112,21 +118,76 @@ pub async fn lint(
     let target_files_len = paths.len();
     let reporter_lock =
       Arc::new(Mutex::new(create_reporter(reporter_kind.clone())));
+    let fix_count = Arc::new(AtomicUsize::new(0));
 
     run_parallelized(paths, {
       let has_error = has_error.clone();
       let lint_rules = lint_rules.clone();
       let reporter_lock = reporter_lock.clone();
       let incremental_cache = incremental_cache.clone();
+      let fix_count = fix_count.clone();
+      let maybe_plugin_host = maybe_plugin_host.clone();
       move |file_path| {
         let file_text = fs::read_to_string(&file_path)?;
 
-        // don't bother rechecking this file if it didn't have any diagnostics before
-        if incremental_cache.is_file_same(&file_path, &file_text) {
+        // don't bother rechecking this file if it didn't have any diagnostics
+        // before, unless plugins are used as their code might have changed
+        if maybe_plugin_host.is_none()
+          && incremental_cache.is_file_same(&file_path, &file_text)
+        {
           return Ok(());
         }
 
-        let r = lint_file(&file_path, file_text, lint_rules);
+        let r = if fix {
+          let r = lint_file_with_fixes(
+            &file_path,
+            file_text.clone(),
+            lint_rules.clone(),
+          );
+          if let Ok((_, fixed_text, count)) = &r {
+            if *count > 0 {
+              fix_count.fetch_add(*count, Ordering::Relaxed);
+              if fix_dry_run {
+                let _g = reporter_lock.lock().unwrap();
+                info!("");
+                info!("{} {}:", colors::bold("from"), file_path.display());
+                info!("{}", diff(&file_text, fixed_text));
+              } else {
+                fs::write(&file_path, fixed_text)?;
+              }
+            }
+          }
+          match r {
+            // nothing was written, so report the problems of the file as it
+            // is on disk, including the ones that would be fixed
+            Ok(_) if fix_dry_run => {
+              lint_file(&file_path, file_text, lint_rules)
+            }
+            r => r.map(|(file_diagnostics, fixed_text, _)| {
+              (file_diagnostics, fixed_text)
+            }),
+          }
+        } else {
+          lint_file(&file_path, file_text, lint_rules)
+        };
+        let r = match (r, &maybe_plugin_host) {
+          (Ok((mut file_diagnostics, file_text)), Some(plugin_host)) => {
+            plugin_host
+              .lint_blocking(
+                file_path.to_string_lossy().to_string(),
+                MediaType::from(&file_path),
+                file_text.clone(),
+              )
+              .map(|plugin_diagnostics| {
+                extend_with_plugin_diagnostics(
+                  &mut file_diagnostics,
+                  plugin_diagnostics,
+                );
+                (file_diagnostics, file_text)
+              })
+          }
+          (r, _) => r,
+        };
         if let Ok((file_diagnostics, file_text)) = &r {
           if file_diagnostics.is_empty() {
             // update the incremental cache if there were no diagnostics
@@ -148,6 +209,14 @@ pub async fn lint(
     incremental_cache.wait_completion().await;
     reporter_lock.lock().unwrap().close(target_files_len);
 
+    match (fix_count.load(Ordering::Relaxed), fix_dry_run) {
+      (0, _) => (),
+      (1, false) => info!("Fixed 1 problem"),
+      (n, false) => info!("Fixed {} problems", n),
+      (1, true) => info!("Would fix 1 problem"),
+      (n, true) => info!("Would fix {} problems", n),
+    }
+
     Ok(())
   };
   if cli_options.watch_paths().is_some() {
@@ -156,9 +225,24 @@ pub async fn lint(
         "Lint watch on standard input is not supported.",
       ));
     }
-    file_watcher::watch_func(
-      resolver,
-      operation,
+    // the included paths are watched, so new files are linted as well
+    let (sender, receiver) = unbounded_channel();
+    let mut paths_to_watch = files.include.clone();
+    if paths_to_watch.is_empty() {
+      paths_to_watch.push(PathBuf::from("."));
+    }
+    sender.send(paths_to_watch)?;
+    file_watcher::watch_func2(
+      receiver,
+      |files: FilesConfig| {
+        let operation = &operation;
+        Ok(async move {
+          let target_files = collect_lint_files(&files)?;
+          debug!("Found {} files", target_files.len());
+          operation(target_files).await
+        })
+      },
+      files.clone(),
       file_watcher::PrintConfig {
         job_name: "Lint".to_string(),
         clear_screen: !cli_options.no_clear_screen(),
@@ -168,7 +252,25 @@ pub async fn lint(
   } else {
     if lint_options.is_stdin {
       let reporter_lock = Arc::new(Mutex::new(create_reporter(reporter_kind)));
-      let r = lint_stdin(lint_rules);
+      let r = match (lint_stdin(lint_rules), &maybe_plugin_host) {
+        (Ok((mut file_diagnostics, source_code)), Some(plugin_host)) => {
+          plugin_host
+            .lint(
+              STDIN_FILE_NAME.to_string(),
+              MediaType::TypeScript,
+              source_code.clone(),
+            )
+            .await
+            .map(|plugin_diagnostics| {
+              extend_with_plugin_diagnostics(
+                &mut file_diagnostics,
+                plugin_diagnostics,
+              );
+              (file_diagnostics, source_code)
+            })
+        }
+        (r, _) => r,
+      };
       handle_lint_result(
         STDIN_FILE_NAME,
         r,
@@ -259,6 +361,20 @@ fn lint_file(
   Ok((file_diagnostics, source_code))
 }
 
+/// Lints a file and applies the fixes of its diagnostics to its text.
+/// Returns the diagnostics that are left, the fixed text and the number of
+/// applied fixes.
+fn lint_file_with_fixes(
+  file_path: &Path,
+  source_code: String,
+  lint_rules: Vec<&'static dyn LintRule>,
+) -> Result<(Vec<LintDiagnostic>, String, usize), AnyError> {
+  let file_name = file_path.to_string_lossy().to_string();
+  let media_type = MediaType::from(file_path);
+
+  fix::lint_with_fixes(file_name, media_type, source_code, lint_rules)
+}
+
 /// Lint stdin and write result to stdout.
 /// Treats input as TypeScript.
 /// Compatible with `--json` flag.
@@ -454,24 +570,31 @@ pub fn format_diagnostic(
     }
   }
 
-  let hint = if let Some(hint) = maybe_hint {
-    format!("    {} {}\n", colors::cyan("hint:"), hint)
-  } else {
+  let mut footer_lines = vec![];
+  if let Some(hint) = maybe_hint {
+    footer_lines.push(format!("    {} {}", colors::cyan("hint:"), hint));
+  }
+  // the rules of lint plugins, whose codes contain a slash, aren't documented
+  // on the website
+  if !diagnostic_code.contains('/') {
+    footer_lines.push(format!(
+      "    {} for further information visit https://lint.deno.land/#{}",
+      colors::cyan("help:"),
+      diagnostic_code
+    ));
+  }
+  let footer = if footer_lines.is_empty() {
     "".to_string()
+  } else {
+    format!("\n\n{}", footer_lines.join("\n"))
   };
-  let help = format!(
-    "    {} for further information visit https://lint.deno.land/#{}",
-    colors::cyan("help:"),
-    diagnostic_code
-  );
 
   format!(
-    "{message_line}\n{snippets}\n    at {formatted_location}\n\n{hint}{help}",
+    "{message_line}\n{snippets}\n    at {formatted_location}{footer}",
     message_line = message_line,
     snippets = lines.join("\n"),
     formatted_location = formatted_location,
-    hint = hint,
-    help = help
+    footer = footer
   )
 }
 
@@ -509,6 +632,211 @@ impl LintReporter for JsonLintReporter {
   }
 }
 
+/// Reports diagnostics in the SARIF 2.1.0 format, which is understood by
+/// GitHub code scanning and other static analysis dashboards.
+struct SarifLintReporter {
+  cwd: PathBuf,
+  rule_ids: Vec<String>,
+  results: Vec<serde_json::Value>,
+  notifications: Vec<serde_json::Value>,
+}
+
+impl SarifLintReporter {
+  fn new() -> SarifLintReporter {
+    SarifLintReporter {
+      cwd: std::env::current_dir().unwrap_or_default(),
+      rule_ids: Vec::new(),
+      results: Vec::new(),
+      notifications: Vec::new(),
+    }
+  }
+
+  fn artifact_location(&self, file_path: &str) -> serde_json::Value {
+    match Path::new(file_path).strip_prefix(&self.cwd) {
+      Ok(relative_path) => serde_json::json!({
+        "uri": to_forward_slashes(relative_path),
+        "uriBaseId": "%SRCROOT%",
+      }),
+      Err(_) => match Url::from_file_path(file_path) {
+        Ok(url) => serde_json::json!({ "uri": url.to_string() }),
+        Err(_) => serde_json::json!({ "uri": file_path }),
+      },
+    }
+  }
+
+  fn to_sarif(&self) -> serde_json::Value {
+    let mut original_uri_base_ids = serde_json::Map::new();
+    if let Ok(cwd_url) = Url::from_directory_path(&self.cwd) {
+      original_uri_base_ids.insert(
+        "%SRCROOT%".to_string(),
+        serde_json::json!({ "uri": cwd_url.to_string() }),
+      );
+    }
+    serde_json::json!({
+      "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+      "version": "2.1.0",
+      "runs": [{
+        "tool": {
+          "driver": {
+            "name": "deno lint",
+            "version": crate::version::deno(),
+            "informationUri": "https://lint.deno.land",
+            "rules": self.rule_ids.iter().map(|id| serde_json::json!({
+              "id": id,
+              "helpUri": format!("https://lint.deno.land/#{id}"),
+            })).collect::<Vec<_>>(),
+          },
+        },
+        "originalUriBaseIds": original_uri_base_ids,
+        "invocations": [{
+          "executionSuccessful": self.notifications.is_empty(),
+          "toolExecutionNotifications": self.notifications,
+        }],
+        "results": self.results,
+      }],
+    })
+  }
+}
+
+impl LintReporter for SarifLintReporter {
+  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
+    let rule_index = match self.rule_ids.iter().position(|id| id == &d.code) {
+      Some(index) => index,
+      None => {
+        self.rule_ids.push(d.code.clone());
+        self.rule_ids.len() - 1
+      }
+    };
+    let message = match &d.hint {
+      Some(hint) => format!("{}\n{}", d.message, hint),
+      None => d.message.clone(),
+    };
+    self.results.push(serde_json::json!({
+      "ruleId": d.code,
+      "ruleIndex": rule_index,
+      "level": "error",
+      "message": { "text": message },
+      "locations": [{
+        "physicalLocation": {
+          "artifactLocation": self.artifact_location(&d.filename),
+          "region": {
+            "startLine": d.range.start.line_index + 1,
+            "startColumn": d.range.start.column_index + 1,
+            "endLine": d.range.end.line_index + 1,
+            "endColumn": d.range.end.column_index + 1,
+          },
+        },
+      }],
+    }));
+  }
+
+  fn visit_error(&mut self, file_path: &str, err: &AnyError) {
+    self.notifications.push(serde_json::json!({
+      "level": "error",
+      "message": { "text": err.to_string() },
+      "locations": [{
+        "physicalLocation": {
+          "artifactLocation": self.artifact_location(file_path),
+        },
+      }],
+    }));
+  }
+
+  fn close(&mut self, _check_count: usize) {
+    let json = serde_json::to_string_pretty(&self.to_sarif());
+    println!("{}", json.unwrap());
+  }
+}
+
+/// Reports diagnostics as GitHub Actions workflow commands, which are shown
+/// as annotations on the changed lines of pull requests.
+struct GithubLintReporter {
+  cwd: PathBuf,
+  lint_count: u32,
+}
+
+impl GithubLintReporter {
+  fn new() -> GithubLintReporter {
+    GithubLintReporter {
+      cwd: std::env::current_dir().unwrap_or_default(),
+      lint_count: 0,
+    }
+  }
+
+  fn relative_path(&self, file_path: &str) -> String {
+    match Path::new(file_path).strip_prefix(&self.cwd) {
+      Ok(relative_path) => to_forward_slashes(relative_path),
+      Err(_) => file_path.to_string(),
+    }
+  }
+}
+
+impl LintReporter for GithubLintReporter {
+  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
+    self.lint_count += 1;
+
+    let message = match &d.hint {
+      Some(hint) => format!("{}\n{}", d.message, hint),
+      None => d.message.clone(),
+    };
+    println!(
+      "::error file={},line={},endLine={},col={},endColumn={},title={}::{}",
+      escape_github_property(&self.relative_path(&d.filename)),
+      d.range.start.line_index + 1,
+      d.range.end.line_index + 1,
+      d.range.start.column_index + 1,
+      d.range.end.column_index + 1,
+      escape_github_property(&d.code),
+      escape_github_data(&message),
+    );
+  }
+
+  fn visit_error(&mut self, file_path: &str, err: &AnyError) {
+    println!(
+      "::error file={}::{}",
+      escape_github_property(&self.relative_path(file_path)),
+      escape_github_data(&format!("Error linting: {err}")),
+    );
+  }
+
+  fn close(&mut self, check_count: usize) {
+    match self.lint_count {
+      1 => info!("Found 1 problem"),
+      n if n > 1 => info!("Found {} problems", self.lint_count),
+      _ => (),
+    }
+
+    match check_count {
+      n if n <= 1 => info!("Checked {} file", n),
+      n if n > 1 => info!("Checked {} files", n),
+      _ => unreachable!(),
+    }
+  }
+}
+
+fn to_forward_slashes(path: &Path) -> String {
+  path
+    .components()
+    .map(|c| c.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Escapes the message of a GitHub Actions workflow command.
+fn escape_github_data(text: &str) -> String {
+  text
+    .replace('%', "%25")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
+}
+
+/// Escapes a property value of a GitHub Actions workflow command.
+fn escape_github_property(text: &str) -> String {
+  escape_github_data(text)
+    .replace(':', "%3A")
+    .replace(',', "%2C")
+}
+
 fn sort_diagnostics(diagnostics: &mut [LintDiagnostic]) {
   // Sort so that we guarantee a deterministic output which is useful for tests
   diagnostics.sort_by(|a, b| {
@@ -530,6 +858,17 @@ fn sort_diagnostics(diagnostics: &mut [LintDiagnostic]) {
   });
 }
 
+/// Adds the diagnostics of the lint plugins to the ones of the built-in rules,
+/// dropping the built-in diagnostics about ignore directives for plugin rules.
+fn extend_with_plugin_diagnostics(
+  file_diagnostics: &mut Vec<LintDiagnostic>,
+  plugin_diagnostics: Vec<LintDiagnostic>,
+) {
+  file_diagnostics
+    .retain(|d| !is_plugin_ignore_diagnostic(&d.code, &d.message));
+  file_diagnostics.extend(plugin_diagnostics);
+}
+
 pub fn get_configured_rules(
   rules: LintRulesConfig,
 ) -> Vec<&'static dyn LintRule> {
@@ -573,4 +912,52 @@ mod test {
     recommended_rule_names.sort();
     assert_eq!(rule_names, recommended_rule_names);
   }
+
+  #[test]
+  fn github_escaping() {
+    assert_eq!(
+      escape_github_data("50% of\r\nlines: a, b"),
+      "50%25 of%0D%0Alines: a, b"
+    );
+    assert_eq!(escape_github_property("C:\\a,b.ts"), "C%3A\\a%2Cb.ts");
+  }
+
+  #[test]
+  fn sarif_output() {
+    let cwd = std::env::current_dir().unwrap();
+    let position = |line_index, column_index| deno_lint::diagnostic::Position {
+      line_index,
+      column_index,
+      byte_index: 0,
+    };
+    let mut reporter = SarifLintReporter::new();
+    reporter.visit_diagnostic(
+      &LintDiagnostic {
+        range: deno_lint::diagnostic::Range {
+          start: position(1, 2),
+          end: position(1, 4),
+        },
+        filename: cwd.join("src").join("a.ts").to_string_lossy().to_string(),
+        message: "Empty block statement".to_string(),
+        code: "no-empty".to_string(),
+        hint: None,
+      },
+      vec![],
+    );
+    let sarif = reporter.to_sarif();
+    let run = &sarif["runs"][0];
+    assert_eq!(sarif["version"], "2.1.0");
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "no-empty");
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "no-empty");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["message"]["text"], "Empty block statement");
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/a.ts");
+    assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 3);
+    assert_eq!(location["region"]["endColumn"], 5);
+  }
 }
@@ -0,0 +1,538 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Custom lint rules from the modules in the `lint.plugins` config. The
+//! plugins run in a worker on a dedicated thread, so they can be shared by
+//! the parallel linting of files and by the language server.
+
+use crate::args::ConfigFlag;
+use crate::args::Flags;
+use crate::args::LintRulesConfig;
+use crate::args::TypeCheckMode;
+use crate::proc_state::ProcState;
+use crate::util::path::specifier_to_file_path;
+use crate::worker::create_main_worker;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::common::comments::Comment;
+use deno_ast::swc::common::comments::CommentKind;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::ParseParams;
+use deno_ast::ParsedSource;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::Position;
+use deno_lint::diagnostic::Range;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::create_basic_runtime;
+use deno_runtime::worker::MainWorker;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::thread;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+type LintResult = Result<Vec<LintDiagnostic>, AnyError>;
+
+enum Request {
+  Ready(oneshot::Sender<Result<(), AnyError>>),
+  Lint(LintRequest, oneshot::Sender<LintResult>),
+}
+
+struct LintRequest {
+  file_name: String,
+  media_type: MediaType,
+  source_code: String,
+}
+
+/// A handle to the worker running the lint plugins. Requests are queued and
+/// handled one at a time.
+#[derive(Debug, Clone)]
+pub struct LintPluginHost(mpsc::UnboundedSender<Request>);
+
+impl LintPluginHost {
+  /// Starts loading the plugins. Their rules are enabled by the rules config
+  /// the same way as the built-in rules.
+  pub fn new(
+    config_specifier: ModuleSpecifier,
+    plugins: Vec<ModuleSpecifier>,
+    rules: LintRulesConfig,
+  ) -> Self {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Request>();
+    let _join_handle = thread::spawn(move || {
+      let runtime = create_basic_runtime();
+      runtime.block_on(async {
+        // keep the error as a string, it's sent for every request
+        let mut runner =
+          LintPluginRunner::create(&config_specifier, &plugins, &rules)
+            .await
+            .map_err(|err| format!("Failed loading lint plugins: {err}"));
+        while let Some(request) = rx.recv().await {
+          match request {
+            Request::Ready(tx) => {
+              let result = match &runner {
+                Ok(_) => Ok(()),
+                Err(err) => Err(generic_error(err.clone())),
+              };
+              tx.send(result).ok();
+            }
+            Request::Lint(request, tx) => {
+              let result = match &mut runner {
+                Ok(runner) => runner.lint(request),
+                Err(err) => Err(generic_error(err.clone())),
+              };
+              tx.send(result).ok();
+            }
+          }
+        }
+      })
+    });
+
+    Self(tx)
+  }
+
+  /// Waits for the plugins to be loaded, erroring if any of them failed.
+  pub async fn ready(&self) -> Result<(), AnyError> {
+    let (tx, rx) = oneshot::channel();
+    self
+      .0
+      .send(Request::Ready(tx))
+      .map_err(|_| generic_error("Lint plugin host has stopped."))?;
+    rx.await?
+  }
+
+  pub async fn lint(
+    &self,
+    file_name: String,
+    media_type: MediaType,
+    source_code: String,
+  ) -> LintResult {
+    self.send(file_name, media_type, source_code)?.await?
+  }
+
+  /// Same as `lint`, for callers that run outside of an async runtime.
+  pub fn lint_blocking(
+    &self,
+    file_name: String,
+    media_type: MediaType,
+    source_code: String,
+  ) -> LintResult {
+    self
+      .send(file_name, media_type, source_code)?
+      .blocking_recv()?
+  }
+
+  fn send(
+    &self,
+    file_name: String,
+    media_type: MediaType,
+    source_code: String,
+  ) -> Result<oneshot::Receiver<LintResult>, AnyError> {
+    let (tx, rx) = oneshot::channel();
+    let request = LintRequest {
+      file_name,
+      media_type,
+      source_code,
+    };
+    self
+      .0
+      .send(Request::Lint(request, tx))
+      .map_err(|_| generic_error("Lint plugin host has stopped."))?;
+    Ok(rx)
+  }
+}
+
+/// Gets if a diagnostic of the built-in rules is about an ignore directive
+/// for a plugin rule, which the built-in rules don't know. Only the codes of
+/// plugin rules contain a slash.
+pub fn is_plugin_ignore_diagnostic(code: &str, message: &str) -> bool {
+  matches!(code, "ban-unknown-rule-code" | "ban-unused-ignore")
+    && message.contains('/')
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginRuleInfo {
+  code: String,
+  tags: Vec<String>,
+}
+
+/// Gets if a plugin rule is enabled by the rules config, the same way as the
+/// built-in rules are: by its tags, or by being included, unless excluded.
+fn is_rule_enabled(rule: &PluginRuleInfo, rules: &LintRulesConfig) -> bool {
+  if rules
+    .exclude
+    .iter()
+    .flatten()
+    .any(|code| *code == rule.code)
+  {
+    return false;
+  }
+  if rules
+    .include
+    .iter()
+    .flatten()
+    .any(|code| *code == rule.code)
+  {
+    return true;
+  }
+  match &rules.tags {
+    Some(tags) => rule.tags.iter().any(|tag| tags.contains(tag)),
+    None => rule.tags.iter().any(|tag| tag == "recommended"),
+  }
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginDiagnostic {
+  code: String,
+  message: String,
+  hint: Option<String>,
+  start: usize,
+  end: usize,
+}
+
+struct LintPluginRunner {
+  worker: MainWorker,
+  run_plugins_fn: v8::Global<v8::Function>,
+  /// The codes of the enabled rules.
+  codes: Vec<String>,
+}
+
+impl LintPluginRunner {
+  async fn create(
+    config_specifier: &ModuleSpecifier,
+    plugins: &[ModuleSpecifier],
+    rules: &LintRulesConfig,
+  ) -> Result<Self, AnyError> {
+    let config_path = specifier_to_file_path(config_specifier)?;
+    let flags = Flags {
+      config_flag: ConfigFlag::Path(config_path.to_string_lossy().to_string()),
+      type_check_mode: TypeCheckMode::None,
+      ..Default::default()
+    };
+    let ps = ProcState::build(flags).await?;
+    // plugins only look at the code, so they don't get any permissions
+    let permissions = PermissionsContainer::new(Permissions::from_options(
+      &Default::default(),
+    )?);
+    let mut worker = create_main_worker(&ps, plugins[0].clone(), permissions)
+      .await?
+      .into_main_worker();
+
+    let (install_plugin_fn, run_plugins_fn) = {
+      let scope = &mut worker.js_runtime.handle_scope();
+      let install_plugin_fn = JsRuntime::eval::<v8::Function>(
+        scope,
+        "Deno[Deno.internal].lint.installPlugin",
+      )
+      .unwrap();
+      let run_plugins_fn = JsRuntime::eval::<v8::Function>(
+        scope,
+        "Deno[Deno.internal].lint.runPlugins",
+      )
+      .unwrap();
+      (
+        v8::Global::new(scope, install_plugin_fn),
+        v8::Global::new(scope, run_plugins_fn),
+      )
+    };
+
+    let mut codes = Vec::new();
+    for specifier in plugins {
+      let id = worker.preload_side_module(specifier).await?;
+      worker.evaluate_module(id).await?;
+      let namespace = worker.js_runtime.get_module_namespace(id)?;
+      let scope = &mut worker.js_runtime.handle_scope();
+      let specifier = serde_v8::to_v8(scope, specifier.as_str())?;
+      let namespace = v8::Local::new(scope, namespace);
+      let plugin_rules: Vec<PluginRuleInfo> = call_function(
+        scope,
+        &install_plugin_fn,
+        &[specifier, namespace.into()],
+      )?;
+      for rule in plugin_rules {
+        if is_rule_enabled(&rule, rules) {
+          log::debug!("Loaded lint rule: {}", rule.code);
+          codes.push(rule.code);
+        }
+      }
+    }
+
+    Ok(Self {
+      worker,
+      run_plugins_fn,
+      codes,
+    })
+  }
+
+  fn lint(&mut self, request: LintRequest) -> LintResult {
+    let parsed_source = deno_ast::parse_program(ParseParams {
+      specifier: request.file_name.clone(),
+      text_info: SourceTextInfo::new(request.source_code.into()),
+      media_type: request.media_type,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })?;
+    let ignore_directives = IgnoreDirectives::parse(&parsed_source);
+    if ignore_directives.ignores_file() {
+      return Ok(Vec::new());
+    }
+    let text_info = parsed_source.text_info();
+    let start_pos = text_info.range().start.as_byte_pos().0;
+
+    let diagnostics: Vec<PluginDiagnostic> = {
+      let scope = &mut self.worker.js_runtime.handle_scope();
+      let args = [
+        serde_v8::to_v8(scope, &request.file_name)?,
+        serde_v8::to_v8(scope, text_info.text_str())?,
+        serde_v8::to_v8(scope, parsed_source.program_ref())?,
+        serde_v8::to_v8(scope, start_pos)?,
+        serde_v8::to_v8(scope, &self.codes)?,
+      ];
+      call_function(scope, &self.run_plugins_fn, &args)?
+    };
+
+    let text = text_info.text_str();
+    Ok(
+      diagnostics
+        .into_iter()
+        .map(|d| LintDiagnostic {
+          range: Range {
+            start: position(text, d.start),
+            end: position(text, d.end),
+          },
+          filename: request.file_name.clone(),
+          message: d.message,
+          code: d.code,
+          hint: d.hint,
+        })
+        .filter(|d| !ignore_directives.ignores(d))
+        .collect(),
+    )
+  }
+}
+
+/// The `// deno-lint-ignore-file` and `// deno-lint-ignore` directives of a
+/// file, which apply to the plugin rules the same way as to the built-in
+/// ones.
+struct IgnoreDirectives {
+  /// The codes of the file directive, where no codes ignore all rules.
+  maybe_file_codes: Option<Vec<String>>,
+  /// The codes of the line directives by their line index.
+  line_codes: HashMap<usize, Vec<String>>,
+}
+
+impl IgnoreDirectives {
+  fn parse(parsed_source: &ParsedSource) -> Self {
+    let comments = parsed_source.comments();
+    // the file directive needs to be a leading comment of the file, which
+    // comes after the shebang when there's one
+    let program_start = parsed_source.program_ref().start();
+    let maybe_first_item_start = match parsed_source.program_ref() {
+      ast::Program::Module(module) => module.body.first().map(|i| i.start()),
+      ast::Program::Script(script) => script.body.first().map(|s| s.start()),
+    };
+    let maybe_file_codes = [Some(program_start), maybe_first_item_start]
+      .into_iter()
+      .flatten()
+      .filter_map(|pos| comments.get_leading(pos))
+      .flatten()
+      .find_map(|comment| {
+        parse_ignore_comment("deno-lint-ignore-file", comment)
+      });
+
+    let text_info = parsed_source.text_info();
+    let line_codes = comments
+      .get_vec()
+      .iter()
+      .filter_map(|comment| {
+        let codes = parse_ignore_comment("deno-lint-ignore", comment)?;
+        Some((text_info.line_index(comment.start()), codes))
+      })
+      .collect();
+
+    Self {
+      maybe_file_codes,
+      line_codes,
+    }
+  }
+
+  fn ignores_file(&self) -> bool {
+    matches!(&self.maybe_file_codes, Some(codes) if codes.is_empty())
+  }
+
+  /// Gets if a diagnostic is ignored by the file directive or by a line
+  /// directive on the line before it.
+  fn ignores(&self, diagnostic: &LintDiagnostic) -> bool {
+    if let Some(codes) = &self.maybe_file_codes {
+      if codes.contains(&diagnostic.code) {
+        return true;
+      }
+    }
+    let line_index = diagnostic.range.start.line_index;
+    line_index > 0
+      && self
+        .line_codes
+        .get(&(line_index - 1))
+        .map(|codes| codes.contains(&diagnostic.code))
+        .unwrap_or(false)
+  }
+}
+
+/// Parses the codes of an ignore directive line comment, which are separated
+/// by commas or whitespace.
+fn parse_ignore_comment(
+  directive: &str,
+  comment: &Comment,
+) -> Option<Vec<String>> {
+  if comment.kind != CommentKind::Line {
+    return None;
+  }
+  let text = comment.text.trim();
+  if text.split_whitespace().next()? != directive {
+    return None;
+  }
+  Some(
+    text[directive.len()..]
+      .split(|c: char| c == ',' || c.is_whitespace())
+      .filter(|code| !code.is_empty())
+      .map(|code| code.to_string())
+      .collect(),
+  )
+}
+
+fn call_function<'s, T: DeserializeOwned>(
+  scope: &mut v8::HandleScope<'s>,
+  function: &v8::Global<v8::Function>,
+  args: &[v8::Local<'s, v8::Value>],
+) -> Result<T, AnyError> {
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  let function = v8::Local::new(tc_scope, function);
+  let this = v8::undefined(tc_scope).into();
+  match function.call(tc_scope, this, args) {
+    Some(value) => Ok(serde_v8::from_v8(tc_scope, value)?),
+    None => {
+      let exception = tc_scope.exception().unwrap();
+      Err(JsError::from_v8_exception(tc_scope, exception).into())
+    }
+  }
+}
+
+/// Gets the position of a byte index reported by a plugin, clamped to the
+/// text.
+fn position(text: &str, byte_index: usize) -> Position {
+  let mut byte_index = byte_index.min(text.len());
+  while !text.is_char_boundary(byte_index) {
+    byte_index -= 1;
+  }
+  let line_start = text[..byte_index].rfind('\n').map_or(0, |i| i + 1);
+  Position {
+    line_index: text[..line_start].matches('\n').count(),
+    column_index: text[line_start..byte_index].chars().count(),
+    byte_index,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_position() {
+    let text = "const a = 1;\nconst b = \"é\";\n";
+    let p = position(text, 6);
+    assert_eq!((p.line_index, p.column_index, p.byte_index), (0, 6, 6));
+    let p = position(text, 26);
+    assert_eq!((p.line_index, p.column_index, p.byte_index), (1, 12, 26));
+    // inside of a multi-byte character
+    let p = position(text, 25);
+    assert_eq!((p.line_index, p.column_index, p.byte_index), (1, 11, 24));
+    let p = position(text, 100);
+    assert_eq!((p.line_index, p.column_index, p.byte_index), (2, 0, 29));
+  }
+
+  #[test]
+  fn test_ignore_directives() {
+    let source = r#"#!/usr/bin/env -S deno run
+// deno-lint-ignore-file team/a
+// deno-lint-ignore team/b, team/c
+console.log(1);
+console.log(2);
+"#;
+    let parsed_source = deno_ast::parse_program(ParseParams {
+      specifier: "file:///main.ts".to_string(),
+      text_info: SourceTextInfo::new(source.into()),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let ignore_directives = IgnoreDirectives::parse(&parsed_source);
+    assert!(!ignore_directives.ignores_file());
+    let diagnostic = |code: &str, line_index: usize| {
+      let position = Position {
+        line_index,
+        column_index: 0,
+        byte_index: 0,
+      };
+      LintDiagnostic {
+        range: Range {
+          start: position,
+          end: position,
+        },
+        filename: "main.ts".to_string(),
+        message: "message".to_string(),
+        code: code.to_string(),
+        hint: None,
+      }
+    };
+    assert!(ignore_directives.ignores(&diagnostic("team/a", 4)));
+    assert!(ignore_directives.ignores(&diagnostic("team/b", 3)));
+    assert!(ignore_directives.ignores(&diagnostic("team/c", 3)));
+    assert!(!ignore_directives.ignores(&diagnostic("team/b", 4)));
+    assert!(!ignore_directives.ignores(&diagnostic("team/d", 3)));
+  }
+
+  #[test]
+  fn test_is_rule_enabled() {
+    let rule = |code: &str, tags: &[&str]| PluginRuleInfo {
+      code: code.to_string(),
+      tags: tags.iter().map(|t| t.to_string()).collect(),
+    };
+    let config = |tags: Option<&[&str]>, include: &[&str], exclude: &[&str]| {
+      let to_vec = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+      LintRulesConfig {
+        tags: tags.map(to_vec),
+        include: Some(to_vec(include)),
+        exclude: Some(to_vec(exclude)),
+      }
+    };
+    let recommended = rule("team/a", &["recommended"]);
+    let strict = rule("team/b", &["strict"]);
+    assert!(is_rule_enabled(&recommended, &LintRulesConfig::default()));
+    assert!(!is_rule_enabled(&strict, &LintRulesConfig::default()));
+    assert!(is_rule_enabled(
+      &strict,
+      &config(Some(&["strict"]), &[], &[])
+    ));
+    assert!(!is_rule_enabled(
+      &recommended,
+      &config(Some(&["strict"]), &[], &[])
+    ));
+    assert!(is_rule_enabled(&strict, &config(None, &["team/b"], &[])));
+    assert!(!is_rule_enabled(
+      &recommended,
+      &config(None, &[], &["team/a"])
+    ));
+  }
+}
@@ -2,6 +2,7 @@
 
 pub mod bench;
 pub mod bundle;
+pub mod cache_archive;
 pub mod check;
 pub mod coverage;
 pub mod doc;
@@ -10,6 +11,7 @@ pub mod info;
 pub mod init;
 pub mod installer;
 pub mod lint;
+pub mod outdated;
 pub mod repl;
 pub mod run;
 pub mod standalone;
@@ -0,0 +1,769 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! `deno outdated` and `deno update`, which look for newer versions of the
+//! versioned `npm:` specifiers and module registry urls of the source files,
+//! the configuration file and the import map, and of the dependencies of the
+//! package.json.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::ModuleSpecifier;
+use deno_graph::npm::NpmPackageReq;
+use deno_graph::semver::Version;
+use deno_graph::semver::VersionReq;
+use deno_runtime::deno_node::PackageJson;
+use deno_runtime::permissions::PermissionsContainer;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::args::FileFlags;
+use crate::args::Flags;
+use crate::args::OutdatedFlags;
+use crate::args::TypeCheckMode;
+use crate::args::UpdateFlags;
+use crate::colors;
+use crate::lsp::ModuleRegistry;
+use crate::npm::NpmPackageInfo;
+use crate::npm::NpmResolutionSnapshot;
+use crate::proc_state::ProcState;
+use crate::util::fs::canonicalize_path;
+use crate::util::fs::FileCollector;
+use crate::util::path::is_supported_ext;
+use crate::util::path::specifier_to_file_path;
+
+static NPM_SPECIFIER_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r#"["'`]npm:((?:@[^/"'`\s]+/)?[^@/"'`\s]+)@([^/"'`\s]+)"#).unwrap()
+});
+static REMOTE_SPECIFIER_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r#"["'`](https?://[^/"'`\s]+/[^"'`\s]*@[^"'`\s]*)["'`]"#).unwrap()
+});
+static VERSION_REQ_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^([\^~=]?)(\d+)(\.\d+)?(\.\d+)?(-[0-9A-Za-z.-]+)?$").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum DependencyKind {
+  /// An `npm:` specifier.
+  Npm,
+  /// A dependency of the package.json.
+  PackageJson,
+  /// A module of a registry, like `https://deno.land/x`.
+  Registry,
+}
+
+/// A versioned dependency, which might be written in several places.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Dependency {
+  kind: DependencyKind,
+  /// The name of the npm package, or the url of the module before the
+  /// version.
+  name: String,
+  /// The version, or version requirement, as written.
+  version: String,
+}
+
+impl Dependency {
+  fn display_name(&self) -> String {
+    match self.kind {
+      DependencyKind::Npm => format!("npm:{}", self.name),
+      DependencyKind::PackageJson | DependencyKind::Registry => {
+        self.name.clone()
+      }
+    }
+  }
+}
+
+/// The range of the version of a dependency in a file.
+#[derive(Debug, Clone)]
+struct Location {
+  path: PathBuf,
+  range: Range<usize>,
+}
+
+/// The versions that are newer than the current version of a dependency.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct NewerVersions {
+  /// The version that is currently used.
+  current: String,
+  /// The newest semver compatible version.
+  compatible: Option<String>,
+  latest: Option<String>,
+}
+
+struct OutdatedDependency {
+  dependency: Dependency,
+  locations: Vec<Location>,
+  versions: NewerVersions,
+}
+
+pub async fn outdated(
+  flags: Flags,
+  outdated_flags: OutdatedFlags,
+) -> Result<(), AnyError> {
+  let ps = ProcState::build(with_fresh_npm_info(flags)).await?;
+  let outdated = find_outdated_dependencies(&ps, &outdated_flags.files).await?;
+  if outdated.is_empty() {
+    log::info!("All dependencies are up to date.");
+    return Ok(());
+  }
+
+  let rows = outdated
+    .iter()
+    .map(|o| {
+      [
+        o.dependency.display_name(),
+        o.versions.current.clone(),
+        o.versions
+          .compatible
+          .clone()
+          .unwrap_or_else(|| "-".to_string()),
+        o.versions.latest.clone().unwrap_or_else(|| "-".to_string()),
+      ]
+    })
+    .collect::<Vec<_>>();
+  let header = ["Dependency", "Current", "Compatible", "Latest"];
+  let mut widths = header.map(|h| h.len());
+  for row in &rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+  let format_row = |cells: [&str; 4]| {
+    cells
+      .iter()
+      .zip(widths)
+      .map(|(cell, width)| format!("{cell:width$}"))
+      .collect::<Vec<_>>()
+      .join("  ")
+      .trim_end()
+      .to_string()
+  };
+  println!("{}", colors::bold(format_row(header)));
+  for row in &rows {
+    println!("{}", format_row([&row[0], &row[1], &row[2], &row[3]]));
+  }
+  Ok(())
+}
+
+pub async fn update(
+  flags: Flags,
+  update_flags: UpdateFlags,
+) -> Result<(), AnyError> {
+  let ps = ProcState::build(with_fresh_npm_info(flags.clone())).await?;
+  let outdated = find_outdated_dependencies(&ps, &update_flags.files).await?;
+
+  let mut edits = BTreeMap::<PathBuf, Vec<(Range<usize>, String)>>::new();
+  let mut updated = Vec::new();
+  for o in &outdated {
+    let maybe_version = if update_flags.latest {
+      o.versions
+        .latest
+        .as_ref()
+        .or(o.versions.compatible.as_ref())
+    } else {
+      o.versions.compatible.as_ref()
+    };
+    let version = match maybe_version {
+      Some(version) => version,
+      None => continue,
+    };
+    let new_version_text = match o.dependency.kind {
+      DependencyKind::Npm | DependencyKind::PackageJson => {
+        match update_version_req(&o.dependency.version, version) {
+          Some(text) => text,
+          None => continue,
+        }
+      }
+      DependencyKind::Registry => version.clone(),
+    };
+    if new_version_text == o.dependency.version {
+      continue;
+    }
+    log::info!(
+      "{} {} {} -> {}",
+      if update_flags.dry_run {
+        colors::yellow("Would update").to_string()
+      } else {
+        colors::green("Update").to_string()
+      },
+      o.dependency.display_name(),
+      o.dependency.version,
+      new_version_text,
+    );
+    for location in &o.locations {
+      edits
+        .entry(location.path.clone())
+        .or_default()
+        .push((location.range.clone(), new_version_text.clone()));
+    }
+    updated.push(&o.dependency);
+  }
+
+  if updated.is_empty() {
+    log::info!("All dependencies are up to date.");
+    return Ok(());
+  }
+  if update_flags.dry_run {
+    return Ok(());
+  }
+
+  for (path, file_edits) in &edits {
+    let mut file_edits = file_edits.clone();
+    let mut text = std::fs::read_to_string(path)
+      .with_context(|| format!("Failed reading {}", path.display()))?;
+    // apply the edits from the end so the earlier ranges stay the same
+    file_edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, new_text) in file_edits {
+      text.replace_range(range, &new_text);
+    }
+    std::fs::write(path, text)
+      .with_context(|| format!("Failed writing {}", path.display()))?;
+  }
+
+  if let Some(lockfile) = &ps.lockfile {
+    {
+      let mut lockfile = lockfile.lock();
+      for dependency in &updated {
+        match dependency.kind {
+          DependencyKind::Npm | DependencyKind::PackageJson => lockfile
+            .remove_npm_specifier(&format!(
+              "{}@{}",
+              dependency.name, dependency.version
+            )),
+          DependencyKind::Registry => lockfile.remove_remote_with_prefix(
+            &format!("{}@{}/", dependency.name, dependency.version),
+          ),
+        }
+      }
+      lockfile.write()?;
+    }
+    let roots = edits
+      .keys()
+      .filter(|path| is_supported_ext(path))
+      .map(|path| resolve_url_or_path(&path.to_string_lossy()))
+      .collect::<Result<Vec<_>, _>>()?;
+    relock(flags, roots).await?;
+  }
+  Ok(())
+}
+
+/// Loads the updated dependencies to add them to the lockfile.
+async fn relock(
+  mut flags: Flags,
+  roots: Vec<ModuleSpecifier>,
+) -> Result<(), AnyError> {
+  flags.type_check_mode = TypeCheckMode::None;
+  let ps = ProcState::build(flags).await?;
+  ps.package_json_deps_installer
+    .ensure_top_level_install()
+    .await?;
+  ps.npm_resolver.resolve_pending().await?;
+  if !roots.is_empty() {
+    ps.prepare_module_load(
+      roots,
+      false,
+      ps.options.ts_type_lib_window(),
+      PermissionsContainer::allow_all(),
+      PermissionsContainer::allow_all(),
+    )
+    .await?;
+  }
+  if let Some(lockfile) = &ps.lockfile {
+    lockfile.lock().write()?;
+  }
+  Ok(())
+}
+
+/// Always gets the latest information of the npm packages instead of the
+/// cached one.
+fn with_fresh_npm_info(mut flags: Flags) -> Flags {
+  flags.cache_blocklist = vec!["npm:".to_string()];
+  flags
+}
+
+async fn find_outdated_dependencies(
+  ps: &ProcState,
+  files: &FileFlags,
+) -> Result<Vec<OutdatedDependency>, AnyError> {
+  let mut dependencies = BTreeMap::<Dependency, Vec<Location>>::new();
+  let mut remote_urls = Vec::new();
+  for path in collect_dependency_files(ps, files)? {
+    let text = std::fs::read_to_string(&path)
+      .with_context(|| format!("Failed reading {}", path.display()))?;
+    for captures in NPM_SPECIFIER_RE.captures_iter(&text) {
+      let name = captures.get(1).unwrap();
+      let version = captures.get(2).unwrap();
+      dependencies
+        .entry(Dependency {
+          kind: DependencyKind::Npm,
+          name: name.as_str().to_string(),
+          version: version.as_str().to_string(),
+        })
+        .or_default()
+        .push(Location {
+          path: path.clone(),
+          range: version.range(),
+        });
+    }
+    for captures in REMOTE_SPECIFIER_RE.captures_iter(&text) {
+      let url = captures.get(1).unwrap();
+      remote_urls.push((path.clone(), url.start(), url.as_str().to_string()));
+    }
+  }
+  if let Some(package_json) = ps.options.maybe_package_json() {
+    if package_json.exists {
+      collect_package_json_dependencies(package_json, &mut dependencies)?;
+    }
+  }
+  let registry_versions =
+    collect_registry_dependencies(ps, remote_urls, &mut dependencies).await?;
+
+  let npm_snapshot = ps.npm_resolver.snapshot();
+  let mut outdated = Vec::new();
+  for (dependency, locations) in dependencies {
+    let maybe_versions = match dependency.kind {
+      DependencyKind::Npm | DependencyKind::PackageJson => {
+        let package_info =
+          ps.npm_resolver.api().package_info(&dependency.name).await?;
+        npm_newer_versions(
+          &dependency.version,
+          resolved_npm_version(&npm_snapshot, &dependency),
+          &package_info,
+        )
+      }
+      DependencyKind::Registry => registry_versions
+        .get(&dependency.name)
+        .and_then(|versions| {
+          registry_newer_versions(&dependency.version, versions)
+        }),
+    };
+    if let Some(versions) = maybe_versions {
+      outdated.push(OutdatedDependency {
+        dependency,
+        locations,
+        versions,
+      });
+    }
+  }
+  Ok(outdated)
+}
+
+/// Collects the source files, along with the configuration file and the
+/// import map when they are local.
+fn collect_dependency_files(
+  ps: &ProcState,
+  files: &FileFlags,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let mut paths = FileCollector::new(is_supported_ext)
+    .ignore_git_folder()
+    .ignore_node_modules()
+    .add_ignore_paths(&files.ignore)
+    .collect_files(&files.include)?;
+  let maybe_config_specifier = ps
+    .options
+    .maybe_config_file()
+    .as_ref()
+    .map(|c| c.specifier.clone());
+  let maybe_import_map_specifier = ps.options.resolve_import_map_specifier()?;
+  for specifier in maybe_config_specifier
+    .into_iter()
+    .chain(maybe_import_map_specifier)
+  {
+    if specifier.scheme() != "file" {
+      continue;
+    }
+    let path = canonicalize_path(&specifier_to_file_path(&specifier)?)?;
+    if !paths.contains(&path) {
+      paths.push(path);
+    }
+  }
+  Ok(paths)
+}
+
+/// Collects the `dependencies` and `devDependencies` of a package.json, which
+/// aren't aliases, workspace or file dependencies.
+fn collect_package_json_dependencies(
+  package_json: &PackageJson,
+  dependencies: &mut BTreeMap<Dependency, Vec<Location>>,
+) -> Result<(), AnyError> {
+  let path = &package_json.path;
+  let text = std::fs::read_to_string(path)
+    .with_context(|| format!("Failed reading {}", path.display()))?;
+  for (name, version, range) in package_json_version_ranges(&text, package_json)
+  {
+    dependencies
+      .entry(Dependency {
+        kind: DependencyKind::PackageJson,
+        name: name.to_string(),
+        version: version.to_string(),
+      })
+      .or_default()
+      .push(Location {
+        path: path.clone(),
+        range,
+      });
+  }
+  Ok(())
+}
+
+/// Finds the ranges of the versions of the parsed package.json dependencies
+/// in its text. Only the versions that are written without escapes are
+/// returned, so that the range can be replaced with a new version.
+fn package_json_version_ranges<'a>(
+  text: &str,
+  package_json: &'a PackageJson,
+) -> Vec<(&'a str, &'a str, Range<usize>)> {
+  use jsonc_parser::ast::Value;
+  let ast = match jsonc_parser::parse_to_ast(
+    text,
+    &Default::default(),
+    &Default::default(),
+  ) {
+    Ok(ast) => ast,
+    Err(err) => {
+      log::debug!("Failed parsing {}: {}", package_json.path.display(), err);
+      return Vec::new();
+    }
+  };
+  let obj = match ast.value {
+    Some(Value::Object(obj)) => obj,
+    _ => return Vec::new(),
+  };
+
+  let mut ranges = Vec::new();
+  let fields = [
+    ("dependencies", &package_json.dependencies),
+    ("devDependencies", &package_json.dev_dependencies),
+  ];
+  for (field, maybe_deps) in fields {
+    let (deps_obj, deps) = match (obj.get_object(field), maybe_deps) {
+      (Some(deps_obj), Some(deps)) => (deps_obj, deps),
+      _ => continue,
+    };
+    for (name, version) in deps {
+      if !VERSION_REQ_RE.is_match(version) {
+        continue;
+      }
+      // the parsed package.json keeps the last of duplicate keys
+      let maybe_lit = deps_obj
+        .properties
+        .iter()
+        .rev()
+        .find(|prop| prop.name.as_str() == name)
+        .and_then(|prop| match &prop.value {
+          Value::StringLit(lit) => Some(lit),
+          _ => None,
+        });
+      if let Some(lit) = maybe_lit {
+        let range = lit.range.start + 1..lit.range.end - 1;
+        if text[range.clone()] == *version {
+          ranges.push((name.as_str(), version.as_str(), range));
+        }
+      }
+    }
+  }
+  ranges
+}
+
+/// Collects the urls of the modules of registries that provide completions
+/// for their versions, like `https://deno.land/x`. Returns the versions of
+/// each module.
+async fn collect_registry_dependencies(
+  ps: &ProcState,
+  remote_urls: Vec<(PathBuf, usize, String)>,
+  dependencies: &mut BTreeMap<Dependency, Vec<Location>>,
+) -> Result<HashMap<String, Vec<String>>, AnyError> {
+  let mut registry = ModuleRegistry::new(
+    &ps.dir.registries_folder_path(),
+    ps.http_client.clone(),
+  )?;
+  let mut module_versions = HashMap::new();
+  let mut url_versions = HashMap::<String, Option<String>>::new();
+  for (path, start, url) in remote_urls {
+    if !url_versions.contains_key(&url) {
+      let specifier = match ModuleSpecifier::parse(&url) {
+        Ok(specifier) => specifier,
+        Err(_) => continue,
+      };
+      registry.enable(specifier.as_str()).await?;
+      let maybe_version = registry
+        .get_module_versions(&specifier)
+        .await
+        .and_then(|(version, versions)| {
+          let name = module_name(&url, &version)?;
+          module_versions.insert(name, versions);
+          Some(version)
+        });
+      url_versions.insert(url.clone(), maybe_version);
+    }
+    let version = match url_versions.get(&url).cloned().flatten() {
+      Some(version) => version,
+      None => continue,
+    };
+    // the name is checked to be followed by the version
+    let name = module_name(&url, &version).unwrap();
+    let version_start = start + name.len() + 1;
+    dependencies
+      .entry(Dependency {
+        kind: DependencyKind::Registry,
+        name,
+        version: version.clone(),
+      })
+      .or_default()
+      .push(Location {
+        path,
+        range: version_start..version_start + version.len(),
+      });
+  }
+  Ok(module_versions)
+}
+
+/// Gets the url of a module before its version, like
+/// `https://deno.land/x/oak` for `https://deno.land/x/oak@v12.0.0/mod.ts`.
+fn module_name(url: &str, version: &str) -> Option<String> {
+  let index = url.find(&format!("@{version}"))?;
+  Some(url[..index].to_string())
+}
+
+/// Gets the version an npm dependency is resolved to, like in the lockfile.
+fn resolved_npm_version(
+  snapshot: &NpmResolutionSnapshot,
+  dependency: &Dependency,
+) -> Option<Version> {
+  let req = NpmPackageReq::from_str(&format!(
+    "{}@{}",
+    dependency.name, dependency.version
+  ))
+  .ok()?;
+  let package = snapshot.resolve_pkg_from_pkg_req(&req).ok()?;
+  Some(package.pkg_id.nv.version.clone())
+}
+
+/// Finds the newer versions of an npm dependency. The current version is the
+/// resolved one or, when the dependency isn't resolved yet, the version that
+/// would be installed for it.
+fn npm_newer_versions(
+  version_req: &str,
+  maybe_resolved_version: Option<Version>,
+  package_info: &NpmPackageInfo,
+) -> Option<NewerVersions> {
+  let version_req = VersionReq::parse_from_npm(version_req).ok()?;
+  if version_req.tag().is_some() {
+    return None;
+  }
+  let versions = package_info
+    .versions
+    .keys()
+    .filter_map(|v| Version::parse_from_npm(v).ok())
+    .collect::<Vec<_>>();
+  let current = match maybe_resolved_version {
+    Some(version) => version,
+    None => versions
+      .iter()
+      .filter(|v| version_req.matches(v))
+      .max()?
+      .clone(),
+  };
+  let latest = package_info
+    .dist_tags
+    .get("latest")
+    .and_then(|v| Version::parse_from_npm(v).ok());
+  newer_versions(&current, &versions, latest).map(|newer| NewerVersions {
+    current: current.to_string(),
+    compatible: newer.compatible.map(|v| v.to_string()),
+    latest: newer.latest.map(|v| v.to_string()),
+  })
+}
+
+fn registry_newer_versions(
+  current: &str,
+  versions: &[String],
+) -> Option<NewerVersions> {
+  let parse = |v: &str| Version::parse_from_npm(v.trim_start_matches('v')).ok();
+  let current_version = parse(current)?;
+  let versions = versions
+    .iter()
+    .filter_map(|text| parse(text).map(|v| (v, text)))
+    .collect::<HashMap<_, _>>();
+  let parsed_versions = versions.keys().cloned().collect::<Vec<_>>();
+  let newer = newer_versions(&current_version, &parsed_versions, None)?;
+  // keep the versions as written by the registry, like `v1.0.0`
+  Some(NewerVersions {
+    current: current.to_string(),
+    compatible: newer.compatible.map(|v| versions[&v].clone()),
+    latest: newer.latest.map(|v| versions[&v].clone()),
+  })
+}
+
+struct NewerSemverVersions {
+  compatible: Option<Version>,
+  latest: Option<Version>,
+}
+
+/// Finds the newest version that is compatible with the current one and the
+/// latest version, which is the given one or otherwise the newest stable
+/// version. Returns `None` when neither is newer than the current version.
+fn newer_versions(
+  current: &Version,
+  versions: &[Version],
+  latest: Option<Version>,
+) -> Option<NewerSemverVersions> {
+  // `^1.2.3` allows minor and patch updates, `^0.2.3` patch updates and
+  // `^0.0.3` none, the same way as npm
+  let compatible_req =
+    VersionReq::parse_from_npm(&format!("^{current}")).ok()?;
+  let compatible = versions
+    .iter()
+    .filter(|v| *v > current && compatible_req.matches(v))
+    .filter(|v| v.pre.is_empty() || !current.pre.is_empty())
+    .max()
+    .cloned();
+  let latest = latest
+    .or_else(|| versions.iter().filter(|v| v.pre.is_empty()).max().cloned())
+    .filter(|v| v > current);
+  if compatible.is_none() && latest.is_none() {
+    None
+  } else {
+    Some(NewerSemverVersions { compatible, latest })
+  }
+}
+
+/// Updates a version requirement to a new version, keeping its prefix and
+/// its number of components, like `^1.2` to `^1.3` for `1.3.4`.
+fn update_version_req(version_req: &str, version: &str) -> Option<String> {
+  let captures = VERSION_REQ_RE.captures(version_req)?;
+  let prefix = captures.get(1).unwrap().as_str();
+  let component_count = 1
+    + usize::from(captures.get(3).is_some())
+    + usize::from(captures.get(4).is_some());
+  // keep the whole version for a pre-release
+  let is_prerelease = captures.get(5).is_some() || version.contains('-');
+  if is_prerelease || component_count == 3 {
+    return Some(format!("{prefix}{version}"));
+  }
+  let components = version.split('.').take(component_count);
+  Some(format!(
+    "{prefix}{}",
+    components.collect::<Vec<_>>().join(".")
+  ))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn versions(versions: &[&str]) -> Vec<Version> {
+    versions
+      .iter()
+      .map(|v| Version::parse_from_npm(v).unwrap())
+      .collect()
+  }
+
+  fn newer(current: &str, all: &[&str]) -> (Option<String>, Option<String>) {
+    let current = Version::parse_from_npm(current).unwrap();
+    match newer_versions(&current, &versions(all), None) {
+      Some(newer) => (
+        newer.compatible.map(|v| v.to_string()),
+        newer.latest.map(|v| v.to_string()),
+      ),
+      None => (None, None),
+    }
+  }
+
+  #[test]
+  fn test_newer_versions() {
+    let all = [
+      "0.0.1",
+      "0.0.2",
+      "0.1.0",
+      "0.1.1",
+      "0.2.0",
+      "1.0.0",
+      "1.2.0",
+      "1.3.0",
+      "2.0.0",
+      "3.0.0-beta.1",
+    ];
+    assert_eq!(
+      newer("1.0.0", &all),
+      (Some("1.3.0".to_string()), Some("2.0.0".to_string()))
+    );
+    assert_eq!(
+      newer("0.1.0", &all),
+      (Some("0.1.1".to_string()), Some("2.0.0".to_string()))
+    );
+    assert_eq!(newer("0.0.1", &all), (None, Some("2.0.0".to_string())));
+    assert_eq!(newer("2.0.0", &all), (None, None));
+  }
+
+  #[test]
+  fn test_registry_newer_versions() {
+    let all = ["v1.0.0", "v1.0.1", "v2.0.0"]
+      .iter()
+      .map(|v| v.to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      registry_newer_versions("v1.0.0", &all),
+      Some(NewerVersions {
+        current: "v1.0.0".to_string(),
+        compatible: Some("v1.0.1".to_string()),
+        latest: Some("v2.0.0".to_string()),
+      })
+    );
+    assert_eq!(registry_newer_versions("v2.0.0", &all), None);
+  }
+
+  #[test]
+  fn test_package_json_version_ranges() {
+    let text = r#"{
+  "name": "pkg",
+  "dependencies": {
+    "chalk": "^5.0.0",
+    "alias": "npm:chalk@5",
+    "escaped": "\u0031.0.0",
+    "local": "file:../local"
+  },
+  "devDependencies": { "typescript": "~4.9" },
+  "peerDependencies": { "react": "^18.0.0" },
+  "scripts": { "dependencies": "echo \"chalk\": \"^5.0.0\"" }
+}"#;
+    let mut package_json = PackageJson::empty(PathBuf::from("package.json"));
+    let deps = |entries: &[(&str, &str)]| {
+      Some(
+        entries
+          .iter()
+          .map(|(k, v)| (k.to_string(), v.to_string()))
+          .collect::<HashMap<_, _>>(),
+      )
+    };
+    package_json.dependencies = deps(&[
+      ("chalk", "^5.0.0"),
+      ("alias", "npm:chalk@5"),
+      ("escaped", "1.0.0"),
+      ("local", "file:../local"),
+    ]);
+    package_json.dev_dependencies = deps(&[("typescript", "~4.9")]);
+
+    let ranges = package_json_version_ranges(text, &package_json);
+    assert_eq!(ranges.len(), 2);
+    for (name, version, range) in ranges {
+      assert!(["chalk", "typescript"].contains(&name));
+      assert_eq!(&text[range], version);
+    }
+  }
+
+  #[test]
+  fn test_update_version_req() {
+    assert_eq!(update_version_req("^1.2.3", "1.4.0").unwrap(), "^1.4.0");
+    assert_eq!(update_version_req("~1.2", "1.2.5").unwrap(), "~1.2");
+    assert_eq!(update_version_req("1", "2.0.1").unwrap(), "2");
+    assert_eq!(update_version_req("1.0.0-rc.1", "1.0.0").unwrap(), "1.0.0");
+    assert_eq!(update_version_req(">=1.0.0 <2", "2.0.0"), None);
+  }
+}
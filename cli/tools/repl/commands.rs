@@ -0,0 +1,79 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+/// A command of the REPL, entered as a line starting with a dot, the same way
+/// as in the Node.js REPL.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplCommand {
+  Clear,
+  Editor,
+  Help,
+  Load(String),
+  Save(String),
+}
+
+pub const HELP_TEXT: &str = "\
+.clear     Reset the REPL context, discarding all declarations
+.editor    Enter editor mode to write multiple lines (ctrl+d to evaluate, ctrl+c to cancel)
+.help      Print this help message
+.load      Evaluate a file or URL in the current context, e.g. `.load ./script.ts`
+.save      Save the code evaluated in this session to a file, e.g. `.save ./session.ts`
+
+Press ctrl+r to search the history of the current project.";
+
+/// Parses a line of the REPL as a command. Returns `None` when the line is
+/// code to evaluate, and an error message for unknown or incomplete commands.
+pub fn parse_command(line: &str) -> Option<Result<ReplCommand, String>> {
+  let line = line.trim();
+  let rest = line.strip_prefix('.')?;
+  // a leading dot can also be code, like `.5 + 1`
+  if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+    return None;
+  }
+
+  let (keyword, arg) = match rest.split_once(char::is_whitespace) {
+    Some((keyword, arg)) => (keyword, arg.trim()),
+    None => (rest, ""),
+  };
+  let file_arg = |command: fn(String) -> ReplCommand| {
+    if arg.is_empty() {
+      Err(format!("The .{keyword} command requires a file path."))
+    } else {
+      Ok(command(arg.to_string()))
+    }
+  };
+  let result = match keyword {
+    "clear" => Ok(ReplCommand::Clear),
+    "editor" => Ok(ReplCommand::Editor),
+    "help" => Ok(ReplCommand::Help),
+    "load" => file_arg(ReplCommand::Load),
+    "save" => file_arg(ReplCommand::Save),
+    _ => Err(format!(
+      "Invalid REPL keyword: .{keyword}. Type .help for the available commands."
+    )),
+  };
+  Some(result)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_command() {
+    assert_eq!(parse_command("1 + 1"), None);
+    assert_eq!(parse_command(".5 + 1"), None);
+    assert_eq!(parse_command(" .help "), Some(Ok(ReplCommand::Help)));
+    assert_eq!(parse_command(".clear"), Some(Ok(ReplCommand::Clear)));
+    assert_eq!(parse_command(".editor"), Some(Ok(ReplCommand::Editor)));
+    assert_eq!(
+      parse_command(".load  ./my script.ts"),
+      Some(Ok(ReplCommand::Load("./my script.ts".to_string())))
+    );
+    assert_eq!(
+      parse_command(".save out.ts"),
+      Some(Ok(ReplCommand::Save("out.ts".to_string())))
+    );
+    assert!(matches!(parse_command(".save"), Some(Err(_))));
+    assert!(matches!(parse_command(".foo"), Some(Err(_))));
+  }
+}
@@ -46,6 +46,9 @@ use super::channel::RustylineSyncMessageSender;
 pub struct EditorHelper {
   pub context_id: u64,
   pub sync_sender: RustylineSyncMessageSender,
+  /// Lines aren't validated in the `.editor` mode, they're only evaluated
+  /// once the whole buffer was entered.
+  pub is_editor_mode: bool,
 }
 
 impl EditorHelper {
@@ -235,6 +238,9 @@ impl Validator for EditorHelper {
     &self,
     ctx: &mut ValidationContext,
   ) -> Result<ValidationResult, ReadlineError> {
+    if self.is_editor_mode {
+      return Ok(ValidationResult::Valid(None));
+    }
     Ok(validate(ctx.input()))
   }
 }
@@ -451,6 +457,21 @@ impl ReplEditor {
     self.inner.lock().readline("> ")
   }
 
+  /// Reads a line of the buffer of the `.editor` mode.
+  pub fn readline_editor_mode(&self) -> Result<String, ReadlineError> {
+    let mut editor = self.inner.lock();
+    editor.helper_mut().unwrap().is_editor_mode = true;
+    let result = editor.readline("");
+    editor.helper_mut().unwrap().is_editor_mode = false;
+    result
+  }
+
+  /// Updates the execution context used for completions, after the context
+  /// was reset with `.clear`.
+  pub fn set_context_id(&self, context_id: u64) {
+    self.inner.lock().helper_mut().unwrap().context_id = context_id;
+  }
+
   pub fn update_history(&self, entry: String) {
     self.inner.lock().add_history_entry(entry);
     if let Err(e) = self.inner.lock().append_history(&self.history_file_path) {
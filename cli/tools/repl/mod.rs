@@ -4,15 +4,18 @@ use crate::args::Flags;
 use crate::args::ReplFlags;
 use crate::colors;
 use crate::proc_state::ProcState;
+use crate::util::path::specifier_to_file_path;
 use crate::worker::create_main_worker;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 use rustyline::error::ReadlineError;
+use std::path::PathBuf;
 
 mod cdp;
 mod channel;
+mod commands;
 mod editor;
 mod session;
 
@@ -20,6 +23,7 @@ use channel::rustyline_channel;
 use channel::RustylineSyncMessage;
 use channel::RustylineSyncMessageHandler;
 use channel::RustylineSyncResponse;
+use commands::ReplCommand;
 use editor::EditorHelper;
 use editor::ReplEditor;
 use session::EvaluationOutput;
@@ -29,8 +33,15 @@ async fn read_line_and_poll(
   repl_session: &mut ReplSession,
   message_handler: &mut RustylineSyncMessageHandler,
   editor: ReplEditor,
+  is_editor_mode: bool,
 ) -> Result<String, ReadlineError> {
-  let mut line_fut = tokio::task::spawn_blocking(move || editor.readline());
+  let mut line_fut = tokio::task::spawn_blocking(move || {
+    if is_editor_mode {
+      editor.readline_editor_mode()
+    } else {
+      editor.readline()
+    }
+  });
   let mut poll_worker = true;
 
   loop {
@@ -79,11 +90,31 @@ async fn read_eval_file(
   Ok((*file.source).to_string())
 }
 
-pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
+/// Reads the lines of the `.editor` mode until ctrl+d is pressed. Returns
+/// `None` when it was cancelled with ctrl+c.
+async fn read_editor_buffer_and_poll(
+  repl_session: &mut ReplSession,
+  message_handler: &mut RustylineSyncMessageHandler,
+  editor: &ReplEditor,
+) -> Result<Option<String>, ReadlineError> {
+  let mut lines = Vec::new();
+  loop {
+    let line =
+      read_line_and_poll(repl_session, message_handler, editor.clone(), true)
+        .await;
+    match line {
+      Ok(line) => lines.push(line),
+      Err(ReadlineError::Eof) => return Ok(Some(lines.join("\n"))),
+      Err(ReadlineError::Interrupted) => return Ok(None),
+      Err(err) => return Err(err),
+    }
+  }
+}
+
+async fn create_repl_session(ps: &ProcState) -> Result<ReplSession, AnyError> {
   let main_module = resolve_url_or_path("./$deno$repl.ts").unwrap();
-  let ps = ProcState::build(flags).await?;
   let mut worker = create_main_worker(
-    &ps,
+    ps,
     main_module,
     PermissionsContainer::new(Permissions::from_options(
       &ps.options.permissions_options(),
@@ -92,16 +123,41 @@ pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
   .await?;
   worker.setup_repl().await?;
   let worker = worker.into_main_worker();
-  let mut repl_session = ReplSession::initialize(ps.clone(), worker).await?;
+  ReplSession::initialize(ps.clone(), worker).await
+}
+
+/// Gets the history file of the project the REPL is run in, which is the
+/// directory of the config file or of the package.json. Falls back to the
+/// global history file outside of a project.
+fn history_file_path(ps: &ProcState) -> PathBuf {
+  let maybe_project_file = ps
+    .options
+    .maybe_config_file_specifier()
+    .and_then(|specifier| specifier_to_file_path(&specifier).ok())
+    .or_else(|| {
+      ps.options
+        .maybe_package_json()
+        .as_ref()
+        .map(|package_json| package_json.path.clone())
+    });
+  match maybe_project_file.as_ref().and_then(|path| path.parent()) {
+    Some(project_dir) => ps.dir.repl_project_history_file_path(project_dir),
+    None => ps.dir.repl_history_file_path(),
+  }
+}
+
+pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
+  let ps = ProcState::build(flags).await?;
+  let mut repl_session = create_repl_session(&ps).await?;
   let mut rustyline_channel = rustyline_channel();
 
   let helper = EditorHelper {
     context_id: repl_session.context_id,
     sync_sender: rustyline_channel.0,
+    is_editor_mode: false,
   };
 
-  let history_file_path = ps.dir.repl_history_file_path();
-  let editor = ReplEditor::new(helper, history_file_path)?;
+  let editor = ReplEditor::new(helper, history_file_path(&ps))?;
 
   if let Some(eval_files) = repl_flags.eval_files {
     for eval_file in eval_files {
@@ -144,18 +200,82 @@ pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
     }
   }
 
+  // the code evaluated in this session, for `.save`
+  let mut session_sources: Vec<String> = Vec::new();
+
   loop {
     let line = read_line_and_poll(
       &mut repl_session,
       &mut rustyline_channel.1,
       editor.clone(),
+      false,
     )
     .await;
     match line {
       Ok(line) => {
         editor.set_should_exit_on_interrupt(false);
         editor.update_history(line.clone());
-        let output = repl_session.evaluate_line_and_get_output(&line).await;
+        let source = match commands::parse_command(&line) {
+          None => line,
+          Some(Err(message)) => {
+            println!("{message}");
+            continue;
+          }
+          Some(Ok(ReplCommand::Help)) => {
+            println!("{}", commands::HELP_TEXT);
+            continue;
+          }
+          Some(Ok(ReplCommand::Clear)) => {
+            println!("Clearing context...");
+            repl_session = create_repl_session(&ps).await?;
+            editor.set_context_id(repl_session.context_id);
+            session_sources.clear();
+            continue;
+          }
+          Some(Ok(ReplCommand::Save(file))) => {
+            let mut text = session_sources.join("\n");
+            text.push('\n');
+            match std::fs::write(&file, text) {
+              Ok(()) => println!("Session saved to: {file}"),
+              Err(e) => println!("Failed to save session to \"{file}\": {e}"),
+            }
+            continue;
+          }
+          Some(Ok(ReplCommand::Load(file))) => {
+            match read_eval_file(&ps, &file).await {
+              Ok(source) => source,
+              Err(e) => {
+                println!("Failed to load \"{file}\": {e}");
+                continue;
+              }
+            }
+          }
+          Some(Ok(ReplCommand::Editor)) => {
+            println!(
+              "// Entering editor mode (ctrl+d to finish, ctrl+c to cancel)"
+            );
+            let buffer = read_editor_buffer_and_poll(
+              &mut repl_session,
+              &mut rustyline_channel.1,
+              &editor,
+            )
+            .await;
+            match buffer {
+              Ok(Some(source)) => {
+                editor.update_history(source.clone());
+                source
+              }
+              Ok(None) => continue,
+              Err(err) => {
+                println!("Error: {err:?}");
+                break;
+              }
+            }
+          }
+        };
+
+        let output = repl_session.evaluate_line_and_get_output(&source).await;
+        session_sources.push(source);
 
         // We check for close and break here instead of making it a loop condition to get
         // consistent behavior in when the user evaluates a call to close().
@@ -0,0 +1,309 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::colors;
+use crate::emit::emit_parsed_source;
+use crate::proc_state::ProcState;
+
+use deno_ast::swc::ast;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ParseParams;
+use deno_ast::SourcePos;
+use deno_ast::SourceRange;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc::UnboundedReceiver;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::LocalInspectorSession;
+use deno_core::ModuleSpecifier;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Replaces the code of changed modules in a running worker for
+/// `--watch-hmr`, using the live edit of the V8 debugger. Only the functions
+/// of a module are replaced, its top level code isn't evaluated again, so
+/// changes to the top level code restart the process instead.
+pub struct HmrRunner {
+  session: LocalInspectorSession,
+  notification_rx: UnboundedReceiver<Value>,
+  /// The ids of the scripts parsed by V8, by their url.
+  script_ids: HashMap<String, String>,
+  ps: ProcState,
+}
+
+impl HmrRunner {
+  pub fn new(mut session: LocalInspectorSession, ps: ProcState) -> Self {
+    let notification_rx = session.take_notification_rx();
+    Self {
+      session,
+      notification_rx,
+      script_ids: HashMap::new(),
+      ps,
+    }
+  }
+
+  /// Enables the debugger, which needs to be done before the modules are
+  /// loaded to know the ids of their scripts.
+  pub async fn start(&mut self) -> Result<(), AnyError> {
+    self
+      .session
+      .post_message::<()>("Debugger.enable", None)
+      .await?;
+    Ok(())
+  }
+
+  /// Replaces the code of the modules of the changed files. Returns the
+  /// specifiers of the replaced modules, or `None` when one of the files
+  /// isn't a loaded module or when its changes can't be applied in place,
+  /// like changes to its top level code.
+  pub async fn replace_modules(
+    &mut self,
+    paths: &[PathBuf],
+  ) -> Result<Option<Vec<ModuleSpecifier>>, AnyError> {
+    // notifications are only received while a message is posted, so post
+    // one to get the scripts that were parsed since the last change
+    self
+      .session
+      .post_message::<()>("Runtime.getIsolateId", None)
+      .await?;
+    while let Ok(Some(notification)) = self.notification_rx.try_next() {
+      if notification["method"] != "Debugger.scriptParsed" {
+        continue;
+      }
+      let params = &notification["params"];
+      if let (Some(url), Some(script_id)) =
+        (params["url"].as_str(), params["scriptId"].as_str())
+      {
+        self
+          .script_ids
+          .insert(url.to_string(), script_id.to_string());
+      }
+    }
+
+    let mut specifiers = Vec::with_capacity(paths.len());
+    for path in paths {
+      let specifier = match ModuleSpecifier::from_file_path(path) {
+        Ok(specifier) => specifier,
+        Err(_) => return Ok(None),
+      };
+      let script_id = match self.script_ids.get(specifier.as_str()) {
+        Some(script_id) => script_id.clone(),
+        None => {
+          log::debug!("Changed file is not a loaded module: {}", specifier);
+          return Ok(None);
+        }
+      };
+      let source_code = match self.emit(&specifier, path)? {
+        Some(source_code) => source_code,
+        None => return Ok(None),
+      };
+
+      let result = self
+        .session
+        .post_message(
+          "Debugger.getScriptSource",
+          Some(json!({ "scriptId": script_id })),
+        )
+        .await?;
+      let old_top_level_code = result["scriptSource"]
+        .as_str()
+        .and_then(|source| top_level_code(&specifier, source));
+      let new_top_level_code = top_level_code(&specifier, &source_code);
+      if old_top_level_code.is_none()
+        || old_top_level_code != new_top_level_code
+      {
+        log::debug!("Top level code changed: {}", specifier);
+        return Ok(None);
+      }
+
+      let result = self
+        .session
+        .post_message(
+          "Debugger.setScriptSource",
+          Some(json!({
+            "scriptId": script_id,
+            "scriptSource": source_code,
+          })),
+        )
+        .await?;
+      // older versions of V8 report failures as an exception instead of a
+      // status
+      let status = result["status"].as_str().unwrap_or("Ok");
+      if status != "Ok" || result.get("exceptionDetails").is_some() {
+        log::debug!("Failed replacing {}: {}", specifier, result);
+        return Ok(None);
+      }
+      log::info!(
+        "{} Replaced changed module {}",
+        colors::intense_blue("HMR"),
+        specifier
+      );
+      specifiers.push(specifier);
+    }
+    Ok(Some(specifiers))
+  }
+
+  /// Gets the code of a module from its changed file, the same way as the
+  /// module loader. Returns `None` for modules that can't be replaced.
+  fn emit(
+    &self,
+    specifier: &ModuleSpecifier,
+    path: &Path,
+  ) -> Result<Option<String>, AnyError> {
+    // the file might have been removed
+    let source: Arc<str> = match std::fs::read_to_string(path) {
+      Ok(source) => source.into(),
+      Err(_) => return Ok(None),
+    };
+    let media_type = MediaType::from(specifier);
+    let code = match media_type {
+      MediaType::JavaScript
+      | MediaType::Unknown
+      | MediaType::Cjs
+      | MediaType::Mjs => source.to_string(),
+      MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Jsx
+      | MediaType::Tsx => {
+        // make sure the previous source isn't used
+        self.ps.parsed_source_cache.free(specifier);
+        let code = emit_parsed_source(
+          &self.ps.emit_cache,
+          &self.ps.parsed_source_cache,
+          specifier,
+          media_type,
+          &source,
+          &self.ps.emit_options,
+          self.ps.emit_options_hash,
+        )?;
+        self.ps.parsed_source_cache.free(specifier);
+        code
+      }
+      _ => return Ok(None),
+    };
+    Ok(Some(code))
+  }
+}
+
+/// Gets the code of a module that runs when it's evaluated, which is its
+/// code without the bodies of its functions. Returns `None` when the module
+/// can't be parsed.
+fn top_level_code(specifier: &ModuleSpecifier, source: &str) -> Option<String> {
+  let parsed_source = deno_ast::parse_module(ParseParams {
+    specifier: specifier.to_string(),
+    text_info: SourceTextInfo::new(source.into()),
+    media_type: MediaType::JavaScript,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })
+  .ok()?;
+  let mut collector = FunctionBodyCollector::default();
+  parsed_source.module().visit_with(&mut collector);
+
+  // comments between the statements are left out, like the source map
+  let text_info = parsed_source.text_info();
+  let mut bodies = collector.ranges.into_iter().peekable();
+  let mut code = String::new();
+  for item in &parsed_source.module().body {
+    let item_range = item.range();
+    let mut pos = item_range.start;
+    while let Some(body) = bodies.next_if(|body| body.end <= item_range.end) {
+      code.push_str(text_info.range_text(&SourceRange::new(pos, body.start)));
+      code.push_str("{}");
+      pos = body.end;
+    }
+    code.push_str(text_info.range_text(&SourceRange::new(pos, item_range.end)));
+    code.push('\n');
+  }
+  Some(code)
+}
+
+/// Collects the ranges of the outermost function bodies in source order.
+#[derive(Default)]
+struct FunctionBodyCollector {
+  ranges: Vec<SourceRange>,
+}
+
+impl FunctionBodyCollector {
+  fn add(&mut self, start: SourcePos, end: SourcePos) {
+    self.ranges.push(SourceRange::new(start, end));
+  }
+}
+
+impl Visit for FunctionBodyCollector {
+  fn visit_function(&mut self, node: &ast::Function) {
+    // the parameters are only evaluated when the function is called as well
+    if let Some(first_param) = node.params.first() {
+      let end = node.body.as_ref().map(|b| b.end()).unwrap_or(node.end());
+      self.add(first_param.start(), end);
+    } else if let Some(body) = &node.body {
+      self.add(body.start(), body.end());
+    }
+  }
+
+  fn visit_arrow_expr(&mut self, node: &ast::ArrowExpr) {
+    self.add(node.body.start(), node.body.end());
+  }
+
+  fn visit_constructor(&mut self, node: &ast::Constructor) {
+    if let Some(body) = &node.body {
+      self.add(body.start(), body.end());
+    }
+  }
+
+  fn visit_getter_prop(&mut self, node: &ast::GetterProp) {
+    node.key.visit_with(self);
+    if let Some(body) = &node.body {
+      self.add(body.start(), body.end());
+    }
+  }
+
+  fn visit_setter_prop(&mut self, node: &ast::SetterProp) {
+    node.key.visit_with(self);
+    if let Some(body) = &node.body {
+      self.add(body.start(), body.end());
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_top_level_code() {
+    let specifier = ModuleSpecifier::parse("file:///main.js").unwrap();
+    let get = |source: &str| top_level_code(&specifier, source).unwrap();
+    let source = r#"import { a } from "./a.js";
+export function add(a, b = 1) {
+  return a + b;
+}
+class Counter {
+  count = 0;
+  increment() { this.count++; }
+}
+const double = (value) => value * 2;
+console.log(add(a, 2));
+//# sourceMappingURL=data:application/json;base64,AAAA
+"#;
+    assert_eq!(
+      get(source),
+      get(
+        &source
+          .replace("a + b", "b + a")
+          .replace("this.count++", "this.count += 1")
+          .replace("value * 2", "value + value")
+          .replace("AAAA", "BBBB")
+      )
+    );
+    assert_ne!(get(source), get(&source.replace("add(a, 2)", "add(a, 3)")));
+    assert_ne!(get(source), get(&source.replace("count = 0", "count = 1")));
+  }
+}
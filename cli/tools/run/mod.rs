@@ -19,6 +19,10 @@ use crate::proc_state::ProcState;
 use crate::util;
 use crate::worker::create_main_worker;
 
+mod hmr;
+
+pub use hmr::HmrRunner;
+
 pub async fn run_script(
   flags: Flags,
   run_flags: RunFlags,
@@ -34,6 +38,9 @@ To grant permissions, set them before the script argument. For example:
     );
   }
 
+  if flags.hmr {
+    return run_with_hmr(flags, run_flags.script).await;
+  }
   if flags.watch.is_some() {
     return run_with_watch(flags, run_flags.script).await;
   }
@@ -133,6 +140,39 @@ async fn run_with_watch(flags: Flags, script: String) -> Result<i32, AnyError> {
   Ok(0)
 }
 
+async fn run_with_hmr(flags: Flags, script: String) -> Result<i32, AnyError> {
+  let flags = Arc::new(flags);
+  let main_module = resolve_url_or_path(&script)?;
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  let mut ps =
+    ProcState::build_for_file_watcher((*flags).clone(), sender.clone()).await?;
+
+  let operation = |main_module: ModuleSpecifier, changed_paths_rx| {
+    ps.reset_for_file_watcher();
+    let ps = ps.clone();
+    Ok(async move {
+      let permissions = PermissionsContainer::new(Permissions::from_options(
+        &ps.options.permissions_options(),
+      )?);
+      let worker = create_main_worker(&ps, main_module, permissions).await?;
+      worker.run_for_hmr(changed_paths_rx).await
+    })
+  };
+
+  util::file_watcher::watch_hmr_func(
+    receiver,
+    operation,
+    main_module,
+    util::file_watcher::PrintConfig {
+      job_name: "Process".to_string(),
+      clear_screen: !flags.no_clear_screen,
+    },
+  )
+  .await?;
+
+  Ok(0)
+}
+
 pub async fn eval_command(
   flags: Flags,
   eval_flags: EvalFlags,
@@ -1,9 +1,11 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::TaskFlags;
 use crate::colors;
 use crate::proc_state::ProcState;
+use crate::util::file_watcher;
 use crate::util::fs::canonicalize_path;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
@@ -22,6 +24,18 @@ use std::rc::Rc;
 pub async fn execute_script(
   flags: Flags,
   task_flags: TaskFlags,
+) -> Result<i32, AnyError> {
+  if flags.watch.is_some() && task_flags.task.is_some() {
+    execute_script_with_watch(flags, task_flags).await?;
+    Ok(0)
+  } else {
+    execute_task(flags, task_flags).await
+  }
+}
+
+async fn execute_task(
+  flags: Flags,
+  task_flags: TaskFlags,
 ) -> Result<i32, AnyError> {
   let ps = ProcState::build(flags).await?;
   let tasks_config = ps.options.resolve_tasks_config()?;
@@ -108,6 +122,48 @@ pub async fn execute_script(
   }
 }
 
+/// Runs the task again whenever one of the paths given to `--watch` changes.
+/// The paths are required, since watching the whole directory would include
+/// the files the task writes itself.
+async fn execute_script_with_watch(
+  flags: Flags,
+  task_flags: TaskFlags,
+) -> Result<(), AnyError> {
+  let cli_options = CliOptions::from_flags(flags.clone())?;
+  let paths_to_watch = cli_options.watch_paths().clone().unwrap_or_default();
+  if paths_to_watch.is_empty() {
+    bail!(
+      "Watching a task requires the paths of its inputs, for example --watch=src"
+    );
+  }
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  sender.send(paths_to_watch)?;
+
+  let clear_screen = !flags.no_clear_screen;
+  let operation = |flags: Flags| {
+    let task_flags = task_flags.clone();
+    Ok(async move {
+      let task_name = task_flags.task.clone().unwrap_or_default();
+      let exit_code = execute_task(flags, task_flags).await?;
+      if exit_code != 0 {
+        bail!("Task {} failed with exit code {}.", task_name, exit_code);
+      }
+      Ok(())
+    })
+  };
+
+  file_watcher::watch_func2(
+    receiver,
+    operation,
+    flags,
+    file_watcher::PrintConfig {
+      job_name: "Task".to_string(),
+      clear_screen,
+    },
+  )
+  .await
+}
+
 fn get_script_with_args(script: &str, ps: &ProcState) -> String {
   let additional_args = ps
     .options
@@ -3,9 +3,9 @@
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::TestOptions;
+use crate::args::TestReporterConfig;
 use crate::args::TypeCheckMode;
 use crate::colors;
-use crate::display;
 use crate::file_fetcher::File;
 use crate::graph_util::graph_valid_with_cli_options;
 use crate::ops;
@@ -13,7 +13,9 @@ use crate::proc_state::ProcState;
 use crate::util::checksum;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
+use crate::util::fs::canonicalize_path;
 use crate::util::fs::collect_specifiers;
+use crate::util::git;
 use crate::util::path::get_extension;
 use crate::util::path::is_supported_ext;
 use crate::worker::create_main_worker_for_test_or_bench;
@@ -28,8 +30,9 @@ use deno_core::futures::future;
 use deno_core::futures::stream;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
+use deno_core::parking_lot::Condvar;
 use deno_core::parking_lot::Mutex;
-use deno_core::url::Url;
+use deno_core::v8;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_io::Stdio;
 use deno_runtime::deno_io::StdioPipe;
@@ -44,8 +47,9 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use regex::Regex;
 use serde::Deserialize;
+use serde::Serialize;
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Write as _;
 use std::io::Read;
@@ -59,6 +63,18 @@ use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Notify;
+
+mod reporters;
+mod shard;
+
+use reporters::CompoundTestReporter;
+use reporters::DotTestReporter;
+use reporters::JsonTestReporter;
+use reporters::JunitTestReporter;
+use reporters::PrettyTestReporter;
+use reporters::TapTestReporter;
+use reporters::TestReporter;
 
 /// The test mode is used to determine how a specifier is to be tested.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -123,7 +139,7 @@ impl TestFilter {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct TestLocation {
   pub file_name: String,
@@ -131,7 +147,7 @@ pub struct TestLocation {
   pub column_number: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub struct TestDescription {
   pub id: usize,
@@ -154,16 +170,18 @@ pub enum TestOutput {
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TestResult {
   Ok,
   Ignored,
   Failed(Box<JsError>),
   Cancelled,
+  /// The test, or one of its steps, passed after being retried.
+  Flaky,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestStepDescription {
   pub id: usize,
@@ -187,7 +205,7 @@ impl TestStepDescription {
 }
 
 #[allow(clippy::derive_partial_eq_without_eq)]
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TestStepResult {
   Ok,
@@ -206,7 +224,7 @@ impl TestStepResult {
   }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestPlan {
   pub origin: String,
@@ -241,6 +259,7 @@ pub struct TestSummary {
   pub ignored_steps: usize,
   pub filtered_out: usize,
   pub measured: usize,
+  pub flaky: Vec<TestDescription>,
   pub failures: Vec<(TestDescription, Box<JsError>)>,
   pub uncaught_errors: Vec<(String, Box<JsError>)>,
 }
@@ -250,6 +269,9 @@ struct TestSpecifierOptions {
   concurrent_jobs: NonZeroUsize,
   fail_fast: Option<NonZeroUsize>,
   filter: TestFilter,
+  timeout: Option<u64>,
+  reporter: TestReporterConfig,
+  junit_path: Option<String>,
 }
 
 impl TestSummary {
@@ -265,6 +287,7 @@ impl TestSummary {
       ignored_steps: 0,
       filtered_out: 0,
       measured: 0,
+      flaky: Vec::new(),
       failures: Vec::new(),
       uncaught_errors: Vec::new(),
     }
@@ -275,376 +298,36 @@ impl TestSummary {
   }
 }
 
-struct PrettyTestReporter {
-  parallel: bool,
+fn get_test_reporter(
+  options: &TestSpecifierOptions,
   echo_output: bool,
-  in_new_line: bool,
-  last_wait_id: Option<usize>,
-  cwd: Url,
-  did_have_user_output: bool,
-  started_tests: bool,
-}
-
-impl PrettyTestReporter {
-  fn new(parallel: bool, echo_output: bool) -> PrettyTestReporter {
-    PrettyTestReporter {
-      parallel,
-      echo_output,
-      in_new_line: true,
-      last_wait_id: None,
-      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
-      did_have_user_output: false,
-      started_tests: false,
-    }
-  }
-
-  fn force_report_wait(&mut self, description: &TestDescription) {
-    if !self.in_new_line {
-      println!();
-    }
-    if self.parallel {
-      print!(
-        "{}",
-        colors::gray(format!(
-          "{} => ",
-          self.to_relative_path_or_remote_url(&description.origin)
-        ))
-      );
-    }
-    print!("{} ...", description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.last_wait_id = Some(description.id);
-  }
-
-  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
-    let url = Url::parse(path_or_url).unwrap();
-    if url.scheme() == "file" {
-      if let Some(mut r) = self.cwd.make_relative(&url) {
-        if !r.starts_with("../") {
-          r = format!("./{r}");
-        }
-        return r;
-      }
-    }
-    path_or_url.to_string()
-  }
-
-  fn force_report_step_wait(&mut self, description: &TestStepDescription) {
-    self.write_output_end();
-    if !self.in_new_line {
-      println!();
-    }
-    print!("{}{} ...", "  ".repeat(description.level), description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.last_wait_id = Some(description.id);
-  }
-
-  fn force_report_step_result(
-    &mut self,
-    description: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-  ) {
-    let status = match result {
-      TestStepResult::Ok => colors::green("ok").to_string(),
-      TestStepResult::Ignored => colors::yellow("ignored").to_string(),
-      TestStepResult::Pending(_) => colors::gray("pending").to_string(),
-      TestStepResult::Failed(_) => colors::red("FAILED").to_string(),
-    };
-
-    self.write_output_end();
-    if self.in_new_line || self.last_wait_id != Some(description.id) {
-      self.force_report_step_wait(description);
-    }
-
-    println!(
-      " {} {}",
-      status,
-      colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-    );
-
-    if let Some(js_error) = result.error() {
-      let err_string = format_test_error(js_error);
-      let err_string = format!("{}: {}", colors::red_bold("error"), err_string);
-      for line in err_string.lines() {
-        println!("{}{}", "  ".repeat(description.level + 1), line);
-      }
-    }
-    self.in_new_line = true;
-  }
-
-  fn write_output_end(&mut self) {
-    if self.did_have_user_output {
-      println!("{}", colors::gray("----- output end -----"));
-      self.in_new_line = true;
-      self.did_have_user_output = false;
-    }
-  }
-
-  fn report_register(&mut self, _description: &TestDescription) {}
-
-  fn report_plan(&mut self, plan: &TestPlan) {
-    if self.parallel {
-      return;
-    }
-    let inflection = if plan.total == 1 { "test" } else { "tests" };
-    println!(
-      "{}",
-      colors::gray(format!(
-        "running {} {} from {}",
-        plan.total,
-        inflection,
-        self.to_relative_path_or_remote_url(&plan.origin)
-      ))
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_wait(&mut self, description: &TestDescription) {
-    if !self.parallel {
-      self.force_report_wait(description);
-    }
-    self.started_tests = true;
-  }
-
-  fn report_output(&mut self, output: &[u8]) {
-    if !self.echo_output {
-      return;
-    }
-
-    if !self.did_have_user_output && self.started_tests {
-      self.did_have_user_output = true;
-      if !self.in_new_line {
-        println!();
-      }
-      println!("{}", colors::gray("------- output -------"));
-      self.in_new_line = true;
-    }
-
-    // output everything to stdout in order to prevent
-    // stdout and stderr racing
-    std::io::stdout().write_all(output).unwrap();
-  }
-
-  fn report_result(
-    &mut self,
-    description: &TestDescription,
-    result: &TestResult,
-    elapsed: u64,
-  ) {
-    if self.parallel {
-      self.force_report_wait(description);
-    }
-
-    self.write_output_end();
-    if self.in_new_line || self.last_wait_id != Some(description.id) {
-      self.force_report_wait(description);
-    }
-
-    let status = match result {
-      TestResult::Ok => colors::green("ok").to_string(),
-      TestResult::Ignored => colors::yellow("ignored").to_string(),
-      TestResult::Failed(_) => colors::red("FAILED").to_string(),
-      TestResult::Cancelled => colors::gray("cancelled").to_string(),
-    };
-
-    println!(
-      " {} {}",
-      status,
-      colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_uncaught_error(&mut self, origin: &str, _error: &JsError) {
-    if !self.in_new_line {
-      println!();
+) -> Box<dyn TestReporter + Send> {
+  let parallel = options.concurrent_jobs.get() > 1;
+  let reporter: Box<dyn TestReporter + Send> = match options.reporter {
+    TestReporterConfig::Pretty => {
+      Box::new(PrettyTestReporter::new(parallel, echo_output))
     }
-    println!(
-      "Uncaught error from {} {}",
-      self.to_relative_path_or_remote_url(origin),
-      colors::red("FAILED")
-    );
-    self.in_new_line = true;
-    self.did_have_user_output = false;
-  }
-
-  fn report_step_register(&mut self, _description: &TestStepDescription) {}
-
-  fn report_step_wait(&mut self, description: &TestStepDescription) {
-    if !self.parallel {
-      self.force_report_step_wait(description);
+    TestReporterConfig::Dot => Box::new(DotTestReporter::new()),
+    TestReporterConfig::Tap => Box::new(TapTestReporter::new()),
+    TestReporterConfig::Json => Box::new(JsonTestReporter::new()),
+    TestReporterConfig::Junit => {
+      return Box::new(JunitTestReporter::new(
+        options
+          .junit_path
+          .as_ref()
+          .filter(|path| *path != "-")
+          .map(PathBuf::from),
+      ));
     }
-  }
-
-  fn report_step_result(
-    &mut self,
-    description: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if self.parallel {
-      self.write_output_end();
-      let root;
-      let mut ancestor_names = vec![];
-      let mut current_desc = description;
-      loop {
-        if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
-          ancestor_names.push(&step_desc.name);
-          current_desc = step_desc;
-        } else {
-          root = tests.get(&current_desc.parent_id).unwrap();
-          break;
-        }
-      }
-      ancestor_names.reverse();
-      print!(
-        "{}",
-        colors::gray(format!(
-          "{} =>",
-          self.to_relative_path_or_remote_url(&description.origin)
-        ))
-      );
-      print!(" {} ...", root.name);
-      for name in ancestor_names {
-        print!(" {name} ...");
-      }
-      print!(" {} ...", description.name);
-      self.in_new_line = false;
-      self.last_wait_id = Some(description.id);
-    }
-    self.force_report_step_result(description, result, elapsed);
-  }
-
-  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
-    if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
-      #[allow(clippy::type_complexity)] // Type alias doesn't look better here
-      let mut failures_by_origin: BTreeMap<
-        String,
-        (Vec<(&TestDescription, &JsError)>, Option<&JsError>),
-      > = BTreeMap::default();
-      let mut failure_titles = vec![];
-      for (description, js_error) in &summary.failures {
-        let (failures, _) = failures_by_origin
-          .entry(description.origin.clone())
-          .or_default();
-        failures.push((description, js_error.as_ref()));
-      }
-      for (origin, js_error) in &summary.uncaught_errors {
-        let (_, uncaught_error) =
-          failures_by_origin.entry(origin.clone()).or_default();
-        let _ = uncaught_error.insert(js_error.as_ref());
-      }
-      println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
-      for (origin, (failures, uncaught_error)) in failures_by_origin {
-        for (description, js_error) in failures {
-          let failure_title = format!(
-            "{} {}",
-            &description.name,
-            colors::gray(format!(
-              "=> {}:{}:{}",
-              self.to_relative_path_or_remote_url(
-                &description.location.file_name
-              ),
-              description.location.line_number,
-              description.location.column_number
-            ))
-          );
-          println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!();
-          failure_titles.push(failure_title);
-        }
-        if let Some(js_error) = uncaught_error {
-          let failure_title = format!(
-            "{} (uncaught error)",
-            self.to_relative_path_or_remote_url(&origin)
-          );
-          println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
-          println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
-          println!();
-          failure_titles.push(failure_title);
-        }
-      }
-      println!("{}\n", colors::white_bold_on_red(" FAILURES "));
-      for failure_title in failure_titles {
-        println!("{failure_title}");
-      }
-    }
-
-    let status = if summary.has_failed() {
-      colors::red("FAILED").to_string()
-    } else {
-      colors::green("ok").to_string()
-    };
-
-    let get_steps_text = |count: usize| -> String {
-      if count == 0 {
-        String::new()
-      } else if count == 1 {
-        " (1 step)".to_string()
-      } else {
-        format!(" ({count} steps)")
-      }
-    };
-
-    let mut summary_result = String::new();
-
-    write!(
-      summary_result,
-      "{} passed{} | {} failed{}",
-      summary.passed,
-      get_steps_text(summary.passed_steps),
-      summary.failed,
-      get_steps_text(summary.failed_steps + summary.pending_steps),
-    )
-    .unwrap();
-
-    let ignored_steps = get_steps_text(summary.ignored_steps);
-    if summary.ignored > 0 || !ignored_steps.is_empty() {
-      write!(
-        summary_result,
-        " | {} ignored{}",
-        summary.ignored, ignored_steps
-      )
-      .unwrap()
-    }
-
-    if summary.measured > 0 {
-      write!(summary_result, " | {} measured", summary.measured,).unwrap();
-    }
-
-    if summary.filtered_out > 0 {
-      write!(summary_result, " | {} filtered out", summary.filtered_out)
-        .unwrap()
-    };
+  };
 
-    println!(
-      "\n{} | {} {}\n",
-      status,
-      summary_result,
-      colors::gray(format!(
-        "({})",
-        display::human_elapsed(elapsed.as_millis())
-      )),
-    );
-    self.in_new_line = true;
+  match &options.junit_path {
+    // the junit report is written in addition to the selected reporter
+    Some(path) if path != "-" => Box::new(CompoundTestReporter::new(vec![
+      reporter,
+      Box::new(JunitTestReporter::new(Some(PathBuf::from(path)))),
+    ])),
+    _ => reporter,
   }
 }
 
@@ -736,7 +419,7 @@ async fn test_specifier(
   )
   .await?;
 
-  worker.run_test_specifier(mode).await
+  worker.run_test_specifier(mode, options.timeout).await
 }
 
 fn extract_files_from_regex_blocks(
@@ -1002,6 +685,8 @@ async fn test_specifiers(
   let (sender, mut receiver) = unbounded_channel::<TestEvent>();
   let sender = TestEventSender::new(sender);
   let concurrent_jobs = options.concurrent_jobs;
+  let mut reporter =
+    get_test_reporter(&options, log_level != Some(Level::Error));
 
   let join_handles =
     specifiers_with_mode
@@ -1046,11 +731,6 @@ async fn test_specifiers(
     .buffer_unordered(concurrent_jobs.get())
     .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
 
-  let mut reporter = Box::new(PrettyTestReporter::new(
-    concurrent_jobs.get() > 1,
-    log_level != Some(Level::Error),
-  ));
-
   let handler = {
     tokio::task::spawn(async move {
       let earlier = Instant::now();
@@ -1093,6 +773,10 @@ async fn test_specifiers(
                 TestResult::Ok => {
                   summary.passed += 1;
                 }
+                TestResult::Flaky => {
+                  summary.passed += 1;
+                  summary.flaky.push(description.clone());
+                }
                 TestResult::Ignored => {
                   summary.ignored += 1;
                 }
@@ -1101,7 +785,7 @@ async fn test_specifiers(
                   summary.failures.push((description.clone(), error.clone()));
                 }
                 TestResult::Cancelled => {
-                  unreachable!("should be handled in TestEvent::UncaughtError");
+                  summary.failed += 1;
                 }
               }
               reporter.report_result(description, &result, elapsed);
@@ -1121,12 +805,15 @@ async fn test_specifiers(
           }
 
           TestEvent::StepRegister(description) => {
-            reporter.report_step_register(&description);
             test_steps.insert(description.id, description);
           }
 
           TestEvent::StepWait(id) => {
-            reporter.report_step_wait(test_steps.get(&id).unwrap());
+            // steps are registered with the reporter once they are waited on,
+            // since the steps of a retried attempt are discarded unreported
+            let description = test_steps.get(&id).unwrap();
+            reporter.report_step_register(description);
+            reporter.report_step_wait(description);
           }
 
           TestEvent::StepResult(id, result, duration) => {
@@ -1158,6 +845,7 @@ async fn test_specifiers(
 
       let elapsed = Instant::now().duration_since(earlier);
       reporter.report_summary(&summary, &elapsed);
+      reporter.flush_report()?;
 
       if used_only {
         return Err(generic_error(
@@ -1294,6 +982,79 @@ async fn fetch_specifiers_with_test_mode(
   Ok(specifiers_with_mode)
 }
 
+/// Collects the dependencies of a module, recursively.
+fn get_dependencies<'a>(
+  graph: &'a deno_graph::ModuleGraph,
+  maybe_module: Option<&'a deno_graph::Module>,
+  // This needs to be accessible to skip getting dependencies if they're already there,
+  // otherwise this will cause a stack overflow with circular dependencies
+  output: &mut HashSet<&'a ModuleSpecifier>,
+  no_check: bool,
+) {
+  if let Some(module) = maybe_module.and_then(|m| m.esm()) {
+    for dep in module.dependencies.values() {
+      if let Some(specifier) = &dep.get_code() {
+        if !output.contains(specifier) {
+          output.insert(specifier);
+          get_dependencies(graph, graph.get(specifier), output, no_check);
+        }
+      }
+      if !no_check {
+        if let Some(specifier) = &dep.get_type() {
+          if !output.contains(specifier) {
+            output.insert(specifier);
+            get_dependencies(graph, graph.get(specifier), output, no_check);
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Only keeps the test modules that depend, directly or transitively, on a
+/// file that changed since the given git ref. Test modules that changed
+/// themselves are kept as well.
+async fn filter_changed_specifiers(
+  ps: &ProcState,
+  specifiers_with_mode: Vec<(ModuleSpecifier, TestMode)>,
+  git_ref: &str,
+) -> Result<Vec<(ModuleSpecifier, TestMode)>, AnyError> {
+  // the paths are compared canonicalized, because the root directory output
+  // by git has symlinks resolved, unlike the collected specifiers
+  let changed_files = git::changed_files(&std::env::current_dir()?, git_ref)?
+    .into_iter()
+    .filter_map(|path| canonicalize_path(&path).ok())
+    .collect::<HashSet<_>>();
+  let no_check = ps.options.type_check_mode() == TypeCheckMode::None;
+
+  // documentation only modules, like markdown files, have no dependencies
+  let roots = specifiers_with_mode
+    .iter()
+    .filter(|(_, mode)| *mode != TestMode::Documentation)
+    .map(|(specifier, _)| specifier.clone())
+    .collect::<Vec<_>>();
+  let graph = ps.create_graph(roots).await?;
+
+  Ok(
+    specifiers_with_mode
+      .into_iter()
+      .filter(|(specifier, _)| {
+        let mut modules = HashSet::new();
+        modules.insert(specifier);
+        get_dependencies(&graph, graph.get(specifier), &mut modules, no_check);
+        modules.iter().any(|specifier| {
+          specifier
+            .to_file_path()
+            .ok()
+            .and_then(|path| canonicalize_path(&path).ok())
+            .map(|path| changed_files.contains(&path))
+            .unwrap_or(false)
+        })
+      })
+      .collect(),
+  )
+}
+
 pub async fn run_tests(
   cli_options: CliOptions,
   test_options: TestOptions,
@@ -1316,6 +1077,46 @@ pub async fn run_tests(
     return Err(generic_error("No test modules found"));
   }
 
+  let specifiers_with_mode = match &test_options.changed {
+    Some(git_ref) => {
+      let total = specifiers_with_mode.len();
+      let specifiers_with_mode =
+        filter_changed_specifiers(&ps, specifiers_with_mode, git_ref).await?;
+      log::info!(
+        "{} {} of {} test modules affected by changes since {}",
+        colors::green("Running"),
+        specifiers_with_mode.len(),
+        total,
+        git_ref
+      );
+      if specifiers_with_mode.is_empty() {
+        return Ok(());
+      }
+      specifiers_with_mode
+    }
+    None => specifiers_with_mode,
+  };
+
+  let specifiers_with_mode = match &test_options.shard {
+    Some(shard) => {
+      let total = specifiers_with_mode.len();
+      let cwd =
+        ModuleSpecifier::from_directory_path(std::env::current_dir()?).unwrap();
+      let specifiers_with_mode =
+        shard::filter_shard(specifiers_with_mode, shard, &cwd)?;
+      log::info!(
+        "{} shard {}/{} with {} of {} test modules",
+        colors::green("Running"),
+        shard.index,
+        shard.total,
+        specifiers_with_mode.len(),
+        total
+      );
+      specifiers_with_mode
+    }
+    None => specifiers_with_mode,
+  };
+
   check_specifiers(&ps, permissions.clone(), specifiers_with_mode.clone())
     .await?;
 
@@ -1331,6 +1132,9 @@ pub async fn run_tests(
       concurrent_jobs: test_options.concurrent_jobs,
       fail_fast: test_options.fail_fast,
       filter: TestFilter::from_flag(&test_options.filter),
+      timeout: test_options.timeout,
+      reporter: test_options.reporter,
+      junit_path: test_options.junit_path,
     },
   )
   .await?;
@@ -1377,44 +1181,6 @@ pub async fn run_tests_with_watch(
 
       // TODO(@kitsonk) - This should be totally derivable from the graph.
       for specifier in test_modules {
-        fn get_dependencies<'a>(
-          graph: &'a deno_graph::ModuleGraph,
-          maybe_module: Option<&'a deno_graph::Module>,
-          // This needs to be accessible to skip getting dependencies if they're already there,
-          // otherwise this will cause a stack overflow with circular dependencies
-          output: &mut HashSet<&'a ModuleSpecifier>,
-          no_check: bool,
-        ) {
-          if let Some(module) = maybe_module.and_then(|m| m.esm()) {
-            for dep in module.dependencies.values() {
-              if let Some(specifier) = &dep.get_code() {
-                if !output.contains(specifier) {
-                  output.insert(specifier);
-                  get_dependencies(
-                    graph,
-                    graph.get(specifier),
-                    output,
-                    no_check,
-                  );
-                }
-              }
-              if !no_check {
-                if let Some(specifier) = &dep.get_type() {
-                  if !output.contains(specifier) {
-                    output.insert(specifier);
-                    get_dependencies(
-                      graph,
-                      graph.get(specifier),
-                      output,
-                      no_check,
-                    );
-                  }
-                }
-              }
-            }
-          }
-        }
-
         // This test module and all it's dependencies
         let mut modules = HashSet::new();
         modules.insert(&specifier);
@@ -1494,6 +1260,9 @@ pub async fn run_tests_with_watch(
           concurrent_jobs: test_options.concurrent_jobs,
           fail_fast: test_options.fail_fast,
           filter: TestFilter::from_flag(&test_options.filter),
+          timeout: test_options.timeout,
+          reporter: test_options.reporter,
+          junit_path: test_options.junit_path.clone(),
         },
       )
       .await?;
@@ -1552,6 +1321,94 @@ impl FailFastTracker {
   }
 }
 
+/// Enforces the timeouts of the tests and steps of a module. A thread
+/// terminates the execution of the isolate once a timeout passes, which also
+/// stops a test that blocks the event loop.
+#[derive(Clone)]
+pub struct TestTimeoutWatchdog(Arc<TestTimeoutWatchdogInner>);
+
+#[derive(Default)]
+struct TestTimeoutWatchdogInner {
+  state: Mutex<TestTimeoutWatchdogState>,
+  condvar: Condvar,
+  timed_out: Notify,
+}
+
+#[derive(Default)]
+struct TestTimeoutWatchdogState {
+  deadlines: HashMap<usize, (Instant, Arc<Notify>)>,
+  timed_out: Option<usize>,
+  stopped: bool,
+}
+
+impl TestTimeoutWatchdog {
+  pub fn start(isolate: v8::IsolateHandle) -> Self {
+    let watchdog = Self(Default::default());
+    let inner = watchdog.0.clone();
+    std::thread::spawn(move || {
+      let mut state = inner.state.lock();
+      while !state.stopped {
+        let next_deadline = state
+          .deadlines
+          .iter()
+          .map(|(id, (deadline, _))| (*deadline, *id))
+          .min();
+        match next_deadline {
+          Some((deadline, id)) if deadline <= Instant::now() => {
+            state.timed_out = Some(id);
+            state.deadlines.clear();
+            isolate.terminate_execution();
+            inner.timed_out.notify_one();
+            break;
+          }
+          Some((deadline, _)) => {
+            inner.condvar.wait_until(&mut state, deadline);
+          }
+          None => inner.condvar.wait(&mut state),
+        }
+      }
+    });
+    watchdog
+  }
+
+  /// Starts the timeout of a test or step. The returned notifier is notified
+  /// once it ended within the timeout.
+  pub fn add(&self, id: usize, timeout: Duration) -> Arc<Notify> {
+    let ended = Arc::new(Notify::new());
+    let mut state = self.0.state.lock();
+    if state.timed_out.is_none() {
+      state
+        .deadlines
+        .insert(id, (Instant::now() + timeout, ended.clone()));
+      self.0.condvar.notify_one();
+    }
+    ended
+  }
+
+  /// Ends the timeout of a test or step. Returns false if a timeout passed
+  /// in the meantime.
+  pub fn remove(&self, id: usize) -> bool {
+    let mut state = self.0.state.lock();
+    if let Some((_, ended)) = state.deadlines.remove(&id) {
+      ended.notify_one();
+    }
+    state.timed_out.is_none()
+  }
+
+  /// Resolves once a timeout passed and the execution was terminated.
+  pub async fn timed_out(&self) {
+    self.0.timed_out.notified().await
+  }
+
+  /// Stops the thread, returning the id of the test or step that timed out.
+  pub fn stop(&self) -> Option<usize> {
+    let mut state = self.0.state.lock();
+    state.stopped = true;
+    self.0.condvar.notify_one();
+    state.timed_out
+  }
+}
+
 #[derive(Clone)]
 pub struct TestEventSender {
   sender: UnboundedSender<TestEvent>,
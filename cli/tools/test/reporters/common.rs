@@ -0,0 +1,157 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::colors;
+use crate::display;
+use crate::tools::test::format_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestSummary;
+use crate::util::path::to_relative_path_or_remote_url;
+
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Prints the "FLAKY", "ERRORS" and "FAILURES" sections followed by the one
+/// line summary that is shared by the human readable reporters.
+pub fn report_summary(cwd: &Url, summary: &TestSummary, elapsed: &Duration) {
+  if !summary.flaky.is_empty() {
+    println!("\n{}\n", colors::black_on_yellow(" FLAKY "));
+    for description in &summary.flaky {
+      println!(
+        "{} {}",
+        &description.name,
+        colors::gray(format!(
+          "=> {}:{}:{}",
+          to_relative_path_or_remote_url(cwd, &description.location.file_name),
+          description.location.line_number,
+          description.location.column_number
+        ))
+      );
+    }
+  }
+
+  if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
+    #[allow(clippy::type_complexity)] // Type alias doesn't look better here
+    let mut failures_by_origin: BTreeMap<
+      String,
+      (Vec<(&TestDescription, &JsError)>, Option<&JsError>),
+    > = BTreeMap::default();
+    let mut failure_titles = vec![];
+    for (description, js_error) in &summary.failures {
+      let (failures, _) = failures_by_origin
+        .entry(description.origin.clone())
+        .or_default();
+      failures.push((description, js_error.as_ref()));
+    }
+    for (origin, js_error) in &summary.uncaught_errors {
+      let (_, uncaught_error) =
+        failures_by_origin.entry(origin.clone()).or_default();
+      let _ = uncaught_error.insert(js_error.as_ref());
+    }
+    println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
+    for (origin, (failures, uncaught_error)) in failures_by_origin {
+      for (description, js_error) in failures {
+        let failure_title = format!(
+          "{} {}",
+          &description.name,
+          colors::gray(format!(
+            "=> {}:{}:{}",
+            to_relative_path_or_remote_url(
+              cwd,
+              &description.location.file_name
+            ),
+            description.location.line_number,
+            description.location.column_number
+          ))
+        );
+        println!("{}", &failure_title);
+        println!(
+          "{}: {}",
+          colors::red_bold("error"),
+          format_test_error(js_error)
+        );
+        println!();
+        failure_titles.push(failure_title);
+      }
+      if let Some(js_error) = uncaught_error {
+        let failure_title = format!(
+          "{} (uncaught error)",
+          to_relative_path_or_remote_url(cwd, &origin)
+        );
+        println!("{}", &failure_title);
+        println!(
+          "{}: {}",
+          colors::red_bold("error"),
+          format_test_error(js_error)
+        );
+        println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
+        println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
+        println!();
+        failure_titles.push(failure_title);
+      }
+    }
+    println!("{}\n", colors::white_bold_on_red(" FAILURES "));
+    for failure_title in failure_titles {
+      println!("{failure_title}");
+    }
+  }
+
+  let status = if summary.has_failed() {
+    colors::red("FAILED").to_string()
+  } else {
+    colors::green("ok").to_string()
+  };
+
+  let get_steps_text = |count: usize| -> String {
+    if count == 0 {
+      String::new()
+    } else if count == 1 {
+      " (1 step)".to_string()
+    } else {
+      format!(" ({count} steps)")
+    }
+  };
+
+  let mut summary_result = String::new();
+
+  write!(
+    summary_result,
+    "{} passed{} | {} failed{}",
+    summary.passed,
+    get_steps_text(summary.passed_steps),
+    summary.failed,
+    get_steps_text(summary.failed_steps + summary.pending_steps),
+  )
+  .unwrap();
+
+  if !summary.flaky.is_empty() {
+    write!(summary_result, " | {} flaky", summary.flaky.len()).unwrap();
+  }
+
+  let ignored_steps = get_steps_text(summary.ignored_steps);
+  if summary.ignored > 0 || !ignored_steps.is_empty() {
+    write!(
+      summary_result,
+      " | {} ignored{}",
+      summary.ignored, ignored_steps
+    )
+    .unwrap()
+  }
+
+  if summary.measured > 0 {
+    write!(summary_result, " | {} measured", summary.measured,).unwrap();
+  }
+
+  if summary.filtered_out > 0 {
+    write!(summary_result, " | {} filtered out", summary.filtered_out).unwrap()
+  };
+
+  println!(
+    "\n{} | {} {}\n",
+    status,
+    summary_result,
+    colors::gray(format!("({})", display::human_elapsed(elapsed.as_millis()))),
+  );
+}
@@ -0,0 +1,201 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::TestReporter;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::io::Write;
+use std::time::Duration;
+
+/// A single line of the newline delimited JSON output of `deno test --json`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JsonTestEvent<'a> {
+  Plan(&'a TestPlan),
+  Register(&'a TestDescription),
+  #[serde(rename_all = "camelCase")]
+  Wait {
+    id: usize,
+  },
+  #[serde(rename_all = "camelCase")]
+  Output {
+    output: String,
+  },
+  #[serde(rename_all = "camelCase")]
+  Result {
+    id: usize,
+    result: &'a TestResult,
+    elapsed: u64,
+  },
+  #[serde(rename_all = "camelCase")]
+  UncaughtError {
+    origin: &'a str,
+    error: &'a JsError,
+  },
+  StepRegister(&'a TestStepDescription),
+  #[serde(rename_all = "camelCase")]
+  StepWait {
+    id: usize,
+  },
+  #[serde(rename_all = "camelCase")]
+  StepResult {
+    id: usize,
+    result: &'a TestStepResult,
+    elapsed: u64,
+  },
+  #[serde(rename_all = "camelCase")]
+  Summary {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    flaky: usize,
+    ignored: usize,
+    passed_steps: usize,
+    failed_steps: usize,
+    pending_steps: usize,
+    ignored_steps: usize,
+    filtered_out: usize,
+    measured: usize,
+    elapsed: u64,
+  },
+}
+
+/// Writes every test event to stdout as soon as it is received, one JSON
+/// object per line, so that other tools can follow a test run live.
+pub struct JsonTestReporter;
+
+impl JsonTestReporter {
+  pub fn new() -> JsonTestReporter {
+    JsonTestReporter
+  }
+
+  fn write_event(&mut self, event: JsonTestEvent) {
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &event).unwrap();
+    stdout.write_all(b"\n").unwrap();
+    // flush for faster feedback when line buffered
+    stdout.flush().unwrap();
+  }
+}
+
+impl TestReporter for JsonTestReporter {
+  fn report_register(&mut self, description: &TestDescription) {
+    self.write_event(JsonTestEvent::Register(description));
+  }
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    self.write_event(JsonTestEvent::Plan(plan));
+  }
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    self.write_event(JsonTestEvent::Wait { id: description.id });
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    self.write_event(JsonTestEvent::Output {
+      output: String::from_utf8_lossy(output).to_string(),
+    });
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    self.write_event(JsonTestEvent::Result {
+      id: description.id,
+      result,
+      elapsed,
+    });
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.write_event(JsonTestEvent::UncaughtError { origin, error });
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    self.write_event(JsonTestEvent::StepRegister(description));
+  }
+
+  fn report_step_wait(&mut self, description: &TestStepDescription) {
+    self.write_event(JsonTestEvent::StepWait { id: description.id });
+  }
+
+  fn report_step_result(
+    &mut self,
+    description: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.write_event(JsonTestEvent::StepResult {
+      id: description.id,
+      result,
+      elapsed,
+    });
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    self.write_event(JsonTestEvent::Summary {
+      total: summary.total,
+      passed: summary.passed,
+      failed: summary.failed,
+      flaky: summary.flaky.len(),
+      ignored: summary.ignored,
+      passed_steps: summary.passed_steps,
+      failed_steps: summary.failed_steps,
+      pending_steps: summary.pending_steps,
+      ignored_steps: summary.ignored_steps,
+      filtered_out: summary.filtered_out,
+      measured: summary.measured,
+      elapsed: elapsed.as_millis() as u64,
+    });
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_json_test_event_serialization() {
+    assert_eq!(
+      serde_json::to_string(&JsonTestEvent::Wait { id: 1 }).unwrap(),
+      r#"{"type":"wait","id":1}"#
+    );
+    assert_eq!(
+      serde_json::to_string(&JsonTestEvent::Result {
+        id: 2,
+        result: &TestResult::Ok,
+        elapsed: 10,
+      })
+      .unwrap(),
+      r#"{"type":"result","id":2,"result":"ok","elapsed":10}"#
+    );
+    let plan = TestPlan {
+      origin: "file:///a_test.ts".to_string(),
+      total: 1,
+      filtered_out: 0,
+      used_only: false,
+    };
+    assert_eq!(
+      serde_json::to_string(&JsonTestEvent::Plan(&plan)).unwrap(),
+      r#"{"type":"plan","origin":"file:///a_test.ts","total":1,"filteredOut":0,"usedOnly":false}"#
+    );
+  }
+}
@@ -0,0 +1,341 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::TestReporter;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestLocation;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+use crate::util::path::to_relative_path_or_remote_url;
+use crate::util::xml::escape_xml;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+enum JunitTestCaseStatus {
+  Passed,
+  Skipped,
+  Failed { message: String, details: String },
+  Error { message: String, details: String },
+}
+
+impl JunitTestCaseStatus {
+  fn is_failure(&self) -> bool {
+    matches!(self, JunitTestCaseStatus::Failed { .. })
+  }
+
+  fn is_error(&self) -> bool {
+    matches!(self, JunitTestCaseStatus::Error { .. })
+  }
+
+  fn is_skipped(&self) -> bool {
+    matches!(self, JunitTestCaseStatus::Skipped)
+  }
+}
+
+#[derive(Debug, Clone)]
+struct JunitTestCase {
+  name: String,
+  classname: String,
+  location: Option<TestLocation>,
+  time_ms: u64,
+  status: JunitTestCaseStatus,
+}
+
+/// Collects every result and writes a JUnit XML report once all tests have
+/// finished. Each test module is reported as a `<testsuite>` and steps are
+/// flattened into test cases named after their ancestors.
+pub struct JunitTestReporter {
+  maybe_path: Option<PathBuf>,
+  cwd: Url,
+  cases: Vec<(String, JunitTestCase)>,
+  elapsed: Duration,
+}
+
+impl JunitTestReporter {
+  /// Creates a reporter that writes to the file at `maybe_path`, or to
+  /// stdout when no path is provided.
+  pub fn new(maybe_path: Option<PathBuf>) -> JunitTestReporter {
+    JunitTestReporter {
+      maybe_path,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      cases: Vec::new(),
+      elapsed: Duration::ZERO,
+    }
+  }
+
+  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
+    to_relative_path_or_remote_url(&self.cwd, path_or_url)
+  }
+
+  fn add_case(&mut self, origin: &str, case: JunitTestCase) {
+    self.cases.push((origin.to_string(), case));
+  }
+
+  fn serialize(&self) -> String {
+    let mut suites: IndexMap<&str, Vec<&JunitTestCase>> = IndexMap::new();
+    for (origin, case) in &self.cases {
+      suites.entry(origin.as_str()).or_default().push(case);
+    }
+
+    let all_cases = self.cases.iter().map(|(_, c)| c).collect::<Vec<_>>();
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+      xml,
+      r#"<testsuites name="deno test" tests="{}" failures="{}" errors="{}" time="{}">"#,
+      all_cases.len(),
+      count_cases(&all_cases, JunitTestCaseStatus::is_failure),
+      count_cases(&all_cases, JunitTestCaseStatus::is_error),
+      format_seconds(self.elapsed.as_millis() as u64),
+    )
+    .unwrap();
+
+    for (origin, cases) in suites {
+      let time_ms: u64 = cases.iter().map(|case| case.time_ms).sum();
+      writeln!(
+        xml,
+        r#"  <testsuite name="{}" tests="{}" skipped="{}" errors="{}" failures="{}" time="{}">"#,
+        escape_xml(&self.to_relative_path_or_remote_url(origin)),
+        cases.len(),
+        count_cases(&cases, JunitTestCaseStatus::is_skipped),
+        count_cases(&cases, JunitTestCaseStatus::is_error),
+        count_cases(&cases, JunitTestCaseStatus::is_failure),
+        format_seconds(time_ms),
+      )
+      .unwrap();
+      for case in cases {
+        write!(
+          xml,
+          r#"    <testcase name="{}" classname="{}" time="{}""#,
+          escape_xml(&case.name),
+          escape_xml(&case.classname),
+          format_seconds(case.time_ms),
+        )
+        .unwrap();
+        if let Some(location) = &case.location {
+          write!(
+            xml,
+            r#" file="{}" line="{}" col="{}""#,
+            escape_xml(
+              &self.to_relative_path_or_remote_url(&location.file_name)
+            ),
+            location.line_number,
+            location.column_number,
+          )
+          .unwrap();
+        }
+        match &case.status {
+          JunitTestCaseStatus::Passed => {
+            writeln!(xml, "/>").unwrap();
+          }
+          JunitTestCaseStatus::Skipped => {
+            writeln!(xml, ">").unwrap();
+            writeln!(xml, "      <skipped/>").unwrap();
+            writeln!(xml, "    </testcase>").unwrap();
+          }
+          JunitTestCaseStatus::Failed { message, details }
+          | JunitTestCaseStatus::Error { message, details } => {
+            let tag = if case.status.is_failure() {
+              "failure"
+            } else {
+              "error"
+            };
+            writeln!(xml, ">").unwrap();
+            writeln!(
+              xml,
+              r#"      <{tag} message="{}">{}</{tag}>"#,
+              escape_xml(message),
+              escape_xml(details),
+            )
+            .unwrap();
+            writeln!(xml, "    </testcase>").unwrap();
+          }
+        }
+      }
+      writeln!(xml, "  </testsuite>").unwrap();
+    }
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+  }
+}
+
+fn count_cases(
+  cases: &[&JunitTestCase],
+  predicate: fn(&JunitTestCaseStatus) -> bool,
+) -> usize {
+  cases.iter().filter(|case| predicate(&case.status)).count()
+}
+
+fn format_seconds(time_ms: u64) -> String {
+  format!("{:.3}", time_ms as f64 / 1000.0)
+}
+
+/// Returns the error message and the stack trace of an error to embed in the
+/// report, where `escape_xml` removes their color codes.
+fn error_message_and_details(error: &JsError) -> (String, String) {
+  let message = error
+    .exception_message
+    .trim_start_matches("Uncaught ")
+    .to_string();
+  let details = error.stack.clone().unwrap_or_else(|| message.clone());
+  (message, details)
+}
+
+impl TestReporter for JunitTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, _output: &[u8]) {}
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    let status = match result {
+      TestResult::Ok | TestResult::Flaky => JunitTestCaseStatus::Passed,
+      TestResult::Ignored => JunitTestCaseStatus::Skipped,
+      TestResult::Failed(error) => {
+        let (message, details) = error_message_and_details(error);
+        JunitTestCaseStatus::Failed { message, details }
+      }
+      TestResult::Cancelled => JunitTestCaseStatus::Error {
+        message: "Test was cancelled".to_string(),
+        details:
+          "Test was cancelled because of an uncaught error in the module"
+            .to_string(),
+      },
+    };
+    let classname = self.to_relative_path_or_remote_url(&description.origin);
+    self.add_case(
+      &description.origin,
+      JunitTestCase {
+        name: description.name.clone(),
+        classname,
+        location: Some(description.location.clone()),
+        time_ms: elapsed,
+        status,
+      },
+    );
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    let (message, details) = error_message_and_details(error);
+    let classname = self.to_relative_path_or_remote_url(origin);
+    self.add_case(
+      origin,
+      JunitTestCase {
+        name: format!("{classname} (uncaught error)"),
+        classname,
+        location: None,
+        time_ms: 0,
+        status: JunitTestCaseStatus::Error { message, details },
+      },
+    );
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    description: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let mut names = vec![description.name.as_str()];
+    let mut current_desc = description;
+    while let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
+      names.push(&step_desc.name);
+      current_desc = step_desc;
+    }
+    if let Some(root) = tests.get(&current_desc.parent_id) {
+      names.push(&root.name);
+    }
+    names.reverse();
+
+    let status = match result {
+      TestStepResult::Ok => JunitTestCaseStatus::Passed,
+      TestStepResult::Ignored => JunitTestCaseStatus::Skipped,
+      TestStepResult::Failed(maybe_error) => match maybe_error {
+        Some(error) => {
+          let (message, details) = error_message_and_details(error);
+          JunitTestCaseStatus::Failed { message, details }
+        }
+        None => JunitTestCaseStatus::Failed {
+          message: "Step failed".to_string(),
+          details: "One or more child steps failed".to_string(),
+        },
+      },
+      TestStepResult::Pending(_) => JunitTestCaseStatus::Failed {
+        message: "Step did not complete".to_string(),
+        details: "Step was not awaited before its parent test completed"
+          .to_string(),
+      },
+    };
+    let classname = self.to_relative_path_or_remote_url(&description.origin);
+    self.add_case(
+      &description.origin,
+      JunitTestCase {
+        name: names.join(" > "),
+        classname,
+        location: Some(description.location.clone()),
+        time_ms: elapsed,
+        status,
+      },
+    );
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, elapsed: &Duration) {
+    self.elapsed = *elapsed;
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    let xml = self.serialize();
+    match &self.maybe_path {
+      Some(path) => {
+        if let Some(parent) = path.parent() {
+          std::fs::create_dir_all(parent).with_context(|| {
+            format!("Failed creating directory: {}", parent.display())
+          })?;
+        }
+        std::fs::write(path, xml).with_context(|| {
+          format!("Failed writing JUnit report: {}", path.display())
+        })?;
+      }
+      None => {
+        std::io::stdout().write_all(xml.as_bytes())?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_format_seconds() {
+    assert_eq!(format_seconds(0), "0.000");
+    assert_eq!(format_seconds(1234), "1.234");
+  }
+}
@@ -0,0 +1,310 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::TestReporter;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+use crate::util::path::to_relative_path_or_remote_url;
+
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+/// Reports results in the Test Anything Protocol (version 14) format. Steps
+/// are reported as subtests of the test they belong to, which requires
+/// buffering them until the result of their parent test is known.
+pub struct TapTestReporter {
+  cwd: Url,
+  is_first_plan: bool,
+  n: usize,
+  output_in_new_line: bool,
+  steps: IndexMap<usize, TestStepDescription>,
+  step_results: HashMap<usize, TestStepResult>,
+}
+
+impl TapTestReporter {
+  pub fn new() -> TapTestReporter {
+    TapTestReporter {
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      is_first_plan: true,
+      n: 0,
+      output_in_new_line: true,
+      steps: IndexMap::new(),
+      step_results: HashMap::new(),
+    }
+  }
+
+  fn escape_description(description: &str) -> String {
+    description
+      .replace('\\', "\\\\")
+      .replace('\n', "\\n")
+      .replace('\r', "\\r")
+      .replace('#', "\\#")
+  }
+
+  fn print_line(
+    indent: usize,
+    status: &str,
+    n: usize,
+    name: &str,
+    maybe_directive: Option<&str>,
+  ) {
+    let directive = match maybe_directive {
+      Some(directive) => format!(" # {directive}"),
+      None => String::new(),
+    };
+    println!(
+      "{}{} {} - {}{}",
+      "    ".repeat(indent),
+      status,
+      n,
+      Self::escape_description(name),
+      directive
+    );
+  }
+
+  fn print_diagnostic(
+    indent: usize,
+    message: &str,
+    location: Option<(&str, u32, u32)>,
+  ) {
+    let indent = format!("{}  ", "    ".repeat(indent));
+    println!("{indent}---");
+    println!("{indent}message: {}", yaml_string(message));
+    println!("{indent}severity: fail");
+    if let Some((file, line, column)) = location {
+      println!("{indent}at:");
+      println!("{indent}  file: {}", yaml_string(file));
+      println!("{indent}  line: {line}");
+      println!("{indent}  column: {column}");
+    }
+    println!("{indent}...");
+  }
+
+  fn print_steps(&mut self, parent_id: usize, indent: usize) {
+    let children = self
+      .steps
+      .values()
+      .filter(|step| step.parent_id == parent_id)
+      .cloned()
+      .collect::<Vec<_>>();
+    if children.is_empty() {
+      return;
+    }
+
+    let mut n = 0;
+    for step in &children {
+      let result = match self.step_results.remove(&step.id) {
+        Some(result) => result,
+        None => continue,
+      };
+      if self.steps.values().any(|s| s.parent_id == step.id) {
+        println!(
+          "{}# Subtest: {}",
+          "    ".repeat(indent),
+          Self::escape_description(&step.name)
+        );
+        self.print_steps(step.id, indent + 1);
+      }
+      n += 1;
+      match &result {
+        TestStepResult::Ok => {
+          Self::print_line(indent, "ok", n, &step.name, None);
+        }
+        TestStepResult::Ignored => {
+          Self::print_line(indent, "ok", n, &step.name, Some("SKIP"));
+        }
+        TestStepResult::Pending(_) | TestStepResult::Failed(_) => {
+          Self::print_line(indent, "not ok", n, &step.name, None);
+          let message = match result.error() {
+            Some(error) => error_message(error),
+            None => "Step failed".to_string(),
+          };
+          let file_name =
+            to_relative_path_or_remote_url(&self.cwd, &step.location.file_name);
+          Self::print_diagnostic(
+            indent,
+            &message,
+            Some((
+              &file_name,
+              step.location.line_number,
+              step.location.column_number,
+            )),
+          );
+        }
+      }
+    }
+    if n > 0 {
+      println!("{}1..{}", "    ".repeat(indent), n);
+    }
+  }
+}
+
+fn error_message(error: &JsError) -> String {
+  error
+    .exception_message
+    .trim_start_matches("Uncaught ")
+    .to_string()
+}
+
+fn yaml_string(value: &str) -> String {
+  // JSON strings are valid YAML flow scalars and take care of escaping
+  // newlines and quotes in messages.
+  deno_core::serde_json::to_string(value).unwrap()
+}
+
+impl TestReporter for TapTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    if self.is_first_plan {
+      println!("TAP version 14");
+      self.is_first_plan = false;
+    }
+    println!(
+      "# {}",
+      to_relative_path_or_remote_url(&self.cwd, &plan.origin)
+    );
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    // User output is printed as TAP comments so that it doesn't interfere
+    // with consumers of the report.
+    let output = String::from_utf8_lossy(output);
+    let mut stdout = std::io::stdout();
+    for line in output.split_inclusive('\n') {
+      if self.output_in_new_line {
+        write!(stdout, "# ").unwrap();
+      }
+      write!(stdout, "{line}").unwrap();
+      self.output_in_new_line = line.ends_with('\n');
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    _elapsed: u64,
+  ) {
+    if !self.output_in_new_line {
+      println!();
+      self.output_in_new_line = true;
+    }
+
+    if self.steps.values().any(|s| s.parent_id == description.id) {
+      println!("# Subtest: {}", Self::escape_description(&description.name));
+      self.print_steps(description.id, 1);
+    }
+    self.steps.retain(|_, step| step.root_id != description.id);
+
+    self.n += 1;
+    let location = (
+      to_relative_path_or_remote_url(
+        &self.cwd,
+        &description.location.file_name,
+      ),
+      description.location.line_number,
+      description.location.column_number,
+    );
+    match result {
+      TestResult::Ok | TestResult::Flaky => {
+        Self::print_line(0, "ok", self.n, &description.name, None);
+      }
+      TestResult::Ignored => {
+        Self::print_line(0, "ok", self.n, &description.name, Some("SKIP"));
+      }
+      TestResult::Failed(error) => {
+        Self::print_line(0, "not ok", self.n, &description.name, None);
+        Self::print_diagnostic(
+          0,
+          &error_message(error),
+          Some((&location.0, location.1, location.2)),
+        );
+      }
+      TestResult::Cancelled => {
+        Self::print_line(0, "not ok", self.n, &description.name, None);
+        Self::print_diagnostic(
+          0,
+          "Test was cancelled because of an uncaught error",
+          Some((&location.0, location.1, location.2)),
+        );
+      }
+    }
+    std::io::stdout().flush().unwrap();
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    if !self.output_in_new_line {
+      println!();
+      self.output_in_new_line = true;
+    }
+    self.n += 1;
+    let origin = to_relative_path_or_remote_url(&self.cwd, origin);
+    Self::print_line(
+      0,
+      "not ok",
+      self.n,
+      &format!("{origin} (uncaught error)"),
+      None,
+    );
+    Self::print_diagnostic(0, &error_message(error), None);
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    self.steps.insert(description.id, description.clone());
+  }
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    description: &TestStepDescription,
+    result: &TestStepResult,
+    _elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.step_results.insert(description.id, result.clone());
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    if self.is_first_plan {
+      println!("TAP version 14");
+    }
+    println!("1..{}", self.n);
+    println!("# passed: {}", summary.passed);
+    println!("# failed: {}", summary.failed);
+    println!("# flaky: {}", summary.flaky.len());
+    println!("# ignored: {}", summary.ignored);
+    println!("# filtered out: {}", summary.filtered_out);
+    println!("# duration: {}ms", elapsed.as_millis());
+  }
+
+  fn flush_report(&mut self) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_description() {
+    assert_eq!(TapTestReporter::escape_description("test 1"), "test 1");
+    assert_eq!(
+      TapTestReporter::escape_description("a # b\nc"),
+      "a \\# b\\nc"
+    );
+  }
+}
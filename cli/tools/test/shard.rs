@@ -0,0 +1,271 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::args::TestShardFlags;
+use crate::util::path::to_relative_path_or_remote_url;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+/// The events of a `deno test --json` run that are needed to know how long
+/// each test module took.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum TimingEvent {
+  Register {
+    id: usize,
+    origin: String,
+  },
+  Result {
+    id: usize,
+    elapsed: u64,
+  },
+  #[serde(other)]
+  Other,
+}
+
+/// Reads the total duration of the tests of each module, in milliseconds,
+/// from the output of a previous `deno test --json` run.
+fn parse_timings(text: &str) -> Result<HashMap<String, u64>, AnyError> {
+  let mut origins = HashMap::new();
+  let mut timings = HashMap::new();
+  for (line_index, line) in text.lines().enumerate() {
+    if !line.starts_with('{') {
+      continue;
+    }
+    let event: TimingEvent = serde_json::from_str(line)
+      .with_context(|| format!("Invalid event on line {}", line_index + 1))?;
+    match event {
+      TimingEvent::Register { id, origin } => {
+        origins.insert(id, origin);
+      }
+      TimingEvent::Result { id, elapsed } => {
+        if let Some(origin) = origins.get(&id) {
+          *timings.entry(origin.clone()).or_insert(0) += elapsed;
+        }
+      }
+      TimingEvent::Other => {}
+    }
+  }
+  Ok(timings)
+}
+
+/// Gets the key of a module, which is its path relative to `cwd` so that it
+/// is the same on every machine, or its url for a remote module.
+fn shard_key(cwd: &Url, specifier: &ModuleSpecifier) -> String {
+  to_relative_path_or_remote_url(cwd, specifier.as_str())
+}
+
+/// Matches the timings, which are keyed by the urls of the modules on the
+/// machine that recorded them, to the given keys. The url of a local module
+/// matches the key of the longest suffix of its path.
+fn match_timings(
+  timings: &HashMap<String, u64>,
+  keys: &[String],
+) -> HashMap<String, u64> {
+  let keys = keys.iter().map(|key| key.as_str()).collect::<HashSet<_>>();
+  let mut matched = HashMap::new();
+  for (origin, elapsed) in timings {
+    let maybe_key = if keys.contains(origin.as_str()) {
+      Some(origin.as_str())
+    } else {
+      match Url::parse(origin) {
+        Ok(url) if url.scheme() == "file" => {
+          let path = url.path();
+          path.match_indices('/').find_map(|(index, _)| {
+            keys.get(format!(".{}", &path[index..]).as_str()).copied()
+          })
+        }
+        _ => None,
+      }
+    };
+    if let Some(key) = maybe_key {
+      *matched.entry(key.to_string()).or_insert(0) += elapsed;
+    }
+  }
+  matched
+}
+
+/// Splits the modules in `total` shards of about the same duration. The
+/// longest modules are placed first, each in the shard that is the shortest
+/// so far. Modules without a known duration are assumed to take the average
+/// duration.
+fn balance(
+  keys: &[String],
+  timings: &HashMap<String, u64>,
+  total: usize,
+) -> Vec<usize> {
+  let known = keys
+    .iter()
+    .filter_map(|key| timings.get(key))
+    .collect::<Vec<_>>();
+  let average = if known.is_empty() {
+    1
+  } else {
+    known.iter().copied().sum::<u64>() / known.len() as u64
+  };
+
+  let mut order = (0..keys.len()).collect::<Vec<_>>();
+  let duration = |i: usize| timings.get(&keys[i]).copied().unwrap_or(average);
+  // the sort is stable and the keys are sorted, so ties are deterministic
+  order.sort_by_key(|i| std::cmp::Reverse(duration(*i)));
+
+  let mut shard_durations = vec![0; total];
+  let mut shards = vec![0; keys.len()];
+  for i in order {
+    let (shard, _) = shard_durations
+      .iter()
+      .enumerate()
+      .min_by_key(|(shard, duration)| (**duration, *shard))
+      .unwrap();
+    shard_durations[shard] += duration(i);
+    shards[i] = shard;
+  }
+  shards
+}
+
+/// Only keeps the test modules of the shard passed with `--shard`. Modules
+/// are sorted by their path relative to `cwd` first, so that every machine
+/// computes the same shards as long as the same modules are collected.
+pub fn filter_shard<T>(
+  specifiers: Vec<(ModuleSpecifier, T)>,
+  shard: &TestShardFlags,
+  cwd: &Url,
+) -> Result<Vec<(ModuleSpecifier, T)>, AnyError> {
+  let mut specifiers = specifiers
+    .into_iter()
+    .map(|(specifier, value)| (shard_key(cwd, &specifier), specifier, value))
+    .collect::<Vec<_>>();
+  specifiers.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+  let keys = specifiers
+    .iter()
+    .map(|(key, _, _)| key.clone())
+    .collect::<Vec<_>>();
+
+  let shards = match &shard.timings_path {
+    Some(timings_path) => {
+      let text = std::fs::read_to_string(timings_path).with_context(|| {
+        format!("Failed reading shard timings: {timings_path}")
+      })?;
+      let timings = parse_timings(&text).with_context(|| {
+        format!("Failed parsing shard timings: {timings_path}")
+      })?;
+      balance(&keys, &match_timings(&timings, &keys), shard.total)
+    }
+    None => (0..keys.len()).map(|i| i % shard.total).collect(),
+  };
+
+  Ok(
+    specifiers
+      .into_iter()
+      .zip(shards)
+      .filter(|(_, i)| *i == shard.index - 1)
+      .map(|((_, specifier, value), _)| (specifier, value))
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn specifiers(names: &[&str]) -> Vec<(ModuleSpecifier, ())> {
+    names
+      .iter()
+      .map(|name| {
+        (
+          ModuleSpecifier::parse(&format!("file:///{name}")).unwrap(),
+          (),
+        )
+      })
+      .collect()
+  }
+
+  fn names(specifiers: Vec<(ModuleSpecifier, ())>) -> Vec<String> {
+    specifiers
+      .into_iter()
+      .map(|(specifier, _)| specifier.path()[1..].to_string())
+      .collect()
+  }
+
+  #[test]
+  fn test_filter_shard() {
+    let all = specifiers(&["e.ts", "a.ts", "d.ts", "b.ts", "c.ts"]);
+    let cwd = Url::parse("file:///").unwrap();
+    let shard = |index| TestShardFlags {
+      index,
+      total: 2,
+      timings_path: None,
+    };
+    assert_eq!(
+      names(filter_shard(all.clone(), &shard(1), &cwd).unwrap()),
+      vec!["a.ts", "c.ts", "e.ts"]
+    );
+    assert_eq!(
+      names(filter_shard(all, &shard(2), &cwd).unwrap()),
+      vec!["b.ts", "d.ts"]
+    );
+  }
+
+  #[test]
+  fn test_match_timings() {
+    let cwd = Url::parse("file:///home/b/repo/").unwrap();
+    let keys = ["test/a.ts", "a.ts", "https://deno.land/x/c.ts"]
+      .iter()
+      .map(|name| shard_key(&cwd, &cwd.join(name).unwrap()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      keys,
+      vec!["./test/a.ts", "./a.ts", "https://deno.land/x/c.ts"]
+    );
+    // recorded on another machine with the repository in another directory
+    let timings = HashMap::from([
+      ("file:///home/a/repo/test/a.ts".to_string(), 10),
+      ("file:///home/a/repo/a.ts".to_string(), 20),
+      ("file:///home/a/repo/b.ts".to_string(), 30),
+      ("https://deno.land/x/c.ts".to_string(), 40),
+    ]);
+    assert_eq!(
+      match_timings(&timings, &keys),
+      HashMap::from([
+        ("./test/a.ts".to_string(), 10),
+        ("./a.ts".to_string(), 20),
+        ("https://deno.land/x/c.ts".to_string(), 40),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_parse_timings() {
+    let text = r#"{"type":"plan","origin":"file:///a.ts","total":2,"filteredOut":0,"usedOnly":false}
+{"type":"register","id":1,"name":"a1","origin":"file:///a.ts","location":{"fileName":"file:///a.ts","lineNumber":1,"columnNumber":6}}
+{"type":"register","id":2,"name":"a2","origin":"file:///a.ts","location":{"fileName":"file:///a.ts","lineNumber":2,"columnNumber":6}}
+{"type":"wait","id":1}
+{"type":"result","id":1,"result":"ok","elapsed":10}
+{"type":"result","id":2,"result":"ok","elapsed":15}
+"#;
+    let timings = parse_timings(text).unwrap();
+    assert_eq!(timings.len(), 1);
+    assert_eq!(timings["file:///a.ts"], 25);
+  }
+
+  #[test]
+  fn test_balance() {
+    let keys = ["a.ts", "b.ts", "c.ts", "d.ts"]
+      .iter()
+      .map(|name| name.to_string())
+      .collect::<Vec<_>>();
+    let timings = HashMap::from([
+      ("a.ts".to_string(), 100),
+      ("b.ts".to_string(), 60),
+      ("c.ts".to_string(), 50),
+    ]);
+    // d.ts is unknown and takes the average of 70
+    assert_eq!(balance(&keys, &timings, 2), vec![0, 1, 0, 1]);
+  }
+}
@@ -326,8 +326,7 @@ pub async fn upgrade(
       };
 
       let current_is_most_recent = if upgrade_flags.canary {
-        let latest_hash = latest_version.clone();
-        crate::version::GIT_COMMIT_HASH == latest_hash
+        crate::version::GIT_COMMIT_HASH == latest_version
       } else if !crate::version::is_canary() {
         let current = Version::parse_standard(&crate::version::deno()).unwrap();
         let latest = Version::parse_standard(&latest_version).unwrap();
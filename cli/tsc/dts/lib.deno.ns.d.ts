@@ -744,6 +744,21 @@ declare namespace Deno {
      *
      * Defaults to the parent test or step's value. */
     sanitizeExit?: boolean;
+    /** The number of times the step is run again when it fails. A step that
+     * passes after being retried marks its test as flaky. Only the steps of
+     * its last attempt are reported.
+     *
+     * Unlike the sanitizers, this isn't inherited from the parent test or
+     * step, which already runs its steps again when it is retried.
+     *
+     * @default {0} */
+    retries?: number;
+    /** Fail the step when it doesn't finish within the given number of
+     * milliseconds, showing the async ops it is still waiting for. Like a
+     * test, it is stopped and the rest of its module is cancelled.
+     *
+     * Defaults to the parent test or step's value. */
+    timeout?: number;
   }
 
   /** @category Testing */
@@ -787,6 +802,20 @@ declare namespace Deno {
      *
      * @default {"inherit"} */
     permissions?: PermissionOptions;
+    /** The number of times the test is run again when it fails. A test that
+     * passes after being retried is reported as flaky and doesn't fail the
+     * test run. Only the steps of the last attempt are reported.
+     *
+     * Defaults to the value of the `--retries` flag, or `0`. */
+    retries?: number;
+    /** Fail the test when it doesn't finish within the given number of
+     * milliseconds, showing the async ops it is still waiting for.
+     *
+     * Defaults to the value of the `--timeout` flag or of `test.timeout` in
+     * the configuration file. A test that times out is stopped even if it
+     * blocks the event loop, and the tests of its module that didn't run yet
+     * are cancelled. */
+    timeout?: number;
   }
 
   /** Register a test which will be run when `deno test` is used on the command
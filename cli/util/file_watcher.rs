@@ -15,6 +15,7 @@ use notify::RecommendedWatcher;
 use notify::RecursiveMode;
 use notify::Watcher;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -66,21 +67,24 @@ impl DebouncedReceiver {
   }
 }
 
-async fn error_handler<F>(watch_future: F)
+async fn error_handler<F, T>(watch_future: F) -> Option<T>
 where
-  F: Future<Output = Result<(), AnyError>>,
+  F: Future<Output = Result<T, AnyError>>,
 {
-  let result = watch_future.await;
-  if let Err(err) = result {
-    let error_string = match err.downcast_ref::<JsError>() {
-      Some(e) => format_js_error(e),
-      None => format!("{err:?}"),
-    };
-    eprintln!(
-      "{}: {}",
-      colors::red_bold("error"),
-      error_string.trim_start_matches("error: ")
-    );
+  match watch_future.await {
+    Ok(value) => Some(value),
+    Err(err) => {
+      let error_string = match err.downcast_ref::<JsError>() {
+        Some(e) => format_js_error(e),
+        None => format!("{err:?}"),
+      };
+      eprintln!(
+        "{}: {}",
+        colors::red_bold("error"),
+        error_string.trim_start_matches("error: ")
+      );
+      None
+    }
   }
 }
 
@@ -283,26 +287,6 @@ where
 
   info!("{} {} started.", colors::intense_blue("Watcher"), job_name,);
 
-  fn consume_paths_to_watch(
-    watcher: &mut RecommendedWatcher,
-    receiver: &mut UnboundedReceiver<Vec<PathBuf>>,
-  ) {
-    loop {
-      match receiver.try_recv() {
-        Ok(paths) => {
-          add_paths_to_watcher(watcher, &paths);
-        }
-        Err(e) => match e {
-          mpsc::error::TryRecvError::Empty => {
-            break;
-          }
-          // there must be at least one receiver alive
-          _ => unreachable!(),
-        },
-      }
-    }
-  }
-
   loop {
     let mut watcher = new_watcher(watcher_sender.clone())?;
     consume_paths_to_watch(&mut watcher, &mut paths_to_watch_receiver);
@@ -348,6 +332,116 @@ where
   }
 }
 
+/// The result of an operation run by [`watch_hmr_func`].
+pub enum HmrOutcome {
+  /// The operation completed. It's restarted on the next file change.
+  Finished,
+  /// A file change couldn't be applied in place. The operation is restarted
+  /// right away.
+  Restart,
+}
+
+/// Like [`watch_func2`], but the operation isn't restarted on file changes.
+/// Instead, the changed paths are sent to it through the receiver it's
+/// called with, so it can apply them in place. It's restarted when it
+/// returns [`HmrOutcome::Restart`].
+pub async fn watch_hmr_func<T: Clone, O, F>(
+  mut paths_to_watch_receiver: UnboundedReceiver<Vec<PathBuf>>,
+  mut operation: O,
+  operation_args: T,
+  print_config: PrintConfig,
+) -> Result<(), AnyError>
+where
+  O: FnMut(T, UnboundedReceiver<Vec<PathBuf>>) -> Result<F, AnyError>,
+  F: Future<Output = Result<HmrOutcome, AnyError>>,
+{
+  let (watcher_sender, mut watcher_receiver) =
+    DebouncedReceiver::new_with_sender();
+
+  let PrintConfig {
+    job_name,
+    clear_screen,
+  } = print_config;
+
+  let print_after_restart = create_print_after_restart_fn(clear_screen);
+
+  info!("{} {} started.", colors::intense_blue("Watcher"), job_name,);
+
+  loop {
+    let mut watcher = new_watcher(watcher_sender.clone())?;
+    consume_paths_to_watch(&mut watcher, &mut paths_to_watch_receiver);
+
+    let (changed_paths_sender, changed_paths_receiver) =
+      mpsc::unbounded_channel();
+    let receiver_future = async {
+      loop {
+        let maybe_paths = paths_to_watch_receiver.recv().await;
+        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap());
+      }
+    };
+    let changed_paths_future = async {
+      loop {
+        let changed_paths = watcher_receiver.recv().await.unwrap();
+        // the operation might have stopped listening when it's finishing
+        let _ = changed_paths_sender.send(changed_paths);
+      }
+    };
+    let operation_future =
+      error_handler(operation(operation_args.clone(), changed_paths_receiver)?);
+
+    select! {
+      _ = receiver_future => {},
+      _ = changed_paths_future => {},
+      outcome = operation_future => {
+        if matches!(outcome, Some(HmrOutcome::Restart)) {
+          print_after_restart();
+          continue;
+        }
+        consume_paths_to_watch(&mut watcher, &mut paths_to_watch_receiver);
+        info!(
+          "{} {} finished. Restarting on file change...",
+          colors::intense_blue("Watcher"),
+          job_name,
+        );
+      },
+    };
+
+    let receiver_future = async {
+      loop {
+        let maybe_paths = paths_to_watch_receiver.recv().await;
+        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap());
+      }
+    };
+    select! {
+      _ = receiver_future => {},
+      _ = watcher_receiver.recv() => {
+        print_after_restart();
+        continue;
+      },
+    };
+  }
+}
+
+fn consume_paths_to_watch(
+  watcher: &mut RecommendedWatcher,
+  receiver: &mut UnboundedReceiver<Vec<PathBuf>>,
+) {
+  loop {
+    match receiver.try_recv() {
+      Ok(paths) => {
+        add_paths_to_watcher(watcher, &paths);
+      }
+      Err(e) => match e {
+        mpsc::error::TryRecvError::Empty => {
+          break;
+        }
+        // there must be at least one receiver alive
+        _ => unreachable!(),
+      },
+    }
+  }
+}
+
 fn new_watcher(
   sender: Arc<mpsc::UnboundedSender<Vec<PathBuf>>>,
 ) -> Result<RecommendedWatcher, AnyError> {
@@ -361,9 +455,12 @@ fn new_watcher(
           let paths = event
             .paths
             .iter()
+            .filter(|path| !is_in_ignored_dir(path))
             .filter_map(|path| canonicalize_path(path).ok())
-            .collect();
-          sender.send(paths).unwrap();
+            .collect::<Vec<_>>();
+          if !paths.is_empty() {
+            sender.send(paths).unwrap();
+          }
         }
       }
     },
@@ -373,6 +470,24 @@ fn new_watcher(
   Ok(watcher)
 }
 
+/// Changes inside `node_modules` and `.git` directories, which are written by
+/// package managers and git rather than edited, don't restart the watcher.
+/// Only the part of the path below the current directory is checked, so that
+/// a project that is itself inside such a directory is still watched.
+fn is_in_ignored_dir(path: &Path) -> bool {
+  let cwd = std::env::current_dir().unwrap_or_default();
+  path
+    .strip_prefix(&cwd)
+    .unwrap_or(path)
+    .components()
+    .any(|component| {
+      matches!(
+        component.as_os_str().to_str(),
+        Some("node_modules" | ".git")
+      )
+    })
+}
+
 fn add_paths_to_watcher(watcher: &mut RecommendedWatcher, paths: &[PathBuf]) {
   // Ignore any error e.g. `PathNotFound`
   for path in paths {
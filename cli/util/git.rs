@@ -0,0 +1,200 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::util::fs::canonicalize_path;
+
+/// Runs git in the given directory and returns its stdout.
+fn run_git(cwd: &Path, args: &[&str]) -> Result<String, AnyError> {
+  let output = Command::new("git")
+    .args(args)
+    .current_dir(cwd)
+    .output()
+    .context("Failed to run git. Is it installed and in the PATH?")?;
+  if !output.status.success() {
+    bail!(
+      "git {} failed: {}",
+      args.join(" "),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Gets the canonicalized root directory of the git repository containing
+/// `cwd`.
+pub fn root_dir(cwd: &Path) -> Result<PathBuf, AnyError> {
+  let output = run_git(cwd, &["rev-parse", "--show-toplevel"])?;
+  let root_dir = PathBuf::from(output.trim());
+  canonicalize_path(&root_dir).with_context(|| {
+    format!("Failed to canonicalize '{}'.", root_dir.display())
+  })
+}
+
+/// Converts the NUL separated paths output by git with `-z`, which are
+/// relative to the repository root, to absolute paths.
+fn to_absolute_paths(root_dir: &Path, output: &str) -> Vec<PathBuf> {
+  output
+    .split('\0')
+    .filter(|path| !path.is_empty())
+    .map(|path| root_dir.join(path))
+    .collect()
+}
+
+/// Gets the files that changed since the given ref, including uncommitted
+/// changes and untracked files in the working tree.
+pub fn changed_files(
+  cwd: &Path,
+  git_ref: &str,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let root_dir = root_dir(cwd)?;
+  let mut paths = to_absolute_paths(
+    &root_dir,
+    &run_git(&root_dir, &["diff", "--name-only", "-z", git_ref, "--"])?,
+  );
+  paths.extend(to_absolute_paths(
+    &root_dir,
+    &run_git(
+      &root_dir,
+      &["ls-files", "-z", "--others", "--exclude-standard"],
+    )?,
+  ));
+  paths.sort();
+  paths.dedup();
+  Ok(paths)
+}
+
+/// Gets the files staged in the index, excluding deleted files.
+pub fn staged_files(cwd: &Path) -> Result<Vec<PathBuf>, AnyError> {
+  let root_dir = root_dir(cwd)?;
+  let mut paths = to_absolute_paths(
+    &root_dir,
+    &run_git(
+      &root_dir,
+      &["diff", "--name-only", "-z", "--cached", "--diff-filter=d"],
+    )?,
+  );
+  paths.sort();
+  Ok(paths)
+}
+
+/// Gets the changed lines of each file in the working tree as zero-based line
+/// ranges, compared against `git_ref` or otherwise `HEAD`. Comparing the
+/// working tree keeps the line numbers in sync with the files that get
+/// formatted, also for staged files with further unstaged changes. Untracked
+/// files have no entry.
+pub fn changed_lines(
+  cwd: &Path,
+  git_ref: Option<&str>,
+) -> Result<HashMap<PathBuf, Vec<Range<usize>>>, AnyError> {
+  let root_dir = root_dir(cwd)?;
+  let mut args = vec![
+    "-c",
+    "core.quotepath=off",
+    "diff",
+    "--unified=0",
+    "--no-color",
+    "--no-ext-diff",
+    "--no-prefix",
+  ];
+  args.push(git_ref.unwrap_or("HEAD"));
+  args.push("--");
+  Ok(parse_changed_lines(&root_dir, &run_git(&root_dir, &args)?))
+}
+
+/// Parses the hunk headers of a diff without context lines.
+fn parse_changed_lines(
+  root_dir: &Path,
+  output: &str,
+) -> HashMap<PathBuf, Vec<Range<usize>>> {
+  let mut changed_lines: HashMap<PathBuf, Vec<Range<usize>>> = HashMap::new();
+  let mut current_path = None;
+  for line in output.lines() {
+    if let Some(path) = line.strip_prefix("+++ ") {
+      current_path = if path == "/dev/null" {
+        None
+      } else {
+        Some(root_dir.join(path))
+      };
+    } else if let Some(hunk) = line.strip_prefix("@@ ") {
+      if let (Some(path), Some(range)) =
+        (&current_path, parse_hunk_header(hunk))
+      {
+        changed_lines.entry(path.clone()).or_default().push(range);
+      }
+    }
+  }
+  changed_lines
+}
+
+/// Parses the new side of a hunk header (`-a,b +c,d @@`). A hunk that only
+/// removes lines covers the lines around the removal.
+fn parse_hunk_header(hunk: &str) -> Option<Range<usize>> {
+  let new_side = hunk.split(' ').find_map(|part| part.strip_prefix('+'))?;
+  let (start, count) = match new_side.split_once(',') {
+    Some((start, count)) => (start.parse::<usize>().ok()?, count.parse().ok()?),
+    None => (new_side.parse::<usize>().ok()?, 1),
+  };
+  if count == 0 {
+    // the removal happened after line `start`
+    Some(start.saturating_sub(1)..start + 1)
+  } else {
+    Some(start - 1..start - 1 + count)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_changed_lines() {
+    let output = r#"diff --git a.ts a.ts
+index 1234567..89abcde 100644
+--- a.ts
++++ a.ts
+@@ -1 +1 @@ export const a = 1;
+-const a=1
++const a = 1;
+@@ -10,0 +11,3 @@ function f() {
++  one();
++  two();
++  three();
+@@ -20,2 +23,0 @@
+-  removed();
+-  removed();
+diff --git b.ts b.ts
+deleted file mode 100644
+--- b.ts
++++ /dev/null
+@@ -1,2 +0,0 @@
+-const b = 1;
+-const c = 2;
+diff --git c.ts c.ts
+new file mode 100644
+--- /dev/null
++++ c.ts
+@@ -0,0 +1,2 @@
++const c = 1;
++const d = 2;
+"#;
+    let root_dir = PathBuf::from("/repo");
+    let changed_lines = parse_changed_lines(&root_dir, output);
+    assert_eq!(changed_lines.len(), 2);
+    assert_eq!(
+      changed_lines.get(&root_dir.join("a.ts")).unwrap(),
+      &vec![0..1, 10..13, 22..24]
+    );
+    assert_eq!(
+      changed_lines.get(&root_dir.join("c.ts")).unwrap(),
+      &vec![0..2]
+    );
+  }
+}
@@ -8,6 +8,7 @@ pub mod display;
 pub mod draw_thread;
 pub mod file_watcher;
 pub mod fs;
+pub mod git;
 pub mod logger;
 pub mod path;
 pub mod progress_bar;
@@ -15,3 +16,4 @@ pub mod text_encoding;
 pub mod unix;
 pub mod v8;
 pub mod windows;
+pub mod xml;
@@ -5,8 +5,11 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::error::uri_error;
 use deno_core::error::AnyError;
+use deno_core::url::Url;
+use regex::Regex;
 
 /// Checks if the path has extension Deno supports.
 pub fn is_supported_ext(path: &Path) -> bool {
@@ -138,6 +141,23 @@ pub fn relative_specifier(
   })
 }
 
+/// Gets the path of a local module relative to `cwd`, like `./a.ts` or
+/// `../b.ts`, or the url of a remote module. Unlike the url of a local module,
+/// the path is the same on every machine with the same directory layout.
+pub fn to_relative_path_or_remote_url(cwd: &Url, path_or_url: &str) -> String {
+  if let Ok(url) = Url::parse(path_or_url) {
+    if url.scheme() == "file" {
+      if let Some(mut r) = cwd.make_relative(&url) {
+        if !r.starts_with("../") {
+          r = format!("./{r}");
+        }
+        return r;
+      }
+    }
+  }
+  path_or_url.to_string()
+}
+
 /// This function checks if input path has trailing slash or not. If input path
 /// has trailing slash it will return true else it will return false.
 pub fn path_has_trailing_slash(path: &Path) -> bool {
@@ -222,10 +242,74 @@ pub fn root_url_to_safe_local_dirname(root: &ModuleSpecifier) -> PathBuf {
   result
 }
 
+/// Converts a glob into a regex that matches whole paths using forward
+/// slashes as separators.
+///
+/// Supports `*`, `**`, `?` and `{a,b}` alternatives.
+pub fn glob_to_regex(glob: &str) -> Result<Regex, AnyError> {
+  let glob = glob.replace('\\', "/");
+  let mut pattern = String::from("^");
+  let mut chars = glob.chars().peekable();
+  let mut in_group = false;
+  while let Some(c) = chars.next() {
+    match c {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        if chars.peek() == Some(&'/') {
+          chars.next();
+          pattern.push_str("(?:.*/)?");
+        } else {
+          pattern.push_str(".*");
+        }
+      }
+      '*' => pattern.push_str("[^/]*"),
+      '?' => pattern.push_str("[^/]"),
+      '{' if !in_group => {
+        in_group = true;
+        pattern.push_str("(?:");
+      }
+      '}' if in_group => {
+        in_group = false;
+        pattern.push(')');
+      }
+      ',' if in_group => pattern.push('|'),
+      c => pattern.push_str(&regex::escape(&c.to_string())),
+    }
+  }
+  if in_group {
+    bail!("Unclosed '{{' in glob: {glob}");
+  }
+  pattern.push('$');
+  Ok(Regex::new(&pattern)?)
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
 
+  #[test]
+  fn test_glob_to_regex() {
+    let regex = glob_to_regex("src/**/*.ts").unwrap();
+    assert!(regex.is_match("src/mod.ts"));
+    assert!(regex.is_match("src/a/b/mod.ts"));
+    assert!(!regex.is_match("src/mod.js"));
+    assert!(!regex.is_match("lib/src/mod.ts"));
+
+    let regex = glob_to_regex("*.{js,ts}").unwrap();
+    assert!(regex.is_match("mod.js"));
+    assert!(regex.is_match("mod.ts"));
+    assert!(!regex.is_match("src/mod.ts"));
+
+    let regex = glob_to_regex("src\\file?.ts").unwrap();
+    assert!(regex.is_match("src/file1.ts"));
+    assert!(!regex.is_match("src/file10.ts"));
+
+    let regex = glob_to_regex("src/**").unwrap();
+    assert!(regex.is_match("src/a/b.ts"));
+
+    assert!(glob_to_regex("src/{a,b").is_err());
+  }
+
   #[test]
   fn test_is_supported_ext() {
     assert!(!is_supported_ext(Path::new("tests/subdir/redirects")));
@@ -295,6 +379,21 @@ mod test {
     }
   }
 
+  #[test]
+  fn test_to_relative_path_or_remote_url() {
+    let cwd = Url::parse("file:///repo/").unwrap();
+    let fixtures = [
+      ("file:///repo/a.ts", "./a.ts"),
+      ("file:///repo/test/a.ts", "./test/a.ts"),
+      ("file:///other/a.ts", "../other/a.ts"),
+      ("https://deno.land/x/a.ts", "https://deno.land/x/a.ts"),
+      ("a.ts", "a.ts"),
+    ];
+    for (path_or_url, expected) in fixtures {
+      assert_eq!(to_relative_path_or_remote_url(&cwd, path_or_url), expected);
+    }
+  }
+
   #[test]
   fn test_relative_specifier() {
     let fixtures: Vec<(&str, &str, Option<&str>)> = vec![
@@ -0,0 +1,55 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Matches the ANSI escape sequences, such as the ones used for colors.
+static ANSI_ESCAPE_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(
+    r"(?:\x1b\[|\x{9b})[0-?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]",
+  )
+  .unwrap()
+});
+
+/// Escapes text for the content or an attribute value of an XML or HTML
+/// document. ANSI escape sequences and the other control characters, which
+/// are not allowed in XML 1.0 documents, are removed.
+pub fn escape_xml(text: &str) -> String {
+  let text = ANSI_ESCAPE_RE.replace_all(text, "");
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      '\t' | '\n' | '\r' => escaped.push(c),
+      c if c.is_control() => {}
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_xml() {
+    assert_eq!(
+      escape_xml("if (a < b && c > \"d\") {}"),
+      "if (a &lt; b &amp;&amp; c &gt; &quot;d&quot;) {}"
+    );
+    assert_eq!(escape_xml("it's"), "it&apos;s");
+    assert_eq!(escape_xml("line 1\r\n\tline 2"), "line 1\r\n\tline 2");
+    assert_eq!(escape_xml("\u{1b}[31mred\u{1b}[0m"), "red");
+    assert_eq!(escape_xml("\u{1b}[1;38;5;208mbold\u{1b}[22m"), "bold");
+    assert_eq!(
+      escape_xml("\u{1b}]8;;https://deno.land\u{7}link\u{1b}]8;;\u{7}"),
+      "link"
+    );
+    assert_eq!(escape_xml("a\u{0}b\u{8}c"), "abc");
+  }
+}
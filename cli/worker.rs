@@ -5,6 +5,7 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures::task::LocalFutureObj;
 use deno_core::futures::FutureExt;
@@ -26,6 +27,7 @@ use deno_runtime::web_worker::WebWorkerOptions;
 use deno_runtime::worker::MainWorker;
 use deno_runtime::worker::WorkerOptions;
 use deno_runtime::BootstrapOptions;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 use crate::args::DenoSubcommand;
 use crate::errors;
@@ -35,8 +37,11 @@ use crate::ops;
 use crate::proc_state::ProcState;
 use crate::tools;
 use crate::tools::coverage::CoverageCollector;
+use crate::tools::run::HmrRunner;
 use crate::tools::test::TestMode;
+use crate::tools::test::TestTimeoutWatchdog;
 use crate::util::checksum;
+use crate::util::file_watcher::HmrOutcome;
 use crate::version;
 
 pub struct CliMainWorker {
@@ -175,9 +180,69 @@ impl CliMainWorker {
     executor.execute().await
   }
 
+  /// Runs the main module for `--watch-hmr`, replacing the code of the
+  /// modules of the changed paths while it runs. Stops when a change can't be
+  /// applied in place, so it gets restarted.
+  pub async fn run_for_hmr(
+    mut self,
+    mut changed_paths_rx: UnboundedReceiver<Vec<PathBuf>>,
+  ) -> Result<HmrOutcome, AnyError> {
+    let session = self.worker.create_inspector_session().await;
+    let mut hmr_runner = HmrRunner::new(session, self.ps.clone());
+    self
+      .worker
+      .with_event_loop(hmr_runner.start().boxed_local())
+      .await?;
+
+    self.execute_main_module_possibly_with_npm().await?;
+    self.worker.dispatch_load_event(&located_script_name!())?;
+
+    loop {
+      tokio::select! {
+        result = self.worker.run_event_loop(false) => {
+          result?;
+          if !self
+            .worker
+            .dispatch_beforeunload_event(&located_script_name!())?
+          {
+            break;
+          }
+        }
+        Some(changed_paths) = changed_paths_rx.recv() => {
+          let maybe_specifiers = self
+            .worker
+            .with_event_loop(
+              hmr_runner.replace_modules(&changed_paths).boxed_local(),
+            )
+            .await?;
+          let specifiers = match maybe_specifiers {
+            Some(specifiers) => specifiers,
+            None => {
+              self.worker.dispatch_unload_event(&located_script_name!())?;
+              return Ok(HmrOutcome::Restart);
+            }
+          };
+          for specifier in specifiers {
+            self.worker.execute_script(
+              &located_script_name!(),
+              &format!(
+                "dispatchEvent(new CustomEvent('hmr', {{ detail: {} }}))",
+                json!({ "path": specifier.as_str() }),
+              ),
+            )?;
+          }
+        }
+      }
+    }
+
+    self.worker.dispatch_unload_event(&located_script_name!())?;
+    Ok(HmrOutcome::Finished)
+  }
+
   pub async fn run_test_specifier(
     &mut self,
     mode: TestMode,
+    timeout: Option<u64>,
   ) -> Result<(), AnyError> {
     self.enable_test();
 
@@ -201,15 +266,44 @@ impl CliMainWorker {
     }
 
     self.worker.dispatch_load_event(&located_script_name!())?;
-    self.run_tests(&self.ps.options.shuffle_tests()).await?;
-    loop {
-      if !self
+
+    // The collector is moved to the op state while the tests run, so that a
+    // snapshot can be taken after each of them.
+    let coverage_per_test = self.ps.options.coverage_per_test();
+    if coverage_per_test {
+      if let Some(coverage_collector) = maybe_coverage_collector.take() {
+        self
+          .worker
+          .js_runtime
+          .op_state()
+          .borrow_mut()
+          .put(coverage_collector);
+      }
+    }
+    let timed_out = self
+      .run_tests(&self.ps.options.shuffle_tests(), timeout)
+      .await?;
+    if coverage_per_test {
+      maybe_coverage_collector = self
         .worker
-        .dispatch_beforeunload_event(&located_script_name!())?
-      {
-        break;
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .try_take::<CoverageCollector>();
+    }
+
+    // The ops of a test that timed out may never complete, so don't wait for
+    // the event loop in that case.
+    if !timed_out {
+      loop {
+        if !self
+          .worker
+          .dispatch_beforeunload_event(&located_script_name!())?
+        {
+          break;
+        }
+        self.worker.run_event_loop(false).await?;
       }
-      self.worker.run_event_loop(false).await?;
     }
 
     self.worker.dispatch_unload_event(&located_script_name!())?;
@@ -240,15 +334,17 @@ impl CliMainWorker {
     }
 
     self.worker.dispatch_load_event(&located_script_name!())?;
-    self.run_tests(&None).await?;
-    loop {
-      if !self
-        .worker
-        .dispatch_beforeunload_event(&located_script_name!())?
-      {
-        break;
+    let timed_out = self.run_tests(&None, None).await?;
+    if !timed_out {
+      loop {
+        if !self
+          .worker
+          .dispatch_beforeunload_event(&located_script_name!())?
+        {
+          break;
+        }
+        self.worker.run_event_loop(false).await?;
       }
-      self.worker.run_event_loop(false).await?;
     }
     self.worker.dispatch_unload_event(&located_script_name!())?;
     Ok(())
@@ -345,21 +441,72 @@ impl CliMainWorker {
 
   /// Run tests declared with `Deno.test()`. Test events will be dispatched
   /// by calling ops which are currently only implemented in the CLI crate.
+  /// Returns whether a test timed out, in which case the execution was
+  /// terminated and its ops may never complete.
   pub async fn run_tests(
     &mut self,
     shuffle: &Option<u64>,
-  ) -> Result<(), AnyError> {
-    let promise = {
+    timeout: Option<u64>,
+  ) -> Result<bool, AnyError> {
+    let watchdog = TestTimeoutWatchdog::start(
+      self.worker.js_runtime.v8_isolate().thread_safe_handle(),
+    );
+    self
+      .worker
+      .js_runtime
+      .op_state()
+      .borrow_mut()
+      .put(watchdog.clone());
+    let maybe_promise = {
       let scope = &mut self.worker.js_runtime.handle_scope();
       let cb = self.js_run_tests_callback.as_ref().unwrap().open(scope);
       let this = v8::undefined(scope).into();
-      let options =
-        serde_v8::to_v8(scope, json!({ "shuffle": shuffle })).unwrap();
-      let promise = cb.call(scope, this, &[options]).unwrap();
-      v8::Global::new(scope, promise)
+      let options = serde_v8::to_v8(
+        scope,
+        json!({
+          "shuffle": shuffle,
+          "coveragePerTest": self.ps.options.coverage_per_test(),
+          "retries": self.ps.options.test_retries(),
+          "timeout": timeout,
+        }),
+      )
+      .unwrap();
+      // This is `None` if the first test timed out before awaiting anything.
+      cb.call(scope, this, &[options])
+        .map(|promise| v8::Global::new(scope, promise))
     };
-    self.worker.js_runtime.resolve_value(promise).await?;
-    Ok(())
+    let result = match maybe_promise {
+      Some(promise) => {
+        tokio::select! {
+          result = self.worker.js_runtime.resolve_value(promise) => {
+            result.map(|_| ())
+          }
+          _ = watchdog.timed_out() => Ok(()),
+        }
+      }
+      None => Err(generic_error("Running the tests was terminated.")),
+    };
+    self
+      .worker
+      .js_runtime
+      .op_state()
+      .borrow_mut()
+      .take::<TestTimeoutWatchdog>();
+    match watchdog.stop() {
+      Some(id) => {
+        self
+          .worker
+          .js_runtime
+          .v8_isolate()
+          .cancel_terminate_execution();
+        self.worker.execute_script(
+          &located_script_name!(),
+          &format!("Deno[Deno.internal].testing.reportTimedOut({id});"),
+        )?;
+        Ok(true)
+      }
+      None => result.map(|_| false),
+    }
   }
 
   /// Run benches declared with `Deno.bench()`. Bench events will be dispatched
@@ -373,7 +520,14 @@ impl CliMainWorker {
         .unwrap()
         .open(scope);
       let this = v8::undefined(scope).into();
-      let promise = cb.call(scope, this, &[]).unwrap();
+      let options = serde_v8::to_v8(
+        scope,
+        json!({
+          "metrics": self.ps.options.bench_metrics(),
+        }),
+      )
+      .unwrap();
+      let promise = cb.call(scope, this, &[options]).unwrap();
       v8::Global::new(scope, promise)
     };
     self.worker.js_runtime.resolve_value(promise).await?;
@@ -39,6 +39,8 @@ pub struct PackageJson {
   pub dependencies: Option<HashMap<String, String>>,
   pub dev_dependencies: Option<HashMap<String, String>>,
   pub scripts: Option<IndexMap<String, String>>,
+  /// The globs of the `workspaces` field, or of its `packages`.
+  pub workspaces: Option<Vec<String>>,
 }
 
 impl PackageJson {
@@ -58,6 +60,7 @@ impl PackageJson {
       dependencies: None,
       dev_dependencies: None,
       scripts: None,
+      workspaces: None,
     }
   }
 
@@ -153,6 +156,11 @@ impl PackageJson {
       .get("scripts")
       .and_then(|d| serde_json::from_value(d.to_owned()).ok());
 
+    let workspaces = package_json.get("workspaces").and_then(|w| {
+      let globs = w.get("packages").unwrap_or(w);
+      serde_json::from_value(globs.to_owned()).ok()
+    });
+
     // Ignore unknown types for forwards compatibility
     let typ = if let Some(t) = type_val {
       if let Some(t) = t.as_str() {
@@ -189,6 +197,7 @@ impl PackageJson {
       dependencies,
       dev_dependencies,
       scripts,
+      workspaces,
     };
 
     CACHE.with(|cache| {
@@ -776,8 +776,10 @@ export function spawnSync(
   args: string[],
   options: SpawnSyncOptions,
 ): SpawnSyncResult {
+  // without an `env` option the environment is inherited, which doesn't
+  // require access to all the environment variables like reading them does
   const {
-    env = Deno.env.toObject(),
+    env,
     stdio = ["pipe", "pipe", "pipe"],
     shell = false,
     cwd,
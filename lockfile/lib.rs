@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::io::Write;
 
 use anyhow::Context;
@@ -197,6 +198,11 @@ impl Lockfile {
     Ok(())
   }
 
+  /// Gets the checksums of the remote modules, keyed by their specifier.
+  pub fn remote(&self) -> &BTreeMap<String, String> {
+    &self.content.remote
+  }
+
   // TODO(bartlomieju): this function should return an error instead of a bool,
   // but it requires changes to `deno_graph`'s `Locker`.
   pub fn check_or_insert_remote(
@@ -308,6 +314,50 @@ Use \"--lock-write\" flag to regenerate the lockfile at \"{}\".",
       .specifiers
       .insert(serialized_package_req, serialized_package_id);
   }
+
+  /// Removes the checksums of the remote modules whose specifier starts with
+  /// the given prefix, like the modules of a previous version of a package.
+  pub fn remove_remote_with_prefix(&mut self, prefix: &str) {
+    let len = self.content.remote.len();
+    self
+      .content
+      .remote
+      .retain(|specifier, _| !specifier.starts_with(prefix));
+    if self.content.remote.len() != len {
+      self.has_content_changed = true;
+    }
+  }
+
+  /// Removes an npm package requirement along with the packages that are no
+  /// longer depended on by the remaining requirements.
+  pub fn remove_npm_specifier(&mut self, serialized_package_req: &str) {
+    if self
+      .content
+      .npm
+      .specifiers
+      .remove(serialized_package_req)
+      .is_none()
+    {
+      return;
+    }
+    self.has_content_changed = true;
+
+    let mut used_ids = HashSet::new();
+    let mut pending_ids =
+      self.content.npm.specifiers.values().collect::<Vec<_>>();
+    while let Some(id) = pending_ids.pop() {
+      if used_ids.insert(id.clone()) {
+        if let Some(package) = self.content.npm.packages.get(id) {
+          pending_ids.extend(package.dependencies.values());
+        }
+      }
+    }
+    self
+      .content
+      .npm
+      .packages
+      .retain(|id, _| used_ids.contains(id));
+  }
 }
 
 #[cfg(test)]
@@ -532,4 +582,35 @@ mod tests {
     let check_err = lockfile.check_or_insert_npm_package(npm_package);
     assert!(check_err.is_err());
   }
+
+  #[test]
+  fn remove_remote_and_npm_specifiers() {
+    let temp_dir = TempDir::new();
+    let file_path = setup(&temp_dir);
+
+    let mut lockfile = Lockfile::new(file_path, false).unwrap();
+    lockfile.remove_remote_with_prefix("https://deno.land/std@0.71.0/async/");
+    assert!(lockfile.has_content_changed);
+    let keys: Vec<String> = lockfile.content.remote.keys().cloned().collect();
+    assert_eq!(
+      keys,
+      vec![String::from(
+        "https://deno.land/std@0.71.0/textproto/mod.ts"
+      )]
+    );
+
+    lockfile
+      .insert_npm_specifier("nanoid@3".to_string(), "nanoid@3.3.4".to_string());
+    lockfile.insert_npm_specifier(
+      "picocolors@1".to_string(),
+      "picocolors@1.0.0".to_string(),
+    );
+    lockfile.remove_npm_specifier("picocolors@1");
+    let specifiers: Vec<String> =
+      lockfile.content.npm.specifiers.keys().cloned().collect();
+    assert_eq!(specifiers, vec![String::from("nanoid@3")]);
+    let packages: Vec<String> =
+      lockfile.content.npm.packages.keys().cloned().collect();
+    assert_eq!(packages, vec![String::from("nanoid@3.3.4")]);
+  }
 }
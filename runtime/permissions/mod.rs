@@ -828,15 +828,12 @@ impl UnaryPermission<NetDescriptor> {
     url: &url::Url,
     api_name: Option<&str>,
   ) -> Result<(), AnyError> {
-    let hostname = url
-      .host_str()
-      .ok_or_else(|| uri_error("Missing host"))?
-      .to_string();
+    let hostname = url.host_str().ok_or_else(|| uri_error("Missing host"))?;
     let display_host = match url.port() {
-      None => hostname.clone(),
+      None => hostname.to_string(),
       Some(port) => format!("{hostname}:{port}"),
     };
-    let host = &(&hostname, url.port_or_known_default());
+    let host = &(hostname, url.port_or_known_default());
     let (result, prompted, is_allow_all) = self.query(Some(host)).check(
       self.name,
       api_name,